@@ -6,46 +6,30 @@
 
 use rust_decimal_macros::dec;
 use udc_engine::prelude::*;
-use udc_engine::{
-    DealInput, DealType, DealFees, CustomerInfo, FinanceParams,
-    RuleProfile, TradeInTaxTreatment, RebateTaxTreatment,
-    LeaseTaxMode, TaxStackingMode, ReciprocityType, RoundingMode,
-};
+use udc_engine::{FinanceParams, StateCode};
 
 fn main() {
     println!("=== UDC Engine Basic Deal Example ===\n");
 
     // Create a finance deal input
     let deal = DealInput {
-        deal_type: DealType::Finance,
-        vehicle_price: dec!(32500),
         trade_in_value: Some(dec!(8000)),
         trade_in_payoff: Some(dec!(5500)),
         cash_down: dec!(2500),
-        rebates: vec![],
-        products: vec![],
         fees: DealFees {
-            doc_fee: dec!(299),
+            doc_fee: Some(dec!(299)),
             title_fee: dec!(33),
             registration_fee: dec!(75),
             ..Default::default()
         },
-        home_state: udc_engine::StateCode::TX,
-        transaction_state: udc_engine::StateCode::TX,
-        garaging_state: None,
-        customer: CustomerInfo::default(),
         finance_params: Some(FinanceParams {
-            term_months: 60,
-            apr: dec!(0.0599),
             lender_id: Some("BANK123".to_string()),
             buy_rate: Some(dec!(0.0399)),
             max_reserve_points: Some(dec!(2.0)),
-            deferred_first_payment: false,
-            days_to_first_payment: None,
+            ..FinanceParams::new(60, dec!(0.0599))
         }),
-        lease_params: None,
         deal_date: Some(chrono::Local::now().date_naive()),
-        first_payment_date: None,
+        ..DealInput::new(DealType::Finance, dec!(32500), StateCode::TX)
     };
 
     println!("Deal Input:");
@@ -58,41 +42,30 @@ fn main() {
     println!("  Term: {} months", deal.finance_params.as_ref().unwrap().term_months);
     println!();
 
-    // Create Texas rule profile
-    let rules = RuleProfile {
-        state_code: "TX".to_string(),
-        deal_type: DealType::Finance,
-        effective_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        state_rate: Rate::from_percentage(dec!(6.25)),
-        county_rate: Some(Rate::from_percentage(dec!(0.50))),
-        city_rate: Some(Rate::from_percentage(dec!(1.50))),
-        district_rates: vec![],
-        trade_in_treatment: TradeInTaxTreatment::ReducesTaxableAmount,
-        trade_in_credit_cap: None,
-        rebate_treatment: RebateTaxTreatment::NoReduction,
-        doc_fee_taxable: true,
-        title_fee_taxable: false,
-        registration_fee_taxable: false,
-        lease_tax_mode: LeaseTaxMode::MonthlyPayment,
-        tax_stacking_mode: TaxStackingMode::Additive,
-        reciprocity: ReciprocityType::FullCredit,
-        special_tax_type: udc_engine::types::SpecialTaxType::Standard,
-        flat_tax_amount: None,
-        max_tax_cap: None,
-        min_tax_floor: None,
-        rounding_precision: 2,
-        rounding_mode: RoundingMode::HalfUp,
-    };
+    // Create Texas rule profile (the same JSON the WASM bindings accept)
+    let rules: RuleProfile = serde_json::from_str(
+        r#"{
+            "state_code": "TX",
+            "mode": "finance",
+            "tax_type": "SALES",
+            "rates": {
+                "state_rate": "0.0625",
+                "max_local_rate": "0.02",
+                "default_combined_rate": "0.0825"
+            },
+            "base_rules": {"trade_in_reduces_basis": true, "doc_fee_taxable": true},
+            "ancillaries": {},
+            "reciprocity": {},
+            "meta": {"version": "1", "effective_date": "2024-01-01"}
+        }"#,
+    )
+    .expect("valid rule profile");
 
     println!("Tax Rules:");
-    println!("  State: {}", rules.state_code);
-    println!("  State Rate: {}%", rules.state_rate.as_percentage());
-    println!("  Combined Rate: ~{}%",
-        rules.state_rate.as_percentage()
-        + rules.county_rate.map(|r| r.as_percentage()).unwrap_or_default()
-        + rules.city_rate.map(|r| r.as_percentage()).unwrap_or_default()
-    );
-    println!("  Trade-In Reduces Tax: {:?}", rules.trade_in_treatment);
+    println!("  State: {:?}", rules.state_code);
+    println!("  State Rate: {}%", rules.rates.state_rate * dec!(100));
+    println!("  Combined Rate: ~{}%", rules.rates.default_combined_rate * dec!(100));
+    println!("  Trade-In Reduces Tax: {}", rules.base_rules.trade_in_reduces_basis);
     println!();
 
     // Run the calculation
@@ -28,10 +28,29 @@
 //! - Round Down: Always round payment down
 //!
 //! The final payment may differ to account for cumulative rounding.
+//!
+//! # Rule of 78s
+//! Precomputed contracts earn the total finance charge by sum-of-digits
+//! weighting rather than on the outstanding balance:
+//! ```text
+//! interest_k = F * (n - k + 1) / (n(n+1)/2)
+//! ```
+//! This front-loads interest, so early payoff balances are higher than
+//! under the actuarial method.
+//!
+//! # Simple Daily Interest
+//! Interest accrues on the balance for the actual days between due dates:
+//! ```text
+//! interest = balance * (APR / 365) * days_since_last_payment
+//! ```
+//! Lenders on Actual/360 or 30/360 divide by 360 instead, and 30/360 counts
+//! every month as 30 days (see `DayCount`).
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
+
+use crate::types::{DayCount, InterestMethod};
 
 /// Rounding mode for payment calculations
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -54,12 +73,15 @@ pub struct PaymentResult {
     pub total_of_payments: Decimal,
     /// Total finance charge (interest)
     pub finance_charge: Decimal,
-    /// Effective APR (may differ slightly from input due to rounding)
+    /// Realized APR backed out of the rounded payment (TILA disclosure rate);
+    /// the contract APR when there is no rounded payment to back it out of
     pub effective_apr: Decimal,
+    /// Contract (input) APR the payment was computed from
+    pub contract_apr: Decimal,
 }
 
 /// Single amortization entry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AmortizationEntry {
     /// Payment number (1-indexed)
     pub payment_number: u32,
@@ -88,7 +110,8 @@ pub struct AmortizationEntry {
 /// * `rounding` - Rounding mode for payment
 ///
 /// # Returns
-/// PaymentResult containing monthly payment, total, and finance charge
+/// PaymentResult containing monthly payment, total, finance charge, and
+/// the realized APR of the rounded payment alongside the contract APR
 ///
 /// # Algorithm
 /// Uses the standard PMT formula:
@@ -116,7 +139,8 @@ pub fn calculate_payment(
             monthly_payment: dec!(0),
             total_of_payments: dec!(0),
             finance_charge: dec!(0),
-            effective_apr: dec!(0),
+            effective_apr: apr,
+            contract_apr: apr,
         };
     }
 
@@ -130,7 +154,8 @@ pub fn calculate_payment(
             monthly_payment: payment,
             total_of_payments: total,
             finance_charge: dec!(0),
-            effective_apr: dec!(0),
+            effective_apr: apr,
+            contract_apr: apr,
         };
     }
 
@@ -151,7 +176,8 @@ pub fn calculate_payment(
             monthly_payment: principal / n,
             total_of_payments: principal,
             finance_charge: dec!(0),
-            effective_apr: dec!(0),
+            effective_apr: apr,
+            contract_apr: apr,
         };
     }
 
@@ -159,11 +185,15 @@ pub fn calculate_payment(
     let total = payment * n;
     let finance_charge = total - principal;
 
+    // Rounding changes the true cost of credit; disclose the realized rate
+    let effective_apr = calculate_apr_from_payment(principal, payment, term_months);
+
     PaymentResult {
         monthly_payment: payment,
         total_of_payments: total,
         finance_charge,
-        effective_apr: apr,
+        effective_apr,
+        contract_apr: apr,
     }
 }
 
@@ -175,20 +205,24 @@ pub fn calculate_payment(
 /// * `term_months` - Loan term in months
 /// * `first_payment_date` - Date of first payment
 /// * `rounding` - Rounding mode
+/// * `method` - Interest earning method
 ///
 /// # Returns
 /// Vector of AmortizationEntry for each payment
 ///
 /// # Algorithm
 /// For each payment period:
-/// 1. Calculate interest on remaining balance
+/// 1. Calculate interest on remaining balance (actuarial), by actual days
+///    since the previous due date (simple daily), or by sum-of-digits
+///    weighting of the finance charge (Rule of 78s)
 /// 2. Calculate principal as payment minus interest
 /// 3. Update remaining balance
 /// 4. Adjust final payment to zero out balance
 ///
 /// # Complexity
 /// - Time: O(n) where n = term_months
-/// - Space: O(n) for storing schedule
+/// - Space: O(n) for storing schedule (see [`AmortizationIterator`] for
+///   lazy evaluation)
 ///
 /// # Invariants
 /// - Sum of all principal = original principal (within rounding)
@@ -200,28 +234,127 @@ pub fn generate_amortization_schedule(
     term_months: u32,
     first_payment_date: NaiveDate,
     rounding: RoundingMode,
+    method: InterestMethod,
 ) -> Vec<AmortizationEntry> {
-    let payment_result = calculate_payment(principal, apr, term_months, rounding);
-    let monthly_rate = apr / dec!(12);
-    let payment = payment_result.monthly_payment;
+    AmortizationIterator::new(principal, apr, term_months, first_payment_date, rounding, method)
+        .collect()
+}
+
+/// Lazily computed amortization schedule.
+///
+/// Yields the same entries as [`generate_amortization_schedule`] one period
+/// at a time, holding only the running balance and totals, so a caller that
+/// needs a single period or a summary never allocates the whole schedule.
+///
+/// # Complexity
+/// - Time: O(1) per entry
+/// - Space: O(1)
+#[derive(Debug, Clone)]
+pub struct AmortizationIterator {
+    apr: Decimal,
+    monthly_rate: Decimal,
+    payment: Decimal,
+    finance_charge: Decimal,
+    term_months: u32,
+    first_payment_date: NaiveDate,
+    rounding: RoundingMode,
+    method: InterestMethod,
+    day_count: DayCount,
+    /// Next payment number to yield (1-indexed)
+    next_payment: u32,
+    balance: Decimal,
+    cumulative_principal: Decimal,
+    cumulative_interest: Decimal,
+    period_start: NaiveDate,
+}
+
+impl AmortizationIterator {
+    /// Start a schedule; arguments match [`generate_amortization_schedule`].
+    pub fn new(
+        principal: Decimal,
+        apr: Decimal,
+        term_months: u32,
+        first_payment_date: NaiveDate,
+        rounding: RoundingMode,
+        method: InterestMethod,
+    ) -> Self {
+        let payment_result = calculate_payment(principal, apr, term_months, rounding);
+
+        AmortizationIterator {
+            apr,
+            monthly_rate: apr / dec!(12),
+            payment: payment_result.monthly_payment,
+            finance_charge: payment_result.finance_charge,
+            term_months,
+            first_payment_date,
+            rounding,
+            method,
+            day_count: DayCount::default(),
+            next_payment: 1,
+            balance: principal,
+            cumulative_principal: dec!(0),
+            cumulative_interest: dec!(0),
+            // Simple daily interest: the first period starts one month before the first payment
+            period_start: first_payment_date
+                .checked_sub_months(chrono::Months::new(1))
+                .unwrap_or(first_payment_date),
+        }
+    }
+
+    /// Accrue simple daily interest under `day_count` instead of Actual/365.
+    pub fn with_day_count(mut self, day_count: DayCount) -> Self {
+        self.day_count = day_count;
+        self
+    }
+
+    /// Entry for payment number `n` (1-indexed), computing the periods
+    /// before it without keeping them. Returns None past the final payment.
+    pub fn nth_payment(mut self, n: u32) -> Option<AmortizationEntry> {
+        if n < self.next_payment {
+            return None;
+        }
+        self.nth((n - self.next_payment) as usize)
+    }
+}
 
-    let mut schedule = Vec::with_capacity(term_months as usize);
-    let mut balance = principal;
-    let mut cumulative_principal = dec!(0);
-    let mut cumulative_interest = dec!(0);
+impl Iterator for AmortizationIterator {
+    type Item = AmortizationEntry;
 
-    for i in 1..=term_months {
-        let due_date = add_months(first_payment_date, i - 1);
+    fn next(&mut self) -> Option<AmortizationEntry> {
+        let i = self.next_payment;
+        let term_months = self.term_months;
+        if i > term_months {
+            return None;
+        }
+        self.next_payment += 1;
+
+        let due_date = add_months(self.first_payment_date, i - 1);
+        let days = self.day_count.days_between(self.period_start, due_date);
+        self.period_start = due_date;
 
         // Calculate interest for this period
-        let interest = round_payment(balance * monthly_rate, rounding);
+        let interest = match self.method {
+            InterestMethod::RuleOf78s if i == term_months => {
+                // Final period earns whatever precomputed interest remains
+                self.finance_charge - self.cumulative_interest
+            }
+            InterestMethod::RuleOf78s => {
+                rule_of_78s_interest(self.finance_charge, term_months, i).round_dp(2)
+            }
+            InterestMethod::SimpleDaily => {
+                round_payment(per_diem_interest(self.balance, self.apr, days, self.day_count), self.rounding)
+            }
+            InterestMethod::Actuarial => {
+                round_payment(self.balance * self.monthly_rate, self.rounding)
+            }
+        };
 
         // Calculate principal portion
         let principal_portion = if i == term_months {
             // Final payment: clear remaining balance
-            balance
+            self.balance
         } else {
-            let p = payment - interest;
+            let p = self.payment - interest;
             // Ensure principal doesn't go negative
             p.max(dec!(0))
         };
@@ -231,29 +364,82 @@ pub fn generate_amortization_schedule(
             // Final payment includes any rounding adjustment
             principal_portion + interest
         } else {
-            payment
+            self.payment
         };
 
         // Update balance
-        balance = (balance - principal_portion).max(dec!(0));
+        self.balance = (self.balance - principal_portion).max(dec!(0));
 
         // Update cumulative totals
-        cumulative_principal = cumulative_principal + principal_portion;
-        cumulative_interest = cumulative_interest + interest;
+        self.cumulative_principal += principal_portion;
+        self.cumulative_interest += interest;
 
-        schedule.push(AmortizationEntry {
+        Some(AmortizationEntry {
             payment_number: i,
             due_date,
             payment_amount: round_to_cents(actual_payment),
             principal: round_to_cents(principal_portion),
             interest: round_to_cents(interest),
-            remaining_balance: round_to_cents(balance),
-            cumulative_principal: round_to_cents(cumulative_principal),
-            cumulative_interest: round_to_cents(cumulative_interest),
-        });
+            remaining_balance: round_to_cents(self.balance),
+            cumulative_principal: round_to_cents(self.cumulative_principal),
+            cumulative_interest: round_to_cents(self.cumulative_interest),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.term_months + 1).saturating_sub(self.next_payment) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for AmortizationIterator {}
+
+/// Interest earned in period `k` (1-indexed) under the Rule of 78s.
+///
+/// # Formula
+/// ```text
+/// interest_k = F * (n - k + 1) / (n(n+1)/2)
+/// ```
+///
+/// # Complexity
+/// - Time: O(1)
+/// - Space: O(1)
+pub fn rule_of_78s_interest(finance_charge: Decimal, number_of_payments: u32, k: u32) -> Decimal {
+    if number_of_payments == 0 || k == 0 || k > number_of_payments {
+        return dec!(0);
+    }
+
+    let n = Decimal::from(number_of_payments);
+    let sum_of_digits = n * (n + dec!(1)) / dec!(2);
+    let weight = Decimal::from(number_of_payments - k + 1);
+
+    finance_charge * weight / sum_of_digits
+}
+
+/// Interest accrued on a balance over `days` under simple daily interest.
+///
+/// # Formula
+/// ```text
+/// interest = balance * (APR / 365) * days
+/// ```
+pub fn simple_daily_interest(balance: Decimal, apr: Decimal, days: i64) -> Decimal {
+    per_diem_interest(balance, apr, days, DayCount::Actual365)
+}
+
+/// Interest accrued on a balance over `days` under a day-count convention.
+///
+/// `days` should already be counted with `DayCount::days_between`.
+///
+/// # Formula
+/// ```text
+/// interest = balance * (APR / days_per_year) * days
+/// ```
+pub fn per_diem_interest(balance: Decimal, apr: Decimal, days: i64, day_count: DayCount) -> Decimal {
+    if days <= 0 {
+        return dec!(0);
     }
 
-    schedule
+    balance * apr / Decimal::from(day_count.days_per_year()) * Decimal::from(days)
 }
 
 /// Calculate APR from a known payment (reverse calculation).
@@ -340,6 +526,114 @@ pub fn calculate_apr_from_payment(
     (monthly_rate * dec!(12)).round_dp(6)
 }
 
+/// Calculate the APR of an irregular payment stream over its actual dates.
+///
+/// Follows the actuarial method of Regulation Z Appendix J with a monthly
+/// unit period. Each payment is discounted over the whole months between
+/// the advance and its due date, counted back from the due date, plus the
+/// leftover days as a fraction of a 30-day month. This handles a long or
+/// short first period, a balloon, and skipped or uneven payments, which
+/// `calculate_apr_from_payment` cannot.
+///
+/// # Arguments
+/// * `amount_financed` - Amount advanced on `advance_date`
+/// * `advance_date` - Date credit is extended (interest begins to accrue)
+/// * `cashflows` - Due date and amount of each payment
+///
+/// # Returns
+/// Calculated APR as decimal (0 when the payments do not exceed the amount
+/// financed)
+///
+/// # Algorithm
+/// Newton-Raphson iteration on the monthly rate `i` to solve:
+/// ```text
+/// f(i) = sum(P_k / ((1 + f_k * i) * (1 + i)^t_k)) - A = 0
+/// APR  = 12 * i
+/// ```
+/// where `t_k` is the whole months and `f_k` the fraction of a month to
+/// payment `k`.
+///
+/// # Complexity
+/// - Time: O(k * n log n) where k = iterations, n = payments
+/// - Space: O(n)
+pub fn apr_from_cashflows(
+    amount_financed: Decimal,
+    advance_date: NaiveDate,
+    cashflows: &[(NaiveDate, Decimal)],
+) -> Decimal {
+    let total: Decimal = cashflows.iter().map(|(_, amount)| *amount).sum();
+    if amount_financed <= dec!(0) || total <= amount_financed {
+        return dec!(0);
+    }
+
+    let periods: Vec<(u32, Decimal, Decimal)> = cashflows
+        .iter()
+        .map(|(date, amount)| {
+            let (months, fraction) = unit_periods(advance_date, *date);
+            (months, fraction, *amount)
+        })
+        .collect();
+    let present_value = |rate: Decimal| -> Decimal {
+        periods
+            .iter()
+            .map(|(months, fraction, amount)| {
+                *amount / ((dec!(1) + *fraction * rate) * power_decimal(dec!(1) + rate, *months))
+            })
+            .sum::<Decimal>()
+            - amount_financed
+    };
+
+    // Initial guess: simple interest over the last payment's term
+    let term = periods
+        .iter()
+        .map(|(months, fraction, _)| Decimal::from(*months) + *fraction)
+        .fold(dec!(1), Decimal::max);
+    let mut monthly_rate = (total - amount_financed) / amount_financed / term;
+
+    let tolerance = dec!(0.0000001);
+    let max_iterations = 100;
+
+    for _ in 0..max_iterations {
+        let f = present_value(monthly_rate);
+
+        // Numerical derivative, as in calculate_apr_from_payment
+        let delta = dec!(0.0000001);
+        let derivative = (present_value(monthly_rate + delta) - f) / delta;
+        if derivative == dec!(0) {
+            break;
+        }
+
+        let adjustment = f / derivative;
+        monthly_rate -= adjustment;
+
+        if adjustment.abs() < tolerance {
+            break;
+        }
+    }
+
+    (monthly_rate * dec!(12)).round_dp(6)
+}
+
+/// Whole months and fraction of a 30-day month from `start` to `date`.
+///
+/// Whole months are counted back from `date`; the days left over before
+/// `start` form the fraction (Reg Z Appendix J).
+fn unit_periods(start: NaiveDate, date: NaiveDate) -> (u32, Decimal) {
+    if date <= start {
+        return (0, dec!(0));
+    }
+
+    let month_index = |d: NaiveDate| d.year() * 12 + d.month() as i32;
+    let mut months = (month_index(date) - month_index(start)).max(0) as u32;
+    let back = |months: u32| date.checked_sub_months(chrono::Months::new(months)).unwrap_or(start);
+    while months > 0 && back(months) < start {
+        months -= 1;
+    }
+
+    let days = (back(months) - start).num_days();
+    (months, Decimal::from(days) / dec!(30))
+}
+
 /// Calculate (1 + r)^n efficiently using binary exponentiation.
 ///
 /// # Complexity
@@ -457,6 +751,7 @@ mod tests {
             60,
             NaiveDate::from_ymd_opt(2024, 8, 1).unwrap(),
             RoundingMode::BankersRounding,
+            InterestMethod::Actuarial,
         );
 
         // Should have 60 entries
@@ -485,6 +780,7 @@ mod tests {
             60,
             NaiveDate::from_ymd_opt(2024, 8, 1).unwrap(),
             RoundingMode::BankersRounding,
+            InterestMethod::Actuarial,
         );
 
         // Interest should generally decrease over time
@@ -496,6 +792,129 @@ mod tests {
             first_interest, last_interest);
     }
 
+    #[test]
+    fn test_rule_of_78s_payoff_exceeds_actuarial() {
+        let first_payment = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let actuarial = generate_amortization_schedule(
+            dec!(15000),
+            dec!(0.18),
+            48,
+            first_payment,
+            RoundingMode::BankersRounding,
+            InterestMethod::Actuarial,
+        );
+        let rule_of_78s = generate_amortization_schedule(
+            dec!(15000),
+            dec!(0.18),
+            48,
+            first_payment,
+            RoundingMode::BankersRounding,
+            InterestMethod::RuleOf78s,
+        );
+
+        // Same contract, same payment
+        assert_eq!(actuarial[0].payment_amount, rule_of_78s[0].payment_amount);
+
+        // Payoff at mid-term is higher under Rule of 78s
+        assert!(rule_of_78s[23].remaining_balance > actuarial[23].remaining_balance,
+            "Rule of 78s balance {} should exceed actuarial {}",
+            rule_of_78s[23].remaining_balance, actuarial[23].remaining_balance);
+
+        // Both fully amortize, and Rule of 78s earns exactly the precomputed charge
+        assert_eq!(rule_of_78s[47].remaining_balance, dec!(0));
+        let finance_charge = calculate_payment(dec!(15000), dec!(0.18), 48, RoundingMode::BankersRounding)
+            .finance_charge;
+        assert_eq!(rule_of_78s[47].cumulative_interest, finance_charge);
+    }
+
+    #[test]
+    fn test_iterator_matches_eager_schedule() {
+        let first_payment = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        for method in [InterestMethod::Actuarial, InterestMethod::RuleOf78s, InterestMethod::SimpleDaily] {
+            let schedule = generate_amortization_schedule(
+                dec!(42000),
+                dec!(0.0799),
+                84,
+                first_payment,
+                RoundingMode::BankersRounding,
+                method,
+            );
+            let iter = AmortizationIterator::new(
+                dec!(42000),
+                dec!(0.0799),
+                84,
+                first_payment,
+                RoundingMode::BankersRounding,
+                method,
+            );
+            assert_eq!(iter.len(), 84);
+
+            let lazy: Vec<AmortizationEntry> = iter.collect();
+            assert_eq!(lazy, schedule, "{:?} iterator diverges from eager schedule", method);
+        }
+    }
+
+    #[test]
+    fn test_nth_payment() {
+        let first_payment = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let iter = AmortizationIterator::new(
+            dec!(30000),
+            dec!(0.0649),
+            72,
+            first_payment,
+            RoundingMode::BankersRounding,
+            InterestMethod::Actuarial,
+        );
+        let schedule: Vec<AmortizationEntry> = iter.clone().collect();
+
+        assert_eq!(iter.clone().nth_payment(36), Some(schedule[35].clone()));
+        assert_eq!(iter.clone().nth_payment(72), Some(schedule[71].clone()));
+        assert_eq!(iter.clone().nth_payment(0), None);
+        assert_eq!(iter.nth_payment(73), None);
+    }
+
+    #[test]
+    fn test_rule_of_78s_weights() {
+        // 12-month contract: first month earns 12/78 of the charge
+        assert_eq!(rule_of_78s_interest(dec!(780), 12, 1), dec!(120));
+        assert_eq!(rule_of_78s_interest(dec!(780), 12, 12), dec!(10));
+        assert_eq!(rule_of_78s_interest(dec!(780), 12, 13), dec!(0));
+    }
+
+    #[test]
+    fn test_simple_daily_differs_from_actuarial() {
+        // Term includes February 2025 (28 days)
+        let first_payment = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let actuarial = generate_amortization_schedule(
+            dec!(20000),
+            dec!(0.06),
+            12,
+            first_payment,
+            RoundingMode::BankersRounding,
+            InterestMethod::Actuarial,
+        );
+        let daily = generate_amortization_schedule(
+            dec!(20000),
+            dec!(0.06),
+            12,
+            first_payment,
+            RoundingMode::BankersRounding,
+            InterestMethod::SimpleDaily,
+        );
+
+        // Dec 15 -> Jan 15 is 31 days: more interest than 1/12 of a year
+        assert_eq!(daily[0].interest, dec!(101.92));
+        assert!(daily[0].interest > actuarial[0].interest);
+
+        // Feb 15 -> Mar 15 is 28 days: less interest than 1/12 of a year
+        assert_eq!(daily[2].due_date, NaiveDate::from_ymd_opt(2025, 3, 15).unwrap());
+        assert!(daily[2].interest < actuarial[2].interest,
+            "February interest {} should be below actuarial {}",
+            daily[2].interest, actuarial[2].interest);
+
+        assert_eq!(daily[11].remaining_balance, dec!(0));
+    }
+
     #[test]
     fn test_apr_calculation() {
         // First calculate payment, then reverse-calculate APR
@@ -507,6 +926,80 @@ mod tests {
         assert!(diff < dec!(0.001), "Calculated APR {} should be close to 6%", calculated_apr);
     }
 
+    #[test]
+    fn test_effective_apr_after_round_up() {
+        // Exact payment is $386.656..., rounded up to $386.66
+        let result = calculate_payment(dec!(20000), dec!(0.06), 60, RoundingMode::RoundUp);
+
+        assert_eq!(result.contract_apr, dec!(0.06));
+        assert!(result.effective_apr > dec!(0.06),
+            "Effective APR {} should exceed the contract rate", result.effective_apr);
+        assert!(result.effective_apr - dec!(0.06) < dec!(0.0001));
+    }
+
+    #[test]
+    fn test_effective_apr_without_payment_is_contract_rate() {
+        for (principal, term_months) in [(dec!(0), 60), (dec!(20000), 0)] {
+            let result = calculate_payment(principal, dec!(0.0599), term_months, RoundingMode::RoundUp);
+            assert_eq!(result.monthly_payment, dec!(0));
+            assert_eq!(result.effective_apr, dec!(0.0599));
+            assert_eq!(result.effective_apr, result.contract_apr);
+        }
+    }
+
+    /// Monthly due dates starting `first`, `count` payments
+    fn monthly_dates(first: NaiveDate, count: u32) -> Vec<NaiveDate> {
+        (0..count)
+            .map(|i| first.checked_add_months(chrono::Months::new(i)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_apr_from_cashflows_regular_stream() {
+        let advance = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let result = calculate_payment(dec!(20000), dec!(0.06), 60, RoundingMode::BankersRounding);
+        let cashflows: Vec<_> = monthly_dates(NaiveDate::from_ymd_opt(2024, 2, 15).unwrap(), 60)
+            .into_iter()
+            .map(|date| (date, result.monthly_payment))
+            .collect();
+
+        let apr = apr_from_cashflows(dec!(20000), advance, &cashflows);
+        let equal_periods = calculate_apr_from_payment(dec!(20000), result.monthly_payment, 60);
+        assert!((apr - equal_periods).abs() < dec!(0.00001), "{} vs {}", apr, equal_periods);
+    }
+
+    #[test]
+    fn test_apr_from_cashflows_long_first_period_with_balloon() {
+        // $20,000 note at 6%: 36 payments amortizing $12,000 plus interest
+        // on an $8,000 balloon, first payment 45 days out carrying the extra
+        // 15 days' interest
+        let advance = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let amortizing = calculate_payment(dec!(12000), dec!(0.06), 36, RoundingMode::BankersRounding);
+        let payment = amortizing.monthly_payment + dec!(40);
+        let odd_days = (dec!(20000) * dec!(0.06) / dec!(360) * dec!(15)).round_dp(2);
+
+        let mut cashflows: Vec<_> = monthly_dates(NaiveDate::from_ymd_opt(2024, 2, 15).unwrap(), 36)
+            .into_iter()
+            .map(|date| (date, payment))
+            .collect();
+        cashflows[0].1 += odd_days;
+        cashflows[35].1 += dec!(8000);
+
+        // Priced at the note rate, the stream discloses the note rate
+        let apr = apr_from_cashflows(dec!(20000), advance, &cashflows);
+        assert!((apr - dec!(0.06)).abs() < dec!(0.0001), "APR {}", apr);
+
+        // A $400 prepaid finance charge cuts the amount financed, so the
+        // disclosed APR rises about a point above the note rate
+        let apr = apr_from_cashflows(dec!(19600), advance, &cashflows);
+        assert!(apr > dec!(0.0695) && apr < dec!(0.0705), "APR {}", apr);
+
+        // Spreading the balloon over equal periods overstates the rate
+        let total: Decimal = cashflows.iter().map(|(_, amount)| *amount).sum();
+        let equal_periods = calculate_apr_from_payment(dec!(19600), total / dec!(36), 36);
+        assert!(equal_periods - apr > dec!(0.02));
+    }
+
     #[test]
     fn test_power_decimal() {
         assert_eq!(power_decimal(dec!(2), 0), dec!(1));
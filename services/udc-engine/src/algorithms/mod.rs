@@ -34,7 +34,12 @@ pub mod tax;
 pub use amortization::{
     calculate_payment,
     generate_amortization_schedule,
+    AmortizationIterator,
+    rule_of_78s_interest,
+    simple_daily_interest,
+    per_diem_interest,
     calculate_apr_from_payment,
+    apr_from_cashflows,
 };
 pub use lease::{
     calculate_lease_payment,
@@ -0,0 +1,321 @@
+//! Content-addressed result cache.
+//!
+//! The engine is a pure function of its input and profiles, so a repeated
+//! calculation can be answered from memory. Results are keyed by the SHA-256
+//! checksum of the normalized deal input (with the deal date resolved, so a
+//! dateless deal is keyed to the day it runs) together with a checksum of
+//! the content of each program and product profile, so any change to a
+//! profile invalidates earlier results. The rule profile is not part of the
+//! key: state rules come from the built-in profiles, not the caller's.
+//!
+//! The cache is an in-process LRU shared by all callers. It is only compiled
+//! with the `cache` feature.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+
+use crate::engine::run_udc;
+use crate::phases::p0_normalize::normalize_deal_input;
+use crate::phases::p7_finalize::{compute_input_checksum, sha256_checksum};
+use crate::types::{DealInput, ProductProfile, ProgramProfile, RuleProfile, UdcError, UdcOutput, UdcResult};
+
+/// Maximum number of outputs kept before the least recently used is evicted
+pub const CACHE_CAPACITY: usize = 256;
+
+/// Cache counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that ran the pipeline
+    pub misses: u64,
+    /// Outputs currently cached
+    pub entries: usize,
+    /// Maximum number of cached outputs
+    pub capacity: usize,
+}
+
+/// Least-recently-used map from cache key to output
+#[derive(Debug)]
+struct ResultCache {
+    capacity: usize,
+    entries: HashMap<String, UdcOutput>,
+    /// Keys from least to most recently used
+    order: VecDeque<String>,
+    hits: u64,
+    misses: u64,
+}
+
+impl ResultCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Look up a key, counting the hit or miss and marking it most recent
+    fn get(&mut self, key: &str) -> Option<UdcOutput> {
+        match self.entries.get(key) {
+            Some(output) => {
+                self.hits += 1;
+                let output = output.clone();
+                self.touch(key);
+                Some(output)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: String, output: UdcOutput) {
+        if self.entries.insert(key.clone(), output).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &str) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            if let Some(k) = self.order.remove(pos) {
+                self.order.push_back(k);
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+fn global_cache() -> &'static Mutex<ResultCache> {
+    static CACHE: OnceLock<Mutex<ResultCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(ResultCache::new(CACHE_CAPACITY)))
+}
+
+fn with_cache<T>(f: impl FnOnce(&mut ResultCache) -> T) -> T {
+    // A panic while holding the lock cannot leave the map inconsistent
+    let mut cache = global_cache().lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut cache)
+}
+
+/// Build the cache key: the normalized input's checksum plus a content
+/// checksum of the program and each product profile.
+fn cache_key(
+    input: &DealInput,
+    program_profile: Option<&ProgramProfile>,
+    product_profiles: Option<&[ProductProfile]>,
+) -> UdcResult<String> {
+    let normalized = normalize_deal_input(input.clone())?;
+    let mut key = compute_input_checksum(&normalized.inner)?;
+
+    if let Some(program) = program_profile {
+        key.push_str(&format!("|program:{}", profile_checksum(program)?));
+    }
+    for product in product_profiles.unwrap_or_default() {
+        key.push_str(&format!("|product:{}", profile_checksum(product)?));
+    }
+
+    Ok(key)
+}
+
+fn profile_checksum(profile: &impl Serialize) -> UdcResult<String> {
+    let json = serde_json::to_vec(profile)
+        .map_err(|e| UdcError::serialization(format!("Failed to serialize profile: {}", e)))?;
+    Ok(sha256_checksum(&json))
+}
+
+/// Run UDC, answering repeated calculations from the result cache.
+///
+/// Takes the same arguments as `run_udc`. On a hit the cached output is
+/// cloned, so identical input and profiles return an identical output,
+/// including its ids and audit trace. Errors are not cached.
+pub fn run_udc_cached(
+    input: DealInput,
+    rule_profile: RuleProfile,
+    program_profile: Option<ProgramProfile>,
+    product_profiles: Option<Vec<ProductProfile>>,
+) -> UdcResult<UdcOutput> {
+    let key = cache_key(&input, program_profile.as_ref(), product_profiles.as_deref())?;
+
+    if let Some(output) = with_cache(|cache| cache.get(&key)) {
+        log::debug!("UDC result cache hit: {}", key);
+        return Ok(output);
+    }
+
+    let output = run_udc(input, rule_profile, program_profile, product_profiles)?;
+    with_cache(|cache| cache.insert(key, output.clone()));
+
+    Ok(output)
+}
+
+/// Current result cache counters.
+pub fn cache_stats() -> CacheStats {
+    with_cache(|cache| cache.stats())
+}
+
+/// Drop every cached output and reset the counters.
+pub fn clear_cache() {
+    with_cache(|cache| cache.clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DealType, FinanceParams, StateCode};
+    use rust_decimal_macros::dec;
+
+    fn make_input(vehicle_price: rust_decimal::Decimal) -> DealInput {
+        DealInput {
+            cash_down: dec!(3000),
+            finance_params: Some(FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(DealType::Finance, vehicle_price, StateCode::TX)
+        }
+    }
+
+    fn make_rules() -> RuleProfile {
+        use crate::types::{ProfileMeta, TaxRates, TaxType};
+
+        RuleProfile {
+            state_code: StateCode::TX,
+            mode: DealType::Finance,
+            tax_type: TaxType::Sales,
+            rates: TaxRates {
+                state_rate: dec!(0.0625),
+                ..Default::default()
+            },
+            base_rules: Default::default(),
+            ancillaries: Default::default(),
+            reciprocity: Default::default(),
+            lease_tax_mode: None,
+            lease_sourced_to_garaging: false,
+            tax_cap_reduction: false,
+            lease_monthly_tax_base: Default::default(),
+            lease_trade_credit: true,
+            lease_doc_fee_taxable: None,
+            lease_prepaid_tax_refundable: false,
+            finance_tax_monthly: false,
+            tax_rounding: Default::default(),
+            government_fees: Default::default(),
+            meta: ProfileMeta::default(),
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_returns_identical_output() {
+        // Price unique to this test so concurrent tests share no entries
+        let input = make_input(dec!(31111));
+        let before = cache_stats();
+
+        let first = run_udc_cached(input.clone(), make_rules(), None, None).unwrap();
+        let second = run_udc_cached(input, make_rules(), None, None).unwrap();
+
+        // Output ids are random outside deterministic mode, so equality
+        // means the second result came from the cache
+        assert_eq!(first, second);
+        let after = cache_stats();
+        assert!(after.hits > before.hits);
+        assert!(after.entries >= 1);
+    }
+
+    fn make_program(default_max_reserve: rust_decimal::Decimal) -> ProgramProfile {
+        ProgramProfile {
+            id: "captive".to_string(),
+            name: "Captive Finance".to_string(),
+            lender_code: "CAPTIVE".to_string(),
+            program_type: DealType::Finance,
+            is_captive: true,
+            brand: None,
+            rate_sheet: crate::types::RateSheet {
+                effective_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                expiration_date: None,
+                tiers: vec![],
+                reserve_caps: vec![],
+                default_max_reserve,
+                special_rates: vec![],
+            },
+            structure_rules: Default::default(),
+            fee_config: Default::default(),
+            eligibility: Default::default(),
+            lease_program: None,
+            meta: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_profile_content_change_forces_miss() {
+        let input = make_input(dec!(32222));
+        // Same id and version, different content
+        let v1 = make_program(dec!(0.02));
+        let v2 = make_program(dec!(0.025));
+        assert_eq!((&v1.id, &v1.meta.version), (&v2.id, &v2.meta.version));
+
+        let key_v1 = cache_key(&input, Some(&v1), None).unwrap();
+        let key_v2 = cache_key(&input, Some(&v2), None).unwrap();
+        assert_ne!(key_v1, key_v2);
+        assert_ne!(key_v1, cache_key(&input, None, None).unwrap());
+
+        let mut cache = ResultCache::new(4);
+        let output = run_udc(input, make_rules(), Some(v1), None).unwrap();
+        cache.insert(key_v1.clone(), output);
+        assert!(cache.get(&key_v1).is_some());
+        assert!(cache.get(&key_v2).is_none());
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 1);
+    }
+
+    #[test]
+    fn test_key_uses_resolved_deal_date() {
+        let dateless = make_input(dec!(34444));
+        let mut today = dateless.clone();
+        today.deal_date = Some(dateless.effective_deal_date());
+        let mut yesterday = dateless.clone();
+        yesterday.deal_date = today.deal_date.and_then(|d| d.pred_opt());
+
+        let key = cache_key(&dateless, None, None).unwrap();
+        assert_eq!(key, cache_key(&today, None, None).unwrap());
+        assert_ne!(key, cache_key(&yesterday, None, None).unwrap());
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let output = run_udc(make_input(dec!(33333)), make_rules(), None, None).unwrap();
+        let mut cache = ResultCache::new(2);
+        cache.insert("a".to_string(), output.clone());
+        cache.insert("b".to_string(), output.clone());
+
+        // Touching "a" makes "b" the least recently used
+        assert!(cache.get("a").is_some());
+        cache.insert("c".to_string(), output);
+
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.stats().entries, 2);
+    }
+}
@@ -13,10 +13,17 @@
 //! ```
 
 use crate::types::{
-    DealInput, UdcOutput, UdcResult, UdcError,
-    RuleProfile, ProgramProfile, ProductProfile,
+    DealInput, UdcOutput, UdcResult, UdcError, ValidationResult,
+    RuleProfile, ProgramProfile, ProductProfile, RoundingStrategy, Money, OutputDiff,
+    FinanceQuote, PaymentMatrix, PaymentMatrixCell, PayoffQuote, LeasePayoffQuote, EquityPoint, LeaseStructure, AmortizationEntry, PaymentFrequency, InterestMethod, DayCount, LtvBasis, Rate, StateCode,
+    Jurisdiction, TaxBreakdown, RULE_PROFILE_SCHEMA_VERSION,
 };
 use crate::phases;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
 
 /// Engine configuration options
 #[derive(Debug, Clone, Default)]
@@ -32,6 +39,35 @@ pub struct EngineConfig {
 
     /// Enable parallel phase execution where possible
     pub enable_parallel: bool,
+
+    /// Derive output/deal IDs from the input checksum so identical
+    /// inputs produce identical outputs
+    pub deterministic: bool,
+
+    /// When intermediate money values are rounded; `RoundAtEnd` reconciles
+    /// lease payments with tax-engine-rs
+    pub rounding: RoundingStrategy,
+
+    /// Remove F&I products that are ineligible for the deal type or vehicle
+    /// (they are always flagged with a warning)
+    pub exclude_ineligible_products: bool,
+
+    /// Move a finance term the lender program does not offer to its
+    /// nearest offered term (it is always flagged with a warning)
+    pub snap_to_available_term: bool,
+
+    /// Fill zero title, registration and plate fees from the governing
+    /// state's statutory schedule, with a warning for each fee filled.
+    /// Off by default: a zero fee is charged as zero
+    pub fill_government_fees: bool,
+
+    /// Combined tax rate above which P4 warns of a likely bad rate table
+    /// entry or override (`None` uses the 15% default)
+    pub max_combined_rate: Option<Decimal>,
+
+    /// Vehicle value loan-to-value is measured against when the lender
+    /// program does not set one
+    pub ltv_basis: LtvBasis,
 }
 
 /// Main entry point for UDC calculations.
@@ -42,7 +78,9 @@ pub struct EngineConfig {
 /// # Arguments
 ///
 /// * `input` - The deal input containing all deal parameters
-/// * `rule_profile` - State tax rules for the governing jurisdiction
+/// * `rule_profile` - State tax rules for the governing jurisdiction. They
+///   replace the built-in profile when the deal is governed by their
+///   `state_code`; deals governed elsewhere use the built-in rules
 /// * `program_profile` - Optional lender/lessor program profile
 /// * `product_profiles` - Optional F&I product profiles
 ///
@@ -106,9 +144,6 @@ pub fn run_udc_with_config(
     let start_time = std::time::Instant::now();
 
     // Serialize profiles to JSON for pipeline
-    let rule_json = serde_json::to_string(&rule_profile)
-        .map_err(|e| UdcError::serialization(format!("Failed to serialize rule profile: {}", e)))?;
-
     let program_json = program_profile
         .as_ref()
         .map(|p| serde_json::to_string(p))
@@ -122,11 +157,22 @@ pub fn run_udc_with_config(
         .map_err(|e| UdcError::serialization(format!("Failed to serialize product profiles: {}", e)))?;
 
     // Execute pipeline
-    let output = phases::execute_pipeline(
+    let options = phases::PipelineOptions {
+        deterministic: config.deterministic,
+        rounding: config.rounding,
+        strict_validation: config.strict_validation,
+        exclude_ineligible_products: config.exclude_ineligible_products,
+        snap_to_available_term: config.snap_to_available_term,
+        fill_government_fees: config.fill_government_fees,
+        max_combined_rate: config.max_combined_rate,
+        ltv_basis: config.ltv_basis,
+        rule_profile: Some(rule_profile),
+    };
+    let output = phases::execute_pipeline_with_options(
         input,
-        &rule_json,
         program_json.as_deref(),
         products_json.as_deref(),
+        &options,
     )?;
 
     let duration_ms = start_time.elapsed().as_millis() as u64;
@@ -149,61 +195,98 @@ pub fn run_udc_with_config(
 ///
 /// # Returns
 ///
-/// JSON-encoded `UdcOutput` or error message.
+/// JSON-encoded `UdcOutput` or error message. Use [`run_udc_json_typed`]
+/// to get the `UdcError` (and its [`code`](UdcError::code)) instead.
 pub fn run_udc_json(
     deal_json: &str,
     rules_json: &str,
     program_json: Option<&str>,
     products_json: Option<&str>,
 ) -> Result<String, String> {
+    run_udc_json_typed(deal_json, rules_json, program_json, products_json).map_err(|e| match e {
+        UdcError::Serialization { message } => message,
+        e => format!("Calculation error: {}", e),
+    })
+}
+
+/// Run UDC from JSON inputs, returning a typed error.
+///
+/// Same as [`run_udc_json`], but failures are returned as `UdcError`.
+/// Inputs that fail to parse are reported as `UdcError::Serialization`,
+/// naming the payload and argument that failed (e.g., "Failed to parse
+/// rule profile (rules_json): ...").
+pub fn run_udc_json_typed(
+    deal_json: &str,
+    rules_json: &str,
+    program_json: Option<&str>,
+    products_json: Option<&str>,
+) -> UdcResult<String> {
+    let parse_error = |what: &str, arg: &str, e: serde_json::Error| {
+        UdcError::serialization(format!("Failed to parse {} ({}): {}", what, arg, e))
+    };
+
     // Parse deal input
     let input: DealInput = serde_json::from_str(deal_json)
-        .map_err(|e| format!("Failed to parse deal input: {}", e))?;
+        .map_err(|e| parse_error("deal input", "deal_json", e))?;
 
     // Parse rule profile
     let rules: RuleProfile = serde_json::from_str(rules_json)
-        .map_err(|e| format!("Failed to parse rule profile: {}", e))?;
+        .map_err(|e| parse_error("rule profile", "rules_json", e))?;
 
     // Parse optional program profile
     let program: Option<ProgramProfile> = program_json
         .map(|json| serde_json::from_str(json))
         .transpose()
-        .map_err(|e| format!("Failed to parse program profile: {}", e))?;
+        .map_err(|e| parse_error("program profile", "program_json", e))?;
 
     // Parse optional product profiles
     let products: Option<Vec<ProductProfile>> = products_json
         .map(|json| serde_json::from_str(json))
         .transpose()
-        .map_err(|e| format!("Failed to parse product profiles: {}", e))?;
+        .map_err(|e| parse_error("product profiles", "products_json", e))?;
 
     // Run engine
-    let output = run_udc(input, rules, program, products)
-        .map_err(|e| format!("Calculation error: {}", e))?;
+    let output = run_udc(input, rules, program, products)?;
 
     // Serialize output
     serde_json::to_string(&output)
-        .map_err(|e| format!("Failed to serialize output: {}", e))
+        .map_err(|e| UdcError::serialization(format!("Failed to serialize output: {}", e)))
 }
 
 /// Validate deal input without running full calculation.
 ///
-/// Useful for pre-validation before committing to a deal.
-pub fn validate_deal(input: &DealInput) -> UdcResult<Vec<String>> {
-    // Run P0 normalization to validate
-    let result = phases::normalize_deal_input(input.clone());
+/// Useful for pre-validation before committing to a deal. Blocking
+/// problems are returned as field-level `errors`; unusual but legal
+/// values are returned as `warnings`. Both are always collected so a
+/// form can highlight every offending field at once.
+pub fn validate_deal(input: &DealInput) -> UdcResult<ValidationResult> {
+    let mut result = ValidationResult::with_warnings(phases::collect_warnings(input));
 
-    match result {
-        Ok(normalized) => {
-            // Return any warnings from normalization
-            Ok(vec![]) // Simplified - would return actual warnings
+    // Run P0 normalization to validate
+    match phases::normalize_deal_input(input.clone()) {
+        Ok(_) => {}
+        Err(UdcError::Validation { message, field }) => {
+            result.add_error("VALIDATION_ERROR", field.unwrap_or_default(), message);
         }
-        Err(e) => Err(e),
+        Err(e) => return Err(e),
     }
+
+    Ok(result)
+}
+
+/// Run P0 only: validate and normalize a deal without loading profiles.
+///
+/// Returns the normalized input with its computed values (net trade,
+/// rebate, product and fee totals) so a UI can show them before running
+/// the full calculation. Validation failures are returned as errors.
+pub fn normalize_only(input: DealInput) -> UdcResult<phases::NormalizedDealInput> {
+    phases::normalize_deal_input(input)
 }
 
 /// Calculate tax only (without full structure calculation).
 ///
-/// Useful for tax estimates before finalizing deal terms.
+/// Useful for tax estimates before finalizing deal terms. `rule_profile`
+/// applies as in [`run_udc`].
 pub fn calculate_tax_only(
     input: DealInput,
     rule_profile: RuleProfile,
@@ -213,24 +296,93 @@ pub fn calculate_tax_only(
     let routed = phases::route_deal(normalized)?;
     let jurisdictioned = phases::resolve_jurisdiction(routed)?;
 
-    let rule_json = serde_json::to_string(&rule_profile)
-        .map_err(|e| UdcError::serialization(e.to_string()))?;
-
-    let profiled = phases::load_profiles(jurisdictioned)?;
+    let options = phases::PipelineOptions {
+        rule_profile: Some(rule_profile),
+        ..Default::default()
+    };
+    let profiled = phases::load_profiles_with_options(jurisdictioned, None, &[], &options)?;
     let taxed = phases::calculate_tax(profiled)?;
 
-    // Convert TaxCalculation to TaxBreakdown for API compatibility
-    Ok(crate::types::TaxBreakdown {
+    Ok(tax_breakdown(&taxed))
+}
+
+/// Calculate tax for the same deal in several candidate jurisdictions,
+/// e.g. for a buyer deciding where to register.
+///
+/// The deal is validated and routed (P0-P1) once; each jurisdiction then
+/// resolves and taxes (P2-P4) its own copy as an in-state sale there: the
+/// buyer's home state, county, city and ZIP, the transaction state, and
+/// the garaging state are all set to it, so no other state's tax is
+/// credited.
+///
+/// Rules in `rules_by_state` replace the built-in profile of that
+/// jurisdiction's state; other states use the built-in rules.
+///
+/// # Returns
+/// One result per jurisdiction, in order. A jurisdiction that fails (e.g.
+/// an unknown state code) does not affect the others; a deal that fails
+/// validation fails every entry.
+pub fn calculate_tax_batch(
+    base_deal: DealInput,
+    jurisdictions: Vec<Jurisdiction>,
+    rules_by_state: &HashMap<StateCode, RuleProfile>,
+) -> Vec<UdcResult<TaxBreakdown>> {
+    let routed = match phases::normalize_deal_input(base_deal).and_then(phases::route_deal) {
+        Ok(routed) => routed,
+        Err(e) => return jurisdictions.iter().map(|_| Err(e.clone())).collect(),
+    };
+
+    jurisdictions
+        .iter()
+        .enumerate()
+        .map(|(i, jurisdiction)| {
+            let state: StateCode = jurisdiction.state.trim().to_uppercase().parse().map_err(|_| {
+                UdcError::validation_field(
+                    format!("Unknown state code '{}'", jurisdiction.state),
+                    format!("jurisdictions[{}].state", i),
+                )
+            })?;
+
+            let mut deal = routed.clone();
+            let input = &mut deal.input.inner;
+            input.home_state = state;
+            input.transaction_state = state;
+            input.garaging_state = None;
+            input.customer.county = jurisdiction.county.clone();
+            input.customer.city = jurisdiction.city.clone();
+            input.customer.zip_code = Some(jurisdiction.zip.clone()).filter(|zip| !zip.is_empty());
+
+            let options = phases::PipelineOptions {
+                rule_profile: rules_by_state.get(&state).cloned(),
+                ..Default::default()
+            };
+            let jurisdictioned = phases::resolve_jurisdiction(deal)?;
+            let profiled = phases::load_profiles_with_options(jurisdictioned, None, &[], &options)?;
+            Ok(tax_breakdown(&phases::calculate_tax(profiled)?))
+        })
+        .collect()
+}
+
+/// Convert a P4 TaxCalculation to a TaxBreakdown for API compatibility
+fn tax_breakdown(taxed: &phases::TaxComputedDeal) -> TaxBreakdown {
+    TaxBreakdown {
         net_tax: crate::types::Money::new(taxed.tax.net_tax),
         gross_tax: crate::types::Money::new(taxed.tax.primary_tax),
         reciprocity_credit: crate::types::Money::new(taxed.tax.reciprocity_credit),
         effective_rate: crate::types::Rate::from_decimal(taxed.tax.effective_rate),
+        effective_rate_percent: crate::types::Rate::from_decimal(taxed.tax.effective_rate).to_string(),
         tax_base: crate::types::Money::new(taxed.tax.tax_base),
         special_tax_type: taxed.tax.special_tax.as_ref().map(|s| s.name.clone()),
         trade_in_applied: taxed.tax.base_breakdown.trade_credit_applied > rust_decimal_macros::dec!(0),
         trade_in_credit_used: crate::types::Money::new(taxed.tax.base_breakdown.trade_credit_applied),
         rebates_applied: taxed.tax.base_breakdown.rebates_applied > rust_decimal_macros::dec!(0),
         rebate_amount_used: crate::types::Money::new(taxed.tax.base_breakdown.rebates_applied),
+        selling_price: crate::types::Money::new(taxed.tax.base_breakdown.selling_price),
+        taxable_fees: crate::types::Money::new(taxed.tax.base_breakdown.taxable_fees),
+        taxable_products: crate::types::Money::new(taxed.tax.base_breakdown.taxable_products),
+        exemption_applied: crate::types::Money::new(taxed.tax.base_breakdown.exemption_applied),
+        base_adjustments: crate::types::Money::new(taxed.tax.base_breakdown.adjustments),
+        base_cap_applied: taxed.tax.base_breakdown.cap_applied.clone(),
         line_items: taxed.tax.components.iter().map(|c| crate::types::TaxLineItem {
             name: c.name.clone(),
             level: match c.level {
@@ -241,33 +393,364 @@ pub fn calculate_tax_only(
                 crate::phases::p4_tax_cipher::TaxLevel::Special => "Special".to_string(),
             },
             rate: crate::types::Rate::from_decimal(c.rate),
+            rate_percent: crate::types::Rate::from_decimal(c.rate).to_string(),
             taxable_base: crate::types::Money::new(c.base),
             tax_amount: crate::types::Money::new(c.amount),
             is_credit: false,
+            self_collected: c.self_collected,
         }).collect(),
+    }
+}
+
+/// Calculate loan math only, for a known amount financed.
+///
+/// Skips P0-P4 entirely: no price, fees, tax or jurisdiction. P5 builds a
+/// bare loan structure for the principal, giving the same payment, total
+/// of payments and finance charge the full pipeline would. An actuarial
+/// amortization schedule is included when `first_payment_date` is given.
+pub fn calculate_finance_only(
+    amount_financed: Decimal,
+    apr: Decimal,
+    term: u32,
+    frequency: PaymentFrequency,
+    first_payment_date: Option<NaiveDate>,
+) -> UdcResult<FinanceQuote> {
+    if amount_financed < Decimal::ZERO {
+        return Err(UdcError::validation_field(
+            "Amount financed cannot be negative",
+            "amount_financed",
+        ));
+    }
+    if term == 0 {
+        return Err(UdcError::validation_field("Term must be positive", "term"));
+    }
+    if apr < Decimal::ZERO {
+        return Err(UdcError::validation_field("APR cannot be negative", "apr"));
+    }
+
+    let structure = phases::p5_structure::build_loan_structure(amount_financed, apr, term, frequency)?;
+
+    let amortization_schedule = first_payment_date.map(|date| {
+        let first_payment_date = if frequency == PaymentFrequency::SemiMonthly {
+            phases::p6_cashflow::next_semi_monthly_date(date)
+        } else {
+            date
+        };
+        phases::p6_cashflow::amortize_finance(
+            &structure,
+            InterestMethod::Actuarial,
+            DayCount::default(),
+            first_payment_date,
+        )
+    });
+
+    Ok(FinanceQuote {
+        amount_financed: Money::new(structure.amount_financed),
+        apr: Rate::from_decimal(apr),
+        term_months: term as u16,
+        payment_frequency: frequency,
+        number_of_payments: structure.number_of_payments as u16,
+        payment: Money::new(structure.monthly_payment),
+        total_of_payments: Money::new(structure.total_of_payments),
+        finance_charge: Money::new(structure.finance_charge),
+        amortization_schedule,
+    })
+}
+
+/// Quote monthly payments across a grid of terms and APRs.
+///
+/// Each cell is the [`calculate_finance_only`] quote for that term and APR,
+/// trimmed to the payment, total of payments and finance charge so the
+/// matrix stays compact. Cells are ordered by term, then APR.
+pub fn generate_payment_matrix(
+    amount_financed: Decimal,
+    terms: &[u32],
+    aprs: &[Decimal],
+) -> UdcResult<PaymentMatrix> {
+    let mut cells = Vec::with_capacity(terms.len() * aprs.len());
+    for &term in terms {
+        for &apr in aprs {
+            let quote = calculate_finance_only(amount_financed, apr, term, PaymentFrequency::Monthly, None)?;
+            cells.push(PaymentMatrixCell {
+                term_months: quote.term_months,
+                apr: quote.apr,
+                monthly_payment: quote.payment,
+                total_of_payments: quote.total_of_payments,
+                finance_charge: quote.finance_charge,
+            });
+        }
+    }
+    cells.sort_by(|a, b| a.term_months.cmp(&b.term_months).then(a.apr.cmp(&b.apr)));
+
+    Ok(PaymentMatrix {
+        amount_financed: Money::new(amount_financed.round_dp(2)),
+        cells,
+    })
+}
+
+/// Quote an early payoff with the unearned interest rebated.
+///
+/// `schedule` is the contract's amortization schedule and `interest_method`
+/// its `FinanceParams::interest_method`. The gross payoff is every payment
+/// after `as_of_payment`; the rebate depends on the method:
+///
+/// ```text
+/// Rule of 78s:            rebate = F * r(r+1) / (n(n+1))
+/// Actuarial/simple daily: rebate = scheduled interest after as_of_payment
+/// ```
+///
+/// where `F` is the total finance charge, `n` the number of payments and
+/// `r` those remaining. The Rule of 78s earns interest early, so its rebate
+/// is smaller and its net payoff higher than actuarial at the same point.
+pub fn payoff_with_rebate(
+    schedule: &[AmortizationEntry],
+    interest_method: InterestMethod,
+    as_of_payment: u16,
+) -> UdcResult<PayoffQuote> {
+    let number_of_payments = schedule.len();
+    if as_of_payment as usize > number_of_payments {
+        return Err(UdcError::validation_field(
+            format!("Payoff after payment {} is past the {}-payment term", as_of_payment, number_of_payments),
+            "as_of_payment",
+        ));
+    }
+
+    let remaining = &schedule[as_of_payment as usize..];
+    let gross_remaining: Decimal = remaining.iter().map(|e| e.payment_amount.as_decimal()).sum();
+
+    let interest_rebate = match interest_method {
+        InterestMethod::RuleOf78s => {
+            let finance_charge: Decimal = schedule.iter().map(|e| e.interest.as_decimal()).sum();
+            let n = Decimal::from(number_of_payments as u64);
+            let r = Decimal::from(remaining.len() as u64);
+            if n.is_zero() {
+                Decimal::ZERO
+            } else {
+                (finance_charge * r * (r + Decimal::ONE) / (n * (n + Decimal::ONE))).round_dp(2)
+            }
+        }
+        InterestMethod::Actuarial | InterestMethod::SimpleDaily => {
+            remaining.iter().map(|e| e.interest.as_decimal()).sum()
+        }
+    };
+
+    Ok(PayoffQuote {
+        as_of_payment,
+        remaining_payments: remaining.len() as u16,
+        interest_method,
+        gross_remaining: Money::new(gross_remaining),
+        interest_rebate: Money::new(interest_rebate),
+        net_payoff: Money::new(gross_remaining - interest_rebate),
     })
 }
 
+/// Project equity month by month: vehicle value less loan balance.
+///
+/// `schedule` is the contract's amortization schedule. `vehicle_value_curve`
+/// is `(month, value)` points; the value between points is interpolated
+/// linearly and held flat before the first and after the last point. Use
+/// [`linear_value_curve`] for a straight-line default. An empty schedule or
+/// curve projects nothing.
+///
+/// The first point is month 0 (the amount financed against the value at
+/// signing); see [`break_even_month`] for when equity turns positive.
+pub fn equity_projection(
+    schedule: &[AmortizationEntry],
+    vehicle_value_curve: &[(u32, Decimal)],
+) -> Vec<EquityPoint> {
+    let Some(first) = schedule.first() else {
+        return Vec::new();
+    };
+    if vehicle_value_curve.is_empty() {
+        return Vec::new();
+    }
+
+    let mut curve = vehicle_value_curve.to_vec();
+    curve.sort_by_key(|&(month, _)| month);
+
+    let starting_balance = first.remaining_balance + first.principal;
+    std::iter::once((0, starting_balance))
+        .chain(schedule.iter().map(|e| (u32::from(e.payment_number), e.remaining_balance)))
+        .map(|(month, loan_balance)| {
+            let vehicle_value = projected_value(&curve, month).round_dp(2);
+            EquityPoint {
+                month,
+                loan_balance,
+                vehicle_value: Money::new(vehicle_value),
+                equity: Money::new(vehicle_value - loan_balance.as_decimal()),
+            }
+        })
+        .collect()
+}
+
+/// First month of a projection with zero or positive equity, if any.
+pub fn break_even_month(points: &[EquityPoint]) -> Option<u32> {
+    points.iter().find(|p| !p.equity.as_decimal().is_sign_negative()).map(|p| p.month)
+}
+
+/// Straight-line value curve: `value` at signing, losing `annual_rate` of
+/// it each year (floored at zero) through `months`.
+pub fn linear_value_curve(value: Decimal, annual_rate: Decimal, months: u32) -> Vec<(u32, Decimal)> {
+    let lost = annual_rate * Decimal::from(months) / Decimal::from(12);
+    vec![(0, value), (months, (value * (Decimal::ONE - lost)).max(Decimal::ZERO))]
+}
+
+/// Value on a sorted, non-empty curve at `month`.
+fn projected_value(curve: &[(u32, Decimal)], month: u32) -> Decimal {
+    let after = curve.partition_point(|&(m, _)| m <= month);
+    match (after.checked_sub(1).map(|i| curve[i]), curve.get(after)) {
+        (Some((_, value)), None) => value,
+        (None, Some(&(_, value))) => value,
+        (Some((m0, v0)), Some(&(m1, v1))) => {
+            v0 + (v1 - v0) * Decimal::from(month - m0) / Decimal::from(m1 - m0)
+        }
+        (None, None) => Decimal::ZERO,
+    }
+}
+
+/// Quote an early termination payoff of a lease after a given payment.
+///
+/// The lease balance is the residual plus the depreciation not yet paid
+/// down, taken straight-line over the term. Tax prepaid on the lease,
+/// whether collected at signing or capitalized, is refunded for the
+/// unexpired share of the term where the governing state's `rules` allow
+/// it (`lease_prepaid_tax_refundable`):
+///
+/// ```text
+/// gross  = residual + depreciation * r / n
+/// refund = prepaid_tax * r / n
+/// net    = gross - refund
+/// ```
+///
+/// where `n` is the term and `r` the payments remaining. Monthly-taxed
+/// leases have no prepaid tax, so nothing is refunded.
+pub fn calculate_lease_payoff(
+    lease: &LeaseStructure,
+    rules: &RuleProfile,
+    as_of_payment: u16,
+) -> UdcResult<LeasePayoffQuote> {
+    let term = lease.term_months;
+    if term == 0 {
+        return Err(UdcError::validation_field("Lease term must be positive", "term_months"));
+    }
+    if as_of_payment > term {
+        return Err(UdcError::validation_field(
+            format!("Payoff after payment {} is past the {}-month term", as_of_payment, term),
+            "as_of_payment",
+        ));
+    }
+
+    let remaining_payments = term - as_of_payment;
+    let unexpired = Decimal::from(remaining_payments) / Decimal::from(term);
+
+    let unpaid_depreciation = (lease.depreciation.as_decimal() * unexpired).round_dp(2);
+    let gross_payoff = lease.residual_value.as_decimal() + unpaid_depreciation;
+
+    let prepaid_tax = lease.upfront_tax.map_or(Decimal::ZERO, |t| t.as_decimal())
+        + lease.capitalized_tax.as_decimal();
+    let prepaid_tax_refund = if rules.lease_prepaid_tax_refundable {
+        (prepaid_tax * unexpired).round_dp(2)
+    } else {
+        Decimal::ZERO
+    };
+
+    Ok(LeasePayoffQuote {
+        as_of_payment,
+        remaining_payments,
+        residual_value: lease.residual_value,
+        unpaid_depreciation: Money::new(unpaid_depreciation),
+        gross_payoff: Money::new(gross_payoff),
+        prepaid_tax: Money::new(prepaid_tax),
+        prepaid_tax_refund: Money::new(prepaid_tax_refund),
+        net_payoff: Money::new(gross_payoff - prepaid_tax_refund),
+    })
+}
+
+/// Compare two calculated scenarios (e.g., 60 vs 72 months).
+///
+/// Returns the `b - a` deltas in the figures a desk manager presents side
+/// by side, plus the names of every compared field that changed.
+pub fn diff_outputs(a: &UdcOutput, b: &UdcOutput) -> OutputDiff {
+    let payment = |o: &UdcOutput| o.monthly_payment().unwrap_or(Money::ZERO);
+
+    let figures = [
+        ("monthly_payment", payment(a), payment(b)),
+        ("amount_financed", a.amount_financed(), b.amount_financed()),
+        ("total_of_payments", a.total_of_payments(), b.total_of_payments()),
+        ("net_tax", a.tax_breakdown.net_tax, b.tax_breakdown.net_tax),
+        ("due_at_signing", a.due_at_signing(), b.due_at_signing()),
+    ];
+
+    let mut changed_fields = Vec::new();
+    if a.deal_type != b.deal_type {
+        changed_fields.push("deal_type".to_string());
+    }
+    if a.term_months() != b.term_months() {
+        changed_fields.push("term_months".to_string());
+    }
+    for (name, before, after) in &figures {
+        if before != after {
+            changed_fields.push(name.to_string());
+        }
+    }
+
+    let [monthly_payment, amount_financed, total_of_payments, net_tax, due_at_signing] =
+        figures.map(|(_, before, after)| after - before);
+    OutputDiff {
+        monthly_payment,
+        amount_financed,
+        total_of_payments,
+        net_tax,
+        due_at_signing,
+        changed_fields,
+    }
+}
+
 /// Get engine version information.
 pub fn engine_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
 /// Get engine build information.
+///
+/// Clients can check `supported_states` to tell whether their state has a
+/// hand-tuned rule profile or falls back to the generic default.
 pub fn engine_info() -> EngineInfo {
+    let (supported_states, default_profile_states) = StateCode::iter()
+        .partition(|&state| phases::p3_profiles::has_tuned_profile(state));
+
     EngineInfo {
         version: env!("CARGO_PKG_VERSION"),
         name: env!("CARGO_PKG_NAME"),
         features: get_enabled_features(),
+        supported_states,
+        default_profile_states,
+        rule_profile_schema_version: RULE_PROFILE_SCHEMA_VERSION,
     }
 }
 
 /// Engine build information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EngineInfo {
+    /// Engine version
     pub version: &'static str,
+    /// Crate name
     pub name: &'static str,
+    /// Optional features compiled in
     pub features: Vec<&'static str>,
+    /// States with hand-tuned built-in rule profiles
+    pub supported_states: Vec<StateCode>,
+    /// States that use the generic default rule profile
+    pub default_profile_states: Vec<StateCode>,
+    /// `RuleProfile` JSON schema version this engine reads
+    pub rule_profile_schema_version: &'static str,
+}
+
+impl EngineInfo {
+    /// Whether a state has a hand-tuned rule profile.
+    pub fn is_fully_supported(&self, state: StateCode) -> bool {
+        self.supported_states.contains(&state)
+    }
 }
 
 fn get_enabled_features() -> Vec<&'static str> {
@@ -282,41 +765,29 @@ fn get_enabled_features() -> Vec<&'static str> {
     #[cfg(feature = "strict_validation")]
     features.push("strict_validation");
 
+    #[cfg(feature = "parallel")]
+    features.push("parallel");
+
+    #[cfg(feature = "cache")]
+    features.push("cache");
+
+    #[cfg(feature = "metrics")]
+    features.push("metrics");
+
     features
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{DealFees, CustomerInfo, FinanceParams, StateCode};
+    use crate::types::{DealFees, FinanceParams, PaymentFrequency, InterestMethod, StateCode};
     use rust_decimal_macros::dec;
 
     fn make_test_input() -> DealInput {
         DealInput {
-            deal_type: crate::types::DealType::Finance,
-            vehicle_price: dec!(30000),
-            trade_in_value: None,
-            trade_in_payoff: None,
             cash_down: dec!(3000),
-            rebates: vec![],
-            products: vec![],
-            fees: DealFees::default(),
-            home_state: StateCode::TX,
-            transaction_state: StateCode::TX,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: Some(FinanceParams {
-                term_months: 60,
-                apr: dec!(0.0599),
-                lender_id: None,
-                buy_rate: None,
-                max_reserve_points: None,
-                deferred_first_payment: false,
-                days_to_first_payment: None,
-            }),
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
+            finance_params: Some(FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(crate::types::DealType::Finance, dec!(30000), StateCode::TX)
         }
     }
 
@@ -330,12 +801,980 @@ mod tests {
     fn test_engine_info() {
         let info = engine_info();
         assert_eq!(info.name, "udc-engine");
+        assert_eq!(info.features.contains(&"parallel"), cfg!(feature = "parallel"));
     }
 
     #[test]
     fn test_validate_deal() {
         let input = make_test_input();
-        let result = validate_deal(&input);
-        assert!(result.is_ok());
+        let result = validate_deal(&input).unwrap();
+        assert!(result.valid);
+        assert!(result.errors.is_empty());
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_validate_deal_errors_and_warnings() {
+        let mut input = make_test_input();
+        input.vehicle_price = dec!(0);
+        if let Some(params) = input.finance_params.as_mut() {
+            params.apr = dec!(0.2499);
+        }
+
+        let result = validate_deal(&input).unwrap();
+
+        assert!(!result.valid);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].field, "vehicle_price");
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].code, "HIGH_APR");
+        assert_eq!(result.warnings[0].field, "finance_params.apr");
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["errors"][0]["field"], "vehicle_price");
+        assert_eq!(json["warnings"][0]["field"], "finance_params.apr");
+    }
+
+    #[test]
+    fn test_normalize_only() {
+        use crate::types::{Rebate, RebateType};
+
+        let mut input = make_test_input();
+        input.trade_in_value = Some(dec!(12000));
+        input.trade_in_payoff = Some(dec!(14500.499));
+        input.rebates = vec![Rebate {
+            id: "R1".to_string(),
+            name: "Customer Cash".to_string(),
+            amount: dec!(1000),
+            rebate_type: RebateType::Manufacturer,
+            reduces_tax_basis: true,
+            reduces_cap_cost: true,
+            program_code: None,
+        }];
+
+        let normalized = normalize_only(input).unwrap();
+        // Payoff rounded to the cent before netting
+        assert_eq!(normalized.net_trade, dec!(-2500.50));
+        assert!(normalized.has_negative_equity);
+        assert_eq!(normalized.total_rebates, dec!(1000));
+
+        let json = serde_json::to_value(&normalized).unwrap();
+        assert_eq!(json["net_trade"], "-2500.50");
+        assert_eq!(json["total_rebates"], "1000");
+        assert_eq!(json["inner"]["vehicle_price"], "30000");
+    }
+
+    #[test]
+    fn test_missing_finance_params_is_validation_error() {
+        let mut input = make_test_input();
+        input.finance_params = None;
+
+        let err = run_udc(input, make_test_rules(), None, None).unwrap_err();
+        assert_eq!(err.code(), "VALIDATION");
+        assert!(matches!(err, UdcError::Validation { field: Some(ref f), .. } if f == "finance_params"));
+    }
+
+    #[test]
+    fn test_normalize_only_validation_error() {
+        let mut input = make_test_input();
+        input.cash_down = dec!(-500);
+
+        let err = normalize_only(input).unwrap_err();
+        assert_eq!(err.code(), "VALIDATION");
+        assert!(matches!(err, UdcError::Validation { field: Some(ref f), .. } if f == "cash_down"));
+    }
+
+    fn make_test_rules() -> RuleProfile {
+        use crate::types::{TaxType, TaxRates, ProfileMeta};
+
+        RuleProfile {
+            state_code: StateCode::TX,
+            mode: crate::types::DealType::Finance,
+            tax_type: TaxType::Sales,
+            rates: TaxRates {
+                state_rate: dec!(0.0625),
+                ..Default::default()
+            },
+            base_rules: Default::default(),
+            ancillaries: Default::default(),
+            reciprocity: Default::default(),
+            lease_tax_mode: None,
+            lease_sourced_to_garaging: false,
+            tax_cap_reduction: false,
+            lease_monthly_tax_base: Default::default(),
+            lease_trade_credit: true,
+            lease_doc_fee_taxable: None,
+            lease_prepaid_tax_refundable: false,
+            finance_tax_monthly: false,
+            tax_rounding: Default::default(),
+            government_fees: Default::default(),
+            meta: ProfileMeta::default(),
+        }
+    }
+
+    #[test]
+    fn test_run_udc_json_parse_errors() {
+        let rules_json = serde_json::to_string(&make_test_rules()).unwrap();
+
+        let message = run_udc_json("{", &rules_json, None, None).unwrap_err();
+        assert!(message.starts_with("Failed to parse deal input (deal_json):"), "{}", message);
+
+        let err = run_udc_json_typed("{", &rules_json, None, None).unwrap_err();
+        assert_eq!(err.code(), "SERIALIZATION");
+    }
+
+    #[test]
+    fn test_tax_base_breakdown_sums_to_base() {
+        let mut input = make_test_input();
+        input.trade_in_value = Some(dec!(8000));
+        input.trade_in_payoff = Some(dec!(0));
+
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        let tax = &output.tax_breakdown;
+
+        assert_eq!(tax.selling_price.as_decimal(), dec!(30000));
+        assert!(tax.trade_in_credit_used.as_decimal() > dec!(0));
+        let sum = tax.selling_price.as_decimal()
+            + tax.taxable_fees.as_decimal()
+            + tax.taxable_products.as_decimal()
+            - tax.trade_in_credit_used.as_decimal()
+            - tax.rebate_amount_used.as_decimal()
+            - tax.exemption_applied.as_decimal()
+            + tax.base_adjustments.as_decimal();
+        assert_eq!(sum, tax.tax_base.as_decimal());
+    }
+
+    #[test]
+    fn test_fee_itemization_matches_taxable_fees() {
+        use crate::types::OtherFee;
+
+        let mut input = make_test_input();
+        input.fees = DealFees {
+            doc_fee: Some(dec!(150)),
+            title_fee: dec!(33),
+            registration_fee: dec!(75),
+            dealer_handling_fee: dec!(200),
+            other_fees: vec![
+                OtherFee {
+                    name: "Etch".to_string(),
+                    code: Some("ETCH".to_string()),
+                    amount: dec!(199),
+                    dealer_fee: true,
+                    taxable: false,
+                    capitalize: false,
+                },
+                OtherFee {
+                    name: "Courier".to_string(),
+                    code: None,
+                    amount: dec!(25),
+                    dealer_fee: true,
+                    taxable: true,
+                    capitalize: false,
+                },
+            ],
+            ..Default::default()
+        };
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        let lines = &output.fee_itemization;
+
+        assert_eq!(lines.len(), 6);
+        assert!(!lines.iter().find(|l| l.code == "ETCH").unwrap().taxable);
+        assert!(lines.iter().find(|l| l.code == "Courier").unwrap().taxable);
+        assert!(lines.iter().all(|l| !l.capitalized));
+
+        let taxable_sum: rust_decimal::Decimal = lines.iter()
+            .filter(|l| l.taxable)
+            .map(|l| l.amount.as_decimal())
+            .sum();
+        assert_eq!(taxable_sum, output.tax_breakdown.taxable_fees.as_decimal());
+        assert!(taxable_sum > dec!(0));
+    }
+
+    #[test]
+    fn test_credit_tier_from_score_in_output() {
+        let mut input = make_test_input();
+        input.customer.credit_score = Some(710);
+
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        assert_eq!(output.credit_tier, Some(crate::types::CreditTier::Tier2));
+
+        let output = run_udc(make_test_input(), make_test_rules(), None, None).unwrap();
+        assert_eq!(output.credit_tier, None);
+    }
+
+    #[test]
+    fn test_tax_rates_serialize_with_display_percent() {
+        let mut input = make_test_input();
+        input.home_state = StateCode::CA;
+        input.transaction_state = StateCode::CA;
+
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        let json = serde_json::to_value(&output).unwrap();
+        let tax = &json["tax_breakdown"];
+        assert_eq!(tax["effective_rate"], "0.0825");
+        assert_eq!(tax["effective_rate_percent"], "8.25%");
+
+        let line_items = tax["line_items"].as_array().unwrap();
+        assert!(!line_items.is_empty());
+        for (item, line) in line_items.iter().zip(&output.tax_breakdown.line_items) {
+            assert_eq!(item["rate_percent"], line.rate.to_string());
+            assert!(item["rate"].is_string());
+        }
+    }
+
+    #[test]
+    fn test_tax_batch_across_states() {
+        let mut input = make_test_input();
+        input.trade_in_value = Some(dec!(10000));
+        input.trade_in_payoff = Some(dec!(4000));
+
+        let jurisdictions = vec![
+            Jurisdiction::state_only("TX"),
+            Jurisdiction::state_only("CA"),
+            Jurisdiction::state_only("ga"),
+            Jurisdiction::state_only("ZZ"),
+        ];
+        let results = calculate_tax_batch(input, jurisdictions, &HashMap::new());
+        assert_eq!(results.len(), 4);
+
+        // TX credits the trade; CA taxes the full price
+        let tx = results[0].as_ref().unwrap();
+        let ca = results[1].as_ref().unwrap();
+        assert!(tx.trade_in_applied);
+        assert!(!ca.trade_in_applied);
+        assert_eq!(ca.trade_in_credit_used, Money::ZERO);
+        assert!(ca.tax_base > tx.tax_base);
+
+        // GA collects TAVT instead of sales tax
+        let ga = results[2].as_ref().unwrap();
+        assert!(ga.special_tax_type.as_deref().unwrap().contains("TAVT"), "{:?}", ga.special_tax_type);
+
+        // An unknown state fails on its own
+        let err = results[3].as_ref().unwrap_err();
+        assert!(err.to_string().contains("Unknown state code 'ZZ'"));
+
+        // Each jurisdiction is an in-state sale: the TX dealer's state is
+        // replaced too, so CA credits no TX tax and matches a CA sale
+        assert_eq!(ca.reciprocity_credit, Money::ZERO);
+        let mut in_ca = make_test_input();
+        in_ca.trade_in_value = Some(dec!(10000));
+        in_ca.trade_in_payoff = Some(dec!(4000));
+        in_ca.home_state = StateCode::CA;
+        in_ca.transaction_state = StateCode::CA;
+        let in_ca = calculate_tax_batch(in_ca, vec![Jurisdiction::state_only("CA")], &HashMap::new());
+        assert_eq!(in_ca[0].as_ref().unwrap().net_tax, ca.net_tax);
+    }
+
+    #[test]
+    fn test_tax_batch_uses_supplied_state_rules() {
+        let mut rules = make_test_rules();
+        rules.rates.default_combined_rate = dec!(0.10);
+        let rules_by_state = HashMap::from([(StateCode::TX, rules)]);
+
+        let jurisdictions = vec![Jurisdiction::state_only("TX")];
+        let builtin = calculate_tax_batch(make_test_input(), jurisdictions.clone(), &HashMap::new());
+        let supplied = calculate_tax_batch(make_test_input(), jurisdictions, &rules_by_state);
+
+        let builtin = builtin[0].as_ref().unwrap();
+        let supplied = supplied[0].as_ref().unwrap();
+        assert_eq!(supplied.effective_rate, Rate::from_decimal(dec!(0.10)));
+        assert!(supplied.net_tax > builtin.net_tax);
+    }
+
+    #[test]
+    fn test_run_udc_uses_supplied_state_rules() {
+        let mut rules = make_test_rules();
+        rules.rates.default_combined_rate = dec!(0.10);
+
+        let output = run_udc(make_test_input(), rules.clone(), None, None).unwrap();
+        assert_eq!(output.tax_breakdown.effective_rate, Rate::from_decimal(dec!(0.10)));
+        let tax = calculate_tax_only(make_test_input(), rules.clone()).unwrap();
+        assert_eq!(tax.net_tax, output.tax_breakdown.net_tax);
+
+        // Rules for another state leave the governing state's built-in rules
+        rules.state_code = StateCode::OK;
+        let builtin = run_udc(make_test_input(), rules, None, None).unwrap();
+        assert!(builtin.tax_breakdown.net_tax < output.tax_breakdown.net_tax);
+    }
+
+    #[test]
+    fn test_trade_breakdown_positive_equity() {
+        let mut input = make_test_input();
+        input.trade_in_value = Some(dec!(10000));
+        input.trade_in_payoff = Some(dec!(4000));
+        input.trade_in_acv = Some(dec!(9500));
+
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        let trade = output.trade_breakdown.unwrap();
+
+        assert_eq!(trade.gross_allowance.as_decimal(), dec!(10000));
+        assert_eq!(trade.acv.as_decimal(), dec!(9500));
+        assert_eq!(trade.payoff.as_decimal(), dec!(4000));
+        assert_eq!(trade.net_equity.as_decimal(), dec!(6000));
+        assert_eq!(trade.negative_equity.as_decimal(), dec!(0));
+    }
+
+    #[test]
+    fn test_trade_breakdown_underwater() {
+        let mut input = make_test_input();
+        input.trade_in_value = Some(dec!(8000));
+        input.trade_in_payoff = Some(dec!(11000));
+
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        let trade = output.trade_breakdown.unwrap();
+
+        assert_eq!(trade.acv.as_decimal(), dec!(8000));
+        assert_eq!(trade.net_equity.as_decimal(), dec!(0));
+        assert_eq!(trade.negative_equity.as_decimal(), dec!(3000));
+
+        // No trade, no breakdown
+        let output = run_udc(make_test_input(), make_test_rules(), None, None).unwrap();
+        assert!(output.trade_breakdown.is_none());
+    }
+
+    #[test]
+    fn test_deterministic_ids() {
+        let config = EngineConfig {
+            deterministic: true,
+            ..Default::default()
+        };
+        let mut input = make_test_input();
+        input.deal_date = chrono::NaiveDate::from_ymd_opt(2024, 6, 15);
+
+        let first = run_udc_with_config(input.clone(), make_test_rules(), None, None, config.clone()).unwrap();
+        let second = run_udc_with_config(input.clone(), make_test_rules(), None, None, config.clone()).unwrap();
+
+        assert_eq!(first.output_id, second.output_id);
+        assert_eq!(first.deal_id, second.deal_id);
+        assert_eq!(first.audit_trace.input_checksum, second.audit_trace.input_checksum);
+        assert_eq!(first.audit_trace.output_checksum, second.audit_trace.output_checksum);
+        assert_eq!(first.calculated_at.to_rfc3339(), "2024-06-15T00:00:00+00:00");
+        assert_eq!(first.audit_trace.calculated_at, first.calculated_at);
+
+        // The output checksum hashes the output itself
+        let mut unsummed = first.clone();
+        unsummed.audit_trace.output_checksum.clear();
+        #[cfg(feature = "metrics")]
+        {
+            unsummed.audit_trace.phase_timings = Default::default();
+        }
+        assert_eq!(
+            first.audit_trace.output_checksum,
+            crate::phases::p7_finalize::compute_output_checksum(&unsummed).unwrap()
+        );
+
+        // The same input computed against a different program profile is a
+        // different output
+        let program = ProgramProfile {
+            id: "captive".to_string(),
+            name: "Captive Finance".to_string(),
+            lender_code: "CAPTIVE".to_string(),
+            program_type: crate::types::DealType::Finance,
+            is_captive: true,
+            brand: None,
+            rate_sheet: crate::types::RateSheet {
+                effective_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                expiration_date: None,
+                tiers: vec![],
+                reserve_caps: vec![],
+                default_max_reserve: dec!(0),
+                special_rates: vec![],
+            },
+            structure_rules: Default::default(),
+            fee_config: Default::default(),
+            eligibility: Default::default(),
+            lease_program: None,
+            meta: Default::default(),
+        };
+        let programmed = run_udc_with_config(input.clone(), make_test_rules(), Some(program), None, config.clone()).unwrap();
+        assert_eq!(programmed.audit_trace.input_checksum, first.audit_trace.input_checksum);
+        assert_ne!(programmed.output_id, first.output_id);
+        assert_ne!(programmed.deal_id, first.deal_id);
+
+        // A caller-supplied deal_id is preserved
+        let deal_id = uuid::Uuid::new_v4();
+        input.deal_id = Some(deal_id);
+        let third = run_udc_with_config(input, make_test_rules(), None, None, config).unwrap();
+        assert_eq!(third.deal_id, deal_id);
+        assert_ne!(third.output_id, first.output_id);
+    }
+
+    #[test]
+    fn test_random_ids_by_default() {
+        let first = run_udc(make_test_input(), make_test_rules(), None, None).unwrap();
+        let second = run_udc(make_test_input(), make_test_rules(), None, None).unwrap();
+
+        assert_ne!(first.output_id, second.output_id);
+    }
+
+    #[test]
+    fn test_diff_outputs_longer_term() {
+        let short = run_udc(make_test_input(), make_test_rules(), None, None).unwrap();
+
+        let mut input = make_test_input();
+        input.finance_params.as_mut().unwrap().term_months = 72;
+        let long = run_udc(input, make_test_rules(), None, None).unwrap();
+
+        let diff = diff_outputs(&short, &long);
+        assert!(diff.monthly_payment.as_decimal() < dec!(0));
+        assert!(diff.total_of_payments.as_decimal() > dec!(0));
+        assert_eq!(diff.amount_financed, Money::ZERO);
+        assert_eq!(diff.net_tax, Money::ZERO);
+        assert_eq!(
+            diff.changed_fields,
+            vec!["term_months", "monthly_payment", "total_of_payments"]
+        );
+
+        let same = diff_outputs(&short, &short);
+        assert!(same.changed_fields.is_empty());
+    }
+
+    fn schedule_for(method: InterestMethod) -> Vec<AmortizationEntry> {
+        let mut input = make_test_input();
+        input.finance_params.as_mut().unwrap().interest_method = method;
+        run_udc(input, make_test_rules(), None, None).unwrap().amortization_schedule.unwrap()
+    }
+
+    #[test]
+    fn test_rule_of_78s_net_payoff_exceeds_actuarial() {
+        let actuarial = payoff_with_rebate(&schedule_for(InterestMethod::Actuarial), InterestMethod::Actuarial, 24).unwrap();
+        let rule_of_78s = payoff_with_rebate(&schedule_for(InterestMethod::RuleOf78s), InterestMethod::RuleOf78s, 24).unwrap();
+
+        // Same contract and payment; only the final rounding adjustment differs
+        assert_eq!(actuarial.remaining_payments, 36);
+        let gross_gap = actuarial.gross_remaining - rule_of_78s.gross_remaining;
+        assert!(gross_gap.as_decimal().abs() < dec!(1));
+
+        assert!(rule_of_78s.interest_rebate < actuarial.interest_rebate);
+        assert!(rule_of_78s.net_payoff > actuarial.net_payoff,
+            "Rule of 78s payoff {} should exceed actuarial {}",
+            rule_of_78s.net_payoff, actuarial.net_payoff);
+        for quote in [&actuarial, &rule_of_78s] {
+            assert_eq!(quote.net_payoff, quote.gross_remaining - quote.interest_rebate);
+        }
+    }
+
+    #[test]
+    fn test_payoff_rebate_bounds() {
+        let schedule = schedule_for(InterestMethod::Actuarial);
+        let finance_charge: Decimal = schedule.iter().map(|e| e.interest.as_decimal()).sum();
+
+        // Before the first payment the whole finance charge is unearned
+        let upfront = payoff_with_rebate(&schedule, InterestMethod::RuleOf78s, 0).unwrap();
+        assert_eq!(upfront.interest_rebate.as_decimal(), finance_charge);
+        let principal: Decimal = schedule.iter().map(|e| e.principal.as_decimal()).sum();
+        assert_eq!(upfront.net_payoff.as_decimal(), principal);
+
+        let paid_off = payoff_with_rebate(&schedule, InterestMethod::Actuarial, 60).unwrap();
+        assert_eq!(paid_off.net_payoff, Money::ZERO);
+
+        let err = payoff_with_rebate(&schedule, InterestMethod::Actuarial, 61).unwrap_err();
+        assert!(err.to_string().contains("past the 60-payment term"));
+    }
+
+    #[test]
+    fn test_equity_projection_break_even() {
+        let curve = linear_value_curve(dec!(30000), dec!(0.12), 60);
+        let standard = equity_projection(&schedule_for(InterestMethod::Actuarial), &curve);
+
+        // $7,000 of negative equity from the trade rolled into the loan
+        let mut input = make_test_input();
+        input.trade_in_value = Some(dec!(5000));
+        input.trade_in_payoff = Some(dec!(12000));
+        let schedule = run_udc(input, make_test_rules(), None, None).unwrap().amortization_schedule.unwrap();
+        let upside_down = equity_projection(&schedule, &curve);
+
+        assert_eq!(standard.len(), 61);
+        assert_eq!(standard[0].month, 0);
+        assert_eq!(standard[0].vehicle_value.as_decimal(), dec!(30000));
+        assert_eq!(standard[60].vehicle_value.as_decimal(), dec!(12000));
+        assert_eq!(standard[60].loan_balance, Money::ZERO);
+        for point in standard.iter().chain(&upside_down) {
+            assert_eq!(point.equity, point.vehicle_value - point.loan_balance);
+        }
+
+        assert!(upside_down[0].equity < standard[0].equity);
+        let standard_month = break_even_month(&standard).expect("standard deal breaks even");
+        let upside_down_month = break_even_month(&upside_down).expect("paid off by term end");
+        assert!(upside_down_month > standard_month,
+            "negative equity breaks even at {} vs {}", upside_down_month, standard_month);
+    }
+
+    #[test]
+    fn test_equity_projection_interpolates_curve() {
+        let schedule = schedule_for(InterestMethod::Actuarial);
+
+        // Steeper first-year drop, then flat past the last point
+        let curve = [(12, dec!(24000)), (0, dec!(30000)), (36, dec!(18000))];
+        let points = equity_projection(&schedule, &curve);
+        assert_eq!(points[6].vehicle_value.as_decimal(), dec!(27000));
+        assert_eq!(points[24].vehicle_value.as_decimal(), dec!(21000));
+        assert_eq!(points[48].vehicle_value.as_decimal(), dec!(18000));
+
+        assert!(equity_projection(&schedule, &[]).is_empty());
+        assert!(equity_projection(&[], &curve).is_empty());
+    }
+
+    /// 36-month NY lease; NY taxes the cap cost upfront. Returns the lease
+    /// and the built-in NY rules it was taxed under
+    fn make_upfront_tax_lease(capitalize_upfront_tax: bool) -> (LeaseStructure, RuleProfile) {
+        let mut input = make_test_input();
+        input.deal_type = crate::types::DealType::Lease;
+        input.home_state = StateCode::NY;
+        input.transaction_state = StateCode::NY;
+        input.finance_params = None;
+        input.lease_params = Some(crate::types::LeaseParams {
+            capitalize_upfront_tax,
+            ..crate::types::LeaseParams::new(36, crate::types::MoneyFactor::new(dec!(0.00125)), dec!(0.55), 12000)
+        });
+
+        let normalized = phases::p0_normalize::normalize_deal_input(input.clone()).unwrap();
+        let resolved = phases::p2_jurisdiction::resolve_jurisdiction(phases::p1_mode_routing::route_deal(normalized).unwrap()).unwrap();
+        let rules = phases::p3_profiles::load_profiles(resolved).unwrap().profiles.primary_rules;
+        let lease = run_udc(input, make_test_rules(), None, None).unwrap().lease_structure.unwrap();
+        (lease, rules)
+    }
+
+    #[test]
+    fn test_lease_payoff_refunds_unearned_prepaid_tax() {
+        let (lease, rules) = make_upfront_tax_lease(false);
+        let upfront_tax = lease.upfront_tax.expect("NY collects lease tax upfront").as_decimal();
+        assert!(rules.lease_prepaid_tax_refundable);
+
+        let non_refunding = RuleProfile { lease_prepaid_tax_refundable: false, ..rules.clone() };
+        let without_refund = calculate_lease_payoff(&lease, &non_refunding, 12).unwrap();
+        assert_eq!(without_refund.remaining_payments, 24);
+        assert_eq!(without_refund.prepaid_tax.as_decimal(), upfront_tax);
+        assert_eq!(without_refund.prepaid_tax_refund, Money::ZERO);
+        assert_eq!(without_refund.net_payoff, without_refund.gross_payoff);
+
+        // Two-thirds of the term is unexpired, so two-thirds of the tax comes back
+        let with_refund = calculate_lease_payoff(&lease, &rules, 12).unwrap();
+        let refund = (upfront_tax * dec!(24) / dec!(36)).round_dp(2);
+        assert_eq!(with_refund.prepaid_tax_refund.as_decimal(), refund);
+        assert_eq!(with_refund.gross_payoff, without_refund.gross_payoff);
+        assert_eq!(with_refund.net_payoff.as_decimal(), without_refund.net_payoff.as_decimal() - refund);
+    }
+
+    #[test]
+    fn test_lease_payoff_refunds_capitalized_tax() {
+        let (lease, rules) = make_upfront_tax_lease(true);
+        assert!(lease.upfront_tax.is_none());
+        let capitalized_tax = lease.capitalized_tax.as_decimal();
+        assert!(capitalized_tax > dec!(0));
+
+        let quote = calculate_lease_payoff(&lease, &rules, 0).unwrap();
+        assert_eq!(quote.prepaid_tax_refund.as_decimal(), capitalized_tax);
+        assert_eq!(quote.gross_payoff.as_decimal(),
+            lease.residual_value.as_decimal() + lease.depreciation.as_decimal());
+
+        // At term end there is nothing left to refund or pay down
+        let at_term = calculate_lease_payoff(&lease, &rules, 36).unwrap();
+        assert_eq!(at_term.prepaid_tax_refund, Money::ZERO);
+        assert_eq!(at_term.net_payoff, lease.residual_value);
+
+        let err = calculate_lease_payoff(&lease, &rules, 37).unwrap_err();
+        assert!(err.to_string().contains("past the 36-month term"));
+    }
+
+    #[test]
+    fn test_finance_only_matches_pipeline() {
+        for frequency in [PaymentFrequency::Monthly, PaymentFrequency::BiWeekly] {
+            let mut input = make_test_input();
+            input.deal_date = chrono::NaiveDate::from_ymd_opt(2024, 6, 15);
+            input.first_payment_date = chrono::NaiveDate::from_ymd_opt(2024, 7, 15);
+            input.finance_params.as_mut().unwrap().payment_frequency = frequency;
+
+            let output = run_udc(input.clone(), make_test_rules(), None, None).unwrap();
+            let finance = output.finance_structure.unwrap();
+
+            let quote = calculate_finance_only(
+                finance.amount_financed.as_decimal(),
+                dec!(0.0599),
+                60,
+                frequency,
+                input.first_payment_date,
+            )
+            .unwrap();
+
+            assert_eq!(quote.amount_financed, finance.amount_financed);
+            assert_eq!(quote.number_of_payments, finance.number_of_payments);
+            assert_eq!(quote.payment, finance.monthly_payment);
+            assert_eq!(quote.total_of_payments, finance.total_of_payments);
+            assert_eq!(quote.finance_charge, finance.finance_charge);
+            assert_eq!(quote.amortization_schedule, output.amortization_schedule);
+        }
+    }
+
+    #[test]
+    fn test_finance_only_without_schedule() {
+        let quote = calculate_finance_only(dec!(25000), dec!(0), 50, PaymentFrequency::Monthly, None).unwrap();
+
+        assert_eq!(quote.payment.as_decimal(), dec!(500));
+        assert_eq!(quote.finance_charge, Money::ZERO);
+        assert!(quote.amortization_schedule.is_none());
+
+        let result = calculate_finance_only(dec!(-1), dec!(0.05), 60, PaymentFrequency::Monthly, None);
+        assert!(matches!(result, Err(UdcError::Validation { .. })));
+    }
+
+    #[test]
+    fn test_payment_matrix_cells_reconcile() {
+        // Legacy tax-engine-rs generate_payment_matrix(23456.78, 6.99, [72, 36, 60]),
+        // whose -1/0/+1/+2 point variations give 5.99%..8.99%:
+        // (term, APR %, payment, total of payments, finance charge)
+        let legacy = [
+            (36, dec!(5.99), dec!(713.49), dec!(25685.64), dec!(2228.86)),
+            (36, dec!(6.99), dec!(724.17), dec!(26070.12), dec!(2613.34)),
+            (36, dec!(7.99), dec!(734.94), dec!(26457.84), dec!(3001.06)),
+            (36, dec!(8.99), dec!(745.81), dec!(26849.16), dec!(3392.38)),
+            (60, dec!(5.99), dec!(453.38), dec!(27202.80), dec!(3746.02)),
+            (60, dec!(6.99), dec!(464.36), dec!(27861.60), dec!(4404.82)),
+            (60, dec!(7.99), dec!(475.51), dec!(28530.60), dec!(5073.82)),
+            (60, dec!(8.99), dec!(486.81), dec!(29208.60), dec!(5751.82)),
+            (72, dec!(5.99), dec!(388.64), dec!(27982.08), dec!(4525.30)),
+            (72, dec!(6.99), dec!(399.80), dec!(28785.60), dec!(5328.82)),
+            (72, dec!(7.99), dec!(411.16), dec!(29603.52), dec!(6146.74)),
+            (72, dec!(8.99), dec!(422.70), dec!(30434.40), dec!(6977.62)),
+        ];
+
+        let matrix = generate_payment_matrix(
+            dec!(23456.78),
+            &[72, 36, 60],
+            &[dec!(0.0899), dec!(0.0599), dec!(0.0799), dec!(0.0699)],
+        )
+        .unwrap();
+
+        // Ordered by term, then APR, as legacy orders its scenarios
+        assert_eq!(matrix.cells.len(), legacy.len());
+        for (cell, &(term, apr_percent, payment, total_of_payments, finance_charge)) in
+            matrix.cells.iter().zip(&legacy)
+        {
+            assert_eq!((cell.term_months, cell.apr), (term, Rate::from_percentage(apr_percent)));
+            assert_eq!(cell.monthly_payment.as_decimal(), payment, "{} mo at {}%", term, apr_percent);
+            assert_eq!(cell.total_of_payments.as_decimal(), total_of_payments, "{} mo at {}%", term, apr_percent);
+            assert_eq!(cell.finance_charge.as_decimal(), finance_charge, "{} mo at {}%", term, apr_percent);
+        }
+
+        // At 0% the payment and total match legacy (651.58 x 36), but the
+        // rounding cents are not a finance charge (legacy reports 0.10)
+        let zero = generate_payment_matrix(dec!(23456.78), &[36], &[dec!(0)]).unwrap();
+        let cell = &zero.cells[0];
+        assert_eq!(cell.monthly_payment.as_decimal(), dec!(651.58));
+        assert_eq!(cell.total_of_payments.as_decimal(), dec!(23456.88));
+        assert_eq!(cell.finance_charge, Money::ZERO);
+
+        assert!(generate_payment_matrix(dec!(25000), &[0], &[dec!(0.05)]).is_err());
+    }
+
+    #[test]
+    fn test_odd_days_interest_by_day_count() {
+        // 45-day first period: Jan 1 to Feb 15, of which Jan 1 to Jan 15 is odd
+        let odd_days_interest = |day_count: DayCount| {
+            let mut input = make_test_input();
+            input.deal_date = NaiveDate::from_ymd_opt(2024, 1, 1);
+            input.first_payment_date = NaiveDate::from_ymd_opt(2024, 2, 15);
+            input.finance_params.as_mut().unwrap().day_count = day_count;
+            input.finance_params.as_mut().unwrap().charge_odd_days_interest = true;
+
+            let output = run_udc(input, make_test_rules(), None, None).unwrap();
+            let finance = output.finance_structure.unwrap();
+            (finance.amount_financed.as_decimal(), finance.odd_days_interest.as_decimal())
+        };
+
+        let (amount_financed, actual_365) = odd_days_interest(DayCount::Actual365);
+        let (_, thirty_360) = odd_days_interest(DayCount::Thirty360);
+
+        assert_eq!(actual_365, (amount_financed * dec!(0.0599) / dec!(365) * dec!(14)).round_dp(2));
+        assert_eq!(thirty_360, (amount_financed * dec!(0.0599) / dec!(360) * dec!(14)).round_dp(2));
+        assert!(thirty_360 > actual_365);
+
+        // Not charged, so not disclosed, unless the contract opts in
+        let mut input = make_test_input();
+        input.deal_date = NaiveDate::from_ymd_opt(2024, 1, 1);
+        input.first_payment_date = NaiveDate::from_ymd_opt(2024, 2, 15);
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        assert_eq!(output.finance_structure.unwrap().odd_days_interest, Money::ZERO);
+    }
+
+    #[test]
+    fn test_simple_daily_periods_run_due_date_to_due_date() {
+        // 45-day first period: Jan 1 to Feb 15
+        let mut input = make_test_input();
+        input.deal_date = NaiveDate::from_ymd_opt(2024, 1, 1);
+        input.first_payment_date = NaiveDate::from_ymd_opt(2024, 2, 15);
+        input.finance_params.as_mut().unwrap().interest_method = InterestMethod::SimpleDaily;
+
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        let amount_financed = output.finance_structure.unwrap().amount_financed.as_decimal();
+        let schedule = output.amortization_schedule.unwrap();
+        let per_diem = |balance: Decimal, days: u32| (balance * dec!(0.0599) / dec!(365) * Decimal::from(days)).round_dp(2);
+
+        // The first period is Jan 15 to Feb 15 like any other; the odd days
+        // before it are not in the schedule
+        assert_eq!(schedule[0].interest.as_decimal(), per_diem(amount_financed, 31));
+        // Feb 15 to Mar 15, 2024
+        assert_eq!(schedule[1].interest.as_decimal(), per_diem(schedule[0].remaining_balance.as_decimal(), 29));
+    }
+
+    #[test]
+    fn test_tx_finance_state_disclosures() {
+        let output = run_udc(make_test_input(), make_test_rules(), None, None).unwrap();
+        let codes: Vec<&str> = output.disclosures.iter().map(|d| d.code.as_str()).collect();
+
+        assert!(codes.contains(&"TILA-BOX"));
+        assert!(codes.contains(&"TX-DOC-FEE"));
+        assert!(codes.contains(&"TX-RISC-NOTICE"));
+
+        // Sorted by category, then code: federal TILA before the TX notices
+        let keys: Vec<(&str, &str)> = output.disclosures
+            .iter()
+            .map(|d| (d.category.as_str(), d.code.as_str()))
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+        assert_eq!(output.disclosures[0].category, "federal");
+    }
+
+    #[test]
+    fn test_mid_month_finance_prorates_first_payment() {
+        let run = |charge_odd_days_interest: bool| {
+            let mut input = make_test_input();
+            input.deal_date = NaiveDate::from_ymd_opt(2024, 1, 20);
+            input.contract_start_date = input.deal_date;
+            input.first_payment_date = NaiveDate::from_ymd_opt(2024, 3, 1);
+            input.finance_params.as_mut().unwrap().charge_odd_days_interest = charge_odd_days_interest;
+            run_udc(input, make_test_rules(), None, None).unwrap()
+        };
+
+        let prorated = run(true);
+        let regular = run(false);
+
+        // Jan 20 to Feb 1, when the first regular period starts
+        let finance = prorated.finance_structure.as_ref().unwrap();
+        let odd_days = (finance.amount_financed.as_decimal() * dec!(0.0599) / dec!(365) * dec!(12)).round_dp(2);
+        assert_eq!(finance.odd_days_interest.as_decimal(), odd_days);
+
+        let first = &prorated.amortization_schedule.as_ref().unwrap()[0];
+        let unprorated = &regular.amortization_schedule.as_ref().unwrap()[0];
+        assert_eq!(first.interest.as_decimal(), unprorated.interest.as_decimal() + odd_days);
+        assert_eq!(first.payment_amount.as_decimal(), finance.monthly_payment.as_decimal() + odd_days);
+        assert_eq!(first.principal, unprorated.principal);
+    }
+
+    #[test]
+    fn test_odd_days_interest_reconciles_with_tila_totals() {
+        let run = |charge_odd_days_interest: bool| {
+            let mut input = make_test_input();
+            input.deal_date = NaiveDate::from_ymd_opt(2024, 1, 20);
+            input.first_payment_date = NaiveDate::from_ymd_opt(2024, 3, 1);
+            input.finance_params.as_mut().unwrap().charge_odd_days_interest = charge_odd_days_interest;
+            run_udc(input, make_test_rules(), None, None).unwrap()
+        };
+        let charged = run(true);
+        let free = run(false);
+        let finance = charged.finance_structure.as_ref().unwrap();
+        let baseline = free.finance_structure.as_ref().unwrap();
+        let odd_days = finance.odd_days_interest;
+        assert!(odd_days > Money::ZERO);
+
+        // Carried into every TILA total
+        assert_eq!(finance.finance_charge, baseline.finance_charge + odd_days);
+        assert_eq!(finance.total_of_payments, baseline.total_of_payments + odd_days);
+        assert_eq!(finance.total_sale_price, baseline.total_sale_price + odd_days);
+        assert_eq!(
+            finance.finance_charge.as_decimal(),
+            finance.total_of_payments.as_decimal() - finance.amount_financed.as_decimal()
+        );
+
+        // And paid with the first payment
+        let paid = |output: &UdcOutput| -> Decimal {
+            output.amortization_schedule.as_ref().unwrap().iter().map(|e| e.payment_amount.as_decimal()).sum()
+        };
+        assert_eq!(paid(&charged) - paid(&free), odd_days.as_decimal());
+
+        let tila = charged.disclosures.iter().find(|d| d.code == "TILA-BOX").unwrap();
+        assert!(tila.text.contains(&format!("FINANCE CHARGE: ${:.2}", finance.finance_charge.as_decimal())), "{}", tila.text);
+        assert!(tila.text.contains(&format!("Total of Payments: ${:.2}", finance.total_of_payments.as_decimal())), "{}", tila.text);
+    }
+
+    #[test]
+    fn test_deferral_fee_is_prepaid_finance_charge() {
+        let run = |deferral_fee: Option<Decimal>, deferred: bool| {
+            let mut input = make_test_input();
+            input.deal_date = NaiveDate::from_ymd_opt(2024, 1, 15);
+            input.first_payment_date = NaiveDate::from_ymd_opt(2024, 2, 15);
+            let params = input.finance_params.as_mut().unwrap();
+            params.deferred_first_payment = deferred;
+            params.deferral_fee = deferral_fee;
+            run_udc(input, make_test_rules(), None, None).unwrap()
+        };
+
+        let base = run(None, true);
+        let deferred = run(Some(dec!(395)), true);
+        let (base, with_fee) = (
+            base.finance_structure.as_ref().unwrap(),
+            deferred.finance_structure.as_ref().unwrap(),
+        );
+
+        // The loan itself is untouched
+        assert_eq!(with_fee.amount_financed, base.amount_financed);
+        assert_eq!(with_fee.monthly_payment, base.monthly_payment);
+        assert_eq!(with_fee.total_of_payments, base.total_of_payments);
+        assert_eq!(deferred.amortization_schedule, run(None, true).amortization_schedule);
+
+        // The fee is finance charge paid at signing
+        assert_eq!(with_fee.prepaid_finance_charge.as_decimal(), dec!(395));
+        assert_eq!(with_fee.finance_charge.as_decimal(), base.finance_charge.as_decimal() + dec!(395));
+        assert_eq!(with_fee.due_at_signing.as_decimal(), base.due_at_signing.as_decimal() + dec!(395));
+        assert_eq!(with_fee.total_sale_price.as_decimal(), base.total_sale_price.as_decimal() + dec!(395));
+        assert_eq!(deferred.due_at_signing(), with_fee.due_at_signing);
+
+        // The disclosed APR is the rate at which the payments are worth the
+        // amount financed net of the fee
+        assert_eq!(base.disclosed_apr.unwrap().as_decimal(), dec!(0.0599));
+        let apr = with_fee.disclosed_apr.unwrap().as_decimal();
+        assert!(apr > dec!(0.0599));
+        let monthly_rate = apr / dec!(12);
+        let (present_value, _) = deferred.amortization_schedule.as_ref().unwrap().iter().fold(
+            (dec!(0), dec!(1)),
+            |(value, discount), entry| {
+                let discount = discount / (dec!(1) + monthly_rate);
+                (value + entry.payment_amount.as_decimal() * discount, discount)
+            },
+        );
+        let net_amount_financed = with_fee.amount_financed.as_decimal() - dec!(395);
+        assert!((present_value - net_amount_financed).abs() < dec!(1), "{} vs {}", present_value, net_amount_financed);
+
+        // TILA discloses the amount financed net of the fee
+        let tila = deferred.disclosures.iter().find(|d| d.code == "TILA-BOX").unwrap();
+        assert!(tila.text.contains(&format!("Amount Financed: ${:.2}", net_amount_financed)), "{}", tila.text);
+    }
+
+    #[test]
+    fn test_deferral_fee_requires_deferred_first_payment() {
+        let mut input = make_test_input();
+        input.finance_params.as_mut().unwrap().deferral_fee = Some(dec!(395));
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+        let finance = output.finance_structure.as_ref().unwrap();
+
+        assert_eq!(finance.prepaid_finance_charge.as_decimal(), dec!(0));
+        assert_eq!(finance.disclosed_apr.unwrap().as_decimal(), dec!(0.0599));
+        assert!(output.validation.warnings.iter().any(|w| w.code == "DEFERRAL_FEE_IGNORED"));
+    }
+
+    #[test]
+    fn test_negative_deferral_fee_rejected() {
+        let mut input = make_test_input();
+        input.finance_params.as_mut().unwrap().deferral_fee = Some(dec!(-50));
+        match run_udc(input, make_test_rules(), None, None) {
+            Err(UdcError::Validation { field, .. }) => {
+                assert_eq!(field.as_deref(), Some("finance_params.deferral_fee"));
+            }
+            other => panic!("expected validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_disclosed_apr_over_actual_dates() {
+        let run = |deal_date: (u32, u32), charge_odd_days: bool| {
+            let mut input = make_test_input();
+            input.deal_date = NaiveDate::from_ymd_opt(2024, deal_date.0, deal_date.1);
+            input.finance_params.as_mut().unwrap().charge_odd_days_interest = charge_odd_days;
+            input.first_payment_date = NaiveDate::from_ymd_opt(2024, 2, 15);
+            run_udc(input, make_test_rules(), None, None).unwrap()
+        };
+        let disclosed = |output: &UdcOutput| {
+            output.finance_structure.as_ref().unwrap().disclosed_apr.unwrap().as_decimal()
+        };
+
+        // A full first period discloses the contract rate
+        let regular = run((1, 15), false);
+        assert_eq!(disclosed(&regular), dec!(0.0599));
+
+        // A 45-day first period with an ordinary first payment gives the
+        // customer half a month of credit for free
+        let long_first = run((1, 1), false);
+        assert!(disclosed(&long_first) < dec!(0.0599));
+        let tila = long_first.disclosures.iter().find(|d| d.code == "TILA-BOX").unwrap();
+        let percent = disclosed(&long_first) * dec!(100);
+        assert!(tila.text.contains(&format!("{:.2}%", percent)), "{}", tila.text);
+
+        // Charging the odd days in the first payment restores the rate
+        let prorated = run((1, 1), true);
+        assert!((disclosed(&prorated) - dec!(0.0599)).abs() < dec!(0.0005));
+        assert!(disclosed(&prorated) > disclosed(&long_first));
+    }
+
+    #[test]
+    fn test_engine_info_supported_states() {
+        let info = engine_info();
+
+        for state in [StateCode::TX, StateCode::CA, StateCode::GA, StateCode::NC, StateCode::WV] {
+            assert!(info.is_fully_supported(state), "{:?} should be fully supported", state);
+        }
+        assert!(!info.is_fully_supported(StateCode::AL));
+        assert!(info.default_profile_states.contains(&StateCode::AL));
+        assert!(info.supported_states.iter().all(|s| !info.default_profile_states.contains(s)));
+
+        assert_eq!(info.rule_profile_schema_version, RULE_PROFILE_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_default_profile_warning_in_output() {
+        let has_warning = |state: StateCode| {
+            let mut input = make_test_input();
+            input.home_state = state;
+            input.transaction_state = state;
+            let output = run_udc(input, make_test_rules(), None, None).unwrap();
+            output.validation.warnings.iter().any(|w| w.code == "DEFAULT_RULE_PROFILE")
+        };
+
+        assert!(has_warning(StateCode::OK));
+        assert!(!has_warning(StateCode::TX));
+    }
+
+    #[test]
+    fn test_over_capitalized_finance_deal() {
+        let mut input = make_test_input();
+        input.cash_down = dec!(40000);
+        input.first_payment_date = NaiveDate::from_ymd_opt(2024, 3, 1);
+        let output = run_udc(input, make_test_rules(), None, None).unwrap();
+
+        let finance = output.finance_structure.as_ref().unwrap();
+        assert_eq!(finance.amount_financed, Money::ZERO);
+        assert_eq!(finance.monthly_payment, Money::ZERO);
+        assert_eq!(finance.number_of_payments, 0);
+        assert_eq!(finance.finance_charge, Money::ZERO);
+        assert!(output.amortization_schedule.as_ref().unwrap().is_empty());
+
+        // The deal only uses as much of the down payment as it costs
+        let gross = finance.selling_price + finance.taxable_fees + finance.non_taxable_fees + finance.sales_tax;
+        assert_eq!(finance.total_sale_price, gross);
+
+        let warning = output.validation.warnings.iter()
+            .find(|w| w.code == "ZERO_AMOUNT_FINANCED")
+            .expect("zero amount financed warning");
+        let excess = Money::new(dec!(40000)) - gross;
+        assert!(warning.message.contains(&excess.as_decimal().to_string()));
+
+        let schedule = output.disclosures.iter().find(|d| d.code == "TILA-SCHEDULE").unwrap();
+        assert!(schedule.text.starts_with("No payments are due"));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_phase_timings_recorded() {
+        let output = run_udc(make_test_input(), make_test_rules(), None, None).unwrap();
+        let timings = &output.audit_trace.phase_timings;
+
+        let phases: Vec<&str> = timings.phases.iter().map(|t| t.phase.as_str()).collect();
+        assert_eq!(phases, ["P0", "P1", "P2", "P3", "P4", "P5", "P6", "P7"]);
+        for phase in &phases {
+            assert!(timings.get(phase).is_some());
+        }
+        assert_eq!(timings.total_micros(), timings.phases.iter().map(|t| t.micros).sum::<u64>());
+        assert!(engine_info().features.contains(&"metrics"));
     }
 }
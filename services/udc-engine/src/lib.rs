@@ -80,15 +80,19 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 // Core modules
 pub mod types;
 pub mod phases;
+pub mod algorithms;
 pub mod engine;
+pub mod solver;
 pub mod wasm;
+#[cfg(feature = "cache")]
+pub mod cache;
 
 // Re-export commonly used types
 pub use types::{
     // Deal types
     DealInput, DealType, DealFees, CustomerInfo, CustomerType,
-    FinanceParams, LeaseParams,
-    Rebate, RebateSource,
+    FinanceParams, PaymentFrequency, InterestMethod, DayCount, FundingAllocation, FundingPortion, LeaseParams, LeaseFeeCapitalization,
+    ScheduledTaxRate, Rebate, RebateSource,
     TradeIn, Fee, Vehicle, VehicleCondition,
     FiProduct, Jurisdiction,
 
@@ -96,33 +100,46 @@ pub use types::{
     Money, Rate, MoneyFactor,
 
     // Profile types
-    RuleProfile, ProgramProfile, ProductProfile,
+    RuleProfile, ProgramProfile, ProductProfile, DealerProfile, GovernmentFeeSchedule,
     TaxRateComponent, TradeInTaxTreatment, RebateTaxTreatment,
-    LeaseTaxMode, TaxStackingMode, ReciprocityType,
+    LeaseTaxMode, LeaseMonthlyTaxBase, TaxRounding, TaxStackingMode, ReciprocityType,
     RoundingMode, FeeCapitalization,
 
     // Output types
     UdcOutput, TaxBreakdown, TaxLineItem,
-    FinanceStructure, LeaseStructure, CashStructure,
-    AmortizationEntry, Disclosure, AuditTrace, AuditEntry,
-    ValidationResult, ValidationWarning,
+    FinanceStructure, FinanceQuote, PaymentMatrix, PaymentMatrixCell, PayoffQuote, LeasePayoffQuote, EquityPoint, LeaseStructure, CashStructure, ProfitAnalysis, TradeBreakdown,
+    AmortizationEntry, AmountFinancedLineItem, CapCostLineItem, FundingSplit, Disclosure, AuditTrace, AuditEntry,
+    ValidationResult, ValidationWarning, FieldError, OutputDiff, DealSummary,
 
     // Common types
     StateCode, TaxType, CreditTier,
-    UdcError, UdcResult, DecimalExt,
+    UdcError, UdcResult, DecimalExt, RoundingStrategy,
 };
 
 // Re-export engine functions
 pub use engine::{
-    run_udc, run_udc_with_config, run_udc_json,
-    validate_deal, calculate_tax_only,
+    run_udc, run_udc_with_config, run_udc_json, run_udc_json_typed,
+    validate_deal, normalize_only, calculate_tax_only, calculate_tax_batch, calculate_finance_only, generate_payment_matrix,
+    payoff_with_rebate, calculate_lease_payoff, equity_projection, break_even_month, linear_value_curve, diff_outputs,
     engine_version, engine_info, EngineConfig, EngineInfo,
 };
 
+// Re-export the result cache when feature is enabled
+#[cfg(feature = "cache")]
+pub use cache::{run_udc_cached, cache_stats, clear_cache, CacheStats};
+
+// Re-export phase timing types when feature is enabled
+#[cfg(feature = "metrics")]
+pub use types::{PhaseTimings, PhaseTiming};
+
+// Re-export solvers
+pub use solver::{solve_cash_down_for_payment, solve_term_for_payment};
+
 // Re-export WASM bindings when feature is enabled
 #[cfg(feature = "wasm")]
 pub use wasm::{
-    run_udc_wasm, validate_deal_wasm, calculate_tax_wasm,
+    run_udc_wasm, validate_deal_wasm, normalize_deal_wasm, calculate_tax_wasm, calculate_tax_batch_wasm,
+    calculate_finance_wasm, generate_payment_matrix_wasm, calc_payment_wasm, calc_apr_wasm, diff_outputs_wasm,
     get_version, get_engine_info,
 };
 
@@ -23,18 +23,33 @@ pub mod p4_tax_cipher;
 pub mod p5_structure;
 pub mod p6_cashflow;
 pub mod p7_finalize;
+pub mod state_disclosures;
 
 // Re-export main types and functions
-pub use p0_normalize::{normalize_deal_input, NormalizedDealInput};
+pub use p0_normalize::{normalize_deal_input, collect_warnings, NormalizedDealInput};
 pub use p1_mode_routing::{route_deal, RoutedDeal, CalculationMode};
 pub use p2_jurisdiction::{resolve_jurisdiction, JurisdictionContext, JurisdictionResolvedDeal};
-pub use p3_profiles::{load_profiles, ProfileLoadedDeal};
-pub use p4_tax_cipher::{calculate_tax, TaxComputedDeal};
-pub use p5_structure::{build_structure, StructuredDeal};
+pub use p3_profiles::{load_profiles, load_profiles_with_program, load_profiles_with_options, ProfileLoadedDeal};
+pub use p4_tax_cipher::{calculate_tax, calculate_tax_with_options, TaxComputedDeal};
+pub use p5_structure::{build_structure, build_structure_with_options, StructuredDeal};
 pub use p6_cashflow::{generate_cashflow, CashflowDeal};
-pub use p7_finalize::{finalize_output, FinalizedDeal};
+pub use p7_finalize::{finalize_output, finalize_output_with_options, FinalizedDeal};
+pub use state_disclosures::{StateDisclosureRegistry, StateDisclosure, DisclosureContext};
 
-use crate::types::{DealInput, UdcOutput, UdcResult, UdcError};
+use crate::types::{DealInput, RoundingStrategy, UdcOutput, UdcResult, UdcError};
+
+/// Run one phase, recording its wall-clock duration in `$timings` when the
+/// `metrics` feature is enabled. Without the feature this is just `$call`.
+macro_rules! timed_phase {
+    ($timings:ident, $phase:literal, $call:expr) => {{
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let result = $call;
+        #[cfg(feature = "metrics")]
+        $timings.record($phase, started.elapsed());
+        result
+    }};
+}
 
 /// Execute the complete UDC pipeline.
 ///
@@ -42,7 +57,7 @@ use crate::types::{DealInput, UdcOutput, UdcResult, UdcError};
 ///
 /// # Arguments
 /// * `input` - The raw deal input
-/// * `rule_profile_json` - JSON string containing state tax rules
+/// * `rule_profile_json` - Reserved; state rules come from the built-in profiles
 /// * `program_profile_json` - Optional JSON string containing lender/lessor program
 /// * `product_profiles_json` - Optional JSON string containing F&I product profiles
 ///
@@ -50,37 +65,106 @@ use crate::types::{DealInput, UdcOutput, UdcResult, UdcError};
 /// Complete UdcOutput with tax breakdown, payment details, disclosures, and audit trace.
 pub fn execute_pipeline(
     input: DealInput,
-    rule_profile_json: &str,
+    _rule_profile_json: &str,
+    program_profile_json: Option<&str>,
+    product_profiles_json: Option<&str>,
+) -> UdcResult<UdcOutput> {
+    execute_pipeline_with_options(
+        input,
+        program_profile_json,
+        product_profiles_json,
+        &PipelineOptions::default(),
+    )
+}
+
+/// Execute the pipeline with explicit [`PipelineOptions`].
+pub fn execute_pipeline_with_options(
+    input: DealInput,
     program_profile_json: Option<&str>,
     product_profiles_json: Option<&str>,
+    options: &PipelineOptions,
 ) -> UdcResult<UdcOutput> {
+    #[cfg(feature = "metrics")]
+    let mut timings = crate::types::PhaseTimings::default();
+
     // P0: Normalize
-    let normalized = normalize_deal_input(input)?;
+    let normalized = timed_phase!(timings, "P0", normalize_deal_input(input))?;
 
     // P1: Route
-    let routed = route_deal(normalized)?;
+    let routed = timed_phase!(timings, "P1", route_deal(normalized))?;
 
     // P2: Jurisdiction
-    let jurisdictioned = resolve_jurisdiction(routed)?;
+    let jurisdictioned = timed_phase!(timings, "P2", resolve_jurisdiction(routed))?;
 
-    // P3: Profiles (uses internal default profile loading - JSON params are reserved for future use)
-    let profiled = load_profiles(jurisdictioned)?;
+    // P3: Profiles (state rules use internal default profile loading unless
+    // the options carry the governing state's; the program profile supplies
+    // special rates and product profiles gate product eligibility)
+    let program = program_profile_json
+        .map(serde_json::from_str::<crate::types::ProgramProfile>)
+        .transpose()
+        .map_err(|e| UdcError::serialization(format!("Failed to parse program profile: {}", e)))?;
+    let products = product_profiles_json
+        .map(serde_json::from_str::<Vec<crate::types::ProductProfile>>)
+        .transpose()
+        .map_err(|e| UdcError::serialization(format!("Failed to parse product profiles: {}", e)))?
+        .unwrap_or_default();
+    let profiled = timed_phase!(
+        timings,
+        "P3",
+        load_profiles_with_options(jurisdictioned, program.as_ref(), &products, options)
+    )?;
 
     // P4: Tax
-    let taxed = calculate_tax(profiled)?;
+    let taxed = timed_phase!(timings, "P4", calculate_tax_with_options(profiled, options))?;
 
     // P5: Structure
-    let structured = build_structure(taxed)?;
+    let structured = timed_phase!(timings, "P5", build_structure_with_options(taxed, options))?;
 
     // P6: Cashflow
-    let cashflowed = generate_cashflow(structured)?;
+    let cashflowed = timed_phase!(timings, "P6", generate_cashflow(structured))?;
 
     // P7: Finalize
-    let finalized = finalize_output(cashflowed)?;
+    #[cfg_attr(not(feature = "metrics"), allow(unused_mut))]
+    let mut finalized = timed_phase!(timings, "P7", finalize_output_with_options(cashflowed, options))?;
+
+    #[cfg(feature = "metrics")]
+    {
+        finalized.output.audit_trace.phase_timings = timings;
+    }
 
     Ok(finalized.output)
 }
 
+/// Options controlling pipeline behavior.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineOptions {
+    /// Derive output/deal IDs from the input checksum instead of random UUIDs
+    pub deterministic: bool,
+    /// When intermediate money values are rounded (see [`RoundingStrategy`])
+    pub rounding: RoundingStrategy,
+    /// Fail with a validation error where a phase would otherwise warn and
+    /// clamp (e.g., a lease residual at or above the adjusted cap cost)
+    pub strict_validation: bool,
+    /// Drop F&I products their profile makes ineligible for the deal
+    /// instead of only warning
+    pub exclude_ineligible_products: bool,
+    /// Move a finance term the lender program does not offer to its
+    /// nearest offered term instead of only warning
+    pub snap_to_available_term: bool,
+    /// Fill zero title, registration and plate fees from the statutory
+    /// schedule instead of charging them as zero
+    pub fill_government_fees: bool,
+    /// Combined tax rate above which P4 warns (see
+    /// [`p4_tax_cipher::DEFAULT_MAX_COMBINED_RATE`])
+    pub max_combined_rate: Option<rust_decimal::Decimal>,
+    /// Vehicle value loan-to-value is measured against, unless the lender
+    /// program sets its own
+    pub ltv_basis: crate::types::LtvBasis,
+    /// Caller-supplied state rules; they replace the built-in profile of
+    /// the governing state when it is the state they are for
+    pub rule_profile: Option<crate::types::RuleProfile>,
+}
+
 /// Execute pipeline with default/stub profiles (for testing).
 pub fn execute_pipeline_with_defaults(input: DealInput) -> UdcResult<UdcOutput> {
     // Use empty/default profile JSON
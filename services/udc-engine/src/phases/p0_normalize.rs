@@ -9,14 +9,21 @@
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use serde::Serialize;
 
 use crate::types::{
-    DealInput, DealType, DealFees, CustomerInfo, FinanceParams, LeaseParams,
-    UdcError, UdcResult,
+    DealInput, DealType, DealFees, CustomerType, FinanceParams, LeaseParams,
+    ValidationWarning, UdcError, UdcResult,
 };
 
+/// APR above which a finance deal is flagged as unusual
+const HIGH_APR_THRESHOLD: Decimal = dec!(0.20);
+
+/// Finance term above which a deal is flagged as unusually long
+const LONG_TERM_THRESHOLD: u32 = 72;
+
 /// Normalized deal input - guaranteed to have all required fields validated
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct NormalizedDealInput {
     /// The original input, now validated and normalized
     pub inner: DealInput,
@@ -24,12 +31,22 @@ pub struct NormalizedDealInput {
     pub net_trade: Decimal,
     /// Computed: total rebates
     pub total_rebates: Decimal,
-    /// Computed: total taxable products
+    /// Computed: total products flagged taxable on input (P4 taxes
+    /// products by the state's ancillary rules, not this flag)
     pub total_taxable_products: Decimal,
-    /// Computed: total non-taxable products
+    /// Computed: total products flagged non-taxable on input
     pub total_non_taxable_products: Decimal,
     /// Computed: total fees
     pub total_fees: Decimal,
+    /// Computed: destination charged on the vehicle (zero without one)
+    pub vehicle_destination: Decimal,
+    /// Computed: vehicle price plus the vehicle's destination
+    pub selling_price: Decimal,
+    /// Computed: per-diem interest accrued on the trade-in payoff past its
+    /// good-through date (the input payoff is left as quoted)
+    pub payoff_per_diem_adjustment: Decimal,
+    /// Computed: total trade payoff including the per-diem adjustment
+    pub accrued_trade_payoff: Decimal,
     /// Flag: customer has negative equity
     pub has_negative_equity: bool,
 }
@@ -39,15 +56,28 @@ impl NormalizedDealInput {
     pub fn inner(&self) -> &DealInput {
         &self.inner
     }
+
+    /// Destination already part of the selling price: the vehicle's, or a
+    /// fee flagged as included in the price
+    pub fn included_destination(&self) -> Decimal {
+        let fees = &self.inner.fees;
+        let included_fee = if fees.destination_included_in_price {
+            fees.destination_fee
+        } else {
+            Decimal::ZERO
+        };
+        self.vehicle_destination + included_fee
+    }
 }
 
 /// P0: Normalize and validate the deal input.
 ///
 /// # Algorithm
-/// 1. Validate required fields exist and are non-negative
+/// 1. Validate required fields exist and are non-negative, starting with
+///    the finance/lease params the deal type needs
 /// 2. Normalize money values (round to 2 decimals)
 /// 3. Validate deal-type-specific required fields
-/// 4. Compute derived values (net trade, totals)
+/// 4. Compute derived values (per-diem payoff, net trade, totals)
 /// 5. Set defaults for missing optional fields
 ///
 /// # Invariants Enforced
@@ -55,13 +85,15 @@ impl NormalizedDealInput {
 /// - cash_down >= 0
 /// - All fee amounts >= 0
 /// - For Finance: term_months in [12, 84], apr in [0, 0.30]
-/// - For Lease: term_months in [24, 48], money_factor > 0, residual_percent in (0, 1)
+/// - For Lease: term_months in [24, 48], money_factor in [0, 0.01] (0 for
+///   zero-rent promotions), residual_percent in (0, 1)
 ///
 /// # Complexity
 /// - Time: O(n) where n = number of rebates + products + fees
 /// - Space: O(1) additional space (in-place normalization)
 pub fn normalize_deal_input(mut input: DealInput) -> UdcResult<NormalizedDealInput> {
     // === Step 1: Validate core required fields ===
+    validate_required_params(&input)?;
     validate_vehicle_price(input.vehicle_price)?;
     validate_non_negative(input.cash_down, "cash_down")?;
 
@@ -70,6 +102,24 @@ pub fn normalize_deal_input(mut input: DealInput) -> UdcResult<NormalizedDealInp
     input.cash_down = round_money(input.cash_down);
     input.trade_in_value = input.trade_in_value.map(round_money);
     input.trade_in_payoff = input.trade_in_payoff.map(round_money);
+    if let Some(acv) = input.trade_in_acv {
+        validate_non_negative(acv, "trade_in_acv")?;
+    }
+    input.trade_in_acv = input.trade_in_acv.map(round_money);
+    for (i, trade) in input.additional_trades.iter_mut().enumerate() {
+        validate_non_negative(trade.gross_value.as_decimal(), &format!("additional_trades[{}].gross_value", i))?;
+        validate_non_negative(trade.payoff_amount.as_decimal(), &format!("additional_trades[{}].payoff_amount", i))?;
+        trade.gross_value = trade.gross_value.round_cents();
+        trade.payoff_amount = trade.payoff_amount.round_cents();
+        trade.acv = trade.acv.map(|acv| acv.round_cents());
+    }
+    if let Some(per_diem) = input.trade_in_per_diem {
+        validate_non_negative(per_diem, "trade_in_per_diem")?;
+    }
+    if let Some(exempt) = input.exempt_amount {
+        validate_non_negative(exempt, "exempt_amount")?;
+    }
+    input.exempt_amount = input.exempt_amount.map(round_money);
 
     // Normalize rebates
     for rebate in &mut input.rebates {
@@ -86,20 +136,26 @@ pub fn normalize_deal_input(mut input: DealInput) -> UdcResult<NormalizedDealInp
 
     // Normalize fees
     normalize_fees(&mut input.fees)?;
+    validate_apportioned_registration(&input)?;
+    apply_default_doc_fee(&mut input)?;
+    let vehicle_destination = compute_vehicle_destination(&input)?;
 
     // === Step 3: Validate deal-type-specific fields ===
     match input.deal_type {
-        DealType::Finance => validate_finance_params(&input)?,
-        DealType::Lease => validate_lease_params(&input)?,
+        DealType::Finance => input.finance_params.as_ref().map_or(Ok(()), validate_finance_params)?,
+        DealType::Lease => input.lease_params.as_ref().map_or(Ok(()), validate_lease_params)?,
         DealType::Cash => {} // No additional validation needed for cash
     }
 
     // === Step 4: Compute derived values ===
-    let net_trade = compute_net_trade(&input);
+    let payoff_per_diem_adjustment = compute_payoff_per_diem(&input);
+    let accrued_trade_payoff = input.total_trade_payoff() + payoff_per_diem_adjustment;
+    let net_trade = compute_net_trade(&input) - payoff_per_diem_adjustment;
     let has_negative_equity = net_trade < Decimal::ZERO;
     let total_rebates = input.total_rebates();
     let (total_taxable_products, total_non_taxable_products) = compute_product_totals(&input);
     let total_fees = input.fees.total();
+    let selling_price = input.vehicle_price + vehicle_destination;
 
     // === Step 5: Set defaults ===
     set_defaults(&mut input);
@@ -111,10 +167,50 @@ pub fn normalize_deal_input(mut input: DealInput) -> UdcResult<NormalizedDealInp
         total_taxable_products,
         total_non_taxable_products,
         total_fees,
+        vehicle_destination,
+        selling_price,
+        payoff_per_diem_adjustment,
+        accrued_trade_payoff,
         has_negative_equity,
     })
 }
 
+/// Collect non-blocking warnings for a deal input.
+///
+/// Warnings never stop the pipeline; they flag values that are legal but
+/// unusual enough that the desk should double-check them.
+pub fn collect_warnings(input: &DealInput) -> Vec<ValidationWarning> {
+    let mut warnings = Vec::new();
+
+    if let Some(params) = input.finance_params.as_ref().filter(|_| input.deal_type == DealType::Finance) {
+        if params.apr > HIGH_APR_THRESHOLD {
+            warnings.push(ValidationWarning {
+                code: "HIGH_APR".to_string(),
+                field: "finance_params.apr".to_string(),
+                message: format!("APR of {:.2}% is unusually high", params.apr * dec!(100)),
+            });
+        }
+
+        if params.term_months > LONG_TERM_THRESHOLD {
+            warnings.push(ValidationWarning {
+                code: "LONG_TERM".to_string(),
+                field: "finance_params.term_months".to_string(),
+                message: format!("Term of {} months exceeds {} months", params.term_months, LONG_TERM_THRESHOLD),
+            });
+        }
+
+        if params.deferral_fee.is_some_and(|fee| fee > Decimal::ZERO) && !params.deferred_first_payment {
+            warnings.push(ValidationWarning {
+                code: "DEFERRAL_FEE_IGNORED".to_string(),
+                field: "finance_params.deferral_fee".to_string(),
+                message: "Deferral fee is only charged when the first payment is deferred".to_string(),
+            });
+        }
+    }
+
+    warnings
+}
+
 // === Helper Functions ===
 
 fn validate_vehicle_price(price: Decimal) -> UdcResult<()> {
@@ -147,9 +243,25 @@ fn round_money(value: Decimal) -> Decimal {
     value.round_dp(2)
 }
 
+/// A fleet buyer's apportioned registration needs miles to split the
+/// registration fee by. Other buyers' apportionment is ignored in P3 with a
+/// warning, so it is not validated here.
+fn validate_apportioned_registration(input: &DealInput) -> UdcResult<()> {
+    let apportioned = input.fees.apportioned_registration.as_ref();
+    if input.customer.customer_type == CustomerType::FleetCommercial
+        && apportioned.is_some_and(|a| a.total_miles() == 0)
+    {
+        return Err(UdcError::validation_field(
+            "Apportioned registration requires miles in at least one jurisdiction",
+            "fees.apportioned_registration.jurisdictions",
+        ));
+    }
+    Ok(())
+}
+
 fn normalize_fees(fees: &mut DealFees) -> UdcResult<()> {
     // Validate all fees are non-negative
-    validate_non_negative(fees.doc_fee, "fees.doc_fee")?;
+    validate_non_negative(fees.doc_fee_amount(), "fees.doc_fee")?;
     validate_non_negative(fees.title_fee, "fees.title_fee")?;
     validate_non_negative(fees.registration_fee, "fees.registration_fee")?;
     validate_non_negative(fees.plate_fee, "fees.plate_fee")?;
@@ -166,7 +278,7 @@ fn normalize_fees(fees: &mut DealFees) -> UdcResult<()> {
     }
 
     // Round all to 2 decimal places
-    fees.doc_fee = round_money(fees.doc_fee);
+    fees.doc_fee = fees.doc_fee.map(round_money);
     fees.title_fee = round_money(fees.title_fee);
     fees.registration_fee = round_money(fees.registration_fee);
     fees.plate_fee = round_money(fees.plate_fee);
@@ -181,14 +293,60 @@ fn normalize_fees(fees: &mut DealFees) -> UdcResult<()> {
     Ok(())
 }
 
-fn validate_finance_params(input: &DealInput) -> UdcResult<()> {
-    let params = input.finance_params.as_ref().ok_or_else(|| {
-        UdcError::validation_field(
-            "Finance deal requires finance_params",
+/// Charge the dealer's default doc fee on a deal that gives none.
+fn apply_default_doc_fee(input: &mut DealInput) -> UdcResult<()> {
+    let Some(dealer) = input.dealer_profile.as_ref() else {
+        return Ok(());
+    };
+    let default_doc_fee = dealer.default_doc_fee.as_decimal();
+    validate_non_negative(default_doc_fee, "dealer_profile.default_doc_fee")?;
+
+    if input.fees.doc_fee.is_none() {
+        input.fees.doc_fee = Some(round_money(default_doc_fee));
+    }
+    Ok(())
+}
+
+/// Compute the destination charge a vehicle adds to its selling price.
+///
+/// The input is left as given; P4 and P5 read the charge and the selling
+/// price from the derived fields, so P4 backs it out of the tax base in
+/// states that do not tax destination and it is never charged as a fee.
+fn compute_vehicle_destination(input: &DealInput) -> UdcResult<Decimal> {
+    let Some(destination) = input.vehicle.as_ref().and_then(|vehicle| vehicle.destination_fee) else {
+        return Ok(Decimal::ZERO);
+    };
+    validate_non_negative(destination.as_decimal(), "vehicle.destination_fee")?;
+
+    if input.fees.destination_fee > Decimal::ZERO {
+        return Err(UdcError::validation_field(
+            "Destination given on both the vehicle and the fees",
+            "vehicle.destination_fee",
+        ));
+    }
+
+    Ok(round_money(destination.as_decimal()))
+}
+
+/// Check a finance or lease deal carries its params block.
+///
+/// Runs before anything else in P0 so a missing block is reported against
+/// its field, not as a calculation error from a later phase.
+fn validate_required_params(input: &DealInput) -> UdcResult<()> {
+    match input.deal_type {
+        DealType::Finance if input.finance_params.is_none() => Err(UdcError::validation_field(
+            "Finance input required: a finance deal needs finance_params",
             "finance_params",
-        )
-    })?;
+        )),
+        DealType::Lease if input.lease_params.is_none() => Err(UdcError::validation_field(
+            "Lease input required: a lease deal needs lease_params",
+            "lease_params",
+        )),
+        _ => Ok(()),
+    }
+}
 
+fn validate_finance_params(params: &FinanceParams) -> UdcResult<()> {
     // Term validation: 12-84 months typical
     if params.term_months < 12 || params.term_months > 84 {
         return Err(UdcError::validation_field(
@@ -205,17 +363,17 @@ fn validate_finance_params(input: &DealInput) -> UdcResult<()> {
         ));
     }
 
+    if params.deferral_fee.is_some_and(|fee| fee < Decimal::ZERO) {
+        return Err(UdcError::validation_field(
+            "Deferral fee cannot be negative",
+            "finance_params.deferral_fee",
+        ));
+    }
+
     Ok(())
 }
 
-fn validate_lease_params(input: &DealInput) -> UdcResult<()> {
-    let params = input.lease_params.as_ref().ok_or_else(|| {
-        UdcError::validation_field(
-            "Lease deal requires lease_params",
-            "lease_params",
-        )
-    })?;
-
+fn validate_lease_params(params: &LeaseParams) -> UdcResult<()> {
     // Term validation: 24-48 months typical for leases
     if params.term_months < 24 || params.term_months > 60 {
         return Err(UdcError::validation_field(
@@ -224,10 +382,12 @@ fn validate_lease_params(input: &DealInput) -> UdcResult<()> {
         ));
     }
 
-    // Money factor validation: typically 0.00001 to 0.01
-    if params.money_factor <= Decimal::ZERO || params.money_factor > dec!(0.01) {
+    // Money factor validation: typically 0.00001 to 0.01; promotional
+    // leases may run at 0 (no rent charge)
+    let money_factor = params.money_factor.as_decimal();
+    if money_factor < Decimal::ZERO || money_factor > dec!(0.01) {
         return Err(UdcError::validation_field(
-            format!("Money factor must be between 0 and 0.01, got {}", params.money_factor),
+            format!("Money factor must be between 0 and 0.01, got {}", money_factor),
             "lease_params.money_factor",
         ));
     }
@@ -249,16 +409,58 @@ fn validate_lease_params(input: &DealInput) -> UdcResult<()> {
         ));
     }
 
+    // Scheduled tax rate changes fall after the first payment (which is
+    // taxed at the opening rate, with the amount due at signing) and within
+    // the term, in order
+    let mut previous_month = 1;
+    for change in &params.tax_rate_schedule {
+        if change.effective_month <= previous_month || change.effective_month > params.term_months {
+            return Err(UdcError::validation_field(
+                format!(
+                    "Tax rate schedule months must be ascending from month 2 within the {}-month term, got month {}",
+                    params.term_months, change.effective_month
+                ),
+                "lease_params.tax_rate_schedule",
+            ));
+        }
+        if change.rate.as_decimal() < Decimal::ZERO {
+            return Err(UdcError::validation_field(
+                format!("Scheduled tax rate cannot be negative, got {}", change.rate.as_decimal()),
+                "lease_params.tax_rate_schedule",
+            ));
+        }
+        previous_month = change.effective_month;
+    }
+
     Ok(())
 }
 
 fn compute_net_trade(input: &DealInput) -> Decimal {
-    let value = input.trade_in_value.unwrap_or_default();
-    let payoff = input.trade_in_payoff.unwrap_or_default();
-    value - payoff
+    input.net_trade()
 }
 
-fn compute_product_totals(input: &DealInput) -> (Decimal, Decimal) {
+/// Per-diem interest accrued on the trade payoff when the deal funds after
+/// the payoff quote's good-through date.
+fn compute_payoff_per_diem(input: &DealInput) -> Decimal {
+    let (Some(good_through), Some(per_diem)) = (input.trade_in_payoff_good_through, input.trade_in_per_diem) else {
+        return Decimal::ZERO;
+    };
+    let Some(funding_date) = input.funding_date.or(input.deal_date) else {
+        return Decimal::ZERO;
+    };
+    if input.trade_in_payoff.is_none() {
+        return Decimal::ZERO;
+    }
+
+    let days_past = (funding_date - good_through).num_days();
+    if days_past <= 0 {
+        return Decimal::ZERO;
+    }
+
+    round_money(per_diem * Decimal::from(days_past))
+}
+
+pub(crate) fn compute_product_totals(input: &DealInput) -> (Decimal, Decimal) {
     let mut taxable = Decimal::ZERO;
     let mut non_taxable = Decimal::ZERO;
 
@@ -275,9 +477,7 @@ fn compute_product_totals(input: &DealInput) -> (Decimal, Decimal) {
 
 fn set_defaults(input: &mut DealInput) {
     // Set default deal date to today if not provided
-    if input.deal_date.is_none() {
-        input.deal_date = Some(chrono::Local::now().date_naive());
-    }
+    input.deal_date = Some(input.effective_deal_date());
 
     // Set garaging state to home state if not provided
     if input.garaging_state.is_none() {
@@ -293,35 +493,17 @@ fn set_defaults(input: &mut DealInput) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::StateCode;
+    use crate::types::{MoneyFactor, StateCode};
     use rust_decimal_macros::dec;
+    use chrono::NaiveDate;
 
     fn make_basic_finance_input() -> DealInput {
         DealInput {
-            deal_type: DealType::Finance,
-            vehicle_price: dec!(30000),
             trade_in_value: Some(dec!(10000)),
             trade_in_payoff: Some(dec!(5000)),
             cash_down: dec!(2000),
-            rebates: vec![],
-            products: vec![],
-            fees: DealFees::default(),
-            home_state: StateCode::TX,
-            transaction_state: StateCode::TX,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: Some(FinanceParams {
-                term_months: 60,
-                apr: dec!(0.0599),
-                lender_id: None,
-                buy_rate: None,
-                max_reserve_points: None,
-                deferred_first_payment: false,
-                days_to_first_payment: None,
-            }),
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
+            finance_params: Some(FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(DealType::Finance, dec!(30000), StateCode::TX)
         }
     }
 
@@ -336,6 +518,27 @@ mod tests {
         assert!(!normalized.has_negative_equity);
     }
 
+    #[test]
+    fn test_normalize_multiple_trades() {
+        use crate::types::{Money, TradeIn};
+
+        // Basic deal trades 10000 against a 5000 payoff; a second trade adds 6000.004 / 1000
+        let mut input = make_basic_finance_input();
+        input.additional_trades = vec![TradeIn {
+            vin: None,
+            description: "2015 Ford F-150".to_string(),
+            gross_value: Money::new(dec!(6000.004)),
+            payoff_amount: Money::new(dec!(1000)),
+            acv: None,
+            titled_to_buyer: true,
+        }];
+
+        let normalized = normalize_deal_input(input).unwrap();
+        assert_eq!(normalized.inner.additional_trades[0].gross_value, Money::new(dec!(6000)));
+        assert_eq!(normalized.net_trade, dec!(10000));
+        assert!(!normalized.has_negative_equity);
+    }
+
     #[test]
     fn test_normalize_negative_equity() {
         let mut input = make_basic_finance_input();
@@ -365,6 +568,26 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_apportioned_registration_without_miles() {
+        use crate::types::{ApportionedRegistration, JurisdictionMiles};
+
+        let mut input = make_basic_finance_input();
+        input.fees.apportioned_registration = Some(ApportionedRegistration {
+            jurisdictions: vec![JurisdictionMiles { state: StateCode::TX, miles: 0 }],
+        });
+        // Ignored for a retail buyer, so not an error
+        assert!(normalize_deal_input(input.clone()).is_ok());
+
+        input.customer.customer_type = CustomerType::FleetCommercial;
+        match normalize_deal_input(input) {
+            Err(UdcError::Validation { field, .. }) => {
+                assert_eq!(field.as_deref(), Some("fees.apportioned_registration.jurisdictions"));
+            }
+            other => panic!("expected validation error, got {:?}", other.map(|_| ())),
+        }
+    }
+
     #[test]
     fn test_money_rounding() {
         let mut input = make_basic_finance_input();
@@ -376,25 +599,162 @@ mod tests {
         assert_eq!(result.inner.cash_down, dec!(2000.00));
     }
 
+    #[test]
+    fn test_reject_finance_without_finance_params() {
+        let mut input = make_basic_finance_input();
+        input.finance_params = None;
+        // Reported ahead of other invalid fields
+        input.cash_down = dec!(-1);
+
+        match normalize_deal_input(input) {
+            Err(UdcError::Validation { message, field }) => {
+                assert_eq!(field.as_deref(), Some("finance_params"));
+                assert!(message.starts_with("Finance input required"));
+            }
+            other => panic!("expected finance_params validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_dealer_default_doc_fee() {
+        use crate::types::{DealerProfile, Money};
+
+        let mut input = make_basic_finance_input();
+        input.dealer_profile = Some(DealerProfile {
+            default_doc_fee: Money::new(dec!(150)),
+            ..Default::default()
+        });
+
+        // No doc fee on the deal: the dealer's default is charged
+        let result = normalize_deal_input(input.clone()).unwrap();
+        assert_eq!(result.inner.fees.doc_fee, Some(dec!(150)));
+        assert_eq!(result.total_fees, dec!(150));
+
+        // A doc fee on the deal wins, including an explicit zero
+        input.fees.doc_fee = Some(dec!(99));
+        let result = normalize_deal_input(input.clone()).unwrap();
+        assert_eq!(result.inner.fees.doc_fee, Some(dec!(99)));
+
+        input.fees.doc_fee = Some(dec!(0));
+        let result = normalize_deal_input(input).unwrap();
+        assert_eq!(result.inner.fees.doc_fee, Some(dec!(0)));
+        assert_eq!(result.total_fees, dec!(0));
+    }
+
+    #[test]
+    fn test_vehicle_destination_adds_to_selling_price() {
+        use crate::types::{Money, Vehicle, VehicleCondition};
+
+        let mut input = make_basic_finance_input();
+        input.vehicle = Some(Vehicle {
+            vin: "1HGCM82633A004352".to_string(),
+            year: 2024,
+            make: "Honda".to_string(),
+            model: "Accord".to_string(),
+            trim: None,
+            condition: VehicleCondition::New,
+            msrp: Money::new(dec!(32000)),
+            invoice: None,
+            selling_price: Money::new(dec!(30000)),
+            odometer: 10,
+            destination_fee: Some(Money::new(dec!(1395.004))),
+        });
+
+        let result = normalize_deal_input(input.clone()).unwrap();
+        assert_eq!(result.vehicle_destination, dec!(1395));
+        assert_eq!(result.selling_price, dec!(31395));
+
+        // The input is left as given, so normalizing again changes nothing
+        assert_eq!(result.inner.vehicle_price, dec!(30000));
+        assert_eq!(result.inner.fees.destination_fee, dec!(0));
+        let again = normalize_deal_input(result.inner.clone()).unwrap();
+        assert_eq!(again.selling_price, result.selling_price);
+        assert_eq!(again.inner.vehicle_price, dec!(30000));
+
+        // A destination on the fees as well would be charged twice
+        input.fees.destination_fee = dec!(1395);
+        match normalize_deal_input(input) {
+            Err(UdcError::Validation { field, .. }) => {
+                assert_eq!(field.as_deref(), Some("vehicle.destination_fee"));
+            }
+            other => panic!("expected vehicle.destination_fee validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reject_lease_without_lease_params() {
+        let mut input = make_basic_finance_input();
+        input.deal_type = DealType::Lease;
+
+        match normalize_deal_input(input) {
+            Err(UdcError::Validation { message, field }) => {
+                assert_eq!(field.as_deref(), Some("lease_params"));
+                assert!(message.starts_with("Lease input required"));
+            }
+            other => panic!("expected lease_params validation error, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_lease_validation() {
         let mut input = make_basic_finance_input();
         input.deal_type = DealType::Lease;
         input.finance_params = None;
         input.lease_params = Some(LeaseParams {
-            term_months: 36,
-            money_factor: dec!(0.00125),
-            residual_percent: dec!(0.55),
-            annual_miles: 12000,
             excess_mileage_rate: Some(dec!(0.25)),
-            lessor_id: None,
-            msd_count: 0,
-            security_deposit: None,
-            cap_acquisition_fee: true,
-            cap_cost_reduction: Decimal::ZERO,
+            ..LeaseParams::new(36, MoneyFactor::new(dec!(0.00125)), dec!(0.55), 12000)
         });
 
         let result = normalize_deal_input(input);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_lease_money_factor_bounds() {
+        let lease_with_mf = |money_factor: Decimal| {
+            let mut input = make_basic_finance_input();
+            input.deal_type = DealType::Lease;
+            input.finance_params = None;
+            input.lease_params = Some(LeaseParams::new(36, MoneyFactor::new(money_factor), dec!(0.55), 12000));
+            normalize_deal_input(input)
+        };
+
+        // Zero-rent promotions are valid; negative or runaway factors are not
+        assert!(lease_with_mf(dec!(0)).is_ok());
+        assert!(lease_with_mf(dec!(0.01)).is_ok());
+        let err = lease_with_mf(dec!(-0.0001)).unwrap_err();
+        assert!(err.to_string().contains("Money factor"));
+        assert!(lease_with_mf(dec!(0.0125)).is_err());
+    }
+
+    #[test]
+    fn test_trade_payoff_per_diem_after_good_through() {
+        let mut input = make_basic_finance_input();
+        input.trade_in_payoff_good_through = NaiveDate::from_ymd_opt(2024, 3, 1);
+        input.trade_in_per_diem = Some(dec!(4));
+        input.funding_date = NaiveDate::from_ymd_opt(2024, 3, 11);
+
+        let result = normalize_deal_input(input).unwrap();
+        assert_eq!(result.payoff_per_diem_adjustment, dec!(40));
+        assert_eq!(result.accrued_trade_payoff, dec!(5040));
+        assert_eq!(result.inner.trade_in_payoff, Some(dec!(5000)));
+        assert_eq!(result.net_trade, dec!(4960));
+
+        // Normalizing the normalized input accrues the per-diem only once
+        let again = normalize_deal_input(result.inner).unwrap();
+        assert_eq!(again.accrued_trade_payoff, dec!(5040));
+        assert_eq!(again.net_trade, dec!(4960));
+    }
+
+    #[test]
+    fn test_trade_payoff_per_diem_within_good_through() {
+        let mut input = make_basic_finance_input();
+        input.trade_in_payoff_good_through = NaiveDate::from_ymd_opt(2024, 3, 15);
+        input.trade_in_per_diem = Some(dec!(4));
+        input.deal_date = NaiveDate::from_ymd_opt(2024, 3, 11);
+
+        let result = normalize_deal_input(input).unwrap();
+        assert_eq!(result.payoff_per_diem_adjustment, dec!(0));
+        assert_eq!(result.net_trade, dec!(5000));
+    }
 }
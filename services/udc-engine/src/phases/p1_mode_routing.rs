@@ -126,40 +126,15 @@ pub fn get_pipeline_stages(mode: CalculationMode) -> Vec<&'static str> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{DealInput, DealFees, CustomerInfo, FinanceParams, StateCode};
+    use crate::types::{DealInput, FinanceParams, StateCode};
     use crate::phases::p0_normalize::normalize_deal_input;
     use rust_decimal_macros::dec;
 
     fn make_test_input(deal_type: DealType) -> DealInput {
         DealInput {
-            deal_type,
-            vehicle_price: dec!(30000),
-            trade_in_value: None,
-            trade_in_payoff: None,
             cash_down: dec!(2000),
-            rebates: vec![],
-            products: vec![],
-            fees: DealFees::default(),
-            home_state: StateCode::TX,
-            transaction_state: StateCode::TX,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: if deal_type == DealType::Finance {
-                Some(FinanceParams {
-                    term_months: 60,
-                    apr: dec!(0.0599),
-                    lender_id: None,
-                    buy_rate: None,
-                    max_reserve_points: None,
-                    deferred_first_payment: false,
-                    days_to_first_payment: None,
-                })
-            } else {
-                None
-            },
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
+            finance_params: (deal_type == DealType::Finance).then(|| FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(deal_type, dec!(30000), StateCode::TX)
         }
     }
 
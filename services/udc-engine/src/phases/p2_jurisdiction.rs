@@ -22,12 +22,24 @@ pub struct JurisdictionContext {
     pub governing_state: StateCode,
     /// Whether this is an interstate transaction
     pub is_interstate: bool,
-    /// The secondary state (if interstate, for reciprocity calculations)
+    /// The secondary state (set whenever any of the H/T/G states differ)
     pub secondary_state: Option<StateCode>,
+    /// Whether tax paid to the secondary state may be credited. False when
+    /// the secondary state is only where the vehicle is garaged or the
+    /// customer lives, with the sale taxed where it was signed.
+    pub reciprocity_applies: bool,
     /// Special handling flags
     pub flags: JurisdictionFlags,
 }
 
+impl JurisdictionContext {
+    /// Whether any of the H/T/G states differ. Unlike `is_interstate`, this
+    /// also counts a vehicle garaged outside the home and transaction state.
+    pub fn spans_multiple_states(&self) -> bool {
+        self.home_state != self.transaction_state || self.transaction_state != self.garaging_state
+    }
+}
+
 /// Special jurisdiction handling flags
 #[derive(Debug, Clone, Default)]
 pub struct JurisdictionFlags {
@@ -107,13 +119,28 @@ pub fn resolve_jurisdiction(deal: RoutedDeal) -> UdcResult<JurisdictionResolvedD
     // Determine if interstate
     let is_interstate = home_state != transaction_state;
 
-    // Determine governing state based on deal type and states involved
-    let (governing_state, secondary_state) = determine_governing_state(
-        home_state,
-        transaction_state,
-        garaging_state,
-        deal.input.inner.deal_type,
-    );
+    // Same-state triplet: nothing crosses a state line, so there is no
+    // secondary state and no reciprocity to compute
+    let same_state = home_state == transaction_state && transaction_state == garaging_state;
+
+    let (governing_state, secondary_state, reciprocity_applies) = if same_state {
+        (home_state, None, false)
+    } else {
+        // Determine governing state based on deal type and states involved
+        let (governing, reciprocity_state) = determine_governing_state(
+            home_state,
+            transaction_state,
+            garaging_state,
+            deal.input.inner.deal_type,
+        );
+        // Any other differing state is still loaded as the secondary profile
+        let secondary = reciprocity_state.or_else(|| {
+            [transaction_state, garaging_state, home_state]
+                .into_iter()
+                .find(|&state| state != governing)
+        });
+        (governing, secondary, reciprocity_state.is_some())
+    };
 
     // Build flags
     let flags = build_jurisdiction_flags(
@@ -129,6 +156,7 @@ pub fn resolve_jurisdiction(deal: RoutedDeal) -> UdcResult<JurisdictionResolvedD
         governing_state,
         is_interstate,
         secondary_state,
+        reciprocity_applies,
         flags,
     };
 
@@ -246,7 +274,7 @@ pub fn get_special_tax_type(state: StateCode) -> Option<&'static str> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{DealInput, DealFees, CustomerInfo, FinanceParams};
+    use crate::types::{DealInput, FinanceParams};
     use crate::phases::p0_normalize::normalize_deal_input;
     use crate::phases::p1_mode_routing::route_deal;
     use rust_decimal_macros::dec;
@@ -258,34 +286,11 @@ mod tests {
         deal_type: DealType,
     ) -> RoutedDeal {
         let input = DealInput {
-            deal_type,
-            vehicle_price: dec!(30000),
-            trade_in_value: None,
-            trade_in_payoff: None,
             cash_down: dec!(2000),
-            rebates: vec![],
-            products: vec![],
-            fees: DealFees::default(),
-            home_state: home,
             transaction_state: transaction,
             garaging_state: garaging,
-            customer: CustomerInfo::default(),
-            finance_params: if deal_type == DealType::Finance {
-                Some(FinanceParams {
-                    term_months: 60,
-                    apr: dec!(0.0599),
-                    lender_id: None,
-                    buy_rate: None,
-                    max_reserve_points: None,
-                    deferred_first_payment: false,
-                    days_to_first_payment: None,
-                })
-            } else {
-                None
-            },
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
+            finance_params: (deal_type == DealType::Finance).then(|| FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(deal_type, dec!(30000), home)
         };
 
         let normalized = normalize_deal_input(input).unwrap();
@@ -300,6 +305,41 @@ mod tests {
         assert!(!resolved.jurisdiction.is_interstate);
         assert_eq!(resolved.jurisdiction.governing_state, StateCode::TX);
         assert!(resolved.jurisdiction.secondary_state.is_none());
+        assert!(!resolved.jurisdiction.reciprocity_applies);
+    }
+
+    #[test]
+    fn test_same_state_triplet_for_every_deal_type() {
+        for deal_type in [DealType::Cash, DealType::Finance] {
+            let deal = make_test_deal(StateCode::NY, StateCode::NY, Some(StateCode::NY), deal_type);
+            let jurisdiction = resolve_jurisdiction(deal).unwrap().jurisdiction;
+
+            assert!(!jurisdiction.is_interstate, "{:?}", deal_type);
+            assert!(!jurisdiction.spans_multiple_states());
+            assert_eq!(jurisdiction.governing_state, StateCode::NY);
+            assert!(jurisdiction.secondary_state.is_none());
+            assert!(!jurisdiction.reciprocity_applies);
+        }
+    }
+
+    #[test]
+    fn test_any_differing_state_spans_multiple_states() {
+        // Garaged out of state: bought at home, so not interstate, but the
+        // garaging state is loaded with no tax paid there
+        let deal = make_test_deal(StateCode::TX, StateCode::TX, Some(StateCode::OK), DealType::Finance);
+        let jurisdiction = resolve_jurisdiction(deal).unwrap().jurisdiction;
+        assert!(!jurisdiction.is_interstate);
+        assert!(jurisdiction.spans_multiple_states());
+        assert_eq!(jurisdiction.governing_state, StateCode::TX);
+        assert_eq!(jurisdiction.secondary_state, Some(StateCode::OK));
+        assert!(!jurisdiction.reciprocity_applies);
+
+        // Bought out of state: the transaction state's tax is creditable
+        let deal = make_test_deal(StateCode::CA, StateCode::AZ, None, DealType::Finance);
+        let jurisdiction = resolve_jurisdiction(deal).unwrap().jurisdiction;
+        assert!(jurisdiction.is_interstate);
+        assert!(jurisdiction.spans_multiple_states());
+        assert!(jurisdiction.reciprocity_applies);
     }
 
     #[test]
@@ -312,6 +352,16 @@ mod tests {
         assert_eq!(resolved.jurisdiction.secondary_state, Some(StateCode::AZ));
     }
 
+    #[test]
+    fn test_lease_taxed_where_signed_has_no_reciprocity() {
+        // NJ resident leasing a car garaged at a NY dealer: NY governs, and
+        // the NJ profile is secondary without any NJ tax to credit
+        let (governing, reciprocity_state) =
+            determine_governing_state(StateCode::NJ, StateCode::NY, StateCode::NY, DealType::Lease);
+        assert_eq!(governing, StateCode::NY);
+        assert!(reciprocity_state.is_none());
+    }
+
     #[test]
     fn test_no_sales_tax_state() {
         let deal = make_test_deal(StateCode::MT, StateCode::WA, None, DealType::Cash);
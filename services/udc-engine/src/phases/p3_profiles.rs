@@ -10,11 +10,15 @@ use rust_decimal_macros::dec;
 use chrono::NaiveDate;
 
 use crate::types::{
-    DealType, StateCode, TaxType, LeaseTaxMode,
+    DealType, StateCode, TaxType, LeaseTaxMode, LeaseMonthlyTaxBase, TaxRounding,
     RuleProfile, TaxRates, BaseRules, AncillaryRules, ReciprocityRules, ProfileMeta,
-    UdcResult, UdcError,
+    GovernmentFeeSchedule, DealFees, DealerProfile, ProductProfile, ProductPricing, PricingModel,
+    ValidationWarning, CreditTier, CustomerType,
+    UdcResult,
 };
+use super::p0_normalize::{compute_product_totals, NormalizedDealInput};
 use super::p2_jurisdiction::JurisdictionResolvedDeal;
+use super::PipelineOptions;
 
 /// Loaded profile context for deal calculations
 #[derive(Debug, Clone)]
@@ -23,12 +27,88 @@ pub struct ProfileContext {
     pub primary_rules: RuleProfile,
     /// Secondary rule profile (if interstate, for reciprocity)
     pub secondary_rules: Option<RuleProfile>,
+    /// Garaging state rules that source lease tax away from the governing
+    /// state (see `RuleProfile::lease_sourced_to_garaging`)
+    pub lease_sourcing_rules: Option<RuleProfile>,
     /// Program profile (lender/lessor parameters)
     pub program: Option<ProgramProfile>,
     /// Product tax treatments
     pub product_rules: Vec<ProductTaxRule>,
+    /// Dealer profile (pack, holdback)
+    pub dealer: Option<DealerProfile>,
+    /// Lender/lessor program supplied by the caller (rate sheet, lease program)
+    pub lender_program: Option<crate::types::ProgramProfile>,
+    /// Subvented/promotional rate resolved from the deal's program code
+    pub subvention: Option<SubventedRate>,
+    /// Customer credit tier for rate sheet and money factor lookups: the
+    /// explicit tier, else derived from the credit score
+    pub credit_tier: Option<CreditTier>,
+    /// Products priced as a rate of the amount financed (sized in P5)
+    pub amount_financed_pricing: Vec<AmountFinancedPricing>,
+    /// Registration fee split across jurisdictions (fleet apportioned
+    /// registration; empty otherwise)
+    pub apportioned_registration: Vec<ApportionedFee>,
+    /// Finance term the caller asked for, when it was snapped to the
+    /// nearest offered term (see `PipelineOptions::snap_to_available_term`)
+    pub requested_term_months: Option<u32>,
+    /// Non-blocking warnings raised while applying profiles
+    pub warnings: Vec<ValidationWarning>,
 }
 
+impl ProfileContext {
+    /// Whether the lender program allows its fees to be capitalized or
+    /// financed (true when no program is loaded)
+    pub fn program_fees_capitalizable(&self) -> bool {
+        self.lender_program
+            .as_ref()
+            .is_none_or(|program| program.fee_config.fees_capitalizable)
+    }
+
+    /// Rules that determine the lease tax rate and mode: the garaging
+    /// state's when it sources lease tax, otherwise the governing state's
+    pub fn lease_tax_rules(&self) -> &RuleProfile {
+        self.lease_sourcing_rules.as_ref().unwrap_or(&self.primary_rules)
+    }
+}
+
+/// Subvented rate or money factor resolved from a program code
+#[derive(Debug, Clone)]
+pub struct SubventedRate {
+    /// Program code
+    pub code: String,
+    /// Program description
+    pub description: String,
+    /// Subvented APR (finance)
+    pub apr: Decimal,
+    /// Subvented money factor (lease)
+    pub money_factor: Option<Decimal>,
+}
+
+/// One jurisdiction's share of an apportioned registration fee
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApportionedFee {
+    /// Jurisdiction
+    pub state: StateCode,
+    /// Miles driven there
+    pub miles: u32,
+    /// Share of total miles
+    pub share: Decimal,
+    /// Registration fee owed to the jurisdiction
+    pub fee: Decimal,
+}
+
+/// Pricing for a product whose price is a rate of the amount financed
+#[derive(Debug, Clone)]
+pub struct AmountFinancedPricing {
+    /// Product id on the deal
+    pub product_id: String,
+    /// Pricing from the product's profile
+    pub pricing: ProductPricing,
+}
+
+/// Input government fees further than this from the statutory fee are flagged
+pub const GOVERNMENT_FEE_VARIANCE_THRESHOLD: Decimal = dec!(10.00);
+
 /// Simplified program profile for calculations
 #[derive(Debug, Clone)]
 pub struct ProgramProfile {
@@ -78,6 +158,9 @@ pub trait ProfileRepository {
 /// 2. If interstate, load secondary RuleProfile for secondary state
 /// 3. Load ProgramProfile if lender specified
 /// 4. Build ProductTaxRule list from state rules + product overrides
+/// 5. Load DealerProfile if provided
+/// 6. Resolve subvented rate/money factor from the program code
+/// 7. Assign the credit tier (explicit, else from the credit score)
 ///
 /// # Profile Resolution Priority
 /// 1. Exact match: (state, deal_type, effective_date)
@@ -88,14 +171,69 @@ pub trait ProfileRepository {
 /// - Time: O(p) where p = number of products (for tax rule building)
 /// - Space: O(p) for product rules
 pub fn load_profiles(deal: JurisdictionResolvedDeal) -> UdcResult<ProfileLoadedDeal> {
+    load_profiles_with_program(deal, None)
+}
+
+/// P3 with a lender/lessor program supplied by the caller.
+///
+/// The program's special rates are matched against the deal's
+/// `program_code`; an ineligible or unknown code falls back to the
+/// standard rate with a warning.
+pub fn load_profiles_with_program(
+    deal: JurisdictionResolvedDeal,
+    lender_program: Option<&crate::types::ProgramProfile>,
+) -> UdcResult<ProfileLoadedDeal> {
+    load_profiles_with_options(deal, lender_program, &[], &PipelineOptions::default())
+}
+
+/// P3 with a lender program, F&I product profiles, and explicit
+/// [`PipelineOptions`].
+///
+/// Each product on the deal is checked against its profile's eligibility
+/// for the deal type and vehicle. Ineligible products are flagged with a
+/// warning, and removed when `options.exclude_ineligible_products` is set.
+/// Prices over the profile or program caps are flagged as well, as is a
+/// finance term the program does not offer (moved to the nearest offered
+/// term when `options.snap_to_available_term` is set).
+pub fn load_profiles_with_options(
+    mut deal: JurisdictionResolvedDeal,
+    lender_program: Option<&crate::types::ProgramProfile>,
+    product_profiles: &[ProductProfile],
+    options: &PipelineOptions,
+) -> UdcResult<ProfileLoadedDeal> {
+    // Gate F&I products before anything is built from them
+    let mut warnings = apply_product_eligibility(
+        &mut deal.deal.input,
+        product_profiles,
+        options.exclude_ineligible_products,
+    );
+
+    // Flag products the lender will cut back at funding
+    warnings.extend(check_product_caps(&deal.deal.input, product_profiles, lender_program));
+
+    // Flag a vehicle too old or too high-mileage for the program
+    let finance_term = |deal: &JurisdictionResolvedDeal| {
+        deal.deal.input.inner.finance_params.as_ref().map(|params| params.term_months)
+    };
+    let requested_term = finance_term(&deal);
+    if let Some(program) = lender_program {
+        warnings.extend(check_vehicle_limits(&deal.deal.input.inner, &program.structure_rules));
+        warnings.extend(check_available_term(
+            &mut deal.deal.input.inner,
+            &program.structure_rules,
+            options.snap_to_available_term,
+        ));
+    }
+    let requested_term_months = requested_term.filter(|&term| finance_term(&deal) != Some(term));
+
     let jurisdiction = &deal.jurisdiction;
     let input = &deal.deal.input.inner;
 
-    // Load primary rules for governing state
-    let primary_rules = load_rule_profile(
-        jurisdiction.governing_state,
-        input.deal_type,
-    )?;
+    // Load primary rules for governing state, preferring the caller's
+    let mut primary_rules = match &options.rule_profile {
+        Some(rules) if rules.state_code == jurisdiction.governing_state => rules.clone(),
+        _ => load_rule_profile(jurisdiction.governing_state, input.deal_type)?,
+    };
 
     // Load secondary rules if interstate
     let secondary_rules = if let Some(secondary) = jurisdiction.secondary_state {
@@ -104,17 +242,114 @@ pub fn load_profiles(deal: JurisdictionResolvedDeal) -> UdcResult<ProfileLoadedD
         None
     };
 
+    // Leases may be taxed where the vehicle is garaged
+    let mut lease_sourcing_rules = if input.deal_type == DealType::Lease
+        && jurisdiction.garaging_state != jurisdiction.governing_state
+    {
+        let garaging_rules = load_rule_profile(jurisdiction.garaging_state, input.deal_type)?;
+        garaging_rules.lease_sourced_to_garaging.then_some(garaging_rules)
+    } else {
+        None
+    };
+
+    // A caller-supplied combined rate is resolved here, once, so the lease
+    // structure and special taxes charge the same rate as the sales tax
+    if let Some(rate) = input.override_combined_rate {
+        primary_rules.override_combined_rate(rate.as_decimal());
+        if let Some(rules) = lease_sourcing_rules.as_mut() {
+            rules.override_combined_rate(rate.as_decimal());
+        }
+    }
+
+    // Default profiles carry guessed rates; flag them ahead of anything else
+    let defaulted_states = [Some(jurisdiction.governing_state), jurisdiction.secondary_state]
+        .into_iter()
+        .flatten()
+        .filter(|&state| !has_tuned_profile(state))
+        .map(|state| ValidationWarning {
+            code: "DEFAULT_RULE_PROFILE".to_string(),
+            field: "jurisdiction".to_string(),
+            message: format!("Using default profile for {}; rates not verified", state),
+        });
+    warnings.splice(0..0, defaulted_states);
+
     // Load program profile if lender specified
     let program = load_program_profile(input)?;
 
     // Build product tax rules
     let product_rules = build_product_tax_rules(&primary_rules, &input.products);
 
+    // Load dealer profile
+    let dealer = load_dealer_profile(input);
+
+    // Resolve subvented rate from program code
+    let subvention = resolve_subvention(input, lender_program, &mut warnings);
+
+    // Tier drives rate sheet and money factor lookups
+    let credit_tier = input.customer.effective_credit_tier();
+
+    // Products priced off the amount financed are sized in P5
+    let amount_financed_pricing = input.products.iter()
+        .filter_map(|product| {
+            let profile = find_product_profile(product, product_profiles)?;
+            (profile.pricing.model == PricingModel::AmountFinancedBased).then(|| AmountFinancedPricing {
+                product_id: product.id.clone(),
+                pricing: profile.pricing.clone(),
+            })
+        })
+        .collect();
+
+    // Check government fees against the statutory schedule, filling zero
+    // fees from it when asked to
+    let normalized = &mut deal.deal.input;
+    warnings.extend(apply_government_fee_schedule(
+        &mut normalized.inner.fees,
+        &primary_rules.government_fees,
+        options.fill_government_fees,
+    ));
+
+    // Fill the lender's acquisition/bank fee from the program
+    if let Some(program) = lender_program {
+        let deal_type = normalized.inner.deal_type;
+        warnings.extend(apply_program_fees(
+            &mut normalized.inner.fees,
+            deal_type,
+            &program.fee_config,
+        ));
+    }
+    normalized.total_fees = normalized.inner.fees.total();
+
+    // Split the registration fee for a fleet vehicle under IRP plates
+    let fees = &normalized.inner.fees;
+    let apportioned_registration = match &fees.apportioned_registration {
+        Some(registration) if normalized.inner.customer.customer_type == CustomerType::FleetCommercial => {
+            apportion_registration(fees.registration_fee, registration)
+        }
+        Some(_) => {
+            warnings.push(ValidationWarning {
+                code: "APPORTIONED_REGISTRATION_IGNORED".to_string(),
+                field: "fees.apportioned_registration".to_string(),
+                message: "Apportioned registration applies to fleet/commercial buyers only".to_string(),
+            });
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+
     let profiles = ProfileContext {
         primary_rules,
         secondary_rules,
+        lease_sourcing_rules,
         program,
         product_rules,
+        dealer,
+        lender_program: lender_program.cloned(),
+        subvention,
+        credit_tier,
+        amount_financed_pricing,
+        apportioned_registration,
+        requested_term_months,
+        warnings,
     };
 
     log::debug!(
@@ -126,6 +361,214 @@ pub fn load_profiles(deal: JurisdictionResolvedDeal) -> UdcResult<ProfileLoadedD
     Ok(ProfileLoadedDeal { deal, profiles })
 }
 
+/// A product's profile: the one with the same `id`, otherwise the first
+/// profile of the same product type.
+fn find_product_profile<'a>(
+    product: &crate::types::Product,
+    product_profiles: &'a [ProductProfile],
+) -> Option<&'a ProductProfile> {
+    product_profiles.iter()
+        .find(|p| p.id == product.id)
+        .or_else(|| product_profiles.iter().find(|p| p.product_type == product.product_type))
+}
+
+/// Check the deal's vehicle against the program's `max_vehicle_age` and
+/// `max_vehicle_mileage`.
+///
+/// Deals without vehicle details pass.
+fn check_vehicle_limits(
+    input: &crate::types::DealInput,
+    rules: &crate::types::StructureRules,
+) -> Vec<ValidationWarning> {
+    let mut warnings = Vec::new();
+    let Some(vehicle) = input.vehicle.as_ref() else {
+        return warnings;
+    };
+
+    if let (Some(max_age), Some(age)) = (rules.max_vehicle_age, input.vehicle_age()) {
+        if age > max_age {
+            warnings.push(ValidationWarning {
+                code: "VEHICLE_OVER_MAX_AGE".to_string(),
+                field: "vehicle.year".to_string(),
+                message: format!(
+                    "{} vehicle is {} years old; the program allows at most {}",
+                    vehicle.year, age, max_age
+                ),
+            });
+        }
+    }
+    if let Some(max_mileage) = rules.max_vehicle_mileage {
+        if vehicle.odometer > max_mileage {
+            warnings.push(ValidationWarning {
+                code: "VEHICLE_OVER_MAX_MILEAGE".to_string(),
+                field: "vehicle.odometer".to_string(),
+                message: format!(
+                    "Odometer {} exceeds the program maximum of {} by {}",
+                    vehicle.odometer, max_mileage, vehicle.odometer - max_mileage
+                ),
+            });
+        }
+    }
+
+    warnings
+}
+
+/// Check the finance term against the program's `available_terms`.
+///
+/// An empty list offers every term. Otherwise a term not on the list is
+/// flagged with the nearest offered term (the shorter on a tie), and moved
+/// to it when `snap` is set. The warning names the requested term, which
+/// [`load_profiles_with_options`] also keeps as `requested_term_months`.
+fn check_available_term(
+    input: &mut crate::types::DealInput,
+    rules: &crate::types::StructureRules,
+    snap: bool,
+) -> Option<ValidationWarning> {
+    let params = input.finance_params.as_mut()?;
+    let requested = params.term_months;
+    if rules.available_terms.is_empty() || rules.available_terms.contains(&requested) {
+        return None;
+    }
+    let nearest = *rules.available_terms
+        .iter()
+        .min_by_key(|&&term| (term.abs_diff(requested), term))?;
+
+    let offered = rules.available_terms
+        .iter()
+        .map(|term| term.to_string())
+        .collect::<Vec<_>>()
+        .join("/");
+    if snap {
+        params.term_months = nearest;
+        Some(ValidationWarning {
+            code: "TERM_SNAPPED".to_string(),
+            field: "finance_params.term_months".to_string(),
+            message: format!(
+                "Program does not offer a {}-month term ({} months); moved to the nearest, {} months",
+                requested, offered, nearest
+            ),
+        })
+    } else {
+        Some(ValidationWarning {
+            code: "TERM_NOT_OFFERED".to_string(),
+            field: "finance_params.term_months".to_string(),
+            message: format!(
+                "Program does not offer a {}-month term ({} months); nearest offered is {} months",
+                requested, offered, nearest
+            ),
+        })
+    }
+}
+
+/// Check each product on the deal against its profile's eligibility.
+///
+/// Products without a profile (see [`find_product_profile`]) pass.
+fn apply_product_eligibility(
+    normalized: &mut NormalizedDealInput,
+    product_profiles: &[ProductProfile],
+    exclude_ineligible: bool,
+) -> Vec<ValidationWarning> {
+    let input = &mut normalized.inner;
+    let vehicle_age = input.vehicle_age();
+    let mut warnings = Vec::new();
+    let mut ineligible = Vec::new();
+
+    for product in &input.products {
+        let Some(reason) = find_product_profile(product, product_profiles).and_then(|p| {
+            p.ineligibility_reason(input.deal_type, input.vehicle.as_ref(), vehicle_age)
+        }) else {
+            continue;
+        };
+
+        warnings.push(ValidationWarning {
+            code: "PRODUCT_INELIGIBLE".to_string(),
+            field: format!("products.{}", product.id),
+            message: format!(
+                "{} is ineligible: {}{}",
+                product.name,
+                reason,
+                if exclude_ineligible { "; removed from the deal" } else { "" }
+            ),
+        });
+        ineligible.push(product.id.clone());
+    }
+
+    if exclude_ineligible && !ineligible.is_empty() {
+        input.products.retain(|p| !ineligible.contains(&p.id));
+        let (taxable, non_taxable) = compute_product_totals(input);
+        normalized.total_taxable_products = taxable;
+        normalized.total_non_taxable_products = non_taxable;
+    }
+
+    warnings
+}
+
+/// Compare product prices to lender caps.
+///
+/// Each product is checked against its profile's `max_price`, and the
+/// total backend against the program's `max_backend_amount` and
+/// `max_backend_percent` of the vehicle price. Each warning carries the
+/// cap and the overage.
+fn check_product_caps(
+    normalized: &NormalizedDealInput,
+    product_profiles: &[ProductProfile],
+    lender_program: Option<&crate::types::ProgramProfile>,
+) -> Vec<ValidationWarning> {
+    let input = &normalized.inner;
+    let mut warnings = Vec::new();
+
+    for product in &input.products {
+        let Some(max_price) = find_product_profile(product, product_profiles)
+            .and_then(|profile| profile.pricing.max_price)
+        else {
+            continue;
+        };
+        if product.price > max_price {
+            warnings.push(ValidationWarning {
+                code: "PRODUCT_OVER_MAX_PRICE".to_string(),
+                field: format!("products.{}", product.id),
+                message: format!(
+                    "{} price {} exceeds the maximum of {} by {}",
+                    product.name, product.price, max_price, product.price - max_price
+                ),
+            });
+        }
+    }
+
+    let Some(rules) = lender_program.map(|program| &program.structure_rules) else {
+        return warnings;
+    };
+    let backend: Decimal = input.products.iter().map(|p| p.price).sum();
+
+    if let Some(max_amount) = rules.max_backend_amount {
+        if backend > max_amount {
+            warnings.push(ValidationWarning {
+                code: "BACKEND_OVER_MAX_AMOUNT".to_string(),
+                field: "products".to_string(),
+                message: format!(
+                    "Backend {} exceeds the program maximum of {} by {}",
+                    backend, max_amount, backend - max_amount
+                ),
+            });
+        }
+    }
+    if let Some(max_percent) = rules.max_backend_percent {
+        let cap = (normalized.selling_price * max_percent).round_dp(2);
+        if backend > cap {
+            warnings.push(ValidationWarning {
+                code: "BACKEND_OVER_MAX_PERCENT".to_string(),
+                field: "products".to_string(),
+                message: format!(
+                    "Backend {} exceeds the program maximum of {}% of vehicle price ({}) by {}",
+                    backend, (max_percent * dec!(100)).normalize(), cap, backend - cap
+                ),
+            });
+        }
+    }
+
+    warnings
+}
+
 /// Load a rule profile for a state/deal type combination.
 /// In production, this would query a database or cache.
 fn load_rule_profile(state: StateCode, deal_type: DealType) -> UdcResult<RuleProfile> {
@@ -137,19 +580,34 @@ fn load_rule_profile(state: StateCode, deal_type: DealType) -> UdcResult<RulePro
 /// Get a built-in state profile.
 /// These are default profiles - production would load from database.
 fn get_builtin_profile(state: StateCode, deal_type: DealType) -> UdcResult<RuleProfile> {
-    // Special state handling
+    Ok(tuned_profile(state, deal_type).unwrap_or_else(|| default_profile(state, deal_type)))
+}
+
+/// Whether a state has a hand-tuned built-in profile rather than the
+/// generic default.
+pub fn has_tuned_profile(state: StateCode) -> bool {
+    tuned_profile(state, DealType::Cash).is_some()
+}
+
+/// Hand-tuned profile for states with special handling.
+fn tuned_profile(state: StateCode, deal_type: DealType) -> Option<RuleProfile> {
     match state {
-        StateCode::GA => Ok(georgia_profile(deal_type)),
-        StateCode::NC => Ok(north_carolina_profile(deal_type)),
-        StateCode::WV => Ok(west_virginia_profile(deal_type)),
-        StateCode::TX => Ok(texas_profile(deal_type)),
-        StateCode::CA => Ok(california_profile(deal_type)),
-        StateCode::FL => Ok(florida_profile(deal_type)),
-        StateCode::NY => Ok(new_york_profile(deal_type)),
+        StateCode::GA => Some(georgia_profile(deal_type)),
+        StateCode::NC => Some(north_carolina_profile(deal_type)),
+        StateCode::WV => Some(west_virginia_profile(deal_type)),
+        StateCode::TX => Some(texas_profile(deal_type)),
+        StateCode::CA => Some(california_profile(deal_type)),
+        StateCode::FL => Some(florida_profile(deal_type)),
+        StateCode::NY => Some(new_york_profile(deal_type)),
+        StateCode::CO => Some(colorado_profile(deal_type)),
         StateCode::MT | StateCode::OR | StateCode::NH | StateCode::DE => {
-            Ok(no_tax_state_profile(state, deal_type))
+            Some(no_tax_state_profile(state, deal_type))
+        }
+        StateCode::PR => Some(puerto_rico_profile(deal_type)),
+        StateCode::VI | StateCode::GU | StateCode::AS | StateCode::MP => {
+            Some(no_sales_tax_territory_profile(state, deal_type))
         }
-        _ => Ok(default_profile(state, deal_type)),
+        _ => None,
     }
 }
 
@@ -175,7 +633,16 @@ fn georgia_profile(deal_type: DealType) -> RuleProfile {
             offers_reciprocity: true,
             ..Default::default()
         },
-        lease_tax_mode: Some(LeaseTaxMode::CapCostUpfront),
+        lease_tax_mode: Some(LeaseTaxMode::TotalPayments), // Lease TAVT on payments + down
+        lease_sourced_to_garaging: false,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
         meta: ProfileMeta {
             version: "2024.1".to_string(),
             effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
@@ -208,6 +675,15 @@ fn north_carolina_profile(deal_type: DealType) -> RuleProfile {
             ..Default::default()
         },
         lease_tax_mode: Some(LeaseTaxMode::MonthlyPayment),
+        lease_sourced_to_garaging: false,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
         meta: ProfileMeta {
             version: "2024.1".to_string(),
             effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
@@ -236,6 +712,15 @@ fn west_virginia_profile(deal_type: DealType) -> RuleProfile {
         ancillaries: AncillaryRules::default(),
         reciprocity: ReciprocityRules::default(),
         lease_tax_mode: Some(LeaseTaxMode::MonthlyPayment),
+        lease_sourced_to_garaging: false,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
         meta: ProfileMeta::default(),
     }
 }
@@ -268,6 +753,20 @@ fn texas_profile(deal_type: DealType) -> RuleProfile {
             ..Default::default()
         },
         lease_tax_mode: Some(LeaseTaxMode::MonthlyPayment),
+        lease_sourced_to_garaging: false,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule {
+            title_fee: dec!(33.00),
+            registration_fee: dec!(50.75),
+            new_plate_fee: dec!(0), // Plates stay with the vehicle in TX
+            plate_transfer_fee: dec!(0),
+        },
         meta: ProfileMeta {
             version: "2024.1".to_string(),
             effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
@@ -305,6 +804,15 @@ fn california_profile(deal_type: DealType) -> RuleProfile {
             ..Default::default()
         },
         lease_tax_mode: Some(LeaseTaxMode::MonthlyPayment),
+        lease_sourced_to_garaging: true,
+        tax_cap_reduction: true, // Down payment taxed at signing
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: false, // Trade equity taxed with the down payment
+        lease_doc_fee_taxable: Some(true), // Capitalized doc fee is taxed in the lease payment
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
         meta: ProfileMeta {
             version: "2024.1".to_string(),
             effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
@@ -340,6 +848,20 @@ fn florida_profile(deal_type: DealType) -> RuleProfile {
             ..Default::default()
         },
         lease_tax_mode: Some(LeaseTaxMode::MonthlyPayment),
+        lease_sourced_to_garaging: true,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule {
+            title_fee: dec!(77.25),
+            registration_fee: dec!(46.15),
+            new_plate_fee: dec!(225.00), // Initial registration fee for a new plate
+            plate_transfer_fee: dec!(4.60),
+        },
         meta: ProfileMeta::default(),
     }
 }
@@ -369,10 +891,62 @@ fn new_york_profile(deal_type: DealType) -> RuleProfile {
             ..Default::default()
         },
         lease_tax_mode: Some(LeaseTaxMode::CapCostUpfront), // NY taxes lease upfront
+        lease_sourced_to_garaging: true,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: true, // Unearned upfront tax refunded on early termination
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
         meta: ProfileMeta::default(),
     }
 }
 
+fn colorado_profile(deal_type: DealType) -> RuleProfile {
+    // Local rates (including self-collected home-rule cities) are resolved
+    // from the customer's city/county in P4; the combined rate is only the
+    // fallback when the locality is unknown.
+    RuleProfile {
+        state_code: StateCode::CO,
+        mode: deal_type,
+        tax_type: TaxType::Sales,
+        rates: TaxRates {
+            state_rate: dec!(0.029), // 2.9%
+            default_combined_rate: dec!(0.0775),
+            ..Default::default()
+        },
+        base_rules: BaseRules {
+            trade_in_reduces_basis: true,
+            rebates_reduce_basis: false,
+            doc_fee_taxable: true, // Dealer handling fee is taxable at the state level
+            ..Default::default()
+        },
+        ancillaries: AncillaryRules::default(),
+        reciprocity: ReciprocityRules {
+            offers_reciprocity: true,
+            ..Default::default()
+        },
+        lease_tax_mode: Some(LeaseTaxMode::MonthlyPayment),
+        lease_sourced_to_garaging: false,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
+        meta: ProfileMeta {
+            version: "2024.1".to_string(),
+            effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            source: Some("CO DOR".to_string()),
+            ..Default::default()
+        },
+    }
+}
+
 fn no_tax_state_profile(state: StateCode, deal_type: DealType) -> RuleProfile {
     RuleProfile {
         state_code: state,
@@ -386,10 +960,71 @@ fn no_tax_state_profile(state: StateCode, deal_type: DealType) -> RuleProfile {
         ancillaries: AncillaryRules::default(),
         reciprocity: ReciprocityRules::default(),
         lease_tax_mode: Some(LeaseTaxMode::Exempt),
+        lease_sourced_to_garaging: false,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
         meta: ProfileMeta::default(),
     }
 }
 
+fn puerto_rico_profile(deal_type: DealType) -> RuleProfile {
+    RuleProfile {
+        state_code: StateCode::PR,
+        mode: deal_type,
+        tax_type: TaxType::Sales,
+        rates: TaxRates {
+            state_rate: dec!(0.105), // IVU 10.5% territorial
+            max_local_rate: Some(dec!(0.01)), // 1% municipal IVU
+            default_combined_rate: dec!(0.115),
+            ..Default::default()
+        },
+        base_rules: BaseRules {
+            trade_in_reduces_basis: true,
+            rebates_reduce_basis: false,
+            ..Default::default()
+        },
+        ancillaries: AncillaryRules::default(),
+        reciprocity: ReciprocityRules::default(),
+        lease_tax_mode: Some(LeaseTaxMode::MonthlyPayment),
+        lease_sourced_to_garaging: false,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
+        meta: ProfileMeta {
+            version: "2024.1".to_string(),
+            effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            source: Some("PR Hacienda".to_string()),
+            notes: Some("IVU only; vehicle excise (arbitrios) is collected at import".to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+/// USVI, Guam, American Samoa, and the Northern Marianas levy no sales
+/// tax on vehicle sales (gross receipts and import excise fall on the seller)
+fn no_sales_tax_territory_profile(state: StateCode, deal_type: DealType) -> RuleProfile {
+    RuleProfile {
+        meta: ProfileMeta {
+            version: "2024.1".to_string(),
+            effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            notes: Some("Territory with no sales tax on vehicles".to_string()),
+            ..Default::default()
+        },
+        ..no_tax_state_profile(state, deal_type)
+    }
+}
+
 fn default_profile(state: StateCode, deal_type: DealType) -> RuleProfile {
     // Generic profile for states without special handling
     // Production system would have all 50 states defined
@@ -413,6 +1048,15 @@ fn default_profile(state: StateCode, deal_type: DealType) -> RuleProfile {
             ..Default::default()
         },
         lease_tax_mode: Some(LeaseTaxMode::MonthlyPayment),
+        lease_sourced_to_garaging: false,
+        tax_cap_reduction: false,
+        lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+        lease_trade_credit: true,
+        lease_doc_fee_taxable: None,
+        lease_prepaid_tax_refundable: false,
+        finance_tax_monthly: false,
+        tax_rounding: TaxRounding::PerComponent,
+        government_fees: GovernmentFeeSchedule::default(),
         meta: ProfileMeta {
             version: "default".to_string(),
             notes: Some("Default profile - verify state-specific rules".to_string()),
@@ -445,6 +1089,191 @@ fn load_program_profile(input: &crate::types::DealInput) -> UdcResult<Option<Pro
     }
 }
 
+/// Load the dealer profile supplied with the deal.
+/// In production, this would be looked up by dealer ID.
+fn load_dealer_profile(input: &crate::types::DealInput) -> Option<DealerProfile> {
+    input.dealer_profile.clone()
+}
+
+/// Resolve the deal's program code to a subvented rate.
+///
+/// Eligibility requires the program to be active on the deal date, the
+/// customer's credit tier to qualify, and the vehicle to match the
+/// program's model requirements. Lease codes must carry a money factor.
+fn resolve_subvention(
+    input: &crate::types::DealInput,
+    lender_program: Option<&crate::types::ProgramProfile>,
+    warnings: &mut Vec<ValidationWarning>,
+) -> Option<SubventedRate> {
+    let (code, field) = match input.deal_type {
+        DealType::Finance => (
+            input.finance_params.as_ref()?.program_code.as_deref()?,
+            "finance_params.program_code",
+        ),
+        DealType::Lease => (
+            input.lease_params.as_ref()?.program_code.as_deref()?,
+            "lease_params.program_code",
+        ),
+        DealType::Cash => return None,
+    };
+
+    let mut warn = |code: &str, message: String| {
+        warnings.push(ValidationWarning {
+            code: code.to_string(),
+            field: field.to_string(),
+            message,
+        });
+    };
+
+    let Some(special) = lender_program.and_then(|p| p.find_special_rate(code)) else {
+        warn("PROGRAM_NOT_FOUND", format!("Program {} not found; using standard rate", code));
+        return None;
+    };
+
+    let deal_date = input.effective_deal_date();
+    let credit_tier = input.customer.effective_credit_tier();
+    let reason = if !special.is_active_on(deal_date) {
+        Some(format!("not active on {}", deal_date))
+    } else if !special.is_tier_eligible(credit_tier) {
+        Some("credit tier not eligible".to_string())
+    } else if !special.is_vehicle_eligible(input.vehicle.as_ref()) {
+        Some("vehicle not eligible".to_string())
+    } else if input.deal_type == DealType::Lease && special.money_factor.is_none() {
+        Some("no subvented money factor".to_string())
+    } else {
+        None
+    };
+
+    if let Some(reason) = reason {
+        warn("PROGRAM_INELIGIBLE", format!("Program {} {}; using standard rate", code, reason));
+        return None;
+    }
+
+    Some(SubventedRate {
+        code: special.code.clone(),
+        description: special.description.clone(),
+        apr: special.rate,
+        money_factor: special.money_factor,
+    })
+}
+
+/// Split a registration fee across jurisdictions by mileage (IRP).
+///
+/// # Formula
+/// ```text
+/// fee_j = registration_fee * miles_j / total_miles
+/// ```
+///
+/// Each share is rounded to the cent and the last jurisdiction takes the
+/// rounding remainder, so the fees always sum to `registration_fee`.
+pub fn apportion_registration(
+    registration_fee: Decimal,
+    registration: &crate::types::ApportionedRegistration,
+) -> Vec<ApportionedFee> {
+    let total_miles = Decimal::from(registration.total_miles());
+    if total_miles.is_zero() {
+        return Vec::new();
+    }
+
+    let mut allocated = dec!(0);
+    let last = registration.jurisdictions.len().saturating_sub(1);
+    registration.jurisdictions
+        .iter()
+        .enumerate()
+        .map(|(i, jurisdiction)| {
+            let share = Decimal::from(jurisdiction.miles) / total_miles;
+            let fee = if i == last {
+                registration_fee - allocated
+            } else {
+                (registration_fee * share).round_dp(2)
+            };
+            allocated += fee;
+
+            ApportionedFee {
+                state: jurisdiction.state,
+                miles: jurisdiction.miles,
+                share,
+                fee,
+            }
+        })
+        .collect()
+}
+
+/// Apply a state's government fee schedule to the deal fees.
+///
+/// With `fill_zero_fees`, fees left at zero are populated from the
+/// schedule and flagged; otherwise a zero fee is kept. Fees that differ
+/// from the statutory amount by more than
+/// [`GOVERNMENT_FEE_VARIANCE_THRESHOLD`] are kept but produce a warning.
+fn apply_government_fee_schedule(
+    fees: &mut DealFees,
+    schedule: &GovernmentFeeSchedule,
+    fill_zero_fees: bool,
+) -> Vec<ValidationWarning> {
+    let plate_fee = schedule.plate_fee(fees.plate_transfer);
+    let mut warnings = Vec::new();
+
+    for (field, amount, statutory) in [
+        ("fees.title_fee", &mut fees.title_fee, schedule.title_fee),
+        ("fees.registration_fee", &mut fees.registration_fee, schedule.registration_fee),
+        ("fees.plate_fee", &mut fees.plate_fee, plate_fee),
+    ] {
+        if statutory.is_zero() {
+            continue;
+        }
+        if amount.is_zero() {
+            if fill_zero_fees {
+                *amount = statutory;
+                warnings.push(ValidationWarning {
+                    code: "GOVERNMENT_FEE_APPLIED".to_string(),
+                    field: field.to_string(),
+                    message: format!("No fee given; statutory fee {} applied", statutory),
+                });
+            }
+        } else if (*amount - statutory).abs() > GOVERNMENT_FEE_VARIANCE_THRESHOLD {
+            warnings.push(ValidationWarning {
+                code: "GOVERNMENT_FEE_VARIANCE".to_string(),
+                field: field.to_string(),
+                message: format!("{} differs from statutory fee {}", amount, statutory),
+            });
+        }
+    }
+
+    warnings
+}
+
+/// Apply the lender program's acquisition fee (lease) or bank fee (finance).
+///
+/// A zero input fee is filled from the program; a non-zero fee that
+/// disagrees with the program is kept and flagged with a warning.
+fn apply_program_fees(
+    fees: &mut DealFees,
+    deal_type: DealType,
+    fee_config: &crate::types::ProgramFeeConfig,
+) -> Vec<ValidationWarning> {
+    let (field, amount, program_fee) = match deal_type {
+        DealType::Lease => ("fees.acquisition_fee", &mut fees.acquisition_fee, fee_config.acquisition_fee),
+        DealType::Finance => ("fees.bank_fee", &mut fees.bank_fee, fee_config.bank_fee),
+        DealType::Cash => return Vec::new(),
+    };
+
+    if program_fee.is_zero() {
+        return Vec::new();
+    }
+    if amount.is_zero() {
+        *amount = program_fee;
+        return Vec::new();
+    }
+    if *amount != program_fee {
+        return vec![ValidationWarning {
+            code: "PROGRAM_FEE_MISMATCH".to_string(),
+            field: field.to_string(),
+            message: format!("{} differs from program fee {}", amount, program_fee),
+        }];
+    }
+    Vec::new()
+}
+
 /// Build product tax rules from state rules and product list
 fn build_product_tax_rules(
     rules: &RuleProfile,
@@ -454,7 +1283,7 @@ fn build_product_tax_rules(
         .iter()
         .map(|p| {
             let product_type = format!("{:?}", p.product_type).to_lowercase();
-            let taxable = rules.ancillaries.is_product_taxable(&product_type);
+            let taxable = rules.ancillaries.is_product_type_taxable(p.product_type);
             ProductTaxRule {
                 product_type,
                 taxable,
@@ -467,41 +1296,18 @@ fn build_product_tax_rules(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{DealInput, DealFees, CustomerInfo, FinanceParams};
+    use crate::types::{DealInput, FinanceParams};
     use crate::phases::p0_normalize::normalize_deal_input;
     use crate::phases::p1_mode_routing::route_deal;
     use crate::phases::p2_jurisdiction::resolve_jurisdiction;
 
     fn make_test_deal(home: StateCode, deal_type: DealType) -> JurisdictionResolvedDeal {
         let input = DealInput {
-            deal_type,
-            vehicle_price: dec!(30000),
             trade_in_value: Some(dec!(10000)),
             trade_in_payoff: Some(dec!(5000)),
             cash_down: dec!(2000),
-            rebates: vec![],
-            products: vec![],
-            fees: DealFees::default(),
-            home_state: home,
-            transaction_state: home,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: if deal_type == DealType::Finance {
-                Some(FinanceParams {
-                    term_months: 60,
-                    apr: dec!(0.0599),
-                    lender_id: None,
-                    buy_rate: None,
-                    max_reserve_points: None,
-                    deferred_first_payment: false,
-                    days_to_first_payment: None,
-                })
-            } else {
-                None
-            },
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
+            finance_params: (deal_type == DealType::Finance).then(|| FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(deal_type, dec!(30000), home)
         };
 
         let normalized = normalize_deal_input(input).unwrap();
@@ -520,6 +1326,54 @@ mod tests {
         assert!(!loaded.profiles.primary_rules.base_rules.rebates_reduce_basis);
     }
 
+    #[test]
+    fn test_subvention_without_deal_date_uses_today() {
+        use crate::types::{ProgramProfile, RateSheet, SpecialRate};
+
+        let today = chrono::Local::now().date_naive();
+        let special = SpecialRate {
+            code: "PROMO09".to_string(),
+            description: "0.9% APR promotional financing".to_string(),
+            rate: dec!(0.009),
+            money_factor: None,
+            required_tiers: vec![],
+            vehicle_requirements: vec![],
+            start_date: today - chrono::Duration::days(30),
+            end_date: today + chrono::Duration::days(30),
+            allows_reserve: false,
+            max_reserve_with_special: None,
+        };
+        let program = ProgramProfile {
+            id: "captive".to_string(),
+            name: "Captive Finance".to_string(),
+            lender_code: "CAPTIVE".to_string(),
+            program_type: DealType::Finance,
+            is_captive: true,
+            brand: None,
+            rate_sheet: RateSheet {
+                effective_date: today - chrono::Duration::days(30),
+                expiration_date: None,
+                tiers: vec![],
+                reserve_caps: vec![],
+                default_max_reserve: dec!(0),
+                special_rates: vec![special],
+            },
+            structure_rules: Default::default(),
+            fee_config: Default::default(),
+            eligibility: Default::default(),
+            lease_program: None,
+            meta: Default::default(),
+        };
+
+        let mut deal = make_test_deal(StateCode::TX, DealType::Finance);
+        deal.deal.input.inner.finance_params.as_mut().unwrap().program_code = Some("PROMO09".to_string());
+        deal.deal.input.inner.deal_date = None;
+
+        let loaded = load_profiles_with_program(deal, Some(&program)).unwrap();
+        assert!(!loaded.profiles.warnings.iter().any(|w| w.code.starts_with("PROGRAM_")));
+        assert_eq!(loaded.profiles.subvention.unwrap().apr, dec!(0.009));
+    }
+
     #[test]
     fn test_load_california_profile() {
         let deal = make_test_deal(StateCode::CA, DealType::Finance);
@@ -548,4 +1402,237 @@ mod tests {
         assert_eq!(loaded.profiles.primary_rules.tax_type, TaxType::None);
         assert_eq!(loaded.profiles.primary_rules.rates.state_rate, dec!(0));
     }
+
+    #[test]
+    fn test_territories_not_defaulted() {
+        let deal = make_test_deal(StateCode::PR, DealType::Cash);
+        let rules = load_profiles(deal).unwrap().profiles.primary_rules;
+        assert_eq!(rules.state_code, StateCode::PR);
+        assert_eq!(rules.rates.state_rate, dec!(0.105));
+        assert_eq!(rules.rates.default_combined_rate, dec!(0.115));
+        assert_ne!(rules.meta.version, "default");
+
+        for territory in [StateCode::VI, StateCode::GU, StateCode::AS, StateCode::MP] {
+            let deal = make_test_deal(territory, DealType::Cash);
+            let rules = load_profiles(deal).unwrap().profiles.primary_rules;
+            assert_eq!(rules.tax_type, TaxType::None, "{:?}", territory);
+            assert_eq!(rules.rates.state_rate, dec!(0));
+        }
+    }
+
+    #[test]
+    fn test_default_profile_warning() {
+        let deal = make_test_deal(StateCode::AL, DealType::Finance);
+        let warnings = load_profiles(deal).unwrap().profiles.warnings;
+        assert_eq!(warnings[0].code, "DEFAULT_RULE_PROFILE");
+        assert_eq!(warnings[0].message, "Using default profile for AL; rates not verified");
+
+        let deal = make_test_deal(StateCode::TX, DealType::Finance);
+        let warnings = load_profiles(deal).unwrap().profiles.warnings;
+        assert!(warnings.iter().all(|w| w.code != "DEFAULT_RULE_PROFILE"));
+    }
+
+    #[test]
+    fn test_government_fees_filled_from_schedule() {
+        let mut deal = make_test_deal(StateCode::TX, DealType::Finance);
+        deal.deal.input.inner.fees.title_fee = dec!(0);
+        deal.deal.input.inner.fees.registration_fee = dec!(0);
+
+        let options = PipelineOptions { fill_government_fees: true, ..Default::default() };
+        let result = load_profiles_with_options(deal, None, &[], &options).unwrap();
+        let fees = &result.deal.deal.input.inner.fees;
+
+        assert_eq!(fees.title_fee, dec!(33.00));
+        assert_eq!(fees.registration_fee, dec!(50.75));
+        assert_eq!(result.deal.deal.input.total_fees, fees.total());
+        let warnings = &result.profiles.warnings;
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().all(|w| w.code == "GOVERNMENT_FEE_APPLIED"));
+        assert_eq!(warnings[0].field, "fees.title_fee");
+    }
+
+    #[test]
+    fn test_zero_government_fees_kept_by_default() {
+        let mut deal = make_test_deal(StateCode::TX, DealType::Finance);
+        deal.deal.input.inner.fees.title_fee = dec!(0);
+        deal.deal.input.inner.fees.registration_fee = dec!(0);
+
+        let result = load_profiles(deal).unwrap();
+        let fees = &result.deal.deal.input.inner.fees;
+
+        assert_eq!(fees.title_fee, dec!(0));
+        assert_eq!(fees.registration_fee, dec!(0));
+        assert!(result.profiles.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_government_fee_variance_warning() {
+        let mut deal = make_test_deal(StateCode::FL, DealType::Finance);
+        deal.deal.input.inner.fees.title_fee = dec!(150);
+        deal.deal.input.inner.fees.plate_transfer = true;
+
+        let options = PipelineOptions { fill_government_fees: true, ..Default::default() };
+        let result = load_profiles_with_options(deal, None, &[], &options).unwrap();
+        let fees = &result.deal.deal.input.inner.fees;
+
+        // Input fee is kept but flagged; plate uses the transfer fee
+        assert_eq!(fees.title_fee, dec!(150));
+        assert_eq!(fees.plate_fee, dec!(4.60));
+        let variances: Vec<_> =
+            result.profiles.warnings.iter().filter(|w| w.code == "GOVERNMENT_FEE_VARIANCE").collect();
+        assert_eq!(variances.len(), 1);
+        assert_eq!(variances[0].field, "fees.title_fee");
+    }
+
+    fn two_state_miles(tx_miles: u32, ok_miles: u32) -> crate::types::ApportionedRegistration {
+        use crate::types::{ApportionedRegistration, JurisdictionMiles};
+
+        ApportionedRegistration {
+            jurisdictions: vec![
+                JurisdictionMiles { state: StateCode::TX, miles: tx_miles },
+                JurisdictionMiles { state: StateCode::OK, miles: ok_miles },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_apportioned_registration_two_state_split() {
+        let mut deal = make_test_deal(StateCode::TX, DealType::Finance);
+        deal.deal.input.inner.customer.customer_type = CustomerType::FleetCommercial;
+        deal.deal.input.inner.fees.registration_fee = dec!(1200);
+        deal.deal.input.inner.fees.apportioned_registration = Some(two_state_miles(75_000, 25_000));
+
+        let result = load_profiles(deal).unwrap();
+        let lines = &result.profiles.apportioned_registration;
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].state, lines[0].share, lines[0].fee), (StateCode::TX, dec!(0.75), dec!(900)));
+        assert_eq!((lines[1].state, lines[1].share, lines[1].fee), (StateCode::OK, dec!(0.25), dec!(300)));
+        assert!(result.profiles.warnings.iter().all(|w| w.code != "APPORTIONED_REGISTRATION_IGNORED"));
+    }
+
+    #[test]
+    fn test_apportioned_registration_sums_to_fee() {
+        // Thirds do not divide evenly; the last state takes the remainder
+        let lines = apportion_registration(dec!(100), &two_state_miles(1, 2));
+        assert_eq!(lines[0].fee, dec!(33.33));
+        assert_eq!(lines[1].fee, dec!(66.67));
+        assert_eq!(lines.iter().map(|l| l.fee).sum::<Decimal>(), dec!(100));
+    }
+
+    #[test]
+    fn test_apportioned_registration_requires_fleet_buyer() {
+        let mut deal = make_test_deal(StateCode::TX, DealType::Finance);
+        deal.deal.input.inner.fees.apportioned_registration = Some(two_state_miles(75_000, 25_000));
+
+        let result = load_profiles(deal).unwrap();
+        assert!(result.profiles.apportioned_registration.is_empty());
+        assert!(result.profiles.warnings.iter().any(|w| w.code == "APPORTIONED_REGISTRATION_IGNORED"));
+    }
+
+    fn make_program() -> crate::types::ProgramProfile {
+        use crate::types::{ProgramProfile, RateSheet};
+
+        ProgramProfile {
+            id: "captive-2024".to_string(),
+            name: "Captive Finance".to_string(),
+            lender_code: "CAPTIVE".to_string(),
+            program_type: DealType::Finance,
+            is_captive: true,
+            brand: None,
+            rate_sheet: RateSheet {
+                effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                expiration_date: None,
+                tiers: vec![],
+                reserve_caps: vec![],
+                default_max_reserve: dec!(0),
+                special_rates: vec![],
+            },
+            structure_rules: Default::default(),
+            fee_config: Default::default(),
+            eligibility: Default::default(),
+            lease_program: None,
+            meta: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_vehicle_over_program_age_limit() {
+        use crate::types::{Money, Vehicle, VehicleCondition};
+
+        let load = |year: u16, odometer: u32| {
+            let mut deal = make_test_deal(StateCode::TX, DealType::Finance);
+            deal.deal.input.inner.deal_date = NaiveDate::from_ymd_opt(2024, 6, 1);
+            deal.deal.input.inner.vehicle = Some(Vehicle {
+                vin: "1HGCM82633A004352".to_string(),
+                year,
+                make: "Honda".to_string(),
+                model: "Accord".to_string(),
+                trim: None,
+                condition: VehicleCondition::Used,
+                msrp: Money::new(dec!(30000)),
+                invoice: None,
+                selling_price: Money::new(dec!(14000)),
+                odometer,
+                destination_fee: None,
+            });
+            let mut program = make_program();
+            program.structure_rules.max_vehicle_age = Some(8);
+            program.structure_rules.max_vehicle_mileage = Some(100_000);
+
+            load_profiles_with_program(deal, Some(&program)).unwrap().profiles.warnings
+        };
+
+        // A 2014 model is 10 years old in 2024
+        let warnings = load(2014, 90_000);
+        let warning = warnings.iter().find(|w| w.code == "VEHICLE_OVER_MAX_AGE").expect("age warning");
+        assert_eq!(warning.field, "vehicle.year");
+        assert!(warning.message.contains("10 years old"));
+        assert!(warnings.iter().all(|w| w.code != "VEHICLE_OVER_MAX_MILEAGE"));
+
+        let warnings = load(2018, 120_000);
+        assert!(warnings.iter().all(|w| w.code != "VEHICLE_OVER_MAX_AGE"));
+        assert!(warnings.iter().any(|w| w.code == "VEHICLE_OVER_MAX_MILEAGE"));
+    }
+
+    #[test]
+    fn test_term_not_offered_by_program() {
+        let load = |term_months: u32, options: &PipelineOptions| {
+            let mut deal = make_test_deal(StateCode::TX, DealType::Finance);
+            deal.deal.input.inner.finance_params.as_mut().unwrap().term_months = term_months;
+            let mut program = make_program();
+            program.structure_rules.available_terms = vec![60, 72];
+
+            load_profiles_with_options(deal, Some(&program), &[], options).unwrap()
+        };
+        let term = |loaded: &ProfileLoadedDeal| loaded.deal.deal.input.inner.finance_params.as_ref().unwrap().term_months;
+        let term_warning = |loaded: &ProfileLoadedDeal| {
+            loaded.profiles.warnings.iter().find(|w| w.code.starts_with("TERM_")).cloned()
+        };
+
+        // 66 months sits between 60 and 72; the shorter term is suggested
+        let loaded = load(66, &PipelineOptions::default());
+        let warning = term_warning(&loaded).expect("66-month term flagged");
+        assert_eq!(warning.code, "TERM_NOT_OFFERED");
+        assert_eq!(warning.field, "finance_params.term_months");
+        assert!(warning.message.contains("nearest offered is 60 months"), "{}", warning.message);
+        assert_eq!(term(&loaded), 66);
+        assert_eq!(loaded.profiles.requested_term_months, None);
+
+        // Snapping prices the deal on the suggested term and keeps the
+        // requested one
+        let options = PipelineOptions { snap_to_available_term: true, ..Default::default() };
+        let loaded = load(66, &options);
+        let warning = term_warning(&loaded).expect("snap flagged");
+        assert_eq!(warning.code, "TERM_SNAPPED");
+        assert!(warning.message.contains("a 66-month term"), "{}", warning.message);
+        assert_eq!(term(&loaded), 60);
+        assert_eq!(loaded.profiles.requested_term_months, Some(66));
+
+        let loaded = load(72, &options);
+        assert!(term_warning(&loaded).is_none());
+        assert_eq!(loaded.profiles.requested_term_months, None);
+        let warning = term_warning(&load(70, &PipelineOptions::default())).unwrap();
+        assert!(warning.message.contains("nearest offered is 72 months"), "{}", warning.message);
+    }
 }
@@ -5,6 +5,7 @@
 //! - Deal structure (trade-in, rebates, fees)
 //! - Special state handling (GA TAVT, NC HUT, WV excise)
 //! - Interstate reciprocity
+//! - Customer type (resale and certificate exemptions)
 //!
 //! # Tax Base Formula (Standard)
 //! ```text
@@ -19,15 +20,20 @@
 //! - Tax base is never negative
 //! - Trade/rebate credits are never double-applied
 //! - Reciprocity credits never exceed theoretical home state tax
+//!
+//! # Output Order
+//! Tax components are sorted state, county, city, district, special
+//! (see [`TaxLevel`]).
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
 use crate::types::{
-    TaxType, LeaseTaxMode, StateCode,
-    UdcResult, UdcError, DecimalExt,
+    CustomerInfo, CustomerType, DealFees, DealType, TaxType, TaxRounding, LeaseTaxMode, LeaseMonthlyTaxBase, StateCode, RuleProfile,
+    UdcResult, UdcError, ValidationWarning, DecimalExt,
 };
 use super::p3_profiles::ProfileLoadedDeal;
+use super::PipelineOptions;
 
 /// Complete tax calculation result
 #[derive(Debug, Clone)]
@@ -50,10 +56,21 @@ pub struct TaxCalculation {
     pub components: Vec<TaxComponent>,
     /// Special tax amounts (TAVT, HUT, etc.)
     pub special_tax: Option<SpecialTax>,
+    /// Treatment that follows from the buyer's customer type
+    pub customer_treatment: CustomerTaxTreatment,
     /// Audit trail entries
     pub audit: Vec<TaxAuditEntry>,
 }
 
+/// How the buyer's customer type changes the tax
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomerTaxTreatment {
+    /// Retail or fleet buyer, taxed normally
+    Taxable,
+    /// Dealer buying for resale, or a buyer holding an exemption certificate
+    Exempt,
+}
+
 /// Breakdown of how the tax base was calculated
 #[derive(Debug, Clone)]
 pub struct TaxBaseBreakdown {
@@ -62,6 +79,9 @@ pub struct TaxBaseBreakdown {
     pub taxable_products: Decimal,
     pub trade_credit_applied: Decimal,
     pub rebates_applied: Decimal,
+    /// Price-basis exemption taken off the base (e.g., EV exemption)
+    pub exemption_applied: Decimal,
+    /// Cap/floor/rounding adjustments so the breakdown sums to the base
     pub adjustments: Decimal,
     /// Reason for any cap application
     pub cap_applied: Option<String>,
@@ -75,9 +95,16 @@ pub struct TaxComponent {
     pub rate: Decimal,
     pub base: Decimal,
     pub amount: Decimal,
+    /// Collected directly by the locality (e.g., CO home-rule city)
+    /// rather than by the state
+    pub self_collected: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Jurisdiction level of a tax component.
+///
+/// Declaration order is the output order: P4 sorts components state,
+/// county, city, district, then special.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TaxLevel {
     State,
     County,
@@ -112,8 +139,13 @@ pub struct TaxAuditEntry {
 pub struct TaxComputedDeal {
     pub deal: ProfileLoadedDeal,
     pub tax: TaxCalculation,
+    /// Non-blocking warnings raised while computing tax
+    pub warnings: Vec<ValidationWarning>,
 }
 
+/// Combined rate above which a tax setup is flagged as implausible
+pub const DEFAULT_MAX_COMBINED_RATE: Decimal = dec!(0.15);
+
 /// P4: Calculate all taxes for the deal.
 ///
 /// # Algorithm Overview
@@ -146,6 +178,15 @@ pub struct TaxComputedDeal {
 /// - Time: O(c) where c = number of tax components
 /// - Space: O(c) for component storage
 pub fn calculate_tax(deal: ProfileLoadedDeal) -> UdcResult<TaxComputedDeal> {
+    calculate_tax_with_options(deal, &PipelineOptions::default())
+}
+
+/// P4 with explicit [`PipelineOptions`]. Standard tax components are
+/// rounded by the jurisdiction's [`TaxRounding`], not `options.rounding`.
+pub fn calculate_tax_with_options(
+    deal: ProfileLoadedDeal,
+    options: &PipelineOptions,
+) -> UdcResult<TaxComputedDeal> {
     let rules = &deal.profiles.primary_rules;
     let input = &deal.deal.deal.input;
 
@@ -154,8 +195,25 @@ pub fn calculate_tax(deal: ProfileLoadedDeal) -> UdcResult<TaxComputedDeal> {
     // Step 1: Build initial tax base
     let (base_breakdown, initial_base) = build_tax_base(&deal, &mut audit)?;
 
-    // Step 2: Handle special tax types
+    // Step 2: Exempt buyers owe no tax
+    let customer_treatment = customer_tax_treatment(&input.inner.customer, initial_base, &mut audit);
+    let exempt = customer_treatment == CustomerTaxTreatment::Exempt;
+
+    // Step 3: Handle special tax types
+    let is_lease = input.inner.deal_type == DealType::Lease;
     let (tax_type, special_tax) = match rules.tax_type {
+        _ if exempt => (rules.tax_type, None),
+        // Lease TAVT is taxed on the payments, known once P5 structures the lease
+        TaxType::Tavt if is_lease => {
+            audit.push(TaxAuditEntry {
+                step: "TAVT_LEASE".to_string(),
+                description: "Lease TAVT deferred to lease structuring".to_string(),
+                input_value: initial_base,
+                output_value: dec!(0),
+                rule_applied: "TAVT on base payments + down payment".to_string(),
+            });
+            (TaxType::Tavt, None)
+        }
         TaxType::Tavt => calculate_tavt(&deal, initial_base, &mut audit)?,
         TaxType::Hut => calculate_hut(&deal, initial_base, &mut audit)?,
         TaxType::Excise => calculate_excise(&deal, initial_base, &mut audit)?,
@@ -166,8 +224,11 @@ pub fn calculate_tax(deal: ProfileLoadedDeal) -> UdcResult<TaxComputedDeal> {
         }
     };
 
-    // Step 3: Calculate tax components
-    let (components, primary_tax, effective_rate) = if tax_type == TaxType::None {
+    // Step 4: Calculate tax components
+    let (components, primary_tax, effective_rate) = if exempt
+        || tax_type == TaxType::None
+        || (tax_type == TaxType::Tavt && special_tax.is_none())
+    {
         (vec![], dec!(0), dec!(0))
     } else if special_tax.is_some() {
         // Special tax already calculated
@@ -178,16 +239,20 @@ pub fn calculate_tax(deal: ProfileLoadedDeal) -> UdcResult<TaxComputedDeal> {
             rate: st.rate,
             base: st.base,
             amount: st.amount,
+            self_collected: false,
         };
         (vec![comp], st.amount, st.rate)
     } else {
         calculate_standard_tax(&deal, initial_base, &mut audit)?
     };
 
-    // Step 4: Calculate reciprocity credit
+    let mut components = components;
+    sort_tax_components(&mut components);
+
+    // Step 5: Calculate reciprocity credit
     let reciprocity_credit = calculate_reciprocity_credit(&deal, primary_tax, &mut audit)?;
 
-    // Step 5: Compute net tax
+    // Step 6: Compute net tax
     let net_tax = (primary_tax - reciprocity_credit).max(dec!(0));
 
     audit.push(TaxAuditEntry {
@@ -209,13 +274,82 @@ pub fn calculate_tax(deal: ProfileLoadedDeal) -> UdcResult<TaxComputedDeal> {
         net_tax,
         components,
         special_tax,
+        customer_treatment,
         audit,
     };
 
     // Validate invariants
     validate_tax_invariants(&tax)?;
 
-    Ok(TaxComputedDeal { deal, tax })
+    let max_rate = options.max_combined_rate.unwrap_or(DEFAULT_MAX_COMBINED_RATE);
+    let overridden = input.inner.override_combined_rate.is_some();
+    let warnings = check_combined_rate(effective_rate, max_rate, overridden).into_iter().collect();
+
+    Ok(TaxComputedDeal { deal, tax, warnings })
+}
+
+/// Flag a combined rate that is negative or above `max_rate`.
+///
+/// Local rates come from lookup tables and can be overridden by the caller,
+/// so a bad entry would otherwise silently produce an absurd tax.
+pub fn check_combined_rate(rate: Decimal, max_rate: Decimal, overridden: bool) -> Option<ValidationWarning> {
+    if rate >= dec!(0) && rate <= max_rate {
+        return None;
+    }
+    let field = if overridden { "override_combined_rate" } else { "rules.rates" };
+    Some(ValidationWarning {
+        code: "COMBINED_RATE_OUT_OF_RANGE".to_string(),
+        field: field.to_string(),
+        message: format!(
+            "Combined tax rate {}% is outside 0% to {}%; check the rate table or override",
+            (rate * dec!(100)).normalize(),
+            (max_rate * dec!(100)).normalize()
+        ),
+    })
+}
+
+/// Put tax components in their documented output order.
+///
+/// Components are ordered by [`TaxLevel`] (state, county, city, district,
+/// special) whatever order they were computed in. The sort is stable, so
+/// components at the same level keep their rate-table order.
+pub fn sort_tax_components(components: &mut [TaxComponent]) {
+    components.sort_by_key(|component| component.level);
+}
+
+/// Classify the buyer for tax.
+///
+/// # Rules
+/// - Dealer buying for resale: exempt
+/// - Any buyer flagged `tax_exempt` (government, non-profit with a
+///   certificate): exempt
+/// - Everyone else, fleet/commercial buyers included: taxed as retail.
+///   A fleet buyer's registration fee is apportioned in P3, not here.
+fn customer_tax_treatment(
+    customer: &CustomerInfo,
+    tax_base: Decimal,
+    audit: &mut Vec<TaxAuditEntry>,
+) -> CustomerTaxTreatment {
+    if customer.customer_type == CustomerType::Dealer || customer.tax_exempt {
+        let reason = if customer.customer_type == CustomerType::Dealer {
+            "resale".to_string()
+        } else {
+            format!(
+                "exemption certificate {}",
+                customer.tax_exempt_cert.as_deref().unwrap_or("not provided")
+            )
+        };
+        audit.push(TaxAuditEntry {
+            step: "CUSTOMER_EXEMPT".to_string(),
+            description: format!("{:?} customer is exempt from tax", customer.customer_type),
+            input_value: tax_base,
+            output_value: dec!(0),
+            rule_applied: reason,
+        });
+        return CustomerTaxTreatment::Exempt;
+    }
+
+    CustomerTaxTreatment::Taxable
 }
 
 /// Build the tax base from deal components.
@@ -230,21 +364,32 @@ fn build_tax_base(
     deal: &ProfileLoadedDeal,
     audit: &mut Vec<TaxAuditEntry>,
 ) -> UdcResult<(TaxBaseBreakdown, Decimal)> {
-    let input = &deal.deal.deal.input.inner;
+    let normalized = &deal.deal.deal.input;
+    let input = &normalized.inner;
     let rules = &deal.profiles.primary_rules;
 
-    // Start with selling price
-    let selling_price = input.vehicle_price;
+    // Start with selling price. A destination bundled into the price comes
+    // back out in states that do not tax destination
+    let included_destination = normalized.included_destination();
+    let selling_price = if included_destination > dec!(0) && !rules.base_rules.destination_taxable {
+        let excluded = included_destination.min(normalized.selling_price);
+        audit.push(TaxAuditEntry {
+            step: "DESTINATION_EXCLUDED".to_string(),
+            description: "Removed non-taxable destination included in price".to_string(),
+            input_value: normalized.selling_price,
+            output_value: normalized.selling_price - excluded,
+            rule_applied: format!("destination_taxable=false, included_in_price({})", excluded),
+        });
+        normalized.selling_price - excluded
+    } else {
+        normalized.selling_price
+    };
 
     // Calculate taxable fees
-    let taxable_fees = calculate_taxable_fees(&input.fees, rules);
+    let taxable_fees = calculate_taxable_fees(&input.fees, rules, audit);
 
     // Calculate taxable products
-    let taxable_products = input.products
-        .iter()
-        .filter(|p| p.taxable)
-        .map(|p| p.price)
-        .sum::<Decimal>();
+    let taxable_products = calculate_taxable_products(&input.products, rules, audit);
 
     // Initial base before credits
     let mut base = selling_price + taxable_fees + taxable_products;
@@ -260,8 +405,9 @@ fn build_tax_base(
 
     // Apply trade-in credit if allowed
     let trade_credit_applied = if rules.base_rules.trade_in_reduces_basis {
-        let gross_credit = input.trade_in_value.unwrap_or_default();
-        // Apply cap if exists
+        // Every qualifying trade on the deal counts toward the credit
+        let gross_credit = qualifying_trade_value(input, &rules.base_rules, audit);
+        // Apply cap (to the aggregate) if exists
         let credit = match rules.base_rules.max_trade_in_credit {
             Some(cap) => gross_credit.min(cap),
             None => gross_credit,
@@ -277,15 +423,15 @@ fn build_tax_base(
         audit.push(TaxAuditEntry {
             step: "TRADE_CREDIT".to_string(),
             description: "Applied trade-in credit".to_string(),
-            input_value: input.trade_in_value.unwrap_or_default(),
+            input_value: input.total_trade_value(),
             output_value: trade_credit_applied,
             rule_applied: format!("trade_reduces_basis={}", rules.base_rules.trade_in_reduces_basis),
         });
     }
 
-    // Apply rebate reduction if allowed
+    // Apply rebate reduction if allowed, for rebates flagged to reduce it
     let rebates_applied = if rules.base_rules.rebates_reduce_basis {
-        let total_rebates = input.total_rebates();
+        let total_rebates = input.tax_basis_rebates();
         // Cannot reduce below zero
         total_rebates.min(base)
     } else {
@@ -303,6 +449,25 @@ fn build_tax_base(
         });
     }
 
+    // Apply price-basis exemption (cannot reduce below zero)
+    let exemption_applied = input.exempt_amount
+        .unwrap_or_default()
+        .min(base.max(dec!(0)));
+
+    if exemption_applied > dec!(0) {
+        base -= exemption_applied;
+        audit.push(TaxAuditEntry {
+            step: "EXEMPTION".to_string(),
+            description: format!(
+                "Applied tax exemption: {}",
+                input.exempt_reason.as_deref().unwrap_or("unspecified")
+            ),
+            input_value: input.exempt_amount.unwrap_or_default(),
+            output_value: exemption_applied,
+            rule_applied: "exempt_amount".to_string(),
+        });
+    }
+
     // Apply maximum taxable amount cap if exists
     let cap_applied = if let Some(max) = rules.base_rules.max_taxable_amount {
         if base > max {
@@ -316,8 +481,31 @@ fn build_tax_base(
         None
     };
 
+    // Raise a below-floor base (gifts, family sales) to the state minimum
+    let cap_applied = match rules.base_rules.min_taxable_amount {
+        Some(min) if base < min => {
+            audit.push(TaxAuditEntry {
+                step: "MIN_TAXABLE_FLOOR".to_string(),
+                description: "Raised tax base to the state minimum taxable amount".to_string(),
+                input_value: base,
+                output_value: min,
+                rule_applied: format!("max(base({}), min_taxable_amount({}))", base, min),
+            });
+            let original = base;
+            base = min;
+            Some(format!("Raised from {} to {} minimum per state rule", original, min))
+        }
+        _ => cap_applied,
+    };
+
     // Ensure base is never negative (invariant)
-    base = base.max(dec!(0));
+    base = base.max(dec!(0)).round_money();
+
+    // Whatever the cap, floor and rounding changed, so the breakdown
+    // always sums to the final base
+    let adjustments = base
+        - (selling_price + taxable_fees + taxable_products
+            - trade_credit_applied - rebates_applied - exemption_applied);
 
     let breakdown = TaxBaseBreakdown {
         selling_price,
@@ -325,49 +513,137 @@ fn build_tax_base(
         taxable_products,
         trade_credit_applied,
         rebates_applied,
-        adjustments: dec!(0),
+        exemption_applied,
+        adjustments,
         cap_applied,
     };
 
-    Ok((breakdown, base.round_money()))
+    Ok((breakdown, base))
+}
+
+/// Sum the trade allowances that earn a tax credit.
+///
+/// A trade below `trade_credit_min_value`, or not titled to the buyer where
+/// `same_owner_required` is set, gets no credit and is recorded in the audit.
+fn qualifying_trade_value(
+    input: &crate::types::DealInput,
+    base_rules: &crate::types::BaseRules,
+    audit: &mut Vec<TaxAuditEntry>,
+) -> Decimal {
+    let primary = input.trade_in_value
+        .map(|value| ("Trade-in".to_string(), value, input.trade_in_titled_to_buyer));
+    let additional = input.additional_trades
+        .iter()
+        .map(|trade| (trade.description.clone(), trade.gross_value.as_decimal(), trade.titled_to_buyer));
+
+    let mut total = dec!(0);
+    for (label, value, titled_to_buyer) in primary.into_iter().chain(additional) {
+        let withheld_by = match base_rules.trade_credit_min_value {
+            Some(min) if value < min => Some(format!("trade_credit_min_value({})", min)),
+            _ if base_rules.same_owner_required && !titled_to_buyer => {
+                Some("same_owner_required".to_string())
+            }
+            _ => None,
+        };
+        match withheld_by {
+            Some(rule_applied) => audit.push(TaxAuditEntry {
+                step: "TRADE_CREDIT_WITHHELD".to_string(),
+                description: format!("No trade credit for {}", label),
+                input_value: value,
+                output_value: dec!(0),
+                rule_applied,
+            }),
+            None => total += value,
+        }
+    }
+
+    total
 }
 
 /// Calculate taxable fees based on state rules.
 fn calculate_taxable_fees(
     fees: &crate::types::DealFees,
     rules: &crate::types::RuleProfile,
+    audit: &mut Vec<TaxAuditEntry>,
 ) -> Decimal {
-    let mut taxable = dec!(0);
-
-    // Doc fee
-    if rules.base_rules.doc_fee_taxable {
-        taxable += fees.doc_fee;
-    }
-
-    // Destination (usually taxable)
-    if rules.base_rules.destination_taxable {
-        taxable += fees.destination_fee;
+    // Record where a state override for a fee code beats the dealer's flag
+    for fee in &fees.other_fees {
+        let state_override = fee.code.as_deref().and_then(|code| rules.ancillaries.fee_override(code));
+
+        if let (Some(forced), Some(code)) = (state_override, fee.code.as_deref()) {
+            audit.push(TaxAuditEntry {
+                step: "FEE_TAXABILITY".to_string(),
+                description: format!("State rule sets taxability of {}", fee.name),
+                input_value: fee.amount,
+                output_value: if forced { fee.amount } else { dec!(0) },
+                rule_applied: format!("{}: dealer taxable={}, state taxable={}, state wins",
+                    code, fee.taxable, forced),
+            });
+        }
     }
 
-    // Dealer handling
-    taxable += fees.dealer_handling_fee;
+    fees.items()
+        .iter()
+        .filter(|item| is_fee_taxable(item, rules))
+        .map(|item| item.amount)
+        .sum()
+}
 
-    // Check government fees (usually not taxable)
-    if rules.ancillaries.registration_taxable {
-        taxable += fees.registration_fee;
-    }
-    if rules.ancillaries.title_fee_taxable {
-        taxable += fees.title_fee;
-    }
+/// Calculate taxable products based on state rules.
+///
+/// The state's ancillary rules decide by product type for every state; the
+/// input `Product::taxable` flag never changes the base.
+fn calculate_taxable_products(
+    products: &[crate::types::Product],
+    rules: &crate::types::RuleProfile,
+    audit: &mut Vec<TaxAuditEntry>,
+) -> Decimal {
+    let mut total = dec!(0);
+    for product in products {
+        let taxable = rules.ancillaries.is_product_type_taxable(product.product_type);
+
+        // Record where the state rule disagrees with the input flag
+        if taxable != product.taxable {
+            audit.push(TaxAuditEntry {
+                step: "PRODUCT_TAXABILITY".to_string(),
+                description: format!("State rule sets taxability of {}", product.name),
+                input_value: product.price,
+                output_value: if taxable { product.price } else { dec!(0) },
+                rule_applied: format!("{:?}: input taxable={}, state taxable={}, state wins",
+                    product.product_type, product.taxable, taxable),
+            });
+        }
 
-    // Other fees
-    for fee in &fees.other_fees {
-        if fee.taxable {
-            taxable += fee.amount;
+        if taxable {
+            total += product.price;
         }
     }
 
-    taxable
+    total
+}
+
+/// Whether a fee is part of the tax base under the state's rules.
+///
+/// Doc, destination, title and registration follow the state flags;
+/// dealer handling is always taxable and other government, filing, and
+/// lender fees never are. A custom fee follows the state override for its
+/// code, else the dealer's flag.
+pub fn is_fee_taxable(item: &crate::types::FeeItem<'_>, rules: &crate::types::RuleProfile) -> bool {
+    use crate::types::FeeKind;
+
+    match item.kind {
+        FeeKind::Doc => rules.base_rules.doc_fee_taxable,
+        // A destination bundled into the price is taxed, or backed out,
+        // with the price itself and never appears as an item
+        FeeKind::Destination => rules.base_rules.destination_taxable,
+        FeeKind::DealerHandling => true,
+        FeeKind::Registration => rules.ancillaries.registration_taxable,
+        FeeKind::Title => rules.ancillaries.title_fee_taxable,
+        FeeKind::Other(fee) => fee.code.as_deref()
+            .and_then(|code| rules.ancillaries.fee_override(code))
+            .unwrap_or(fee.taxable),
+        FeeKind::Government | FeeKind::ElectronicFiling | FeeKind::Acquisition | FeeKind::Bank => false,
+    }
 }
 
 /// Calculate standard sales/use tax.
@@ -379,8 +655,34 @@ fn calculate_standard_tax(
     let rules = &deal.profiles.primary_rules;
     let rates = &rules.rates;
 
+    // A host-supplied combined rate replaces the state/local lookup
+    if let Some(rate) = deal.deal.deal.input.inner.override_combined_rate {
+        let rate = rate.as_decimal();
+        let tax = (base * rate).round_money();
+
+        audit.push(TaxAuditEntry {
+            step: "COMBINED_RATE_OVERRIDE".to_string(),
+            description: "Combined rate supplied by caller; rate tables skipped".to_string(),
+            input_value: base,
+            output_value: tax,
+            rule_applied: format!("base * override_rate = {} * {} = {}", base, rate, tax),
+        });
+
+        let component = TaxComponent {
+            name: "Combined".to_string(),
+            level: TaxLevel::State,
+            rate,
+            base,
+            amount: tax,
+            self_collected: false,
+        };
+        return Ok((vec![component], tax, rate));
+    }
+
     let mut components = Vec::new();
     let mut total_tax = dec!(0);
+    // Unrounded component sum, used by TaxRounding::OnTotal
+    let mut exact_tax = dec!(0);
     let mut total_rate = dec!(0);
 
     // State tax
@@ -392,8 +694,10 @@ fn calculate_standard_tax(
             rate: rates.state_rate,
             base,
             amount: state_tax,
+            self_collected: false,
         });
         total_tax += state_tax;
+        exact_tax += base * rates.state_rate;
         total_rate += rates.state_rate;
 
         audit.push(TaxAuditEntry {
@@ -405,20 +709,62 @@ fn calculate_standard_tax(
         });
     }
 
-    // Local taxes (simplified - in production would look up by ZIP/county)
-    // Use default combined rate minus state rate as proxy for local
-    let local_rate = rates.default_combined_rate - rates.state_rate;
-    if local_rate > dec!(0) {
-        let local_tax = (base * local_rate).round_money();
-        components.push(TaxComponent {
-            name: "Local Tax".to_string(),
-            level: TaxLevel::County,
-            rate: local_rate,
-            base,
-            amount: local_tax,
-        });
-        total_tax += local_tax;
-        total_rate += local_rate;
+    // Local taxes - look up by customer city/county, otherwise use
+    // default combined rate minus state rate as proxy for local
+    let customer = &deal.deal.deal.input.inner.customer;
+    match lookup_local_rates(rules.state_code, customer.county.as_deref(), customer.city.as_deref()) {
+        Some(local_rates) => {
+            let fees = &deal.deal.deal.input.inner.fees;
+            for local in local_rates {
+                // Home-rule cities may apply their own base rules
+                let local_base = match local.doc_fee_taxable {
+                    Some(true) if !rules.base_rules.doc_fee_taxable => base + fees.doc_fee_amount(),
+                    Some(false) if rules.base_rules.doc_fee_taxable => (base - fees.doc_fee_amount()).max(dec!(0)),
+                    _ => base,
+                };
+                let local_tax = (local_base * local.rate).round_money();
+                components.push(TaxComponent {
+                    name: local.name.to_string(),
+                    level: local.level,
+                    rate: local.rate,
+                    base: local_base,
+                    amount: local_tax,
+                    self_collected: local.self_collected,
+                });
+                total_tax += local_tax;
+                exact_tax += local_base * local.rate;
+                total_rate += local.rate;
+
+                audit.push(TaxAuditEntry {
+                    step: "LOCAL_TAX".to_string(),
+                    description: format!(
+                        "{} ({})",
+                        local.name,
+                        if local.self_collected { "self-collected" } else { "state-administered" },
+                    ),
+                    input_value: local_base,
+                    output_value: local_tax,
+                    rule_applied: format!("base * rate = {} * {} = {}", local_base, local.rate, local_tax),
+                });
+            }
+        }
+        None => {
+            let local_rate = rates.default_combined_rate - rates.state_rate;
+            if local_rate > dec!(0) {
+                let local_tax = (base * local_rate).round_money();
+                components.push(TaxComponent {
+                    name: "Local Tax".to_string(),
+                    level: TaxLevel::County,
+                    rate: local_rate,
+                    base,
+                    amount: local_tax,
+                    self_collected: false,
+                });
+                total_tax += local_tax;
+                exact_tax += base * local_rate;
+                total_rate += local_rate;
+            }
+        }
     }
 
     // District tax
@@ -430,14 +776,118 @@ fn calculate_standard_tax(
             rate: rates.district_rate,
             base,
             amount: district_tax,
+            self_collected: false,
         });
         total_tax += district_tax;
+        exact_tax += base * rates.district_rate;
         total_rate += rates.district_rate;
     }
 
+    let total_tax = match rules.tax_rounding {
+        TaxRounding::PerComponent => total_tax,
+        TaxRounding::OnTotal => {
+            let total_tax = exact_tax.round_money();
+            allocate_rounding_remainder(&mut components, total_tax);
+            total_tax
+        }
+    };
+
     Ok((components, total_tax, total_rate))
 }
 
+/// Add the difference between a once-rounded total and the sum of the
+/// rounded components to the largest component, so they sum exactly.
+fn allocate_rounding_remainder(components: &mut [TaxComponent], total_tax: Decimal) {
+    let component_sum: Decimal = components.iter().map(|c| c.amount).sum();
+    let remainder = total_tax - component_sum;
+    if remainder.is_zero() {
+        return;
+    }
+    if let Some(largest) = components.iter_mut().max_by_key(|c| c.amount) {
+        largest.amount += remainder;
+    }
+}
+
+/// Local tax rate entry from the local-rate lookup
+#[derive(Debug, Clone)]
+struct LocalRate {
+    name: &'static str,
+    level: TaxLevel,
+    rate: Decimal,
+    /// Collected by the locality rather than the state
+    self_collected: bool,
+    /// Locality-specific doc fee taxability (None = follow state rule)
+    doc_fee_taxable: Option<bool>,
+}
+
+impl LocalRate {
+    fn state_collected(name: &'static str, level: TaxLevel, rate: Decimal) -> Self {
+        LocalRate { name, level, rate, self_collected: false, doc_fee_taxable: None }
+    }
+
+    fn home_rule(name: &'static str, rate: Decimal, doc_fee_taxable: bool) -> Self {
+        LocalRate {
+            name,
+            level: TaxLevel::City,
+            rate,
+            self_collected: true,
+            doc_fee_taxable: Some(doc_fee_taxable),
+        }
+    }
+}
+
+/// Look up local tax rates for a customer's locality.
+///
+/// Returns None when the locality is not in the table, in which case the
+/// caller falls back to the profile's default combined rate.
+///
+/// # Colorado
+/// Home-rule cities (Denver, Aurora, Boulder, ...) self-collect their own
+/// sales tax and may define their own base (e.g., doc fee taxability).
+/// County and special district taxes (RTD, SCFD) are state-administered.
+fn lookup_local_rates(
+    state: StateCode,
+    county: Option<&str>,
+    city: Option<&str>,
+) -> Option<Vec<LocalRate>> {
+    let city = city.map(|c| c.trim().to_ascii_lowercase());
+    let county = county.map(|c| c.trim().to_ascii_lowercase().trim_end_matches(" county").to_string());
+
+    match state {
+        StateCode::CO => {
+            let rtd = LocalRate::state_collected("RTD", TaxLevel::District, dec!(0.01));
+            let scfd = LocalRate::state_collected("SCFD", TaxLevel::District, dec!(0.001));
+
+            match (city.as_deref(), county.as_deref()) {
+                (Some("denver"), _) => Some(vec![
+                    LocalRate::home_rule("Denver", dec!(0.0481), true),
+                    rtd,
+                    scfd,
+                ]),
+                (Some("aurora"), _) => Some(vec![
+                    LocalRate::home_rule("Aurora", dec!(0.0375), false),
+                    LocalRate::state_collected("Arapahoe County", TaxLevel::County, dec!(0.0025)),
+                    rtd,
+                    scfd,
+                ]),
+                (Some("boulder"), _) => Some(vec![
+                    LocalRate::home_rule("Boulder", dec!(0.0386), true),
+                    LocalRate::state_collected("Boulder County", TaxLevel::County, dec!(0.00985)),
+                    rtd,
+                    scfd,
+                ]),
+                (_, Some("jefferson")) => Some(vec![
+                    LocalRate::state_collected("Jefferson County", TaxLevel::County, dec!(0.005)),
+                    rtd,
+                    scfd,
+                ]),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Calculate Georgia TAVT (Title Ad Valorem Tax).
 ///
 /// # Rules
@@ -475,6 +925,76 @@ fn calculate_tavt(
     Ok((TaxType::Tavt, Some(special)))
 }
 
+/// Rate that applies when a lessee buys out the vehicle at lease end.
+///
+/// A buyout is a retail purchase of the residual, so it is taxed the way the
+/// state taxes a sale: at the special tax rate for TAVT, HUT and excise
+/// states, otherwise at the default combined sales rate.
+pub fn buyout_tax_rate(rules: &RuleProfile) -> Decimal {
+    match rules.tax_type {
+        TaxType::Tavt => rules.rates.tavt_rate.unwrap_or(dec!(0.0675)),
+        TaxType::Hut => rules.rates.hut_rate.unwrap_or(dec!(0.03)),
+        TaxType::Excise => rules.rates.excise_rate.unwrap_or(dec!(0.05)),
+        TaxType::None => dec!(0),
+        TaxType::Sales | TaxType::Use => rules.rates.default_combined_rate,
+    }
+}
+
+/// Calculate Georgia TAVT on a lease.
+///
+/// # Rules
+/// - Taxed on the total of base payments plus the down payment (cap cost
+///   reduction), not the selling price
+/// - Same TAVT rate as a retail purchase
+/// - Paid at signing
+pub fn lease_tavt(
+    rules: &RuleProfile,
+    total_base_payments: Decimal,
+    total_cap_reduction: Decimal,
+) -> SpecialTax {
+    let rate = rules.rates.tavt_rate.unwrap_or(dec!(0.0675));
+    let base = total_base_payments + total_cap_reduction.max(dec!(0));
+
+    SpecialTax {
+        tax_type: TaxType::Tavt,
+        name: "Georgia Lease TAVT".to_string(),
+        base,
+        rate,
+        amount: (base * rate).round_money(),
+        cap_applied: None,
+    }
+}
+
+/// Record a special tax figured while structuring a lease (see
+/// [`lease_tavt`]) as the deal's tax.
+///
+/// The base breakdown is kept, with the difference to the lease basis in
+/// `adjustments` so it still sums to the base.
+pub fn record_lease_special_tax(tax: &mut TaxCalculation, special: SpecialTax) {
+    tax.audit.push(TaxAuditEntry {
+        step: "TAVT_LEASE".to_string(),
+        description: special.name.clone(),
+        input_value: special.base,
+        output_value: special.amount,
+        rule_applied: format!("TAVT: {} * {} = {}", special.base, special.rate, special.amount),
+    });
+
+    tax.base_breakdown.adjustments += special.base - tax.tax_base;
+    tax.tax_base = special.base;
+    tax.primary_tax = special.amount;
+    tax.net_tax = (special.amount - tax.reciprocity_credit).max(dec!(0));
+    tax.effective_rate = special.rate;
+    tax.components = vec![TaxComponent {
+        name: special.name.clone(),
+        level: TaxLevel::Special,
+        rate: special.rate,
+        base: special.base,
+        amount: special.amount,
+        self_collected: false,
+    }];
+    tax.special_tax = Some(special);
+}
+
 /// Calculate North Carolina HUT (Highway Use Tax).
 ///
 /// # Rules
@@ -565,6 +1085,11 @@ fn calculate_excise(
 /// - No Credit: Pay full tax in both states
 /// - In Lieu: One tax substitutes for the other
 ///
+/// The credit depends on the other state: `full_credit_states`,
+/// `partial_credit_states` (credit up to that state's `credit_rate`) and
+/// `no_credit_states` are checked in that order, and any other state gets
+/// the blanket `max_credit_rate` rule.
+///
 /// # Invariant
 /// Credit never exceeds theoretical home state tax
 fn calculate_reciprocity_credit(
@@ -574,8 +1099,9 @@ fn calculate_reciprocity_credit(
 ) -> UdcResult<Decimal> {
     let jurisdiction = &deal.deal.jurisdiction;
 
-    // If not interstate, no reciprocity
-    if !jurisdiction.is_interstate {
+    // Same-state deals, and deals taxed only where signed, owe no other
+    // state anything to credit
+    if !jurisdiction.is_interstate || !jurisdiction.reciprocity_applies {
         return Ok(dec!(0));
     }
 
@@ -602,16 +1128,30 @@ fn calculate_reciprocity_credit(
 
     // Calculate theoretical tax in transaction state
     // Note: ProfileLoadedDeal doesn't have tax yet - we calculate base from inputs
-    let input = &deal.deal.deal.input.inner;
-    let base = input.vehicle_price;  // Simplified - full implementation would calculate properly
+    let base = deal.deal.deal.input.selling_price;  // Simplified - full implementation would calculate properly
     // Simplified: assume we'd use secondary state's combined rate
     let transaction_rate = secondary_rules.rates.default_combined_rate;
     let transaction_tax = (base * transaction_rate).round_money();
 
-    // Determine credit amount
-    let credit = if reciprocity.full_credit_states.contains(&secondary_rules.state_code) {
+    // Determine credit amount by the state the tax was paid in
+    let other_state = secondary_rules.state_code;
+    let partial = reciprocity.partial_credit_states.iter().find(|p| p.state == other_state);
+    let credit = if reciprocity.full_credit_states.contains(&other_state) {
         // Full credit - credit equals tax paid in transaction state
         transaction_tax.min(primary_tax)
+    } else if let Some(partial) = partial {
+        // Partial credit for this state - credit up to its agreed rate
+        let max_credit = (base * partial.credit_rate).round_money();
+        transaction_tax.min(max_credit).min(primary_tax)
+    } else if reciprocity.no_credit_states.contains(&other_state) {
+        audit.push(TaxAuditEntry {
+            step: "RECIPROCITY".to_string(),
+            description: "No reciprocity with this state".to_string(),
+            input_value: transaction_tax,
+            output_value: dec!(0),
+            rule_applied: format!("no_credit_states includes {:?}", other_state),
+        });
+        dec!(0)
     } else if let Some(max_rate) = reciprocity.max_credit_rate {
         // Partial credit - credit up to max rate
         let max_credit = (base * max_rate).round_money();
@@ -627,10 +1167,13 @@ fn calculate_reciprocity_credit(
             description: "Reciprocity credit applied".to_string(),
             input_value: transaction_tax,
             output_value: credit,
-            rule_applied: format!(
-                "Credit for tax paid in {:?}: {}",
-                secondary_rules.state_code, credit
-            ),
+            rule_applied: match partial {
+                Some(partial) => format!(
+                    "Credit for tax paid in {:?} up to {}: {}",
+                    other_state, partial.credit_rate, credit
+                ),
+                None => format!("Credit for tax paid in {:?}: {}", other_state, credit),
+            },
         });
     }
 
@@ -673,29 +1216,83 @@ fn validate_tax_invariants(tax: &TaxCalculation) -> UdcResult<()> {
         ));
     }
 
+    // Invariant 5: Net tax + reciprocity credit = primary tax. Caps (the
+    // taxable amount cap, special tax caps) are applied before the primary
+    // tax, and the zero floor on net tax never binds while invariant 3 holds
+    if tax.net_tax + tax.reciprocity_credit != tax.primary_tax {
+        return Err(UdcError::calculation(
+            format!(
+                "Net tax {} + reciprocity credit {} != primary tax {}",
+                tax.net_tax, tax.reciprocity_credit, tax.primary_tax
+            ),
+            "P4_TAX",
+        ));
+    }
+
     Ok(())
 }
 
 /// Calculate lease-specific taxes.
 ///
+/// The rate and [`LeaseTaxConfig`] come from the garaging state when its
+/// rules source lease tax there, otherwise from the governing state.
+///
 /// # Lease Tax Modes
 /// - CapCostUpfront: Tax entire cap cost at signing
 /// - MonthlyPayment: Tax each monthly payment
 /// - DepreciationOnly: Tax only the depreciation portion
+///
+/// When the config sets `tax_cap_reduction`, the payment-taxed modes add an
+/// upfront tax on the cap cost reduction (see [`cap_reduction_tax`]).
+/// `monthly_payment` is the taxable slice of the payment from
+/// [`monthly_lease_tax_base`]. TAVT states take [`lease_tavt`] at signing
+/// whatever the mode.
+///
+/// `trade_credit` is the trade equity applied to the lease. States with
+/// `lease_trade_credit` take it off the taxed cap cost; states without it
+/// tax it (see [`lease_trade_monthly_base`]).
+///
+/// A capitalized doc fee the state exempts on leases is taken off the taxed
+/// cap cost (see [`lease_exempt_doc_fee`]), whatever the retail rule.
 pub fn calculate_lease_tax(
     deal: &ProfileLoadedDeal,
     gross_cap_cost: Decimal,
     monthly_payment: Decimal,
     term_months: u32,
+    total_cap_reduction: Decimal,
+    trade_credit: Decimal,
 ) -> UdcResult<LeaseTaxResult> {
-    let rules = &deal.profiles.primary_rules;
-    let tax_mode = rules.lease_tax_mode.unwrap_or(LeaseTaxMode::MonthlyPayment);
+    let rules = deal.profiles.lease_tax_rules();
+    let config = rules.lease_tax_config();
+    let input = &deal.deal.deal.input.inner;
+    let tax_mode = config.mode;
     let rate = rules.rates.default_combined_rate;
+    let reduction_tax = if config.tax_cap_reduction && taxes_lease_payments(tax_mode) {
+        // Cap cost rebates that reduce the tax basis are not taxed as a reduction
+        let tax_basis_rebates = input.cap_cost_rebates() - input.taxable_cap_cost_rebates();
+        taxed_cap_reduction(rules, total_cap_reduction, trade_credit, tax_basis_rebates)
+    } else {
+        dec!(0)
+    };
+    let exempt_doc_fee = lease_exempt_doc_fee(rules, &input.fees);
+    let taxable_cap_cost = lease_taxable_cap_cost(rules, gross_cap_cost - exempt_doc_fee, trade_credit);
+    let monthly_payment = monthly_payment + lease_trade_monthly_base(rules, trade_credit, term_months);
+
+    if rules.tax_type == TaxType::Tavt {
+        let total_payments = monthly_payment * Decimal::from(term_months);
+        let tavt = lease_tavt(rules, total_payments, total_cap_reduction);
+        return Ok(LeaseTaxResult {
+            mode: tax_mode,
+            upfront_tax: tavt.amount,
+            monthly_tax: dec!(0),
+            total_tax: tavt.amount,
+        });
+    }
 
     match tax_mode {
         LeaseTaxMode::CapCostUpfront | LeaseTaxMode::CapitalizedCost => {
             // Tax full cap cost upfront
-            let upfront_tax = (gross_cap_cost * rate).round_money();
+            let upfront_tax = (taxable_cap_cost * rate).round_money();
             Ok(LeaseTaxResult {
                 mode: tax_mode,
                 upfront_tax,
@@ -706,10 +1303,10 @@ pub fn calculate_lease_tax(
         LeaseTaxMode::MonthlyPayment => {
             // Tax each monthly payment
             let monthly_tax = (monthly_payment * rate).round_money();
-            let total_tax = monthly_tax * Decimal::from(term_months);
+            let total_tax = monthly_tax * Decimal::from(term_months) + reduction_tax;
             Ok(LeaseTaxResult {
                 mode: tax_mode,
-                upfront_tax: dec!(0),
+                upfront_tax: reduction_tax,
                 monthly_tax,
                 total_tax,
             })
@@ -717,7 +1314,7 @@ pub fn calculate_lease_tax(
         LeaseTaxMode::TotalPayments => {
             // Tax on total of all payments upfront or capitalized
             let total_payments = monthly_payment * Decimal::from(term_months);
-            let upfront_tax = (total_payments * rate).round_money();
+            let upfront_tax = (total_payments * rate).round_money() + reduction_tax;
             Ok(LeaseTaxResult {
                 mode: tax_mode,
                 upfront_tax,
@@ -729,17 +1326,17 @@ pub fn calculate_lease_tax(
             // Only tax the depreciation (simplified)
             // In practice, need to calculate actual depreciation
             let monthly_tax = (monthly_payment * rate).round_money();
-            let total_tax = monthly_tax * Decimal::from(term_months);
+            let total_tax = monthly_tax * Decimal::from(term_months) + reduction_tax;
             Ok(LeaseTaxResult {
                 mode: tax_mode,
-                upfront_tax: dec!(0),
+                upfront_tax: reduction_tax,
                 monthly_tax,
                 total_tax,
             })
         }
         LeaseTaxMode::AcquisitionTax => {
             // Special acquisition tax (e.g., TX) - apply to cap cost
-            let upfront_tax = (gross_cap_cost * rate).round_money();
+            let upfront_tax = (taxable_cap_cost * rate).round_money();
             Ok(LeaseTaxResult {
                 mode: tax_mode,
                 upfront_tax,
@@ -758,19 +1355,148 @@ pub fn calculate_lease_tax(
     }
 }
 
+/// Upfront tax on the cap cost reduction for states that tax it.
+///
+/// Only applies when the lease is otherwise taxed on its payments; modes that
+/// tax the full cap cost upfront already include the reduction in their base.
+/// The trade equity in the reduction is left out in lease trade credit states,
+/// and so are rebates flagged to reduce the tax basis where the state lets
+/// rebates reduce it.
+pub fn cap_reduction_tax(
+    rules: &RuleProfile,
+    lease_tax_mode: LeaseTaxMode,
+    total_cap_reduction: Decimal,
+    trade_credit: Decimal,
+    tax_basis_rebates: Decimal,
+) -> Decimal {
+    if !rules.tax_cap_reduction || !taxes_lease_payments(lease_tax_mode) {
+        return dec!(0);
+    }
+    taxed_cap_reduction(rules, total_cap_reduction, trade_credit, tax_basis_rebates)
+}
+
+/// Tax on the cap cost reduction, less the credited trade and rebates
+fn taxed_cap_reduction(
+    rules: &RuleProfile,
+    total_cap_reduction: Decimal,
+    trade_credit: Decimal,
+    tax_basis_rebates: Decimal,
+) -> Decimal {
+    let credited_trade = if rules.lease_trade_credit { trade_credit.max(dec!(0)) } else { dec!(0) };
+    let credited_rebates = if rules.base_rules.rebates_reduce_basis { tax_basis_rebates.max(dec!(0)) } else { dec!(0) };
+    let taxed_reduction = (total_cap_reduction - credited_trade - credited_rebates).max(dec!(0));
+    (taxed_reduction * rules.rates.default_combined_rate).round_money()
+}
+
+/// Cap cost taxed by the cap-cost lease modes: the trade equity comes off
+/// in lease trade credit states, otherwise the cap cost is taxed before the
+/// trade.
+pub fn lease_taxable_cap_cost(rules: &RuleProfile, gross_cap_cost: Decimal, trade_credit: Decimal) -> Decimal {
+    if rules.lease_trade_credit {
+        (gross_cap_cost - trade_credit.max(dec!(0))).max(dec!(0))
+    } else {
+        gross_cap_cost
+    }
+}
+
+/// Capitalized doc fee kept out of the lease tax base.
+///
+/// Zero unless the doc fee is rolled into the cap cost and the state
+/// exempts it on leases (`RuleProfile::doc_fee_taxable_on_lease`). Retail
+/// deals keep following `base_rules.doc_fee_taxable`.
+pub fn lease_exempt_doc_fee(rules: &RuleProfile, fees: &DealFees) -> Decimal {
+    if rules.doc_fee_taxable_on_lease() || !fees.capitalize.doc_fee {
+        return dec!(0);
+    }
+    fees.doc_fee_amount().max(dec!(0))
+}
+
+/// Amount added to the taxed monthly payment for trade equity a state does
+/// not credit on leases.
+///
+/// The trade lowers the payment, so a no-credit state taxes its monthly
+/// share on top. Nothing is added in credit states, or where the trade is
+/// already taxed at signing with the cap cost reduction.
+///
+/// # Formula
+/// ```text
+/// add_back = trade_credit / term_months
+/// ```
+pub fn lease_trade_monthly_base(rules: &RuleProfile, trade_credit: Decimal, term_months: u32) -> Decimal {
+    if rules.lease_trade_credit || rules.tax_cap_reduction || term_months == 0 {
+        return dec!(0);
+    }
+    (trade_credit.max(dec!(0)) / Decimal::from(term_months)).round_money()
+}
+
+/// Amount added to the taxed monthly payment for rebates that lower the
+/// cap cost but not the tax basis.
+///
+/// Like an uncredited trade, the rebate lowers the payment, so its monthly
+/// share is taxed on top unless the state already taxes it at signing with
+/// the cap cost reduction.
+///
+/// # Formula
+/// ```text
+/// add_back = taxable_rebates / term_months
+/// ```
+pub fn lease_rebate_monthly_base(rules: &RuleProfile, taxable_rebates: Decimal, term_months: u32) -> Decimal {
+    if rules.tax_cap_reduction || term_months == 0 {
+        return dec!(0);
+    }
+    (taxable_rebates.max(dec!(0)) / Decimal::from(term_months)).round_money()
+}
+
+/// Whether a lease tax mode taxes the payments (rather than the cap cost)
+fn taxes_lease_payments(lease_tax_mode: LeaseTaxMode) -> bool {
+    matches!(
+        lease_tax_mode,
+        LeaseTaxMode::MonthlyPayment | LeaseTaxMode::TotalPayments | LeaseTaxMode::DepreciationOnly
+    )
+}
+
+/// Slice of the base lease payment a monthly-taxed lease is taxed on (see
+/// `RuleProfile::lease_monthly_tax_base`).
+///
+/// `monthly_exempt_doc_fee` is the monthly share of [`lease_exempt_doc_fee`];
+/// it comes off the bases that still carry the capitalized fees.
+pub fn monthly_lease_tax_base(
+    rules: &RuleProfile,
+    base_monthly_payment: Decimal,
+    monthly_depreciation: Decimal,
+    monthly_capitalized_fees: Decimal,
+    monthly_exempt_doc_fee: Decimal,
+) -> Decimal {
+    match rules.lease_monthly_tax_base {
+        LeaseMonthlyTaxBase::FullPayment => {
+            (base_monthly_payment - monthly_exempt_doc_fee).max(dec!(0))
+        }
+        LeaseMonthlyTaxBase::DepreciationPlusRent => {
+            (base_monthly_payment - monthly_capitalized_fees).max(dec!(0))
+        }
+        LeaseMonthlyTaxBase::DepreciationOnly => {
+            (monthly_depreciation - monthly_exempt_doc_fee).max(dec!(0))
+        }
+    }
+}
+
 /// Result of lease tax calculation
 #[derive(Debug, Clone)]
 pub struct LeaseTaxResult {
+    /// Lease tax mode applied
     pub mode: LeaseTaxMode,
+    /// Tax due at signing (including any tax on the cap cost reduction)
     pub upfront_tax: Decimal,
+    /// Tax added to each monthly payment
     pub monthly_tax: Decimal,
+    /// Tax over the lease
     pub total_tax: Decimal,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{DealInput, DealFees, CustomerInfo, FinanceParams, Product, ProductType, Rebate, RebateType};
+    use crate::types::{DealInput, DealFees, OtherFee, FeeTaxOverride, FinanceParams, Product, ProductType, Rate, Rebate, RebateType, RoundingStrategy};
     use crate::phases::p0_normalize::normalize_deal_input;
     use crate::phases::p1_mode_routing::route_deal;
     use crate::phases::p2_jurisdiction::resolve_jurisdiction;
@@ -783,8 +1509,6 @@ mod tests {
         rebates: Decimal,
     ) -> ProfileLoadedDeal {
         let input = DealInput {
-            deal_type: crate::types::DealType::Finance,
-            vehicle_price,
             trade_in_value: trade_in,
             trade_in_payoff: Some(dec!(0)),
             cash_down: dec!(2000),
@@ -795,32 +1519,18 @@ mod tests {
                     amount: rebates,
                     rebate_type: RebateType::Manufacturer,
                     reduces_tax_basis: true,
+                    reduces_cap_cost: true,
                     program_code: None,
                 }]
             } else {
                 vec![]
             },
-            products: vec![],
             fees: DealFees {
-                doc_fee: dec!(299),
+                doc_fee: Some(dec!(299)),
                 ..Default::default()
             },
-            home_state: state,
-            transaction_state: state,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: Some(FinanceParams {
-                term_months: 60,
-                apr: dec!(0.0599),
-                lender_id: None,
-                buy_rate: None,
-                max_reserve_points: None,
-                deferred_first_payment: false,
-                days_to_first_payment: None,
-            }),
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
+            finance_params: Some(FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(crate::types::DealType::Finance, vehicle_price, state)
         };
 
         let normalized = normalize_deal_input(input).unwrap();
@@ -842,6 +1552,221 @@ mod tests {
         assert!(result.tax.base_breakdown.trade_credit_applied > dec!(0));
     }
 
+    fn make_reduction_lease_tax(tax_cap_reduction: bool, lease_tax_mode: LeaseTaxMode, trade_credit: Decimal) -> LeaseTaxResult {
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        deal.profiles.primary_rules.tax_cap_reduction = tax_cap_reduction;
+        deal.profiles.primary_rules.lease_tax_mode = Some(lease_tax_mode);
+        // $2,000 cash plus the trade equity down on a $30,299 cap cost
+        calculate_lease_tax(&deal, dec!(30299), dec!(400), 36, dec!(2000) + trade_credit, trade_credit).unwrap()
+    }
+
+    #[test]
+    fn test_lease_tax_on_down_payment() {
+        let rate = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0))
+            .profiles.primary_rules.rates.default_combined_rate;
+
+        // A state that does not tax the down payment taxes only the payments
+        let untaxed = make_reduction_lease_tax(false, LeaseTaxMode::MonthlyPayment, dec!(0));
+        assert_eq!(untaxed.upfront_tax, dec!(0));
+        assert_eq!(untaxed.monthly_tax, (dec!(400) * rate).round_money());
+        assert_eq!(untaxed.total_tax, untaxed.monthly_tax * dec!(36));
+
+        // One that does adds the tax on the $2,000 down at signing
+        let taxed = make_reduction_lease_tax(true, LeaseTaxMode::MonthlyPayment, dec!(0));
+        let reduction_tax = (dec!(2000) * rate).round_money();
+        assert_eq!(taxed.upfront_tax, reduction_tax);
+        assert_eq!(taxed.monthly_tax, untaxed.monthly_tax);
+        assert_eq!(taxed.total_tax, untaxed.total_tax + reduction_tax);
+
+        // Trade equity in the reduction is credited in lease trade credit states
+        let with_trade = make_reduction_lease_tax(true, LeaseTaxMode::MonthlyPayment, dec!(5000));
+        assert_eq!(with_trade.upfront_tax, reduction_tax);
+    }
+
+    #[test]
+    fn test_cap_cost_upfront_lease_tax_ignores_cap_reduction_flag() {
+        // The cap cost is already taxed in full, so the reduction is not taxed twice
+        let untaxed = make_reduction_lease_tax(false, LeaseTaxMode::CapCostUpfront, dec!(0));
+        let taxed = make_reduction_lease_tax(true, LeaseTaxMode::CapCostUpfront, dec!(0));
+        assert!(taxed.upfront_tax > dec!(0));
+        assert_eq!(taxed.upfront_tax, untaxed.upfront_tax);
+        assert_eq!(taxed.total_tax, untaxed.total_tax);
+    }
+
+    #[test]
+    fn test_rounding_strategy_does_not_change_tax() {
+        // Tax rounding follows the jurisdiction's TaxRounding alone;
+        // RoundingStrategy only governs the lease payment
+        let at_end = PipelineOptions { rounding: RoundingStrategy::RoundAtEnd, ..Default::default() };
+        let deal = make_test_deal(StateCode::TX, dec!(30000.10), Some(dec!(10000)), dec!(0));
+        let precise = calculate_tax_with_options(deal, &at_end).unwrap();
+
+        let deal = make_test_deal(StateCode::TX, dec!(30000.10), Some(dec!(10000)), dec!(0));
+        assert_eq!(precise.tax.primary_tax, calculate_tax(deal).unwrap().tax.primary_tax);
+    }
+
+    fn make_exempt_deal(exempt_amount: Decimal) -> ProfileLoadedDeal {
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        deal.deal.deal.input.inner.exempt_amount = Some(exempt_amount);
+        deal.deal.deal.input.inner.exempt_reason = Some("EV exemption".to_string());
+        deal
+    }
+
+    #[test]
+    fn test_tax_rounding_on_total() {
+        // Base = 30000.10 + 299 - 10000 = 20299.10
+        // State 6.25% = 1268.69375 -> 1268.69, local 2% = 405.982 -> 405.98
+        // Per component: 1268.69 + 405.98 = 1674.67
+        // On total:      20299.10 * 8.25% = 1674.67575 -> 1674.68
+        let deal = make_test_deal(StateCode::TX, dec!(30000.10), Some(dec!(10000)), dec!(0));
+        let per_component = calculate_tax(deal).unwrap();
+
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000.10), Some(dec!(10000)), dec!(0));
+        deal.profiles.primary_rules.tax_rounding = TaxRounding::OnTotal;
+        let on_total = calculate_tax(deal).unwrap();
+
+        assert_eq!(per_component.tax.primary_tax, dec!(1674.67));
+        assert_eq!(on_total.tax.primary_tax, dec!(1674.68));
+
+        // Either way the components sum to the total
+        let component_sum: Decimal = per_component.tax.components.iter().map(|c| c.amount).sum();
+        assert_eq!(component_sum, dec!(1674.67));
+
+        // The extra cent lands on the state component, the largest
+        let component_sum: Decimal = on_total.tax.components.iter().map(|c| c.amount).sum();
+        assert_eq!(component_sum, dec!(1674.68));
+        assert_eq!(on_total.tax.components[0].amount, dec!(1268.70));
+        assert_eq!(on_total.tax.components[1].amount, dec!(405.98));
+    }
+
+    #[test]
+    fn test_ev_exemption_reduces_base() {
+        let result = calculate_tax(make_exempt_deal(dec!(2500))).unwrap();
+
+        // Base = 30000 + 299 - 2500 = 27799
+        assert_eq!(result.tax.tax_base, dec!(27799));
+        assert_eq!(result.tax.base_breakdown.exemption_applied, dec!(2500));
+        assert_eq!(result.tax.base_breakdown.adjustments, dec!(0));
+        assert!(result.tax.audit.iter().any(|e| e.step == "EXEMPTION"));
+    }
+
+    #[test]
+    fn test_exemption_exceeding_base_clamps_to_zero() {
+        let result = calculate_tax(make_exempt_deal(dec!(50000))).unwrap();
+
+        assert_eq!(result.tax.tax_base, dec!(0));
+        assert_eq!(result.tax.base_breakdown.exemption_applied, dec!(30299));
+        assert_eq!(result.tax.primary_tax, dec!(0));
+    }
+
+    #[test]
+    fn test_dealer_customer_is_exempt() {
+        let retail = calculate_tax(make_test_deal(StateCode::TX, dec!(30000), None, dec!(0))).unwrap();
+
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        deal.deal.deal.input.inner.customer.customer_type = CustomerType::Dealer;
+        let resale = calculate_tax(deal).unwrap();
+
+        // Same base, but a resale purchase carries no tax
+        assert_eq!(retail.tax.customer_treatment, CustomerTaxTreatment::Taxable);
+        assert_eq!(retail.tax.net_tax, dec!(2499.67));
+        assert_eq!(resale.tax.customer_treatment, CustomerTaxTreatment::Exempt);
+        assert_eq!(resale.tax.tax_base, retail.tax.tax_base);
+        assert_eq!(resale.tax.net_tax, dec!(0));
+        assert!(resale.tax.components.is_empty());
+        assert!(resale.tax.audit.iter().any(|e| e.step == "CUSTOMER_EXEMPT"));
+    }
+
+    #[test]
+    fn test_fleet_customer_taxed_as_retail() {
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        deal.deal.deal.input.inner.customer.customer_type = CustomerType::FleetCommercial;
+        let fleet = calculate_tax(deal).unwrap();
+
+        assert_eq!(fleet.tax.customer_treatment, CustomerTaxTreatment::Taxable);
+        assert_eq!(fleet.tax.net_tax, dec!(2499.67));
+    }
+
+    fn make_destination_deal(included_in_price: bool, destination_taxable: bool) -> ProfileLoadedDeal {
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        let fees = &mut deal.deal.deal.input.inner.fees;
+        fees.destination_fee = dec!(1200);
+        fees.destination_included_in_price = included_in_price;
+        deal.profiles.primary_rules.base_rules.destination_taxable = destination_taxable;
+        deal
+    }
+
+    #[test]
+    fn test_destination_separately_taxable() {
+        let result = calculate_tax(make_destination_deal(false, true)).unwrap();
+
+        // Base = 30000 + 299 doc + 1200 destination
+        assert_eq!(result.tax.tax_base, dec!(31499));
+        assert_eq!(result.tax.base_breakdown.taxable_fees, dec!(1499));
+
+        // Listed separately but not taxable: price and doc fee only
+        let result = calculate_tax(make_destination_deal(false, false)).unwrap();
+        assert_eq!(result.tax.tax_base, dec!(30299));
+    }
+
+    #[test]
+    fn test_destination_included_in_price_not_double_counted() {
+        // Taxable destination bundled into the price is taxed once, with the price
+        let deal = make_destination_deal(true, true);
+        assert_eq!(deal.deal.deal.input.inner.fees.charged_destination_fee(), dec!(0));
+        let result = calculate_tax(deal).unwrap();
+        assert_eq!(result.tax.tax_base, dec!(30299));
+        assert_eq!(result.tax.base_breakdown.taxable_fees, dec!(299));
+
+        // Non-taxable destination bundled into the price is backed out of it
+        let result = calculate_tax(make_destination_deal(true, false)).unwrap();
+        assert_eq!(result.tax.tax_base, dec!(29099));
+        assert_eq!(result.tax.base_breakdown.selling_price, dec!(28800));
+        assert_eq!(result.tax.base_breakdown.adjustments, dec!(0));
+        assert!(result.tax.audit.iter().any(|e| e.step == "DESTINATION_EXCLUDED"));
+    }
+
+    fn make_fee_override_deal(dealer_taxable: bool, state_taxable: Option<bool>) -> ProfileLoadedDeal {
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        deal.deal.deal.input.inner.fees.other_fees.push(OtherFee {
+            name: "Nitrogen tire fill".to_string(),
+            code: Some("NITRO".to_string()),
+            amount: dec!(199),
+            dealer_fee: true,
+            taxable: dealer_taxable,
+            capitalize: false,
+        });
+        if let Some(taxable) = state_taxable {
+            deal.profiles.primary_rules.ancillaries.fee_overrides.push(FeeTaxOverride {
+                code: "nitro".to_string(),
+                taxable,
+            });
+        }
+        deal
+    }
+
+    #[test]
+    fn test_state_forces_fee_taxable() {
+        // Dealer marks the fee non-taxable and no state rule applies
+        let result = calculate_tax(make_fee_override_deal(false, None)).unwrap();
+        assert_eq!(result.tax.tax_base, dec!(30299));
+        assert!(!result.tax.audit.iter().any(|e| e.step == "FEE_TAXABILITY"));
+
+        // The state forces it taxable
+        let result = calculate_tax(make_fee_override_deal(false, Some(true))).unwrap();
+        assert_eq!(result.tax.tax_base, dec!(30498));
+        assert_eq!(result.tax.base_breakdown.taxable_fees, dec!(498));
+        let entry = result.tax.audit.iter().find(|e| e.step == "FEE_TAXABILITY").unwrap();
+        assert!(entry.rule_applied.contains("dealer taxable=false, state taxable=true"));
+    }
+
+    #[test]
+    fn test_state_forces_fee_exempt() {
+        let result = calculate_tax(make_fee_override_deal(true, Some(false))).unwrap();
+        assert_eq!(result.tax.tax_base, dec!(30299));
+        assert_eq!(result.tax.base_breakdown.taxable_fees, dec!(299));
+    }
+
     #[test]
     fn test_california_no_trade_credit() {
         let deal = make_test_deal(StateCode::CA, dec!(30000), Some(dec!(10000)), dec!(1000));
@@ -902,4 +1827,383 @@ mod tests {
         assert!(tax.net_tax >= dec!(0), "Net tax must be non-negative");
         assert!(tax.reciprocity_credit <= tax.primary_tax, "Reciprocity <= primary");
     }
+
+    fn make_triplet_deal(home: StateCode, transaction: StateCode, garaging: StateCode) -> ProfileLoadedDeal {
+        let mut input = make_test_deal(home, dec!(30000), None, dec!(0)).deal.deal.input.inner.clone();
+        input.transaction_state = transaction;
+        input.garaging_state = Some(garaging);
+        let routed = route_deal(normalize_deal_input(input).unwrap()).unwrap();
+        load_profiles(resolve_jurisdiction(routed).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_same_state_triplet_skips_reciprocity() {
+        let deal = make_triplet_deal(StateCode::TX, StateCode::TX, StateCode::TX);
+        assert!(!deal.deal.jurisdiction.spans_multiple_states());
+        assert!(deal.profiles.secondary_rules.is_none());
+
+        let tax = calculate_tax(deal).unwrap().tax;
+        assert_eq!(tax.reciprocity_credit, dec!(0));
+        assert_eq!(tax.net_tax, tax.primary_tax);
+        assert!(tax.audit.iter().all(|entry| entry.step != "RECIPROCITY"));
+    }
+
+    #[test]
+    fn test_net_tax_plus_credit_equals_primary_tax() {
+        // TX resident buying in FL: TX credits the FL tax
+        let tax = calculate_tax(make_triplet_deal(StateCode::TX, StateCode::FL, StateCode::TX)).unwrap().tax;
+        assert!(tax.reciprocity_credit > dec!(0));
+        assert_eq!(tax.net_tax + tax.reciprocity_credit, tax.primary_tax);
+
+        // A credit changed without the net tax trips the invariant
+        let mut broken = tax.clone();
+        broken.reciprocity_credit -= dec!(1);
+        let err = validate_tax_invariants(&broken).unwrap_err();
+        assert!(err.to_string().contains("reciprocity credit"), "{}", err);
+    }
+
+    #[test]
+    fn test_reciprocity_credit_by_other_state() {
+        use crate::types::PartialCreditState;
+
+        // TX credits FL in full, AZ up to 3%, and OK not at all. Each other
+        // state charges the same 6%, so only the table changes the credit
+        let deal_in = |transaction: StateCode| {
+            let mut deal = make_triplet_deal(StateCode::TX, transaction, StateCode::TX);
+            let reciprocity = &mut deal.profiles.primary_rules.reciprocity;
+            reciprocity.offers_reciprocity = true;
+            reciprocity.full_credit_states = vec![StateCode::FL];
+            reciprocity.partial_credit_states = vec![PartialCreditState {
+                state: StateCode::AZ,
+                credit_rate: dec!(0.03),
+                conditions: None,
+            }];
+            reciprocity.no_credit_states = vec![StateCode::OK];
+            deal.profiles.secondary_rules.as_mut().unwrap().rates.default_combined_rate = dec!(0.06);
+            calculate_tax(deal).unwrap().tax
+        };
+
+        let full = deal_in(StateCode::FL);
+        let partial = deal_in(StateCode::AZ);
+        let none = deal_in(StateCode::OK);
+        assert_eq!(full.primary_tax, partial.primary_tax);
+        assert_eq!(full.primary_tax, none.primary_tax);
+
+        // 30000 at 6% paid elsewhere; AZ is credited only 30000 at 3%
+        assert_eq!(full.reciprocity_credit, dec!(1800));
+        assert_eq!(partial.reciprocity_credit, dec!(900));
+        assert_eq!(none.reciprocity_credit, dec!(0));
+        assert!(full.net_tax < partial.net_tax && partial.net_tax < none.net_tax);
+        assert!(none.audit.iter().any(|e| e.step == "RECIPROCITY" && e.rule_applied.contains("no_credit_states")));
+    }
+
+    #[test]
+    fn test_differing_state_loads_secondary_profile() {
+        // Bought out of state: the transaction state is the secondary profile
+        let deal = make_triplet_deal(StateCode::CA, StateCode::AZ, StateCode::CA);
+        assert!(deal.deal.jurisdiction.spans_multiple_states());
+        assert_eq!(deal.profiles.secondary_rules.as_ref().map(|r| r.state_code), Some(StateCode::AZ));
+        let tax = calculate_tax(deal).unwrap().tax;
+        assert!(tax.reciprocity_credit <= tax.primary_tax);
+
+        // Garaged out of state: the profile loads, but no tax was paid there
+        let deal = make_triplet_deal(StateCode::TX, StateCode::TX, StateCode::OK);
+        assert!(!deal.deal.jurisdiction.is_interstate);
+        assert!(deal.deal.jurisdiction.spans_multiple_states());
+        assert_eq!(deal.profiles.secondary_rules.as_ref().map(|r| r.state_code), Some(StateCode::OK));
+        let tax = calculate_tax(deal).unwrap().tax;
+        assert_eq!(tax.reciprocity_credit, dec!(0));
+        assert_eq!(tax.net_tax, tax.primary_tax);
+    }
+
+    fn make_co_deal(city: Option<&str>, county: Option<&str>) -> ProfileLoadedDeal {
+        let mut deal = make_test_deal(StateCode::CO, dec!(30000), None, dec!(0));
+        deal.deal.deal.input.inner.customer.city = city.map(str::to_string);
+        deal.deal.deal.input.inner.customer.county = county.map(str::to_string);
+        deal
+    }
+
+    #[test]
+    fn test_colorado_home_rule_city() {
+        let result = calculate_tax(make_co_deal(Some("Denver"), Some("Denver"))).unwrap();
+        let components = &result.tax.components;
+
+        // State 2.9% + Denver (self-collected) + RTD + SCFD
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[0].level, TaxLevel::State);
+        assert_eq!(components[0].amount, dec!(878.67)); // 30299 * 0.029
+
+        let denver = components.iter().find(|c| c.name == "Denver").unwrap();
+        assert!(denver.self_collected);
+        assert_eq!(denver.level, TaxLevel::City);
+        assert_eq!(denver.amount, dec!(1457.38)); // 30299 * 0.0481
+
+        let state_administered: Vec<_> = components.iter().filter(|c| !c.self_collected).collect();
+        assert_eq!(state_administered.len(), 3);
+    }
+
+    #[test]
+    fn test_min_taxable_amount_floor() {
+        // $1 family transfer with no fees in a state with a $500 minimum
+        let mut deal = make_test_deal(StateCode::TX, dec!(1), None, dec!(0));
+        deal.deal.deal.input.inner.fees.doc_fee = Some(dec!(0));
+        deal.profiles.primary_rules.base_rules.min_taxable_amount = Some(dec!(500));
+        let result = calculate_tax(deal).unwrap();
+
+        assert_eq!(result.tax.tax_base, dec!(500));
+        assert_eq!(result.tax.base_breakdown.adjustments, dec!(499));
+        assert!(result.tax.base_breakdown.cap_applied.as_deref().unwrap().contains("minimum"));
+        assert_eq!(result.tax.primary_tax, dec!(41.25)); // 500 * 8.25%
+        let floor = result.tax.audit.iter().find(|a| a.step == "MIN_TAXABLE_FLOOR").unwrap();
+        assert_eq!(floor.input_value, dec!(1));
+
+        // A sale above the floor is taxed on its price
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        deal.profiles.primary_rules.base_rules.min_taxable_amount = Some(dec!(500));
+        let result = calculate_tax(deal).unwrap();
+        assert_eq!(result.tax.tax_base, dec!(30299));
+        assert!(result.tax.audit.iter().all(|a| a.step != "MIN_TAXABLE_FLOOR"));
+    }
+
+    #[test]
+    fn test_components_sorted_by_level() {
+        let component = |name: &str, level: TaxLevel| TaxComponent {
+            name: name.to_string(),
+            level,
+            rate: dec!(0.01),
+            base: dec!(1000),
+            amount: dec!(10),
+            self_collected: false,
+        };
+        let mut components = vec![
+            component("RTD", TaxLevel::District),
+            component("Jefferson County", TaxLevel::County),
+            component("SCFD", TaxLevel::District),
+            component("CO State Tax", TaxLevel::State),
+        ];
+        sort_tax_components(&mut components);
+
+        // State -> County -> District; same-level order is kept
+        let names: Vec<&str> = components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["CO State Tax", "Jefferson County", "RTD", "SCFD"]);
+
+        let jeffco = calculate_tax(make_co_deal(None, Some("Jefferson County"))).unwrap();
+        assert!(jeffco.tax.components.windows(2).all(|pair| pair[0].level <= pair[1].level));
+    }
+
+    #[test]
+    fn test_colorado_state_collected_county() {
+        let denver = calculate_tax(make_co_deal(Some("Denver"), None)).unwrap();
+        let jeffco = calculate_tax(make_co_deal(None, Some("Jefferson County"))).unwrap();
+
+        assert!(jeffco.tax.components.iter().all(|c| !c.self_collected));
+        assert!(jeffco.tax.components.iter().any(|c| c.level == TaxLevel::County));
+        assert!(jeffco.tax.primary_tax < denver.tax.primary_tax);
+    }
+
+    #[test]
+    fn test_colorado_home_rule_doc_fee_exclusion() {
+        let result = calculate_tax(make_co_deal(Some("Aurora"), None)).unwrap();
+
+        // Aurora does not tax the doc fee; the state does
+        let aurora = result.tax.components.iter().find(|c| c.name == "Aurora").unwrap();
+        assert_eq!(aurora.base, result.tax.tax_base - dec!(299));
+        assert_eq!(result.tax.components[0].base, result.tax.tax_base);
+    }
+
+    #[test]
+    fn test_colorado_unknown_locality_falls_back() {
+        let result = calculate_tax(make_co_deal(None, None)).unwrap();
+
+        assert_eq!(result.tax.components.len(), 2);
+        assert_eq!(result.tax.effective_rate, dec!(0.0775));
+    }
+
+    #[test]
+    fn test_combined_rate_override() {
+        let mut deal = make_test_deal(StateCode::AL, dec!(30000), None, dec!(0));
+        deal.deal.deal.input.inner.override_combined_rate = Some(Rate::from_decimal(dec!(0.0913)));
+        let result = calculate_tax(deal).unwrap();
+
+        // Base = 30000 + 299 doc fee; default table would give 7%
+        assert_eq!(result.tax.tax_base, dec!(30299));
+        assert_eq!(result.tax.primary_tax, (dec!(30299) * dec!(0.0913)).round_dp(2));
+        assert_eq!(result.tax.effective_rate, dec!(0.0913));
+        assert_eq!(result.tax.components.len(), 1);
+        assert_eq!(result.tax.components[0].name, "Combined");
+        assert!(result.tax.audit.iter().any(|a| a.step == "COMBINED_RATE_OVERRIDE"));
+        assert!(result.tax.audit.iter().all(|a| a.step != "STATE_TAX"));
+    }
+
+    #[test]
+    fn test_combined_rate_override_bypasses_local_table() {
+        let mut deal = make_co_deal(Some("Denver"), Some("Denver"));
+        deal.deal.deal.input.inner.override_combined_rate = Some(Rate::from_decimal(dec!(0.05)));
+        let result = calculate_tax(deal).unwrap();
+
+        let base = result.tax.tax_base;
+        assert_eq!(result.tax.components.len(), 1);
+        assert_eq!(result.tax.primary_tax, (base * dec!(0.05)).round_dp(2));
+    }
+
+    #[test]
+    fn test_combined_rate_sanity_warning() {
+        // A 20% override is well above the 15% default ceiling
+        let mut deal = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        deal.deal.deal.input.inner.override_combined_rate = Some(Rate::from_decimal(dec!(0.20)));
+        let result = calculate_tax(deal.clone()).unwrap();
+        let warning = result.warnings.iter().find(|w| w.code == "COMBINED_RATE_OUT_OF_RANGE").expect("rate warning");
+        assert_eq!(warning.field, "override_combined_rate");
+        assert!(warning.message.contains("20%"));
+
+        // The threshold is configurable
+        let options = PipelineOptions { max_combined_rate: Some(dec!(0.25)), ..Default::default() };
+        assert!(calculate_tax_with_options(deal, &options).unwrap().warnings.is_empty());
+
+        // Negative rates are always flagged
+        assert!(check_combined_rate(dec!(-0.01), DEFAULT_MAX_COMBINED_RATE, false).is_some());
+    }
+
+    #[test]
+    fn test_normal_combined_rate_not_flagged() {
+        let result = calculate_tax(make_test_deal(StateCode::TX, dec!(30000), None, dec!(0))).unwrap();
+        assert_eq!(result.tax.effective_rate, dec!(0.0825));
+        assert!(result.warnings.is_empty());
+    }
+
+    fn with_second_trade(mut deal: ProfileLoadedDeal, value: Decimal) -> ProfileLoadedDeal {
+        use crate::types::{Money, TradeIn};
+
+        deal.deal.deal.input.inner.additional_trades.push(TradeIn {
+            vin: None,
+            description: "Second trade".to_string(),
+            gross_value: Money::new(value),
+            payoff_amount: Money::ZERO,
+            acv: None,
+            titled_to_buyer: true,
+        });
+        deal
+    }
+
+    #[test]
+    fn test_multiple_trades_capped_in_aggregate() {
+        // IL-style $10,000 cap on $7,000 + $6,000 of trades
+        let mut deal = with_second_trade(make_test_deal(StateCode::TX, dec!(40000), Some(dec!(7000)), dec!(0)), dec!(6000));
+        deal.profiles.primary_rules.base_rules.max_trade_in_credit = Some(dec!(10000));
+        let result = calculate_tax(deal).unwrap();
+
+        assert_eq!(result.tax.base_breakdown.trade_credit_applied, dec!(10000));
+        let audit = result.tax.audit.iter().find(|a| a.step == "TRADE_CREDIT").unwrap();
+        assert_eq!(audit.input_value, dec!(13000));
+    }
+
+    #[test]
+    fn test_trade_below_min_value_gets_no_credit() {
+        let with_min = |trade: Decimal| {
+            let mut deal = make_test_deal(StateCode::TX, dec!(30000), Some(trade), dec!(0));
+            deal.profiles.primary_rules.base_rules.trade_credit_min_value = Some(dec!(2000));
+            calculate_tax(deal).unwrap().tax
+        };
+
+        let small = with_min(dec!(1500));
+        assert_eq!(small.base_breakdown.trade_credit_applied, dec!(0));
+        assert_eq!(small.tax_base, dec!(30299));
+        let withheld = small.audit.iter().find(|a| a.step == "TRADE_CREDIT_WITHHELD").unwrap();
+        assert_eq!(withheld.input_value, dec!(1500));
+        assert_eq!(withheld.rule_applied, "trade_credit_min_value(2000)");
+
+        let qualifying = with_min(dec!(5000));
+        assert_eq!(qualifying.base_breakdown.trade_credit_applied, dec!(5000));
+        assert!(qualifying.audit.iter().all(|a| a.step != "TRADE_CREDIT_WITHHELD"));
+    }
+
+    #[test]
+    fn test_same_owner_required_withholds_other_owners_trade() {
+        // The second trade is titled to someone else
+        let mut deal = with_second_trade(make_test_deal(StateCode::TX, dec!(40000), Some(dec!(7000)), dec!(0)), dec!(6000));
+        deal.deal.deal.input.inner.additional_trades[0].titled_to_buyer = false;
+        deal.profiles.primary_rules.base_rules.same_owner_required = true;
+        let result = calculate_tax(deal.clone()).unwrap();
+
+        assert_eq!(result.tax.base_breakdown.trade_credit_applied, dec!(7000));
+        let withheld = result.tax.audit.iter().find(|a| a.step == "TRADE_CREDIT_WITHHELD").unwrap();
+        assert_eq!(withheld.description, "No trade credit for Second trade");
+        assert_eq!(withheld.rule_applied, "same_owner_required");
+
+        // Without the rule both trades are credited
+        deal.profiles.primary_rules.base_rules.same_owner_required = false;
+        let result = calculate_tax(deal).unwrap();
+        assert_eq!(result.tax.base_breakdown.trade_credit_applied, dec!(13000));
+    }
+
+    #[test]
+    fn test_multiple_trades_positive_equity() {
+        let deal = with_second_trade(make_test_deal(StateCode::TX, dec!(40000), Some(dec!(7000)), dec!(0)), dec!(6000));
+        assert_eq!(deal.deal.deal.input.inner.net_trade(), dec!(13000));
+
+        // Uncapped: both allowances come off the base (40000 + 299 doc fee)
+        let result = calculate_tax(deal).unwrap();
+        assert_eq!(result.tax.base_breakdown.trade_credit_applied, dec!(13000));
+        assert_eq!(result.tax.tax_base, dec!(27299));
+    }
+
+    fn make_product(product_type: ProductType, price: Decimal) -> Product {
+        Product {
+            id: format!("{:?}", product_type).to_lowercase(),
+            name: format!("{:?}", product_type),
+            price,
+            cost: dec!(0),
+            product_type,
+            term_months: None,
+            mileage_limit: None,
+            deductible: None,
+            taxable: true,
+        }
+    }
+
+    #[test]
+    fn test_product_taxability_by_state() {
+        let products = vec![
+            make_product(ProductType::Vsc, dec!(2000)),
+            make_product(ProductType::Gap, dec!(800)),
+        ];
+
+        let mut tx = make_test_deal(StateCode::TX, dec!(30000), None, dec!(0));
+        tx.deal.deal.input.inner.products = products.clone();
+        let mut fl = make_test_deal(StateCode::FL, dec!(30000), None, dec!(0));
+        fl.deal.deal.input.inner.products = products;
+
+        let tx = calculate_tax(tx).unwrap();
+        let fl = calculate_tax(fl).unwrap();
+
+        // TX exempts VSC and GAP; FL taxes VSC but not GAP
+        assert_eq!(tx.tax.base_breakdown.taxable_products, dec!(0));
+        assert_eq!(fl.tax.base_breakdown.taxable_products, dec!(2000));
+    }
+
+    #[test]
+    fn test_product_input_flag_overridden_by_state() {
+        // The same VSC flagged the wrong way for each state
+        let with_vsc = |state: StateCode, flagged_taxable: bool| {
+            let mut deal = make_test_deal(state, dec!(30000), None, dec!(0));
+            let mut vsc = make_product(ProductType::Vsc, dec!(2000));
+            vsc.taxable = flagged_taxable;
+            deal.deal.deal.input.inner.products = vec![vsc];
+            calculate_tax(deal).unwrap()
+        };
+
+        let fl = with_vsc(StateCode::FL, false);
+        assert_eq!(fl.tax.base_breakdown.taxable_products, dec!(2000));
+        let tx = with_vsc(StateCode::TX, true);
+        assert_eq!(tx.tax.base_breakdown.taxable_products, dec!(0));
+        for result in [&fl, &tx] {
+            let entry = result.tax.audit.iter().find(|e| e.step == "PRODUCT_TAXABILITY").expect("override audited");
+            assert!(entry.rule_applied.ends_with("state wins"));
+        }
+
+        // A flag that agrees with the state is not called out
+        let fl = with_vsc(StateCode::FL, true);
+        assert_eq!(fl.tax.base_breakdown.taxable_products, dec!(2000));
+        assert!(fl.tax.audit.iter().all(|e| e.step != "PRODUCT_TAXABILITY"));
+    }
 }
@@ -10,8 +10,17 @@
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-use crate::types::{DealType, LeaseTaxMode, UdcResult, UdcError, DecimalExt};
-use super::p4_tax_cipher::TaxComputedDeal;
+use crate::types::{
+    DayCount, DealType, FeeItem, FeeKind, FundingPortion, LeaseTaxMode, LtvBasis, MsdRounding, PaymentFrequency, RoundingStrategy, ValidationWarning,
+    TaxType, UdcResult, UdcError, DecimalExt,
+};
+use super::p0_normalize::{compute_product_totals, NormalizedDealInput};
+use super::p4_tax_cipher::{
+    buyout_tax_rate, cap_reduction_tax, lease_exempt_doc_fee, lease_tavt, lease_taxable_cap_cost, lease_rebate_monthly_base,
+    lease_trade_monthly_base, CustomerTaxTreatment, monthly_lease_tax_base, record_lease_special_tax, SpecialTax,
+    TaxComputedDeal,
+};
+use super::PipelineOptions;
 
 // ============================================================================
 // CASH STRUCTURE
@@ -66,6 +75,15 @@ pub struct FinanceStructure {
     pub rebates: Decimal,
     /// Negative equity rolled in (if underwater trade)
     pub negative_equity: Decimal,
+    /// Reductions beyond the gross amount, owed back to the customer
+    /// (nonzero only when the amount financed floors at zero)
+    pub excess_credit: Decimal,
+    /// Fees paid at signing instead of financed (a bank fee the lender
+    /// program will not finance)
+    pub upfront_fees: Decimal,
+    /// Cash due at signing: the down payment, fees paid upfront, and any
+    /// prepaid finance charge
+    pub due_at_signing: Decimal,
 
     // === Loan Terms ===
     /// Principal / Amount Financed
@@ -74,18 +92,82 @@ pub struct FinanceStructure {
     pub apr: Decimal,
     /// Term in months
     pub term_months: u32,
+    /// Term the caller asked for, when it was snapped to the nearest term
+    /// the program offers
+    pub requested_term_months: Option<u32>,
+    /// Payment frequency
+    pub payment_frequency: PaymentFrequency,
+    /// Number of payments over the term
+    pub number_of_payments: u32,
+    /// Lender buy rate the reserve is measured against (None when unknown)
+    pub buy_rate: Option<Decimal>,
+    /// Dealer reserve (finance charge spread between contract APR and buy rate)
+    pub dealer_reserve: Decimal,
+    /// Signed line items that sum to the amount financed
+    pub amount_financed_breakdown: Vec<AmountFinancedItem>,
+    /// Lender allocations that sum to the amount financed (empty for a
+    /// single lender)
+    pub funding_splits: Vec<FundingSplitItem>,
 
     // === Payment ===
-    /// Monthly payment
+    /// Periodic payment (monthly unless another frequency is selected)
     pub monthly_payment: Decimal,
-    /// Total of payments (payment * term)
+    /// Tax collected with each payment (zero when the tax is financed)
+    pub monthly_tax: Decimal,
+    /// Periodic payment plus the tax collected with it
+    pub payment_with_tax: Decimal,
+    /// Tax collected with the last payment: the monthly tax trued up so the
+    /// installments add up to the sales tax
+    pub final_monthly_tax: Decimal,
+    /// Total of payments (payment * number of payments)
     pub total_of_payments: Decimal,
-    /// Finance charge (total interest)
+    /// Finance charge (total interest plus prepaid finance charges)
     pub finance_charge: Decimal,
+    /// Prepaid finance charges collected at signing (e.g., a deferral
+    /// fee); TILA's amount financed is `amount_financed` less these
+    pub prepaid_finance_charge: Decimal,
 
     // === TILA Disclosures ===
     /// Total Sale Price (amount financed + finance charge + down payment)
     pub total_sale_price: Decimal,
+
+    /// Amount financed over the vehicle value basis (None when the value is unknown)
+    pub ltv: Option<Decimal>,
+    /// Vehicle value the LTV is measured against
+    pub ltv_basis: LtvBasis,
+}
+
+/// Portion of the amount financed funded by one lender
+#[derive(Debug, Clone)]
+pub struct FundingSplitItem {
+    /// Lender funding this portion
+    pub lender_id: Option<String>,
+    /// Part of the amount financed covered
+    pub portion: FundingPortion,
+    /// Amount funded
+    pub amount: Decimal,
+    /// APR on this portion
+    pub apr: Decimal,
+    /// Term on this portion
+    pub term_months: u32,
+}
+
+/// Labeled component of the amount financed (reductions are negative)
+#[derive(Debug, Clone)]
+pub struct AmountFinancedItem {
+    /// Line item label
+    pub label: String,
+    /// Signed amount
+    pub amount: Decimal,
+}
+
+/// Labeled component of the gross capitalized cost
+#[derive(Debug, Clone)]
+pub struct CapCostItem {
+    /// Line item label
+    pub label: String,
+    /// Amount capitalized
+    pub amount: Decimal,
 }
 
 // ============================================================================
@@ -110,13 +192,15 @@ pub struct LeaseStructure {
     pub capitalized_tax: Decimal,
     /// Gross Capitalized Cost
     pub gross_cap_cost: Decimal,
+    /// Line items that sum to the gross cap cost
+    pub gross_cap_cost_breakdown: Vec<CapCostItem>,
 
     // === Cap Cost Reductions ===
     /// Cash down payment
     pub cash_down: Decimal,
     /// Trade-in credit
     pub trade_credit: Decimal,
-    /// Rebates
+    /// Rebates applied as a cap cost reduction
     pub rebates: Decimal,
     /// Total cap cost reduction
     pub total_cap_reduction: Decimal,
@@ -124,15 +208,27 @@ pub struct LeaseStructure {
     pub adjusted_cap_cost: Decimal,
 
     // === Residual ===
-    /// Residual percentage (of MSRP)
+    /// Residual percentage (of MSRP), after any mileage tier adjustment
     pub residual_percentage: Decimal,
     /// Residual value (dollar amount)
     pub residual_value: Decimal,
 
+    // === Mileage ===
+    /// Annual mileage tier the lease is priced on
+    pub annual_miles: u32,
+    /// Excess mileage charge per mile (program tier rate, else the input's)
+    pub excess_mileage_rate: Option<Decimal>,
+
     // === Lease Charge ===
-    /// Money factor
+    /// Money factor before the MSD reduction (the input's, or the
+    /// subvented rate)
+    pub base_money_factor: Decimal,
+    /// Money factor reduction earned by multiple security deposits (zero
+    /// unless `apply_msd_reduction` is set)
+    pub msd_mf_reduction: Decimal,
+    /// Effective money factor the rent charge is computed from
     pub money_factor: Decimal,
-    /// Equivalent APR (MF * 2400)
+    /// Equivalent APR of the effective money factor (MF * 2400)
     pub equivalent_apr: Decimal,
     /// Term in months
     pub term_months: u32,
@@ -148,22 +244,42 @@ pub struct LeaseStructure {
     // === Payment ===
     /// Base monthly payment (depreciation + rent)
     pub base_monthly_payment: Decimal,
-    /// Monthly tax (if monthly tax mode)
+    /// Monthly tax (if monthly tax mode), at the rate in effect for the
+    /// first payment
     pub monthly_tax: Decimal,
     /// Total monthly payment (base + tax)
     pub total_monthly_payment: Decimal,
+    /// Slice of each payment the monthly tax is charged on
+    pub taxable_monthly_payment: Decimal,
+    /// Scheduled monthly tax rate changes as (first payment number, rate),
+    /// ascending; empty unless the lease is taxed monthly
+    pub tax_rate_changes: Vec<(u32, Decimal)>,
 
     // === Due at Signing ===
     /// First month's payment
     pub first_payment: Decimal,
     /// Security deposit
     pub security_deposit: Decimal,
+    /// Multiple security deposits (refundable)
+    pub msd_deposit: Decimal,
+    /// Prorated rent for the days between contract start and the billing cycle
+    pub interim_rent: Decimal,
     /// Acquisition fee (if upfront)
     pub acquisition_fee_upfront: Decimal,
+    /// All fees paid upfront (including an upfront acquisition fee)
+    pub upfront_fees: Decimal,
     /// Upfront tax (if cap-cost tax mode)
     pub upfront_tax: Decimal,
+    /// Rebates applied toward the amount due at signing (kept off the cap
+    /// cost)
+    pub signing_rebates: Decimal,
     /// Total due at signing
     pub due_at_signing: Decimal,
+    /// Portion of due at signing returned at lease end (deposits, MSDs)
+    pub refundable_due_at_signing: Decimal,
+    /// Portion of due at signing that is spent (first payment, interim
+    /// rent, fees, cap reduction, upfront tax)
+    pub nonrefundable_due_at_signing: Decimal,
 
     // === Totals ===
     /// Total of base payments
@@ -175,6 +291,71 @@ pub struct LeaseStructure {
 
     // === Tax Mode ===
     pub lease_tax_mode: LeaseTaxMode,
+    /// Special tax on the lease in place of sales tax (Georgia TAVT)
+    pub special_tax: Option<SpecialTax>,
+
+    // === Lease End ===
+    /// Cost to return the vehicle vs buy it out
+    pub end_of_lease: EndOfLeaseCosts,
+}
+
+/// Lease-end return cost vs buyout cost
+#[derive(Debug, Clone)]
+pub struct EndOfLeaseCosts {
+    /// Disposition fee due on return
+    pub disposition_fee: Decimal,
+    /// Estimated miles over the allowance for the term
+    pub estimated_excess_miles: u32,
+    /// Estimated excess mileage charge
+    pub excess_mileage_charge: Decimal,
+    /// Excess wear and use charge (not estimated)
+    pub excess_wear_charge: Decimal,
+    /// Disposition + excess mileage + excess wear
+    pub return_cost: Decimal,
+    /// Purchase option fee
+    pub purchase_option_fee: Decimal,
+    /// Tax on the residual plus purchase option fee
+    pub buyout_tax: Decimal,
+    /// Residual + purchase option fee + tax
+    pub buyout_cost: Decimal,
+}
+
+// ============================================================================
+// PROFIT ANALYSIS
+// ============================================================================
+
+/// Ratio of MSRP used to estimate invoice when the vehicle has no invoice
+/// (matches the legacy deal calculator).
+const ESTIMATED_INVOICE_RATIO: Decimal = dec!(0.92);
+
+/// Dealer profit analysis - front-end (vehicle) and back-end (F&I) gross
+#[derive(Debug, Clone)]
+pub struct ProfitAnalysis {
+    // === Front-End ===
+    /// Vehicle cost basis (invoice)
+    pub vehicle_cost: Decimal,
+    /// Vehicle gross (selling price - vehicle cost)
+    pub vehicle_gross: Decimal,
+    /// Holdback credited to the dealer
+    pub holdback: Decimal,
+    /// Pack added to vehicle cost
+    pub pack: Decimal,
+    /// Trade allowance above the trade's ACV (negative when under ACV)
+    pub trade_over_allowance: Decimal,
+    /// Front-end gross (vehicle gross + holdback - pack - trade over-allowance)
+    pub front_end_gross: Decimal,
+
+    // === Back-End ===
+    /// F&I product gross (sum of price - cost)
+    pub fi_product_gross: Decimal,
+    /// Dealer reserve from rate participation
+    pub dealer_reserve: Decimal,
+    /// Back-end gross (product gross + reserve)
+    pub back_end_gross: Decimal,
+
+    // === Totals ===
+    /// Total gross (front-end + back-end)
+    pub total_gross: Decimal,
 }
 
 // ============================================================================
@@ -183,6 +364,7 @@ pub struct LeaseStructure {
 
 /// Complete structure output - contains exactly one structure type
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum DealStructure {
     Cash(CashStructure),
     Finance(FinanceStructure),
@@ -194,6 +376,10 @@ pub enum DealStructure {
 pub struct StructuredDeal {
     pub deal: TaxComputedDeal,
     pub structure: DealStructure,
+    /// Profit analysis (None when no vehicle cost information is available)
+    pub profit: Option<ProfitAnalysis>,
+    /// Non-blocking warnings raised while structuring
+    pub warnings: Vec<ValidationWarning>,
 }
 
 // ============================================================================
@@ -225,23 +411,59 @@ pub struct StructuredDeal {
 /// 5. Calculate depreciation (adjusted cap - residual)
 /// 6. Calculate rent charge (cap + residual) * MF * term
 /// 7. Calculate base payment (depreciation + rent) / term
-/// 8. Add tax per tax mode
+/// 8. Add tax per tax mode (the garaging state's when it sources lease tax)
+/// 9. Check money factor markup against the lessor's cap
+///
+/// ## Profit Analysis
+/// 1. Front-end gross = selling price - vehicle cost + holdback - pack
+/// 2. Back-end gross = sum(product price - cost) + dealer reserve
 ///
 /// # Complexity
 /// - Time: O(p) where p = number of products/fees
 /// - Space: O(1) (structure is fixed size)
 pub fn build_structure(deal: TaxComputedDeal) -> UdcResult<StructuredDeal> {
+    build_structure_with_options(deal, &PipelineOptions::default())
+}
+
+/// P5 with explicit [`PipelineOptions`]; honors `options.rounding` when
+/// building the lease payment and `options.strict_validation` when the
+/// residual leaves nothing to depreciate or a loan cannot reach the
+/// program's minimum payment.
+pub fn build_structure_with_options(
+    mut deal: TaxComputedDeal,
+    options: &PipelineOptions,
+) -> UdcResult<StructuredDeal> {
     let deal_type = deal.deal.deal.deal.input.inner.deal_type;
 
+    let mut warnings = Vec::new();
+
     let structure = match deal_type {
         DealType::Cash => DealStructure::Cash(build_cash_structure(&deal)?),
-        DealType::Finance => DealStructure::Finance(build_finance_structure(&deal)?),
-        DealType::Lease => DealStructure::Lease(build_lease_structure(&deal)?),
+        DealType::Finance => {
+            let mut finance = build_priced_finance_structure(&mut deal, &mut warnings)?;
+            apply_loan_to_value(&deal, &mut finance, options);
+            warnings.extend(check_max_ltv(&deal, &finance));
+            check_min_payment(&deal, &finance, options, &mut warnings)?;
+            warnings.extend(check_zero_amount_financed(&finance));
+            DealStructure::Finance(finance)
+        }
+        DealType::Lease => {
+            let lease = build_lease_structure(&deal, options, &mut warnings)?;
+            validate_lease_invariants(&lease)?;
+            if let Some(special) = &lease.special_tax {
+                record_lease_special_tax(&mut deal.tax, special.clone());
+            }
+            warnings.extend(check_money_factor_markup(&deal, &lease));
+            warnings.extend(check_drive_off_rules(&deal, &lease));
+            DealStructure::Lease(lease)
+        }
     };
 
+    let profit = build_profit_analysis(&deal, &structure);
+
     log::debug!("P5: Built {:?} structure", deal_type);
 
-    Ok(StructuredDeal { deal, structure })
+    Ok(StructuredDeal { deal, structure, profit, warnings })
 }
 
 /// Build cash deal structure.
@@ -250,7 +472,7 @@ fn build_cash_structure(deal: &TaxComputedDeal) -> UdcResult<CashStructure> {
     let normalized = &deal.deal.deal.deal.input;
     let tax = &deal.tax;
 
-    let selling_price = input.vehicle_price;
+    let selling_price = normalized.selling_price;
     let total_fees = input.fees.total_dealer_fees();
     let government_fees = input.fees.total_government_fees();
     let fi_products = normalized.total_taxable_products + normalized.total_non_taxable_products;
@@ -289,11 +511,20 @@ fn build_finance_structure(deal: &TaxComputedDeal) -> UdcResult<FinanceStructure
     let finance_params = input.finance_params.as_ref()
         .ok_or_else(|| UdcError::calculation("Missing finance params", "P5_STRUCTURE"))?;
 
-    let selling_price = input.vehicle_price;
+    let selling_price = normalized.selling_price;
     let taxable_fees = input.fees.total_dealer_fees();
     let non_taxable_fees = input.fees.total_government_fees();
+    // A program that forbids financing its fees leaves the bank fee to be paid at signing
+    let (bank_fee_financed, upfront_fees) = if deal.deal.profiles.program_fees_capitalizable() {
+        (input.fees.bank_fee, dec!(0))
+    } else {
+        (dec!(0), input.fees.bank_fee)
+    };
     let fi_products_financed = normalized.total_taxable_products + normalized.total_non_taxable_products;
     let sales_tax = tax.net_tax;
+    // States that collect the tax with each payment leave it out of the loan
+    let tax_monthly = deal.deal.profiles.primary_rules.finance_tax_monthly;
+    let financed_tax = if tax_monthly { dec!(0) } else { sales_tax };
 
     let cash_down = input.cash_down;
     let trade_credit = normalized.net_trade.max(dec!(0));
@@ -311,22 +542,119 @@ fn build_finance_structure(deal: &TaxComputedDeal) -> UdcResult<FinanceStructure
     let gross_amount = selling_price
         + taxable_fees
         + non_taxable_fees
+        + bank_fee_financed
         + fi_products_financed
-        + sales_tax
+        + financed_tax
         + negative_equity;
 
     let total_reductions = cash_down + trade_credit + rebates;
     let amount_financed = (gross_amount - total_reductions).max(dec!(0)).round_money();
+    // The vehicle's share: price, fees and tax less the reductions, without
+    // the products and negative equity a split may fund separately
+    let vehicle_financed = selling_price
+        + taxable_fees
+        + non_taxable_fees
+        + bank_fee_financed
+        + financed_tax
+        - total_reductions;
+    let excess_credit = (total_reductions - gross_amount).max(dec!(0)).round_money();
+
+    let amount_financed_breakdown = build_amount_financed_breakdown(
+        normalized,
+        bank_fee_financed,
+        negative_equity,
+        financed_tax,
+        trade_credit,
+        excess_credit,
+        amount_financed,
+    )?;
+
+    // Loan calculation - a subvented program rate replaces the standard rate
+    // and leaves no room for dealer reserve. Without an explicit buy rate,
+    // the lender's rate sheet supplies one for the customer's tier.
+    let profiles = &deal.deal.profiles;
+    let (apr, buy_rate) = match &profiles.subvention {
+        Some(program) => (program.apr, Some(program.apr)),
+        None => (
+            finance_params.apr,
+            finance_params.buy_rate.or_else(|| {
+                profiles.lender_program
+                    .as_ref()?
+                    .get_buy_rate(profiles.credit_tier?, finance_params.term_months)
+            }),
+        ),
+    };
+    let payment_frequency = finance_params.payment_frequency;
+    // A program minimum payment shortens the term of a small loan
+    let min_payment = deal.deal.profiles.lender_program
+        .as_ref()
+        .and_then(|program| program.structure_rules.min_payment);
+    let term_months = match min_payment {
+        Some(min_payment) if amount_financed > dec!(0) => term_for_min_payment(
+            amount_financed,
+            apr,
+            finance_params.term_months,
+            payment_frequency,
+            min_payment,
+        )?,
+        _ => finance_params.term_months,
+    };
+    // Nothing is owed when the reductions cover the whole deal
+    let number_of_payments = if amount_financed.is_zero() {
+        0
+    } else {
+        payment_frequency.number_of_payments(term_months)
+    };
+    let periods_per_year = payment_frequency.periods_per_year();
 
-    // Loan calculation
-    let apr = finance_params.apr;
-    let term_months = finance_params.term_months;
+    let (monthly_payment, total_of_payments, interest) =
+        calculate_periodic_payment(amount_financed, apr, number_of_payments, periods_per_year)?;
 
-    let (monthly_payment, total_of_payments, finance_charge) =
-        calculate_loan_payment(amount_financed, apr, term_months)?;
+    let dealer_reserve = calculate_dealer_reserve(
+        amount_financed,
+        apr,
+        buy_rate,
+        number_of_payments,
+        periods_per_year,
+        interest,
+    )?;
+
+    // A deferral fee is a prepaid finance charge: paid at signing, it adds
+    // to the finance charge without changing the principal. It is only
+    // charged when the first payment is actually deferred
+    let prepaid_finance_charge = if finance_params.deferred_first_payment {
+        finance_params.deferral_fee.unwrap_or(dec!(0)).round_money()
+    } else {
+        dec!(0)
+    };
+    let finance_charge = interest + prepaid_finance_charge;
+
+    let funding_splits = build_funding_splits(
+        finance_params,
+        amount_financed,
+        vehicle_financed,
+        fi_products_financed,
+        negative_equity,
+        apr,
+        term_months,
+    )?;
+
+    // Tax collected monthly is spread evenly across the payments, with the
+    // rounding difference trued up on the last one
+    let (monthly_tax, final_monthly_tax) = if tax_monthly && number_of_payments > 0 {
+        let monthly_tax = (sales_tax / Decimal::from(number_of_payments)).round_money();
+        (monthly_tax, sales_tax - monthly_tax * Decimal::from(number_of_payments - 1))
+    } else {
+        (dec!(0), dec!(0))
+    };
+    let payment_with_tax = monthly_payment + monthly_tax;
 
-    // Total Sale Price (TILA)
-    let total_sale_price = amount_financed + finance_charge + cash_down;
+    // Total Sale Price (TILA), including any tax paid outside the loan; only
+    // the part of the down payment the deal actually used counts
+    let down_payment_applied = (cash_down - excess_credit).max(dec!(0));
+    let total_sale_price =
+        amount_financed + finance_charge + down_payment_applied + upfront_fees + (sales_tax - financed_tax);
+    let due_at_signing = cash_down + upfront_fees + prepaid_finance_charge;
 
     Ok(FinanceStructure {
         selling_price,
@@ -338,16 +666,508 @@ fn build_finance_structure(deal: &TaxComputedDeal) -> UdcResult<FinanceStructure
         trade_credit,
         rebates,
         negative_equity,
+        excess_credit,
+        upfront_fees,
+        due_at_signing,
         amount_financed,
         apr,
         term_months,
+        requested_term_months: deal.deal.profiles.requested_term_months,
+        payment_frequency,
+        number_of_payments,
+        buy_rate,
+        dealer_reserve,
+        amount_financed_breakdown,
+        funding_splits,
         monthly_payment,
+        monthly_tax,
+        payment_with_tax,
+        final_monthly_tax,
         total_of_payments,
         finance_charge,
+        prepaid_finance_charge,
         total_sale_price,
+        ltv: None,
+        ltv_basis: LtvBasis::default(),
     })
 }
 
+/// Build the finance structure of a bare loan: a known amount financed at
+/// `apr`, with no price, fees, tax, down payment or trade behind it.
+pub fn build_loan_structure(
+    amount_financed: Decimal,
+    apr: Decimal,
+    term_months: u32,
+    payment_frequency: PaymentFrequency,
+) -> UdcResult<FinanceStructure> {
+    let amount_financed = amount_financed.round_money();
+    let number_of_payments = payment_frequency.number_of_payments(term_months);
+    let (monthly_payment, total_of_payments, finance_charge) = calculate_periodic_payment(
+        amount_financed,
+        apr,
+        number_of_payments,
+        payment_frequency.periods_per_year(),
+    )?;
+
+    Ok(FinanceStructure {
+        selling_price: dec!(0),
+        taxable_fees: dec!(0),
+        non_taxable_fees: dec!(0),
+        fi_products_financed: dec!(0),
+        sales_tax: dec!(0),
+        cash_down: dec!(0),
+        trade_credit: dec!(0),
+        rebates: dec!(0),
+        negative_equity: dec!(0),
+        excess_credit: dec!(0),
+        upfront_fees: dec!(0),
+        due_at_signing: dec!(0),
+        amount_financed,
+        apr,
+        term_months,
+        requested_term_months: None,
+        payment_frequency,
+        number_of_payments,
+        buy_rate: None,
+        dealer_reserve: dec!(0),
+        amount_financed_breakdown: vec![],
+        funding_splits: vec![],
+        monthly_payment,
+        monthly_tax: dec!(0),
+        payment_with_tax: monthly_payment,
+        final_monthly_tax: dec!(0),
+        total_of_payments,
+        finance_charge,
+        prepaid_finance_charge: dec!(0),
+        total_sale_price: amount_financed + finance_charge,
+        ltv: None,
+        ltv_basis: LtvBasis::default(),
+    })
+}
+
+/// Measure loan-to-value against the lender program's basis, else the
+/// configured one.
+///
+/// # Formula
+/// ```text
+/// ltv = amount_financed / basis_value
+/// ```
+fn apply_loan_to_value(deal: &TaxComputedDeal, finance: &mut FinanceStructure, options: &PipelineOptions) {
+    let basis = deal.deal.profiles.lender_program
+        .as_ref()
+        .and_then(|program| program.structure_rules.ltv_basis)
+        .unwrap_or(options.ltv_basis);
+    let value = deal.deal.deal.deal.input.inner.ltv_basis_value(basis);
+
+    finance.ltv_basis = basis;
+    finance.ltv = value.map(|value| (finance.amount_financed / value).round_dp(4));
+}
+
+/// Flag a loan over the program's maximum loan-to-value.
+fn check_max_ltv(deal: &TaxComputedDeal, finance: &FinanceStructure) -> Option<ValidationWarning> {
+    let max_ltv = deal.deal.profiles.lender_program
+        .as_ref()
+        .and_then(|program| program.structure_rules.max_ltv)?;
+    let ltv = finance.ltv?;
+    if ltv <= max_ltv {
+        return None;
+    }
+
+    Some(ValidationWarning {
+        code: "LTV_OVER_PROGRAM_MAX".to_string(),
+        field: "amount_financed".to_string(),
+        message: format!(
+            "LTV {}% against {:?} exceeds the program maximum of {}%",
+            (ltv * dec!(100)).normalize(),
+            finance.ltv_basis,
+            (max_ltv * dec!(100)).normalize()
+        ),
+    })
+}
+
+/// Flag a finance deal whose down payment, trade, and rebates cover the
+/// whole amount, leaving nothing to finance and no payments.
+fn check_zero_amount_financed(finance: &FinanceStructure) -> Option<ValidationWarning> {
+    if !finance.amount_financed.is_zero() {
+        return None;
+    }
+
+    Some(ValidationWarning {
+        code: "ZERO_AMOUNT_FINANCED".to_string(),
+        field: "cash_down".to_string(),
+        message: format!(
+            "Down payment, trade, and rebates cover the full amount; nothing is financed and {} is owed back to the customer",
+            finance.excess_credit
+        ),
+    })
+}
+
+/// Longest term, up to `term_months`, whose payment meets `min_payment`.
+///
+/// Returns the requested term when no term can reach the minimum; the
+/// caller flags that as infeasible.
+fn term_for_min_payment(
+    amount_financed: Decimal,
+    apr: Decimal,
+    term_months: u32,
+    frequency: PaymentFrequency,
+    min_payment: Decimal,
+) -> UdcResult<u32> {
+    for term in (1..=term_months).rev() {
+        let number_of_payments = frequency.number_of_payments(term);
+        if number_of_payments == 0 {
+            continue;
+        }
+        let (payment, _, _) =
+            calculate_periodic_payment(amount_financed, apr, number_of_payments, frequency.periods_per_year())?;
+        if payment >= min_payment {
+            return Ok(term);
+        }
+    }
+    Ok(term_months)
+}
+
+/// Flag a term shortened to meet the program's minimum payment, or a loan
+/// too small to meet it at any term (an error under `strict_validation`).
+fn check_min_payment(
+    deal: &TaxComputedDeal,
+    finance: &FinanceStructure,
+    options: &PipelineOptions,
+    warnings: &mut Vec<ValidationWarning>,
+) -> UdcResult<()> {
+    let Some(min_payment) = deal.deal.profiles.lender_program
+        .as_ref()
+        .and_then(|program| program.structure_rules.min_payment)
+    else {
+        return Ok(());
+    };
+    let requested_term = deal.deal.deal.deal.input.inner.finance_params
+        .as_ref()
+        .map_or(finance.term_months, |params| params.term_months);
+
+    if finance.amount_financed > dec!(0) && finance.monthly_payment < min_payment {
+        let message = format!(
+            "Payment {} on amount financed {} cannot reach the program minimum of {} at any term",
+            finance.monthly_payment, finance.amount_financed, min_payment
+        );
+        if options.strict_validation {
+            return Err(UdcError::validation_field(message, "finance_params.term_months"));
+        }
+        warnings.push(ValidationWarning {
+            code: "MIN_PAYMENT_INFEASIBLE".to_string(),
+            field: "finance_params.term_months".to_string(),
+            message,
+        });
+    } else if finance.term_months < requested_term {
+        warnings.push(ValidationWarning {
+            code: "MIN_PAYMENT_TERM_SHORTENED".to_string(),
+            field: "finance_params.term_months".to_string(),
+            message: format!(
+                "Term shortened from {} to {} months to meet the program minimum payment of {}",
+                requested_term, finance.term_months, min_payment
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+/// Most passes the amount-financed pricing solver makes before giving up
+pub const MAX_PRICING_ITERATIONS: usize = 10;
+
+/// Build the finance structure, sizing products priced off the amount
+/// financed (e.g., GAP at 2% of amount financed).
+///
+/// The price depends on the amount financed, which includes the price, so
+/// this iterates to a fixed point: price each product from the last amount
+/// financed, rebuild, and stop once no price changes by a cent. A product
+/// the state taxes is left at its input price (its tax was fixed in P4).
+///
+/// # Errors
+/// - No rate tier covers the amount financed
+/// - Prices still changing after [`MAX_PRICING_ITERATIONS`] passes
+fn build_priced_finance_structure(
+    deal: &mut TaxComputedDeal,
+    warnings: &mut Vec<ValidationWarning>,
+) -> UdcResult<FinanceStructure> {
+    let ancillaries = &deal.deal.profiles.primary_rules.ancillaries;
+    let products = &deal.deal.deal.deal.input.inner.products;
+    let mut priced = Vec::new();
+    for pricing in &deal.deal.profiles.amount_financed_pricing {
+        let Some(product) = products.iter().find(|p| p.id == pricing.product_id) else {
+            continue;
+        };
+        if ancillaries.is_product_type_taxable(product.product_type) {
+            warnings.push(ValidationWarning {
+                code: "PRODUCT_PRICING_SKIPPED".to_string(),
+                field: format!("products.{}", product.id),
+                message: format!(
+                    "{} is taxable in this state; kept at its input price instead of pricing off the amount financed",
+                    product.name
+                ),
+            });
+            continue;
+        }
+        priced.push(pricing.clone());
+    }
+
+    let mut structure = build_finance_structure(deal)?;
+    if priced.is_empty() {
+        return Ok(structure);
+    }
+
+    for _ in 0..MAX_PRICING_ITERATIONS {
+        let normalized = &mut deal.deal.deal.deal.input;
+        let mut changed = false;
+        for pricing in &priced {
+            let price = pricing.pricing.rate_price(structure.amount_financed).ok_or_else(|| {
+                UdcError::validation_field(
+                    format!("No rate tier covers amount financed {}", structure.amount_financed),
+                    format!("products.{}", pricing.product_id),
+                )
+            })?;
+            if let Some(product) = normalized.inner.products.iter_mut().find(|p| p.id == pricing.product_id) {
+                if product.price != price {
+                    product.price = price;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return Ok(structure);
+        }
+
+        let (taxable, non_taxable) = compute_product_totals(&normalized.inner);
+        normalized.total_taxable_products = taxable;
+        normalized.total_non_taxable_products = non_taxable;
+        structure = build_finance_structure(deal)?;
+    }
+
+    Err(UdcError::calculation(
+        format!(
+            "Amount-financed product pricing did not converge after {} iterations",
+            MAX_PRICING_ITERATIONS
+        ),
+        "P5_STRUCTURE",
+    ))
+}
+
+/// Allocate the amount financed across the deal's funding lenders.
+///
+/// Each portion is the same component the amount financed is built from:
+/// the vehicle (price, fees and tax less the reductions), the financed
+/// products, and the negative equity. Each secondary allocation funds its
+/// portion (a fixed amount comes out of the vehicle), capped by its `amount`
+/// when given; the primary lender funds what the allocations leave of each
+/// portion at the deal's rate and term. A cent of rounding is carried on the
+/// primary split. Returns no splits when the deal has no allocations or
+/// nothing is financed.
+///
+/// # Errors
+/// - A fixed allocation without an amount
+/// - Allocations that exceed the amount financed
+/// - Splits that miss the amount financed by more than
+///   [`AMOUNT_FINANCED_ROUNDING_TOLERANCE`]
+fn build_funding_splits(
+    params: &crate::types::FinanceParams,
+    amount_financed: Decimal,
+    vehicle_financed: Decimal,
+    fi_products_financed: Decimal,
+    negative_equity: Decimal,
+    apr: Decimal,
+    term_months: u32,
+) -> UdcResult<Vec<FundingSplitItem>> {
+    if params.funding_allocations.is_empty() || amount_financed.is_zero() {
+        return Ok(vec![]);
+    }
+
+    // What is still unfunded of each portion
+    let mut vehicle = vehicle_financed;
+    let mut fi_products = fi_products_financed;
+    let mut negative_equity = negative_equity;
+
+    let mut splits = Vec::with_capacity(params.funding_allocations.len() + 1);
+    for allocation in &params.funding_allocations {
+        let unfunded = match allocation.portion {
+            FundingPortion::FiProducts => &mut fi_products,
+            FundingPortion::NegativeEquity => &mut negative_equity,
+            FundingPortion::Vehicle | FundingPortion::Fixed => &mut vehicle,
+        };
+        let amount = match allocation.portion {
+            FundingPortion::Fixed => allocation.amount.ok_or_else(|| {
+                UdcError::validation_field(
+                    format!("Fixed funding allocation for {} needs an amount", allocation.lender_id),
+                    "finance_params.funding_allocations",
+                )
+            })?,
+            _ => allocation.amount.map_or(*unfunded, |cap| cap.min(*unfunded)),
+        }
+        .max(dec!(0))
+        .round_money();
+        if amount.is_zero() {
+            continue;
+        }
+        *unfunded -= amount;
+
+        splits.push(FundingSplitItem {
+            lender_id: Some(allocation.lender_id.clone()),
+            portion: allocation.portion,
+            amount,
+            apr: allocation.apr.unwrap_or(apr),
+            term_months: allocation.term_months.unwrap_or(term_months),
+        });
+    }
+
+    let allocated: Decimal = splits.iter().map(|s| s.amount).sum();
+    let primary = (vehicle + fi_products + negative_equity).round_money();
+    if primary < dec!(0) {
+        return Err(UdcError::validation_field(
+            format!(
+                "Funding allocations total {} but the amount financed is {}",
+                allocated, amount_financed
+            ),
+            "finance_params.funding_allocations",
+        ));
+    }
+
+    let rounding = amount_financed - allocated - primary;
+    if rounding.abs() > AMOUNT_FINANCED_ROUNDING_TOLERANCE {
+        return Err(UdcError::calculation(
+            format!(
+                "Funding splits total {} but the amount financed is {}",
+                allocated + primary, amount_financed
+            ),
+            "P5_STRUCTURE",
+        ));
+    }
+
+    splits.insert(0, FundingSplitItem {
+        lender_id: params.lender_id.clone(),
+        portion: FundingPortion::Vehicle,
+        amount: primary + rounding,
+        apr,
+        term_months,
+    });
+
+    Ok(splits)
+}
+
+/// Most the itemized amount financed may differ from the computed total
+/// before P5 reports an error (one cent of rounding)
+pub const AMOUNT_FINANCED_ROUNDING_TOLERANCE: Decimal = dec!(0.01);
+
+/// Build the signed line items behind the amount financed.
+///
+/// Additions (price, each fee, each product, tax, negative equity) are
+/// positive; reductions (down, trade, rebates) are negative. When the
+/// reductions exceed the deal, the excess owed back to the customer is
+/// added back so the items reach the zero floor. A difference of up to
+/// [`AMOUNT_FINANCED_ROUNDING_TOLERANCE`] is carried as a rounding line.
+///
+/// # Errors
+/// - The items miss the amount financed by more than the tolerance (a
+///   component of the amount financed is not itemized)
+fn build_amount_financed_breakdown(
+    normalized: &NormalizedDealInput,
+    bank_fee: Decimal,
+    negative_equity: Decimal,
+    sales_tax: Decimal,
+    trade_credit: Decimal,
+    excess_credit: Decimal,
+    amount_financed: Decimal,
+) -> UdcResult<Vec<AmountFinancedItem>> {
+    let mut items = Vec::new();
+    let mut push = |label: &str, amount: Decimal| {
+        if !amount.is_zero() {
+            items.push(AmountFinancedItem { label: label.to_string(), amount });
+        }
+    };
+
+    let input = &normalized.inner;
+    let fees = &input.fees;
+
+    // Additions
+    push("Vehicle price", input.vehicle_price);
+    push("Destination", normalized.vehicle_destination);
+    push("Doc fee", fees.doc_fee_amount());
+    push("Electronic filing fee", fees.electronic_filing_fee);
+    push("Destination fee", fees.charged_destination_fee());
+    push("Dealer handling fee", fees.dealer_handling_fee);
+    for fee in fees.other_fees.iter().filter(|f| f.dealer_fee) {
+        push(&fee.name, fee.amount);
+    }
+    push("Title fee", fees.title_fee);
+    push("Registration fee", fees.registration_fee);
+    push("Plate fee", fees.plate_fee);
+    push("Inspection fee", fees.inspection_fee);
+    push("Tire fee", fees.tire_fee);
+    push("Smog fee", fees.smog_fee);
+    push("Bank fee", bank_fee);
+    for product in &input.products {
+        push(&product.name, product.price);
+    }
+    push("Sales tax", sales_tax);
+    push("Negative equity", negative_equity);
+
+    // Reductions
+    push("Cash down", -input.cash_down);
+    push("Trade-in equity", -trade_credit);
+    for rebate in &input.rebates {
+        push(&rebate.name, -rebate.amount);
+    }
+    push("Excess credit owed to customer", excess_credit);
+
+    // Reconcile rounding only
+    let total: Decimal = items.iter().map(|i| i.amount).sum();
+    let rounding = amount_financed - total;
+    if rounding.abs() > AMOUNT_FINANCED_ROUNDING_TOLERANCE {
+        return Err(UdcError::calculation(
+            format!(
+                "Amount financed line items total {} but the amount financed is {}",
+                total, amount_financed
+            ),
+            "P5_STRUCTURE",
+        ));
+    }
+    if !rounding.is_zero() {
+        items.push(AmountFinancedItem { label: "Rounding".to_string(), amount: rounding });
+    }
+
+    Ok(items)
+}
+
+/// Calculate dealer reserve as the finance charge spread between the
+/// contract APR and the lender's buy rate.
+///
+/// # Formula
+/// ```text
+/// reserve = finance_charge(apr) - finance_charge(buy_rate)
+/// ```
+/// No reserve is earned when no buy rate is given or the contract
+/// rate is at or below it.
+fn calculate_dealer_reserve(
+    amount_financed: Decimal,
+    apr: Decimal,
+    buy_rate: Option<Decimal>,
+    number_of_payments: u32,
+    periods_per_year: u32,
+    finance_charge: Decimal,
+) -> UdcResult<Decimal> {
+    let buy_rate = match buy_rate {
+        Some(rate) if rate < apr => rate,
+        _ => return Ok(dec!(0)),
+    };
+
+    let (_, _, buy_rate_charge) = calculate_periodic_payment(
+        amount_financed,
+        buy_rate,
+        number_of_payments,
+        periods_per_year,
+    )?;
+
+    Ok((finance_charge - buy_rate_charge).max(dec!(0)))
+}
+
 /// Calculate loan payment using standard amortization formula.
 ///
 /// # Formula
@@ -366,12 +1186,29 @@ pub fn calculate_loan_payment(
     principal: Decimal,
     apr: Decimal,
     term_months: u32,
+) -> UdcResult<(Decimal, Decimal, Decimal)> {
+    calculate_periodic_payment(principal, apr, term_months, 12)
+}
+
+/// Calculate loan payment for an arbitrary payment frequency.
+///
+/// Same formula as [`calculate_loan_payment`] with the periodic rate
+/// r = APR / periods_per_year and n = number of payments.
+pub fn calculate_periodic_payment(
+    principal: Decimal,
+    apr: Decimal,
+    number_of_payments: u32,
+    periods_per_year: u32,
 ) -> UdcResult<(Decimal, Decimal, Decimal)> {
     if principal <= dec!(0) {
         return Ok((dec!(0), dec!(0), dec!(0)));
     }
 
-    let n = Decimal::from(term_months);
+    if number_of_payments == 0 || periods_per_year == 0 {
+        return Err(UdcError::calculation("Number of payments must be positive", "P5_STRUCTURE"));
+    }
+
+    let n = Decimal::from(number_of_payments);
 
     // Handle 0% APR
     if apr == dec!(0) {
@@ -380,12 +1217,12 @@ pub fn calculate_loan_payment(
         return Ok((payment, total, dec!(0)));
     }
 
-    // Monthly rate
-    let r = apr / dec!(12);
+    // Periodic rate
+    let r = apr / Decimal::from(periods_per_year);
 
     // (1 + r)^n - using iterative calculation for precision
     let one_plus_r = dec!(1) + r;
-    let one_plus_r_n = power_decimal(one_plus_r, term_months);
+    let one_plus_r_n = power_decimal(one_plus_r, number_of_payments);
 
     // Payment formula: P * [r(1+r)^n] / [(1+r)^n - 1]
     let numerator = principal * r * one_plus_r_n;
@@ -421,36 +1258,69 @@ fn power_decimal(base: Decimal, exp: u32) -> Decimal {
 }
 
 /// Build lease deal structure.
-fn build_lease_structure(deal: &TaxComputedDeal) -> UdcResult<LeaseStructure> {
+///
+/// A residual at or above the adjusted cap cost leaves nothing to
+/// depreciate: depreciation is clamped to zero with a warning, or rejected
+/// under `options.strict_validation`.
+fn build_lease_structure(
+    deal: &TaxComputedDeal,
+    options: &PipelineOptions,
+    warnings: &mut Vec<ValidationWarning>,
+) -> UdcResult<LeaseStructure> {
     let input = &deal.deal.deal.deal.input.inner;
     let normalized = &deal.deal.deal.deal.input;
-    let rules = &deal.deal.profiles.primary_rules;
+    // Lease tax rate and mode may come from the garaging state
+    let rules = deal.deal.profiles.lease_tax_rules();
 
     // Get lease params
     let lease_params = input.lease_params.as_ref()
         .ok_or_else(|| UdcError::calculation("Missing lease params", "P5_STRUCTURE"))?;
 
     let lease_tax_mode = rules.lease_tax_mode.unwrap_or(LeaseTaxMode::MonthlyPayment);
+    // Exempt lessees (resale, certificate holders) owe no lease tax
+    let taxed = deal.tax.customer_treatment != CustomerTaxTreatment::Exempt;
 
     // Vehicle values
-    let msrp = input.vehicle_price; // Simplified - should be actual MSRP
-    let selling_price = input.vehicle_price;
+    let msrp = lease_params
+        .msrp
+        .or_else(|| input.vehicle.as_ref().map(|v| v.msrp.as_decimal()))
+        .unwrap_or(input.vehicle_price);
+    let selling_price = normalized.selling_price;
 
     // Capitalized items
-    let capitalized_fees = calculate_capitalizable_fees(&input.fees, lease_params);
+    let (capitalized_fees, upfront_fees) = split_lease_fees(
+        &input.fees,
+        lease_params,
+        deal.deal.profiles.program_fees_capitalizable(),
+    );
     let capitalized_fi_products = input.products
         .iter()
-        .filter(|p| true) // All products capitalizable for now
         .map(|p| p.price)
         .sum::<Decimal>();
 
+    // Trade equity applied as a cap cost reduction
+    let trade_credit = normalized.net_trade.max(dec!(0));
+
+    // Capitalized doc fee the state exempts on leases
+    let exempt_doc_fee = lease_exempt_doc_fee(rules, &input.fees);
+
     // Cap cost tax handling
     let (capitalized_tax, upfront_tax, monthly_tax_rate) = match lease_tax_mode {
-        LeaseTaxMode::CapCostUpfront => {
-            // Tax the entire cap cost upfront
-            let tax_base = selling_price + capitalized_fees + capitalized_fi_products;
+        _ if !taxed => (dec!(0), dec!(0), dec!(0)),
+        LeaseTaxMode::CapCostUpfront | LeaseTaxMode::CapitalizedCost => {
+            // Tax the entire cap cost upfront, less the trade where credited,
+            // and either roll it into the cap cost or collect it at signing
+            let tax_base = lease_taxable_cap_cost(
+                rules,
+                selling_price + capitalized_fees - exempt_doc_fee + capitalized_fi_products,
+                trade_credit,
+            );
             let tax = (tax_base * rules.rates.default_combined_rate).round_money();
-            (tax, tax, dec!(0))
+            if lease_params.capitalize_upfront_tax {
+                (tax, dec!(0), dec!(0))
+            } else {
+                (dec!(0), tax, dec!(0))
+            }
         }
         LeaseTaxMode::MonthlyPayment => {
             // Tax each monthly payment
@@ -461,61 +1331,178 @@ fn build_lease_structure(deal: &TaxComputedDeal) -> UdcResult<LeaseStructure> {
 
     // Gross Cap Cost
     let gross_cap_cost = selling_price + capitalized_fees + capitalized_fi_products + capitalized_tax;
+    let gross_cap_cost_breakdown = build_gross_cap_cost_breakdown(
+        normalized,
+        lease_params,
+        deal.deal.profiles.program_fees_capitalizable(),
+        capitalized_tax,
+    );
 
     // Cap Cost Reductions
     let cash_down = input.cash_down + lease_params.cap_cost_reduction;
-    let trade_credit = normalized.net_trade.max(dec!(0));
-    let rebates = normalized.total_rebates;
+    let rebates = input.cap_cost_rebates();
     let total_cap_reduction = cash_down + trade_credit + rebates;
 
+    // Some states also tax the down payment at signing
+    let upfront_tax = upfront_tax + if taxed {
+        // Cap cost rebates that reduce the tax basis are not taxed as a reduction
+        let tax_basis_rebates = rebates - input.taxable_cap_cost_rebates();
+        cap_reduction_tax(rules, lease_tax_mode, total_cap_reduction, trade_credit, tax_basis_rebates)
+    } else {
+        dec!(0)
+    };
+
     // Adjusted Cap Cost (Net Cap Cost)
     let adjusted_cap_cost = (gross_cap_cost - total_cap_reduction).max(dec!(0)).round_money();
 
-    // Residual calculation
-    let residual_percentage = lease_params.residual_percent;
+    // Residual calculation - the program's mileage tier adjusts the residual
+    // and sets the excess mileage charge
+    let annual_miles = lease_params.selected_mileage.unwrap_or(lease_params.annual_miles);
+    let mileage_option = deal.deal.profiles.lender_program.as_ref()
+        .and_then(|program| program.lease_program.as_ref())
+        .and_then(|lease_program| lease_program.mileage_option(annual_miles));
+    let residual_percentage = lease_params.residual_percent
+        + mileage_option.map_or(dec!(0), |option| option.residual_adjustment);
+    let excess_mileage_rate = mileage_option
+        .map(|option| option.excess_rate)
+        .or(lease_params.excess_mileage_rate);
     let residual_value = (msrp * residual_percentage).round_money();
 
-    // Lease charge calculations
-    let money_factor = lease_params.money_factor;
+    // Lease charge calculations - MSDs lower the base money factor only
+    // when requested; otherwise the money factor is already effective
+    let base_money_factor = deal.deal.profiles.subvention.as_ref()
+        .and_then(|program| program.money_factor)
+        .unwrap_or(lease_params.money_factor.as_decimal());
+    let msd_mf_reduction = deal.deal.profiles.lender_program.as_ref()
+        .and_then(|program| program.lease_program.as_ref())
+        .filter(|_| lease_params.apply_msd_reduction)
+        .map_or(dec!(0), |lease_program| lease_program.msd_reduction(lease_params.msd_count))
+        .min(base_money_factor);
+    let money_factor = base_money_factor - msd_mf_reduction;
     let equivalent_apr = money_factor * dec!(2400);
     let term_months = lease_params.term_months;
     let term = Decimal::from(term_months);
 
-    // Depreciation
-    let depreciation = adjusted_cap_cost - residual_value;
+    // Depreciation (never negative)
+    let depreciation = if residual_value >= adjusted_cap_cost {
+        let message = format!(
+            "Residual value {} is at or above the adjusted cap cost {}; nothing to depreciate",
+            residual_value, adjusted_cap_cost
+        );
+        if options.strict_validation {
+            return Err(UdcError::validation_field(message, "lease_params.residual_percent"));
+        }
+        warnings.push(ValidationWarning {
+            code: "RESIDUAL_EXCEEDS_CAP_COST".to_string(),
+            field: "lease_params.residual_percent".to_string(),
+            message,
+        });
+        dec!(0)
+    } else {
+        adjusted_cap_cost - residual_value
+    };
     let monthly_depreciation = (depreciation / term).round_money();
 
     // Rent Charge
     // Rent = (Adjusted Cap + Residual) * MF * Term
-    let rent_charge = ((adjusted_cap_cost + residual_value) * money_factor * term).round_money();
+    let exact_rent_charge = (adjusted_cap_cost + residual_value) * money_factor * term;
+    let rent_charge = exact_rent_charge.round_money();
     let monthly_rent_charge = (rent_charge / term).round_money();
 
     // Base Payment
-    let base_monthly_payment = monthly_depreciation + monthly_rent_charge;
+    let base_monthly_payment = match options.rounding {
+        RoundingStrategy::RoundEachStep => monthly_depreciation + monthly_rent_charge,
+        RoundingStrategy::RoundAtEnd => ((depreciation + exact_rent_charge) / term).round_money(),
+    };
 
-    // Monthly Tax (if applicable)
-    let monthly_tax = (base_monthly_payment * monthly_tax_rate).round_money();
+    // Monthly Tax (if applicable) on the slice of the payment the state taxes
+    let monthly_capitalized_fees = (capitalized_fees / term).round_money();
+    let taxable_monthly_payment = monthly_lease_tax_base(
+        rules,
+        base_monthly_payment,
+        monthly_depreciation,
+        monthly_capitalized_fees,
+        (exempt_doc_fee / term).round_money(),
+    ) + lease_trade_monthly_base(rules, trade_credit, term_months)
+        + lease_rebate_monthly_base(rules, input.taxable_cap_cost_rebates(), term_months);
+    let monthly_tax = (taxable_monthly_payment * monthly_tax_rate).round_money();
     let total_monthly_payment = base_monthly_payment + monthly_tax;
 
+    // Later payments may be taxed at a scheduled new rate
+    let tax_rate_changes: Vec<(u32, Decimal)> = if monthly_tax_rate > dec!(0) {
+        lease_params.tax_rate_schedule
+            .iter()
+            .map(|change| (change.effective_month, change.rate.as_decimal()))
+            .collect()
+    } else {
+        if !lease_params.tax_rate_schedule.is_empty() {
+            warnings.push(ValidationWarning {
+                code: "TAX_RATE_SCHEDULE_IGNORED".to_string(),
+                field: "lease_params.tax_rate_schedule".to_string(),
+                message: "Tax rate schedule only applies to leases taxed on each monthly payment".to_string(),
+            });
+        }
+        vec![]
+    };
+    let total_base_payments = base_monthly_payment * term;
+
+    // TAVT states tax the base payments plus the down payment at signing
+    let special_tax = (taxed && rules.tax_type == TaxType::Tavt)
+        .then(|| lease_tavt(rules, total_base_payments, total_cap_reduction));
+    let upfront_tax = upfront_tax + special_tax.as_ref().map_or(dec!(0), |tavt| tavt.amount);
+
     // Due at Signing
     let first_payment = total_monthly_payment;
     let security_deposit = lease_params.security_deposit.unwrap_or(dec!(0));
-    let acquisition_fee_upfront = if !lease_params.cap_acquisition_fee {
+    let acquisition_fee_upfront = if !lease_params.cap_acquisition_fee
+        || !deal.deal.profiles.program_fees_capitalizable()
+    {
         input.fees.acquisition_fee
     } else {
         dec!(0)
     };
 
-    let due_at_signing = first_payment + cash_down + security_deposit + acquisition_fee_upfront + upfront_tax;
+    let msd_deposit = msd_deposit(deal, lease_params.msd_count, total_monthly_payment);
+    let interim_rent = interim_rent(input, lease_params.day_count, total_monthly_payment);
+
+    let refundable_due_at_signing = security_deposit + msd_deposit;
+    // Rebates kept off the cap cost are paid toward the amount due
+    let signing_charges = first_payment + interim_rent + cash_down + upfront_fees + upfront_tax;
+    let offered_signing_rebates = input.total_rebates() - rebates;
+    let signing_rebates = offered_signing_rebates.min(signing_charges);
+    if offered_signing_rebates > signing_rebates {
+        warnings.push(ValidationWarning {
+            code: "SIGNING_REBATES_EXCEED_DUE".to_string(),
+            field: "rebates".to_string(),
+            message: format!(
+                "Rebates of {} applied at signing exceed the {} due; {} is not applied",
+                offered_signing_rebates,
+                signing_charges,
+                offered_signing_rebates - signing_rebates
+            ),
+        });
+    }
+    let nonrefundable_due_at_signing = signing_charges - signing_rebates;
+    let due_at_signing = refundable_due_at_signing + nonrefundable_due_at_signing;
 
-    // Totals
-    let total_base_payments = base_monthly_payment * term;
+    // Totals - capitalized tax is paid through the base payments
+    let total_monthly_tax = (1..=term_months)
+        .map(|payment_number| scheduled_monthly_tax(monthly_tax, taxable_monthly_payment, &tax_rate_changes, payment_number))
+        .sum::<Decimal>();
     let total_tax = if lease_tax_mode == LeaseTaxMode::MonthlyPayment {
-        monthly_tax * term
+        total_monthly_tax + upfront_tax
     } else {
         upfront_tax
-    };
-    let total_lease_cost = total_base_payments + total_tax + cash_down;
+    } + capitalized_tax;
+    let total_lease_cost = total_base_payments + total_tax - capitalized_tax + cash_down;
+
+    let end_of_lease = end_of_lease_costs(
+        deal,
+        lease_params,
+        residual_value,
+        annual_miles,
+        excess_mileage_rate,
+    );
 
     Ok(LeaseStructure {
         msrp,
@@ -524,6 +1511,7 @@ fn build_lease_structure(deal: &TaxComputedDeal) -> UdcResult<LeaseStructure> {
         capitalized_fi_products,
         capitalized_tax,
         gross_cap_cost,
+        gross_cap_cost_breakdown,
         cash_down,
         trade_credit,
         rebates,
@@ -531,6 +1519,10 @@ fn build_lease_structure(deal: &TaxComputedDeal) -> UdcResult<LeaseStructure> {
         adjusted_cap_cost,
         residual_percentage,
         residual_value,
+        annual_miles,
+        excess_mileage_rate,
+        base_money_factor,
+        msd_mf_reduction,
         money_factor,
         equivalent_apr,
         term_months,
@@ -541,129 +1533,561 @@ fn build_lease_structure(deal: &TaxComputedDeal) -> UdcResult<LeaseStructure> {
         base_monthly_payment,
         monthly_tax,
         total_monthly_payment,
+        taxable_monthly_payment,
+        tax_rate_changes,
         first_payment,
         security_deposit,
+        msd_deposit,
+        interim_rent,
         acquisition_fee_upfront,
+        upfront_fees,
         upfront_tax,
+        signing_rebates,
         due_at_signing,
+        refundable_due_at_signing,
+        nonrefundable_due_at_signing,
         total_base_payments,
         total_tax,
         total_lease_cost,
         lease_tax_mode,
+        special_tax,
+        end_of_lease,
     })
 }
 
-/// Calculate capitalizable fees for lease.
-fn calculate_capitalizable_fees(
-    fees: &crate::types::DealFees,
-    params: &crate::types::LeaseParams,
+impl LeaseStructure {
+    /// Monthly tax collected with payment `payment_number` (1-based), at
+    /// the rate in effect for it
+    pub fn monthly_tax_for(&self, payment_number: u32) -> Decimal {
+        scheduled_monthly_tax(self.monthly_tax, self.taxable_monthly_payment, &self.tax_rate_changes, payment_number)
+    }
+}
+
+/// Monthly tax for one payment: the last scheduled rate change at or before
+/// it applies, else the opening `monthly_tax`.
+fn scheduled_monthly_tax(
+    monthly_tax: Decimal,
+    taxable_monthly_payment: Decimal,
+    tax_rate_changes: &[(u32, Decimal)],
+    payment_number: u32,
 ) -> Decimal {
-    let mut cap_fees = dec!(0);
+    tax_rate_changes
+        .iter()
+        .rev()
+        .find(|(month, _)| *month <= payment_number)
+        .map_or(monthly_tax, |(_, rate)| (taxable_monthly_payment * rate).round_money())
+}
 
-    // Doc fee is typically capitalized
-    cap_fees += fees.doc_fee;
+/// Cost of each lease-end choice.
+///
+/// # Formula
+/// ```text
+/// excess_miles = max(estimated_annual_miles - annual_miles, 0) * term / 12
+/// return_cost  = disposition_fee + excess_miles * excess_rate + excess_wear
+/// buyout_cost  = residual + purchase_option_fee
+///              + (residual + purchase_option_fee) * buyout_rate
+/// ```
+///
+/// The disposition and purchase option fees come from the deal, else the
+/// lessor program. Excess wear cannot be estimated at signing and is zero.
+/// The buyout is taxed at the lease tax state's purchase rate.
+fn end_of_lease_costs(
+    deal: &TaxComputedDeal,
+    lease_params: &crate::types::LeaseParams,
+    residual_value: Decimal,
+    annual_miles: u32,
+    excess_mileage_rate: Option<Decimal>,
+) -> EndOfLeaseCosts {
+    let input = &deal.deal.deal.deal.input.inner;
+    let program_fees = deal.deal.profiles.lender_program
+        .as_ref()
+        .map(|program| &program.fee_config);
 
-    // Acquisition fee if capitalized
-    if params.cap_acquisition_fee {
-        cap_fees += fees.acquisition_fee;
+    // Return
+    let disposition_fee = if input.fees.disposition_fee > dec!(0) {
+        input.fees.disposition_fee
+    } else {
+        program_fees.map_or(dec!(0), |fees| fees.disposition_fee)
+    };
+    let estimated_excess_miles = lease_params.estimated_annual_miles
+        .map_or(0, |miles| miles.saturating_sub(annual_miles))
+        * lease_params.term_months
+        / 12;
+    let excess_mileage_charge = (Decimal::from(estimated_excess_miles)
+        * excess_mileage_rate.unwrap_or(dec!(0)))
+        .round_money();
+    let excess_wear_charge = dec!(0);
+    let return_cost = disposition_fee + excess_mileage_charge + excess_wear_charge;
+
+    // Buyout
+    let purchase_option_fee = lease_params.purchase_option_fee
+        .or_else(|| program_fees.map(|fees| fees.purchase_option_fee))
+        .unwrap_or(dec!(0));
+    let buyout_rate = buyout_tax_rate(deal.deal.profiles.lease_tax_rules());
+    let buyout_tax = ((residual_value + purchase_option_fee) * buyout_rate).round_money();
+    let buyout_cost = residual_value + purchase_option_fee + buyout_tax;
+
+    EndOfLeaseCosts {
+        disposition_fee,
+        estimated_excess_miles,
+        excess_mileage_charge,
+        excess_wear_charge,
+        return_cost,
+        purchase_option_fee,
+        buyout_tax,
+        buyout_cost,
     }
+}
 
-    // Destination fee
-    cap_fees += fees.destination_fee;
+/// Total of the deal's multiple security deposits.
+///
+/// Each deposit is the monthly payment rounded up per the lease program's
+/// MSD config (to the next $50 when there is none), and the count is capped
+/// at the program maximum.
+fn msd_deposit(deal: &TaxComputedDeal, msd_count: u8, monthly_payment: Decimal) -> Decimal {
+    if msd_count == 0 {
+        return dec!(0);
+    }
+    let msd_config = deal.deal.profiles.lender_program
+        .as_ref()
+        .and_then(|program| program.lease_program.as_ref())
+        .and_then(|lease_program| lease_program.msd_config.as_ref());
+
+    let (count, rounding) = match msd_config {
+        Some(config) => (msd_count.min(config.max_count), config.amount_rounding.clone()),
+        None => (msd_count, MsdRounding::default()),
+    };
+    let per_deposit = match rounding {
+        MsdRounding::NearestFifty => (monthly_payment / dec!(50)).ceil() * dec!(50),
+        MsdRounding::NearestHundred => (monthly_payment / dec!(100)).ceil() * dec!(100),
+        MsdRounding::ExactPayment => monthly_payment,
+    };
 
-    cap_fees
+    per_deposit * Decimal::from(count)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::types::{DealInput, DealFees, CustomerInfo, FinanceParams, LeaseParams, StateCode};
-    use crate::phases::p0_normalize::normalize_deal_input;
-    use crate::phases::p1_mode_routing::route_deal;
-    use crate::phases::p2_jurisdiction::resolve_jurisdiction;
-    use crate::phases::p3_profiles::load_profiles;
-    use crate::phases::p4_tax_cipher::calculate_tax;
+/// Rent for a partial first period, from the contract start to the first
+/// billing date.
+///
+/// # Formula
+/// ```text
+/// interim_rent = monthly_payment * 12 / days_per_year * interim_days
+/// ```
+///
+/// No interim rent is due without both dates, or when the billing cycle
+/// starts on the contract date.
+fn interim_rent(input: &crate::types::DealInput, day_count: DayCount, monthly_payment: Decimal) -> Decimal {
+    let (Some(start), Some(billing_start)) = (input.contract_start_date, input.first_payment_date) else {
+        return dec!(0);
+    };
+    let days = day_count.days_between(start, billing_start);
+    if days <= 0 {
+        return dec!(0);
+    }
 
-    fn make_finance_deal() -> TaxComputedDeal {
-        let input = DealInput {
-            deal_type: DealType::Finance,
-            vehicle_price: dec!(30000),
-            trade_in_value: Some(dec!(10000)),
-            trade_in_payoff: Some(dec!(5000)),
-            cash_down: dec!(2000),
-            rebates: vec![],
-            products: vec![],
-            fees: DealFees {
-                doc_fee: dec!(299),
-                title_fee: dec!(33),
-                registration_fee: dec!(75),
-                ..Default::default()
-            },
-            home_state: StateCode::TX,
-            transaction_state: StateCode::TX,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: Some(FinanceParams {
-                term_months: 60,
-                apr: dec!(0.0599),
-                lender_id: None,
-                buy_rate: None,
-                max_reserve_points: None,
-                deferred_first_payment: false,
-                days_to_first_payment: None,
-            }),
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
-        };
+    let daily_rent = monthly_payment * dec!(12) / Decimal::from(day_count.days_per_year());
+    (daily_rent * Decimal::from(days)).round_money()
+}
 
-        let normalized = normalize_deal_input(input).unwrap();
-        let routed = route_deal(normalized).unwrap();
-        let resolved = resolve_jurisdiction(routed).unwrap();
-        let loaded = load_profiles(resolved).unwrap();
-        calculate_tax(loaded).unwrap()
+/// Validate that the Reg M disclosed lease figures reconcile.
+///
+/// The total lease cost is checked against the cash the lessee actually
+/// pays: every scheduled monthly payment plus the nonrefundable amount due
+/// at signing, less the first payment (collected in both). Charges at
+/// signing that are not part of the lease cost (interim rent, upfront
+/// fees) come out of that, and rebates applied at signing go back in.
+///
+/// Each monthly payment is rounded to the cent, so depreciation plus rent
+/// charge may differ from the total of base payments by up to a cent per
+/// payment.
+fn validate_lease_invariants(lease: &LeaseStructure) -> UdcResult<()> {
+    let term = Decimal::from(lease.term_months);
+
+    // Invariant 1: Payment stream + nonrefundable due at signing - first
+    // payment = total lease cost + other signing charges - signing rebates
+    let payment_stream: Decimal = (1..=lease.term_months)
+        .map(|payment_number| lease.base_monthly_payment + lease.monthly_tax_for(payment_number))
+        .sum();
+    let cash_paid = payment_stream + lease.nonrefundable_due_at_signing - lease.first_payment;
+    let expected_cash =
+        lease.total_lease_cost + lease.interim_rent + lease.upfront_fees - lease.signing_rebates;
+    if (cash_paid - expected_cash).abs() > dec!(0.01) {
+        return Err(UdcError::calculation(
+            format!(
+                "Payments {} + due at signing {} - first payment {} != total lease cost {} + interim rent {} + upfront fees {} - signing rebates {}",
+                payment_stream,
+                lease.nonrefundable_due_at_signing,
+                lease.first_payment,
+                lease.total_lease_cost,
+                lease.interim_rent,
+                lease.upfront_fees,
+                lease.signing_rebates
+            ),
+            "P5_STRUCTURE",
+        ));
+    }
+
+    // Invariant 2: Depreciation + rent charge = total base payments (per-payment rounding)
+    let financed = lease.depreciation + lease.rent_charge;
+    if (financed - lease.total_base_payments).abs() > dec!(0.01) * term {
+        return Err(UdcError::calculation(
+            format!(
+                "Depreciation {} + rent charge {} != total base payments {}",
+                lease.depreciation, lease.rent_charge, lease.total_base_payments
+            ),
+            "P5_STRUCTURE",
+        ));
+    }
+
+    // Invariant 3: Itemized cap cost components = gross cap cost
+    let itemized: Decimal = lease.gross_cap_cost_breakdown.iter().map(|i| i.amount).sum();
+    if itemized != lease.gross_cap_cost {
+        return Err(UdcError::calculation(
+            format!("Cap cost items {} != gross cap cost {}", itemized, lease.gross_cap_cost),
+            "P5_STRUCTURE",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the lease money factor against the lessor's markup cap.
+///
+/// # Formula
+/// ```text
+/// ceiling = buy_rate_mf - msd_reduction + max_markup
+/// ```
+///
+/// Returns a warning when the money factor exceeds the ceiling. No check is
+/// made without a lease program, a tier buy rate, or a `max_markup`, or when
+/// a subvented money factor replaced the dealer's.
+fn check_money_factor_markup(
+    deal: &TaxComputedDeal,
+    lease: &LeaseStructure,
+) -> Option<ValidationWarning> {
+    let profiles = &deal.deal.profiles;
+    if profiles.subvention.is_some() {
+        return None;
+    }
+
+    let input = &deal.deal.deal.deal.input.inner;
+    let lease_program = profiles.lender_program.as_ref()?.lease_program.as_ref()?;
+    let tier_mf = lease_program.money_factor_for(profiles.credit_tier?)?;
+    let max_markup = tier_mf.max_markup?;
+
+    let msd_count = input.lease_params.as_ref().map_or(0, |p| p.msd_count);
+    let buy_rate_mf = tier_mf.money_factor - lease_program.msd_reduction(msd_count);
+    let ceiling = buy_rate_mf + max_markup;
+
+    if lease.money_factor <= ceiling {
+        return None;
+    }
+
+    Some(ValidationWarning {
+        code: "MF_MARKUP_EXCEEDED".to_string(),
+        field: "lease_params.money_factor".to_string(),
+        message: format!(
+            "Money factor {} exceeds allowed ceiling {} (buy rate {} + max markup {})",
+            lease.money_factor, ceiling, buy_rate_mf, max_markup
+        ),
+    })
+}
+
+/// Check the lease's drive-off structure against the lessor's
+/// [`DriveOffRules`](crate::types::DriveOffRules).
+///
+/// Flags a cap cost reduction below the program minimum, nothing due at
+/// signing when zero drive-off isn't allowed, and no cash down when
+/// sign-and-drive isn't offered. No check is made without a lease program.
+fn check_drive_off_rules(deal: &TaxComputedDeal, lease: &LeaseStructure) -> Vec<ValidationWarning> {
+    let Some(lease_program) = deal.deal.profiles.lender_program.as_ref()
+        .and_then(|program| program.lease_program.as_ref())
+    else {
+        return Vec::new();
+    };
+    let rules = &lease_program.drive_off_rules;
+    let mut warnings = Vec::new();
+
+    if let Some(min) = rules.min_cap_reduction {
+        if lease.total_cap_reduction < min {
+            warnings.push(ValidationWarning {
+                code: "CAP_REDUCTION_BELOW_MINIMUM".to_string(),
+                field: "cash_down".to_string(),
+                message: format!(
+                    "Cap cost reduction {} is below the program minimum {}",
+                    lease.total_cap_reduction, min
+                ),
+            });
+        }
+    }
+
+    if lease.due_at_signing.is_zero() {
+        if !rules.zero_drive_off_allowed {
+            warnings.push(ValidationWarning {
+                code: "ZERO_DRIVE_OFF_NOT_ALLOWED".to_string(),
+                field: "cash_down".to_string(),
+                message: "Program does not allow zero drive-off; an amount is due at signing".to_string(),
+            });
+        }
+    } else if lease.cash_down.is_zero() && !rules.sign_and_drive_available {
+        warnings.push(ValidationWarning {
+            code: "SIGN_AND_DRIVE_NOT_AVAILABLE".to_string(),
+            field: "cash_down".to_string(),
+            message: "Program does not offer sign-and-drive; a cash cap cost reduction is required".to_string(),
+        });
+    }
+
+    warnings
+}
+
+/// Build the dealer profit analysis.
+///
+/// Vehicle cost is the invoice when provided, otherwise estimated from MSRP.
+/// Pack and holdback come from the dealer profile when one is loaded.
+/// A trade allowed above its ACV is paid for out of the vehicle's gross, so
+/// the over-allowance comes off front-end gross (an allowance under ACV adds
+/// to it). Returns None when the deal carries no vehicle details.
+fn build_profit_analysis(
+    deal: &TaxComputedDeal,
+    structure: &DealStructure,
+) -> Option<ProfitAnalysis> {
+    let normalized = &deal.deal.deal.deal.input;
+    let input = &normalized.inner;
+    let vehicle = input.vehicle.as_ref()?;
+
+    // Front-end gross. Destination passes through at cost: whether set on
+    // the vehicle or as a fee included in the price, it is part of the
+    // selling price, so it goes on the invoice side too
+    let vehicle_cost = match vehicle.invoice {
+        Some(invoice) => invoice.as_decimal(),
+        None => (vehicle.msrp.as_decimal() * ESTIMATED_INVOICE_RATIO).round_money(),
+    } + normalized.included_destination();
+    let vehicle_gross = normalized.selling_price - vehicle_cost;
+    let (holdback, pack) = match deal.deal.profiles.dealer {
+        Some(ref dealer) => (
+            dealer.holdback(vehicle.msrp).as_decimal(),
+            dealer.pack.as_decimal(),
+        ),
+        None => (dec!(0), dec!(0)),
+    };
+    let primary_over_allowance = match (input.trade_in_value, input.trade_in_acv) {
+        (Some(allowance), Some(acv)) => allowance - acv,
+        _ => dec!(0),
+    };
+    let trade_over_allowance = primary_over_allowance
+        + input.additional_trades
+            .iter()
+            .map(|trade| (trade.gross_value - trade.tax_credit_value()).as_decimal())
+            .sum::<Decimal>();
+    let front_end_gross = vehicle_gross + holdback - pack - trade_over_allowance;
+
+    // Back-end gross
+    let fi_product_gross = input.products
+        .iter()
+        .map(|p| p.price - p.cost)
+        .sum::<Decimal>();
+    let dealer_reserve = match structure {
+        DealStructure::Finance(f) => f.dealer_reserve,
+        _ => dec!(0),
+    };
+    let back_end_gross = fi_product_gross + dealer_reserve;
+
+    Some(ProfitAnalysis {
+        vehicle_cost,
+        vehicle_gross,
+        holdback,
+        pack,
+        trade_over_allowance,
+        front_end_gross,
+        fi_product_gross,
+        dealer_reserve,
+        back_end_gross,
+        total_gross: front_end_gross + back_end_gross,
+    })
+}
+
+/// Split lease fees into capitalized and upfront amounts.
+///
+/// Each fee follows its capitalize flag; anything not capitalized is paid
+/// upfront. The acquisition fee is only capitalized when the lessor's
+/// program allows it. The disposition fee is due at lease end and is excluded.
+///
+/// # Returns
+/// `(capitalized_fees, upfront_fees)`
+fn split_lease_fees(
+    fees: &crate::types::DealFees,
+    params: &crate::types::LeaseParams,
+    program_fees_capitalizable: bool,
+) -> (Decimal, Decimal) {
+    let mut capitalized = dec!(0);
+    let mut upfront = dec!(0);
+
+    // Lender fees belong to finance deals and are not part of a lease
+    for item in fees.items().iter().filter(|i| !matches!(i.kind, FeeKind::Bank)) {
+        if is_lease_fee_capitalized(item, &fees.capitalize, params, program_fees_capitalizable) {
+            capitalized += item.amount;
+        } else {
+            upfront += item.amount;
+        }
+    }
+
+    (capitalized, upfront)
+}
+
+/// Itemize the gross cap cost: the agreed value, each capitalized fee and
+/// product, and any tax rolled in under a cap-cost tax mode.
+///
+/// Mirrors [`split_lease_fees`] so the items sum exactly to the gross cap cost.
+fn build_gross_cap_cost_breakdown(
+    normalized: &NormalizedDealInput,
+    params: &crate::types::LeaseParams,
+    program_fees_capitalizable: bool,
+    capitalized_tax: Decimal,
+) -> Vec<CapCostItem> {
+    let input = &normalized.inner;
+    let mut items = Vec::new();
+    let mut push = |label: &str, amount: Decimal| {
+        if !amount.is_zero() {
+            items.push(CapCostItem { label: label.to_string(), amount });
+        }
+    };
+
+    push("Agreed value", input.vehicle_price);
+    push("Destination", normalized.vehicle_destination);
+    for item in input.fees.items().iter().filter(|i| !matches!(i.kind, FeeKind::Bank)) {
+        if is_lease_fee_capitalized(item, &input.fees.capitalize, params, program_fees_capitalizable) {
+            push(&item.name, item.amount);
+        }
+    }
+    for product in &input.products {
+        push(&product.name, product.price);
+    }
+    push("Capitalized tax", capitalized_tax);
+
+    items
+}
+
+/// Whether a lease fee is rolled into the gross cap cost.
+///
+/// Each fee follows its capitalize flag (government fees share one flag);
+/// the acquisition fee also needs the lessor's program to allow it.
+pub fn is_lease_fee_capitalized(
+    item: &FeeItem<'_>,
+    flags: &crate::types::LeaseFeeCapitalization,
+    params: &crate::types::LeaseParams,
+    program_fees_capitalizable: bool,
+) -> bool {
+    match item.kind {
+        FeeKind::Doc => flags.doc_fee,
+        FeeKind::Acquisition => params.cap_acquisition_fee && program_fees_capitalizable,
+        FeeKind::Destination => flags.destination_fee,
+        FeeKind::DealerHandling => flags.dealer_handling_fee,
+        FeeKind::ElectronicFiling => flags.electronic_filing_fee,
+        FeeKind::Title | FeeKind::Registration | FeeKind::Government => flags.government_fees,
+        FeeKind::Other(fee) => fee.capitalize,
+        FeeKind::Bank => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        DealInput, DealFees, FinanceParams, PaymentFrequency, LeaseParams, StateCode,
+        Money, MoneyFactor, Rate, Product, ProductProfile, ProductType, Vehicle, VehicleCondition, DealerProfile,
+    };
+    use crate::phases::p0_normalize::normalize_deal_input;
+    use crate::phases::p1_mode_routing::route_deal;
+    use crate::phases::p2_jurisdiction::{resolve_jurisdiction, JurisdictionResolvedDeal};
+    use crate::phases::p3_profiles::{load_profiles, ProfileLoadedDeal, load_profiles_with_options, load_profiles_with_program};
+    use crate::phases::p4_tax_cipher::{calculate_tax, calculate_tax_with_options};
+    use chrono::NaiveDate;
+
+    /// Run a deal input through P0-P2
+    fn resolved_deal(input: DealInput) -> JurisdictionResolvedDeal {
+        resolve_jurisdiction(route_deal(normalize_deal_input(input).unwrap()).unwrap()).unwrap()
+    }
+
+    /// Run a deal input through P0-P3 with the built-in profiles
+    fn loaded_deal(input: DealInput) -> ProfileLoadedDeal {
+        load_profiles(resolved_deal(input)).unwrap()
+    }
+
+    /// Run a deal input through P0-P4 with the built-in profiles
+    fn taxed_deal(input: DealInput) -> TaxComputedDeal {
+        calculate_tax(loaded_deal(input)).unwrap()
+    }
+
+    /// Run a deal input through P0-P4 with a lender program
+    fn load_with_program(input: DealInput, program: &crate::types::ProgramProfile) -> TaxComputedDeal {
+        calculate_tax(load_profiles_with_program(resolved_deal(input), Some(program)).unwrap()).unwrap()
+    }
+
+    /// Lease program with no rate, residual or mileage tables
+    fn make_lease_program() -> crate::types::LeaseProgram {
+        crate::types::LeaseProgram {
+            money_factors: vec![],
+            residuals: vec![],
+            mileage_options: vec![],
+            security_deposit: Default::default(),
+            msd_config: None,
+            drive_off_rules: Default::default(),
+        }
+    }
+
+    fn make_rebate(name: &str, amount: Decimal, reduces_cap_cost: bool, reduces_tax_basis: bool) -> crate::types::Rebate {
+        crate::types::Rebate {
+            id: "R1".to_string(),
+            name: name.to_string(),
+            amount,
+            rebate_type: crate::types::RebateType::Manufacturer,
+            reduces_tax_basis,
+            reduces_cap_cost,
+            program_code: None,
+        }
+    }
+
+    fn make_finance_deal() -> TaxComputedDeal {
+        taxed_deal(make_finance_input())
+    }
+
+    fn make_finance_input() -> DealInput {
+        DealInput {
+            trade_in_value: Some(dec!(10000)),
+            trade_in_payoff: Some(dec!(5000)),
+            cash_down: dec!(2000),
+            fees: DealFees {
+                doc_fee: Some(dec!(299)),
+                title_fee: dec!(33),
+                registration_fee: dec!(75),
+                ..Default::default()
+            },
+            finance_params: Some(FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(DealType::Finance, dec!(30000), StateCode::TX)
+        }
     }
 
     fn make_lease_deal() -> TaxComputedDeal {
-        let input = DealInput {
-            deal_type: DealType::Lease,
-            vehicle_price: dec!(35000),
+        taxed_deal(make_lease_input())
+    }
+
+    fn make_lease_input() -> DealInput {
+        DealInput {
             trade_in_value: Some(dec!(5000)),
             trade_in_payoff: Some(dec!(0)),
             cash_down: dec!(3000),
-            rebates: vec![],
-            products: vec![],
             fees: DealFees {
-                doc_fee: dec!(299),
+                doc_fee: Some(dec!(299)),
                 acquisition_fee: dec!(595),
                 ..Default::default()
             },
-            home_state: StateCode::TX,
-            transaction_state: StateCode::TX,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: None,
             lease_params: Some(LeaseParams {
-                term_months: 36,
-                money_factor: dec!(0.00125),
-                residual_percent: dec!(0.55),
-                annual_miles: 12000,
                 excess_mileage_rate: Some(dec!(0.25)),
-                lessor_id: None,
-                msd_count: 0,
                 security_deposit: Some(dec!(0)),
-                cap_acquisition_fee: true,
-                cap_cost_reduction: dec!(0),
+                ..LeaseParams::new(36, MoneyFactor::new(dec!(0.00125)), dec!(0.55), 12000)
             }),
-            deal_date: None,
-            first_payment_date: None,
-        };
-
-        let normalized = normalize_deal_input(input).unwrap();
-        let routed = route_deal(normalized).unwrap();
-        let resolved = resolve_jurisdiction(routed).unwrap();
-        let loaded = load_profiles(resolved).unwrap();
-        calculate_tax(loaded).unwrap()
+            ..DealInput::new(DealType::Lease, dec!(35000), StateCode::TX)
+        }
     }
 
     #[test]
@@ -735,6 +2159,358 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_lease_invariants() {
+        let DealStructure::Lease(lease) = build_structure(make_lease_deal()).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        assert!(validate_lease_invariants(&lease).is_ok());
+
+        let mut corrupted = lease.clone();
+        corrupted.total_lease_cost += dec!(5);
+        assert!(matches!(validate_lease_invariants(&corrupted), Err(UdcError::Calculation { .. })));
+
+        // Totals that no longer match the amount due at signing
+        let mut corrupted = lease.clone();
+        corrupted.nonrefundable_due_at_signing += dec!(5);
+        assert!(matches!(validate_lease_invariants(&corrupted), Err(UdcError::Calculation { .. })));
+
+        // Or the payment stream
+        let mut corrupted = lease.clone();
+        corrupted.monthly_tax += dec!(1);
+        assert!(matches!(validate_lease_invariants(&corrupted), Err(UdcError::Calculation { .. })));
+
+        let mut corrupted = lease;
+        corrupted.rent_charge += dec!(100);
+        assert!(matches!(validate_lease_invariants(&corrupted), Err(UdcError::Calculation { .. })));
+    }
+
+    #[test]
+    fn test_lease_fee_paid_upfront() {
+        let capped = build_structure(make_lease_deal()).unwrap();
+
+        let mut input = make_lease_input();
+        input.fees.capitalize.doc_fee = false;
+        let upfront = build_structure(taxed_deal(input)).unwrap();
+
+        let (DealStructure::Lease(capped), DealStructure::Lease(upfront)) =
+            (capped.structure, upfront.structure)
+        else {
+            panic!("Expected lease structures");
+        };
+        assert_eq!(capped.upfront_fees, dec!(0));
+        assert_eq!(capped.capitalized_fees - upfront.capitalized_fees, dec!(299));
+        assert_eq!(upfront.upfront_fees, dec!(299));
+        assert!(upfront.due_at_signing > capped.due_at_signing);
+        assert!(upfront.base_monthly_payment < capped.base_monthly_payment);
+    }
+
+    #[test]
+    fn test_lease_fees_capitalized_unless_flagged_upfront() {
+        let fees: DealFees = serde_json::from_value(serde_json::json!({
+            "dealer_handling_fee": "199.00",
+            "other_fees": [{ "name": "Etch", "amount": "149.00" }]
+        }))
+        .unwrap();
+        let mut input = make_lease_input();
+        input.fees.dealer_handling_fee = fees.dealer_handling_fee;
+        input.fees.other_fees = fees.other_fees;
+        input.fees.capitalize = fees.capitalize;
+
+        let DealStructure::Lease(capped) = build_structure(taxed_deal(input.clone())).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(capped.upfront_fees, dec!(0));
+
+        input.fees.capitalize.dealer_handling_fee = false;
+        input.fees.other_fees[0].capitalize = false;
+        let DealStructure::Lease(upfront) = build_structure(taxed_deal(input)).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(upfront.upfront_fees, dec!(348));
+        assert_eq!(capped.capitalized_fees - upfront.capitalized_fees, dec!(348));
+    }
+
+    #[test]
+    fn test_round_each_step_matches_legacy_payment() {
+        let options = PipelineOptions { rounding: RoundingStrategy::RoundEachStep, ..Default::default() };
+        let loaded = loaded_deal(make_finance_input());
+        let taxed = calculate_tax_with_options(loaded, &options).unwrap();
+        let result = build_structure_with_options(taxed, &options).unwrap();
+
+        let DealStructure::Finance(fin) = result.structure else {
+            panic!("Expected finance structure");
+        };
+        assert_eq!(fin.amount_financed, dec!(25081.67));
+        let legacy = tax_engine_rs::calculate_finance_payment(25081.67, 5.99, 60);
+        assert_eq!(legacy, 484.78);
+        assert_eq!(fin.monthly_payment, dec!(484.78));
+    }
+
+    #[test]
+    fn test_lease_rounding_strategy() {
+        let each_step = build_structure(make_lease_deal()).unwrap();
+        let options = PipelineOptions { rounding: RoundingStrategy::RoundAtEnd, ..Default::default() };
+        let precise = build_structure_with_options(make_lease_deal(), &options).unwrap();
+
+        let (DealStructure::Lease(each_step), DealStructure::Lease(precise)) =
+            (each_step.structure, precise.structure)
+        else {
+            panic!("Expected lease structures");
+        };
+        assert_eq!(
+            each_step.base_monthly_payment,
+            each_step.monthly_depreciation + each_step.monthly_rent_charge
+        );
+        assert!((each_step.base_monthly_payment - precise.base_monthly_payment).abs() <= dec!(0.01));
+    }
+
+    fn make_cross_border_lease(garaging_state: StateCode) -> LeaseStructure {
+        let mut input = make_lease_input();
+        input.home_state = StateCode::NY;
+        input.transaction_state = StateCode::NY;
+        input.garaging_state = Some(garaging_state);
+
+        let result = build_structure(taxed_deal(input)).unwrap();
+        let DealStructure::Lease(lease) = result.structure else {
+            panic!("Expected lease structure");
+        };
+        lease
+    }
+
+    #[test]
+    fn test_cross_border_lease_sourced_to_garaging_state() {
+        // NY dealer, vehicle garaged in NY: NY taxes the cap cost upfront
+        let in_state = make_cross_border_lease(StateCode::NY);
+        assert_eq!(in_state.lease_tax_mode, LeaseTaxMode::CapCostUpfront);
+        assert!(in_state.upfront_tax > dec!(0));
+        assert_eq!(in_state.monthly_tax, dec!(0));
+
+        // Same NY lease garaged in FL: FL taxes each monthly payment
+        let cross_border = make_cross_border_lease(StateCode::FL);
+        assert_eq!(cross_border.lease_tax_mode, LeaseTaxMode::MonthlyPayment);
+        assert_eq!(cross_border.upfront_tax, dec!(0));
+        assert!(cross_border.monthly_tax > dec!(0));
+        assert!(cross_border.due_at_signing < in_state.due_at_signing);
+    }
+
+    #[test]
+    fn test_cross_border_lease_not_sourced() {
+        // TX does not source lease tax to garaging, so NY rules still apply
+        let lease = make_cross_border_lease(StateCode::TX);
+        assert_eq!(lease.lease_tax_mode, LeaseTaxMode::CapCostUpfront);
+        assert!(lease.upfront_tax > dec!(0));
+    }
+
+    fn make_state_lease(state: StateCode) -> LeaseStructure {
+        let mut input = make_lease_input();
+        input.home_state = state;
+        input.transaction_state = state;
+
+        let result = build_structure(taxed_deal(input)).unwrap();
+        let DealStructure::Lease(lease) = result.structure else {
+            panic!("Expected lease structure");
+        };
+        lease
+    }
+
+    #[test]
+    fn test_georgia_lease_tavt() {
+        let mut input = make_lease_input();
+        input.home_state = StateCode::GA;
+        input.transaction_state = StateCode::GA;
+        let result = build_structure(taxed_deal(input)).unwrap();
+        let DealStructure::Lease(lease) = &result.structure else {
+            panic!("Expected lease structure");
+        };
+
+        // 6.75% of the base payments plus the $3,000 cash + $5,000 trade down
+        let basis = lease.total_base_payments + dec!(8000);
+        let tavt = lease.special_tax.as_ref().expect("lease TAVT");
+        assert_eq!(tavt.tax_type, TaxType::Tavt);
+        assert_eq!(tavt.base, basis);
+        assert_eq!(tavt.amount, (basis * dec!(0.0675)).round_dp(2));
+        assert_eq!(lease.upfront_tax, tavt.amount);
+        assert_eq!(lease.monthly_tax, dec!(0));
+        assert_eq!(lease.total_tax, tavt.amount);
+
+        // The deal's tax is the lease TAVT, not TAVT on the selling price
+        let tax = &result.deal.tax;
+        assert_eq!(tax.special_tax.as_ref().map(|t| t.tax_type), Some(TaxType::Tavt));
+        assert_eq!(tax.net_tax, tavt.amount);
+        assert_eq!(tax.tax_base, basis);
+
+        let mut retail = make_finance_input();
+        retail.home_state = StateCode::GA;
+        retail.transaction_state = StateCode::GA;
+        let retail_tax = taxed_deal(retail).tax;
+        let retail_tavt = retail_tax.special_tax.expect("retail TAVT");
+        assert_eq!(retail_tavt.base, retail_tax.tax_base);
+        assert_ne!(retail_tavt.amount, tavt.amount);
+    }
+
+    #[test]
+    fn test_end_of_lease_options() {
+        let mut input = make_lease_input();
+        input.fees.disposition_fee = dec!(395);
+        if let Some(params) = input.lease_params.as_mut() {
+            params.estimated_annual_miles = Some(15000);
+            params.purchase_option_fee = Some(dec!(300));
+        }
+        let result = build_structure(taxed_deal(input)).unwrap();
+        let DealStructure::Lease(lease) = &result.structure else {
+            panic!("Expected lease structure");
+        };
+        let end = &lease.end_of_lease;
+
+        // 3,000 miles a year over the 12,000 allowance for 3 years at $0.25
+        assert_eq!(end.estimated_excess_miles, 9000);
+        assert_eq!(end.excess_mileage_charge, dec!(2250));
+        assert_eq!(end.excess_wear_charge, dec!(0));
+        assert_eq!(end.return_cost, dec!(395) + dec!(2250));
+
+        let rate = buyout_tax_rate(result.deal.deal.profiles.lease_tax_rules());
+        assert!(rate > dec!(0));
+        assert_eq!(end.buyout_tax, ((lease.residual_value + dec!(300)) * rate).round_dp(2));
+        assert_eq!(end.buyout_cost, lease.residual_value + dec!(300) + end.buyout_tax);
+
+        // Driving within the allowance leaves only the disposition fee
+        let DealStructure::Lease(within) = build_structure(make_lease_deal()).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(within.end_of_lease.estimated_excess_miles, 0);
+        assert_eq!(within.end_of_lease.return_cost, dec!(0));
+        assert_eq!(within.end_of_lease.purchase_option_fee, dec!(0));
+    }
+
+    #[test]
+    fn test_lease_cap_reduction_taxed_upfront() {
+        // CA taxes the $3,000 cash + $5,000 trade down at signing: 8000 * 8.25%
+        let lease = make_state_lease(StateCode::CA);
+        assert_eq!(lease.total_cap_reduction, dec!(8000));
+        assert_eq!(lease.upfront_tax, dec!(660.00));
+        assert!(lease.monthly_tax > dec!(0));
+        assert_eq!(lease.total_tax, lease.monthly_tax * dec!(36) + dec!(660.00));
+        assert_eq!(
+            lease.due_at_signing,
+            lease.first_payment + lease.cash_down + lease.upfront_fees + dec!(660.00)
+        );
+    }
+
+    #[test]
+    fn test_lease_cap_reduction_not_taxed() {
+        // FL taxes only the monthly payment
+        let lease = make_state_lease(StateCode::FL);
+        assert_eq!(lease.upfront_tax, dec!(0));
+        assert!(lease.monthly_tax > dec!(0));
+        assert_eq!(lease.due_at_signing, lease.first_payment + lease.cash_down + lease.upfront_fees);
+    }
+
+    fn make_high_residual_deal() -> TaxComputedDeal {
+        // MSRP $30k at 90% residual ($27,000) against a $25k selling price
+        let mut input = make_lease_input();
+        input.vehicle_price = dec!(25000);
+        input.trade_in_value = None;
+        input.cash_down = dec!(0);
+        input.lease_params.as_mut().unwrap().residual_percent = dec!(0.90);
+        input.vehicle = Some(Vehicle {
+            vin: "1HGCM82633A004352".to_string(),
+            year: 2024,
+            make: "Honda".to_string(),
+            model: "Accord".to_string(),
+            trim: None,
+            condition: VehicleCondition::New,
+            msrp: Money::new(dec!(30000)),
+            invoice: None,
+            selling_price: Money::new(dec!(25000)),
+            odometer: 10,
+            destination_fee: None,
+        });
+
+        taxed_deal(input)
+    }
+
+    #[test]
+    fn test_residual_exceeding_cap_cost_warns_and_clamps() {
+        let result = build_structure(make_high_residual_deal()).unwrap();
+        assert!(result.warnings.iter().any(|w| w.code == "RESIDUAL_EXCEEDS_CAP_COST"));
+
+        let DealStructure::Lease(lease) = result.structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(lease.residual_value, dec!(27000));
+        assert!(lease.adjusted_cap_cost < lease.residual_value);
+        assert_eq!(lease.depreciation, dec!(0));
+        assert_eq!(lease.monthly_depreciation, dec!(0));
+        // Only the rent charge remains
+        assert_eq!(lease.base_monthly_payment, lease.monthly_rent_charge);
+    }
+
+    #[test]
+    fn test_residual_exceeding_cap_cost_strict() {
+        let options = PipelineOptions { strict_validation: true, ..Default::default() };
+        let result = build_structure_with_options(make_high_residual_deal(), &options);
+        assert!(matches!(result, Err(UdcError::Validation { .. })));
+    }
+
+    #[test]
+    fn test_combined_rate_override_reaches_lease_tax() {
+        let overridden = |state: StateCode, input: DealInput| {
+            let mut input = input;
+            input.home_state = state;
+            input.transaction_state = state;
+            input.override_combined_rate = Some(Rate::from_decimal(dec!(0.05)));
+            build_structure(taxed_deal(input)).unwrap()
+        };
+
+        // TX taxes each payment at the override, not the 8.25% table rate
+        let result = overridden(StateCode::TX, make_lease_input());
+        let DealStructure::Lease(lease) = &result.structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(lease.monthly_tax, (lease.taxable_monthly_payment * dec!(0.05)).round_dp(2));
+
+        // GA lease TAVT at the override, not 6.75%
+        let result = overridden(StateCode::GA, make_lease_input());
+        let DealStructure::Lease(lease) = &result.structure else {
+            panic!("Expected lease structure");
+        };
+        let tavt = lease.special_tax.as_ref().expect("lease TAVT");
+        assert_eq!(tavt.rate, dec!(0.05));
+        assert_eq!(tavt.amount, (tavt.base * dec!(0.05)).round_dp(2));
+        assert_eq!(result.deal.tax.effective_rate, dec!(0.05));
+
+        // NC HUT on a retail deal, not 3%
+        let result = overridden(StateCode::NC, make_finance_input());
+        let hut = result.deal.tax.special_tax.as_ref().expect("HUT");
+        assert_eq!(hut.tax_type, TaxType::Hut);
+        assert_eq!(hut.rate, dec!(0.05));
+        assert_eq!(result.deal.tax.net_tax, (hut.base * dec!(0.05)).round_dp(2));
+    }
+
+    #[test]
+    fn test_residual_msrp_precedence() {
+        let residual_for = |input: DealInput| {
+            let DealStructure::Lease(lease) = build_structure(taxed_deal(input)).unwrap().structure else {
+                panic!("Expected lease structure");
+            };
+            (lease.msrp, lease.residual_value)
+        };
+
+        // No MSRP anywhere: the selling price stands in
+        let input = make_lease_input();
+        assert_eq!(residual_for(input.clone()), (dec!(35000), dec!(19250)));
+
+        // The vehicle's MSRP
+        let mut with_vehicle = input;
+        with_vehicle.vehicle = make_high_residual_deal().deal.deal.deal.input.inner.vehicle;
+        assert_eq!(residual_for(with_vehicle.clone()), (dec!(30000), dec!(16500)));
+
+        // An explicit lease MSRP wins over both
+        with_vehicle.lease_params.as_mut().unwrap().msrp = Some(dec!(36000));
+        assert_eq!(residual_for(with_vehicle), (dec!(36000), dec!(19800)));
+    }
+
     #[test]
     fn test_money_factor_to_apr() {
         // MF 0.00125 = 3% APR
@@ -742,4 +2518,1561 @@ mod tests {
         let apr = mf * dec!(2400);
         assert_eq!(apr, dec!(3));
     }
+
+    fn make_profit_deal(
+        invoice: Option<Decimal>,
+        dealer_profile: Option<DealerProfile>,
+    ) -> TaxComputedDeal {
+        tax_profit_input(make_profit_input(invoice, dealer_profile), true)
+    }
+
+    fn make_profit_input(
+        invoice: Option<Decimal>,
+        dealer_profile: Option<DealerProfile>,
+    ) -> DealInput {
+        DealInput {
+            cash_down: dec!(3000),
+            products: vec![
+                Product {
+                    id: "vsc".to_string(),
+                    name: "Service Contract".to_string(),
+                    price: dec!(2500),
+                    cost: dec!(1200),
+                    product_type: ProductType::Vsc,
+                    term_months: Some(60),
+                    mileage_limit: Some(75000),
+                    deductible: Some(dec!(100)),
+                    taxable: false,
+                },
+                Product {
+                    id: "gap".to_string(),
+                    name: "GAP".to_string(),
+                    price: dec!(895),
+                    cost: dec!(300),
+                    product_type: ProductType::Gap,
+                    term_months: None,
+                    mileage_limit: None,
+                    deductible: None,
+                    taxable: false,
+                },
+            ],
+            finance_params: Some(FinanceParams {
+                buy_rate: Some(dec!(0.0499)),
+                ..FinanceParams::new(60, dec!(0.0699))
+            }),
+            vehicle: Some(Vehicle {
+                vin: "1HGCM82633A004352".to_string(),
+                year: 2024,
+                make: "Honda".to_string(),
+                model: "Accord".to_string(),
+                trim: None,
+                condition: VehicleCondition::New,
+                msrp: Money::new(dec!(32000)),
+                invoice: invoice.map(Money::new),
+                selling_price: Money::new(dec!(30000)),
+                odometer: 10,
+                destination_fee: None,
+            }),
+            dealer_profile,
+            ..DealInput::new(DealType::Finance, dec!(30000), StateCode::TX)
+        }
+    }
+
+    fn tax_profit_input(input: DealInput, destination_taxable: bool) -> TaxComputedDeal {
+        let mut loaded = loaded_deal(input);
+        loaded.profiles.primary_rules.base_rules.destination_taxable = destination_taxable;
+        calculate_tax(loaded).unwrap()
+    }
+
+    #[test]
+    fn test_vehicle_destination_priced_taxed_and_costed() {
+        let with_destination = |destination_taxable: bool| {
+            let mut input = make_profit_input(Some(dec!(28500)), None);
+            input.vehicle.as_mut().unwrap().destination_fee = Some(Money::new(dec!(1395)));
+            tax_profit_input(input, destination_taxable)
+        };
+        let baseline = make_profit_deal(Some(dec!(28500)), None);
+
+        // Destination is part of the selling price and taxed with it
+        let taxed = with_destination(true);
+        let normalized = &taxed.deal.deal.deal.input;
+        assert_eq!(normalized.inner.vehicle_price, dec!(30000));
+        assert_eq!(normalized.selling_price, dec!(31395));
+        assert_eq!(normalized.inner.fees.charged_destination_fee(), dec!(0));
+        assert_eq!(taxed.tax.tax_base, baseline.tax.tax_base + dec!(1395));
+
+        // Where destination is not taxable it is backed out of the base
+        let untaxed = with_destination(false);
+        assert_eq!(untaxed.tax.tax_base, baseline.tax.tax_base);
+
+        // It rides on the invoice side too, so front-end gross is unchanged
+        let structured = build_structure(taxed).unwrap();
+        let DealStructure::Finance(ref finance) = structured.structure else {
+            panic!("Expected finance structure");
+        };
+        assert_eq!(finance.selling_price, dec!(31395));
+        assert!(finance.amount_financed_breakdown.iter().any(|i| i.label == "Destination" && i.amount == dec!(1395)));
+        let profit = structured.profit.expect("profit analysis");
+        let baseline = build_structure(baseline).unwrap().profit.expect("profit analysis");
+        assert_eq!(profit.vehicle_cost, dec!(28500) + dec!(1395));
+        assert_eq!(profit.front_end_gross, baseline.front_end_gross);
+    }
+
+    #[test]
+    fn test_destination_fee_included_in_price_is_costed() {
+        // The same charge given as a fee already in the vehicle price
+        let mut input = make_profit_input(Some(dec!(28500)), None);
+        input.vehicle_price = dec!(31395);
+        input.fees.destination_fee = dec!(1395);
+        input.fees.destination_included_in_price = true;
+        let included = build_structure(tax_profit_input(input, true)).unwrap().profit.expect("profit analysis");
+
+        let mut input = make_profit_input(Some(dec!(28500)), None);
+        input.vehicle.as_mut().unwrap().destination_fee = Some(Money::new(dec!(1395)));
+        let on_vehicle = build_structure(tax_profit_input(input, true)).unwrap().profit.expect("profit analysis");
+
+        assert_eq!(included.vehicle_cost, dec!(28500) + dec!(1395));
+        assert_eq!(included.vehicle_gross, on_vehicle.vehicle_gross);
+        assert_eq!(included.front_end_gross, on_vehicle.front_end_gross);
+    }
+
+    #[test]
+    fn test_profit_front_end_gross() {
+        let result = build_structure(make_profit_deal(Some(dec!(28500)), None)).unwrap();
+        let profit = result.profit.expect("profit analysis");
+
+        assert_eq!(profit.vehicle_cost, dec!(28500));
+        assert_eq!(profit.front_end_gross, dec!(30000) - dec!(28500));
+    }
+
+    #[test]
+    fn test_profit_estimated_invoice() {
+        let result = build_structure(make_profit_deal(None, None)).unwrap();
+        let profit = result.profit.expect("profit analysis");
+
+        // 32000 * 0.92
+        assert_eq!(profit.vehicle_cost, dec!(29440));
+    }
+
+    #[test]
+    fn test_profit_back_end_gross() {
+        let result = build_structure(make_profit_deal(Some(dec!(28500)), None)).unwrap();
+        let profit = result.profit.expect("profit analysis");
+
+        // Product gross: (2500 - 1200) + (895 - 300)
+        assert_eq!(profit.fi_product_gross, dec!(1895));
+        assert!(profit.dealer_reserve > dec!(0));
+        assert_eq!(profit.back_end_gross, dec!(1895) + profit.dealer_reserve);
+        assert_eq!(profit.total_gross, profit.front_end_gross + profit.back_end_gross);
+    }
+
+    #[test]
+    fn test_no_profit_without_vehicle() {
+        let result = build_structure(make_finance_deal()).unwrap();
+        assert!(result.profit.is_none());
+    }
+
+    #[test]
+    fn test_profit_pack_lowers_gross() {
+        let dealer = DealerProfile {
+            pack: Money::new(dec!(500)),
+            ..Default::default()
+        };
+        let base = build_structure(make_profit_deal(Some(dec!(28500)), None)).unwrap()
+            .profit.expect("profit analysis");
+        let packed = build_structure(make_profit_deal(Some(dec!(28500)), Some(dealer))).unwrap()
+            .profit.expect("profit analysis");
+
+        assert_eq!(packed.pack, dec!(500));
+        assert_eq!(packed.front_end_gross, base.front_end_gross - dec!(500));
+        assert_eq!(packed.total_gross, base.total_gross - dec!(500));
+    }
+
+    #[test]
+    fn test_profit_trade_over_allowance() {
+        let with_trade = |allowance: Decimal, acv: Decimal| {
+            let mut deal = make_profit_deal(Some(dec!(28500)), None);
+            let input = &mut deal.deal.deal.deal.input.inner;
+            input.trade_in_value = Some(allowance);
+            input.trade_in_acv = Some(acv);
+            build_structure(deal).unwrap().profit.expect("profit analysis")
+        };
+        let base = build_structure(make_profit_deal(Some(dec!(28500)), None)).unwrap()
+            .profit.expect("profit analysis");
+
+        // $2,000 allowed over the trade's ACV comes out of the vehicle gross
+        let over = with_trade(dec!(12000), dec!(10000));
+        assert_eq!(over.trade_over_allowance, dec!(2000));
+        assert_eq!(over.front_end_gross, base.front_end_gross - dec!(2000));
+        assert_eq!(over.total_gross, base.total_gross - dec!(2000));
+
+        // A fair allowance leaves gross unchanged
+        let fair = with_trade(dec!(10000), dec!(10000));
+        assert_eq!(fair.trade_over_allowance, dec!(0));
+        assert_eq!(fair.front_end_gross, base.front_end_gross);
+    }
+
+    #[test]
+    fn test_profit_holdback_raises_gross() {
+        let dealer = DealerProfile {
+            holdback_percent: Rate::from_percentage(dec!(3)),
+            ..Default::default()
+        };
+        let base = build_structure(make_profit_deal(Some(dec!(28500)), None)).unwrap()
+            .profit.expect("profit analysis");
+        let held = build_structure(make_profit_deal(Some(dec!(28500)), Some(dealer))).unwrap()
+            .profit.expect("profit analysis");
+
+        // 3% of 32000 MSRP
+        assert_eq!(held.holdback, dec!(960));
+        assert_eq!(held.total_gross, base.total_gross + dec!(960));
+    }
+
+    #[test]
+    fn test_amount_financed_breakdown_reconciles() {
+        let result = build_structure(make_finance_deal()).unwrap();
+        let DealStructure::Finance(finance) = result.structure else {
+            panic!("Expected finance structure");
+        };
+
+        // TX: 8.25% on the price less the $10k trade allowance, plus the doc fee
+        let tax = ((dec!(30000) - dec!(10000) + dec!(299)) * dec!(0.0825)).round_dp(2);
+        let expected = vec![
+            ("Vehicle price", dec!(30000)),
+            ("Doc fee", dec!(299)),
+            ("Title fee", dec!(33)),
+            ("Registration fee", dec!(75)),
+            ("Sales tax", tax),
+            ("Cash down", dec!(-2000)),
+            ("Trade-in equity", dec!(-5000)),
+        ];
+        let items: Vec<(&str, Decimal)> = finance.amount_financed_breakdown
+            .iter()
+            .map(|i| (i.label.as_str(), i.amount))
+            .collect();
+        assert_eq!(items, expected);
+        assert_eq!(finance.amount_financed, dec!(23407) + tax);
+    }
+
+    #[test]
+    fn test_amount_financed_breakdown_rejects_unitemized_amount() {
+        let normalized = normalize_deal_input(make_finance_input()).unwrap();
+
+        // A cent of rounding is itemized as such
+        let items = build_amount_financed_breakdown(&normalized, dec!(0), dec!(0), dec!(0), dec!(0), dec!(0), dec!(28407.01))
+            .unwrap();
+        assert_eq!(items.last().map(|i| (i.label.as_str(), i.amount)), Some(("Rounding", dec!(0.01))));
+
+        // More than that means a component is missing from the items
+        let err = build_amount_financed_breakdown(&normalized, dec!(0), dec!(0), dec!(0), dec!(0), dec!(0), dec!(28802))
+            .unwrap_err();
+        assert!(err.to_string().contains("line items total 28407"));
+    }
+
+    #[test]
+    fn test_amount_financed_breakdown_excess_credit() {
+        let mut input = make_finance_input();
+        input.cash_down = dec!(40000);
+        let DealStructure::Finance(finance) = build_structure(taxed_deal(input)).unwrap().structure else {
+            panic!("Expected finance structure");
+        };
+
+        assert_eq!(finance.amount_financed, dec!(0));
+        let excess = finance.amount_financed_breakdown.last().unwrap();
+        assert_eq!(excess.label, "Excess credit owed to customer");
+        assert_eq!(excess.amount, finance.excess_credit);
+    }
+
+    fn make_finance_tax_deal(tax_monthly: bool) -> FinanceStructure {
+        let mut loaded = loaded_deal(make_finance_input());
+        loaded.profiles.primary_rules.finance_tax_monthly = tax_monthly;
+
+        match build_structure(calculate_tax(loaded).unwrap()).unwrap().structure {
+            DealStructure::Finance(finance) => finance,
+            _ => panic!("Expected finance structure"),
+        }
+    }
+
+    #[test]
+    fn test_finance_tax_collected_monthly() {
+        let upfront = make_finance_tax_deal(false);
+        let monthly = make_finance_tax_deal(true);
+        let tax = upfront.sales_tax;
+        assert!(tax > dec!(0));
+
+        // Upfront: tax is in the loan and the payment already covers it
+        assert_eq!(upfront.monthly_tax, dec!(0));
+        assert_eq!(upfront.payment_with_tax, upfront.monthly_payment);
+
+        // Monthly: the loan is smaller and the tax rides on each payment
+        assert_eq!(monthly.sales_tax, tax);
+        assert_eq!(monthly.amount_financed, upfront.amount_financed - tax);
+        assert!(monthly.monthly_payment < upfront.monthly_payment);
+        assert_eq!(monthly.monthly_tax, (tax / dec!(60)).round_dp(2));
+        assert_eq!(monthly.payment_with_tax, monthly.monthly_payment + monthly.monthly_tax);
+        // The installments collect exactly the sales tax
+        assert_eq!(monthly.monthly_tax * dec!(59) + monthly.final_monthly_tax, tax);
+        assert!((monthly.final_monthly_tax - monthly.monthly_tax).abs() <= dec!(0.30));
+        assert_eq!(upfront.final_monthly_tax, dec!(0));
+
+        let breakdown: Decimal = monthly.amount_financed_breakdown.iter().map(|i| i.amount).sum();
+        assert_eq!(breakdown, monthly.amount_financed);
+        assert_eq!(
+            monthly.total_sale_price,
+            monthly.amount_financed + monthly.finance_charge + monthly.cash_down + tax
+        );
+    }
+
+    fn make_split_funding_deal(allocations: Vec<crate::types::FundingAllocation>) -> TaxComputedDeal {
+        let mut input = make_finance_input();
+        // $3,000 of negative equity and a $2,500 service contract
+        input.trade_in_value = Some(dec!(8000));
+        input.trade_in_payoff = Some(dec!(11000));
+        input.products = vec![Product {
+            id: "vsc".to_string(),
+            name: "Service Contract".to_string(),
+            price: dec!(2500),
+            cost: dec!(1200),
+            product_type: ProductType::Vsc,
+            term_months: Some(60),
+            mileage_limit: Some(75000),
+            deductible: Some(dec!(100)),
+            taxable: false,
+        }];
+        let params = input.finance_params.as_mut().unwrap();
+        params.lender_id = Some("ALLY".to_string());
+        params.funding_allocations = allocations;
+
+        taxed_deal(input)
+    }
+
+    #[test]
+    fn test_funding_splits_sum_to_amount_financed() {
+        use crate::types::FundingAllocation;
+
+        let deal = make_split_funding_deal(vec![
+            FundingAllocation {
+                lender_id: "PRODUCT-CU".to_string(),
+                portion: FundingPortion::FiProducts,
+                amount: None,
+                apr: Some(dec!(0.0899)),
+                term_months: Some(48),
+            },
+            FundingAllocation {
+                lender_id: "EQUITY-BANK".to_string(),
+                portion: FundingPortion::NegativeEquity,
+                amount: None,
+                apr: None,
+                term_months: None,
+            },
+        ]);
+        let DealStructure::Finance(finance) = build_structure(deal).unwrap().structure else {
+            panic!("Expected finance structure");
+        };
+
+        let splits = &finance.funding_splits;
+        assert_eq!(splits.len(), 3);
+        let total: Decimal = splits.iter().map(|s| s.amount).sum();
+        assert_eq!(total, finance.amount_financed);
+
+        // Primary lender funds the vehicle at the deal rate and term
+        assert_eq!(splits[0].lender_id.as_deref(), Some("ALLY"));
+        assert_eq!(splits[0].portion, FundingPortion::Vehicle);
+        assert_eq!(splits[0].amount, finance.amount_financed - dec!(2500) - dec!(3000));
+        assert_eq!((splits[0].apr, splits[0].term_months), (dec!(0.0599), 60));
+
+        // Product loan carries its own rate and term
+        assert_eq!(splits[1].amount, dec!(2500));
+        assert_eq!((splits[1].apr, splits[1].term_months), (dec!(0.0899), 48));
+
+        // Negative equity lender inherits the deal rate and term
+        assert_eq!(splits[2].amount, dec!(3000));
+        assert_eq!((splits[2].apr, splits[2].term_months), (dec!(0.0599), 60));
+
+        // A capped product loan and a fixed second lien: the primary lender
+        // keeps the uncovered product balance and the rest of the vehicle
+        let deal = make_split_funding_deal(vec![
+            FundingAllocation {
+                lender_id: "PRODUCT-CU".to_string(),
+                portion: FundingPortion::FiProducts,
+                amount: Some(dec!(1000)),
+                apr: None,
+                term_months: None,
+            },
+            FundingAllocation {
+                lender_id: "SECOND".to_string(),
+                portion: FundingPortion::Fixed,
+                amount: Some(dec!(4000.55)),
+                apr: None,
+                term_months: None,
+            },
+        ]);
+        let DealStructure::Finance(capped) = build_structure(deal).unwrap().structure else {
+            panic!("Expected finance structure");
+        };
+        let splits = &capped.funding_splits;
+        let total: Decimal = splits.iter().map(|s| s.amount).sum();
+        assert_eq!(total, capped.amount_financed);
+        assert_eq!(splits[1].amount, dec!(1000));
+        assert_eq!(splits[2].amount, dec!(4000.55));
+        assert_eq!(splits[0].amount, capped.amount_financed - dec!(1000) - dec!(4000.55));
+
+        // Single-lender deals report no splits
+        let single = build_structure(make_finance_deal()).unwrap().structure;
+        assert!(matches!(single, DealStructure::Finance(f) if f.funding_splits.is_empty()));
+    }
+
+    #[test]
+    fn test_funding_splits_validation() {
+        use crate::types::FundingAllocation;
+
+        let fixed = |amount: Option<Decimal>| FundingAllocation {
+            lender_id: "SECOND".to_string(),
+            portion: FundingPortion::Fixed,
+            amount,
+            apr: None,
+            term_months: None,
+        };
+
+        // Fixed allocations need an amount
+        let result = build_structure(make_split_funding_deal(vec![fixed(None)]));
+        assert!(matches!(result, Err(UdcError::Validation { .. })));
+
+        // Allocations cannot exceed the amount financed
+        let result = build_structure(make_split_funding_deal(vec![fixed(Some(dec!(1000000)))]));
+        assert!(matches!(result, Err(UdcError::Validation { .. })));
+    }
+
+    #[test]
+    fn test_semi_monthly_finance_charge() {
+        // $20,000 at 6% over two years
+        let (monthly, monthly_total, monthly_charge) =
+            calculate_periodic_payment(dec!(20000), dec!(0.06), 24, 12).unwrap();
+        let frequency = PaymentFrequency::SemiMonthly;
+        let n = frequency.number_of_payments(24);
+        let (semi, semi_total, semi_charge) =
+            calculate_periodic_payment(dec!(20000), dec!(0.06), n, frequency.periods_per_year()).unwrap();
+
+        assert_eq!(n, 48);
+        assert_eq!(monthly_total, monthly * dec!(24));
+        assert_eq!(semi_total, semi * dec!(48));
+        assert!(semi < monthly / dec!(2) + dec!(1));
+        // Paying twice a month retires principal sooner
+        assert!(semi_charge < monthly_charge,
+            "Semi-monthly charge {} should be below monthly {}", semi_charge, monthly_charge);
+    }
+
+    #[test]
+    fn test_quarterly_payment_count() {
+        assert_eq!(PaymentFrequency::Quarterly.number_of_payments(60), 20);
+        assert_eq!(PaymentFrequency::Quarterly.number_of_payments(62), 21);
+        assert_eq!(PaymentFrequency::BiWeekly.number_of_payments(60), 130);
+    }
+
+    fn make_program(
+        special_rates: Vec<crate::types::SpecialRate>,
+        lease_program: Option<crate::types::LeaseProgram>,
+    ) -> crate::types::ProgramProfile {
+        use crate::types::{
+            ProgramProfile, RateSheet, StructureRules, ProgramFeeConfig, EligibilityRules, ProgramMeta,
+        };
+
+        ProgramProfile {
+            id: "captive-2024".to_string(),
+            name: "Captive Finance".to_string(),
+            lender_code: "CAPTIVE".to_string(),
+            program_type: DealType::Finance,
+            is_captive: true,
+            brand: None,
+            rate_sheet: RateSheet {
+                effective_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                expiration_date: None,
+                tiers: vec![],
+                reserve_caps: vec![],
+                default_max_reserve: dec!(0),
+                special_rates,
+            },
+            structure_rules: StructureRules::default(),
+            fee_config: ProgramFeeConfig::default(),
+            eligibility: EligibilityRules::default(),
+            lease_program,
+            meta: ProgramMeta::default(),
+        }
+    }
+
+    #[test]
+    fn test_credit_score_selects_tier_buy_rate() {
+        use crate::types::{CreditTier, TermRate, TierRates};
+
+        let term_rate = |rate: Decimal| vec![TermRate { min_term: 36, max_term: 72, rate, promotional: false }];
+        let mut program = make_program(vec![], None);
+        program.rate_sheet.tiers = vec![
+            TierRates { tier: CreditTier::Tier1, score_range: None, rates: term_rate(dec!(0.0399)) },
+            TierRates { tier: CreditTier::Tier2, score_range: None, rates: term_rate(dec!(0.0499)) },
+        ];
+
+        let mut input = make_finance_input();
+        input.customer.credit_score = Some(710);
+        let resolved = resolved_deal(input);
+        let loaded = load_profiles_with_program(resolved, Some(&program)).unwrap();
+        assert_eq!(loaded.profiles.credit_tier, Some(CreditTier::Tier2));
+
+        let result = build_structure(calculate_tax(loaded).unwrap()).unwrap();
+        let DealStructure::Finance(fin) = result.structure else {
+            panic!("Expected finance structure");
+        };
+        assert_eq!(fin.buy_rate, Some(dec!(0.0499)));
+        assert!(fin.dealer_reserve > dec!(0), "5.99% contract over a 4.99% buy rate");
+    }
+
+    fn make_subvention_program(code: &str, end_date: NaiveDate) -> crate::types::ProgramProfile {
+        let special = crate::types::SpecialRate {
+            code: code.to_string(),
+            description: "0.9% APR promotional financing".to_string(),
+            rate: dec!(0.009),
+            money_factor: None,
+            required_tiers: vec![],
+            vehicle_requirements: vec![],
+            start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            end_date,
+            allows_reserve: false,
+            max_reserve_with_special: None,
+        };
+        make_program(vec![special], None)
+    }
+
+    fn make_subvented_deal(end_date: NaiveDate) -> TaxComputedDeal {
+        let mut input = make_finance_input();
+        input.deal_date = NaiveDate::from_ymd_opt(2024, 6, 15);
+        input.finance_params.as_mut().unwrap().program_code = Some("PROMO09".to_string());
+        input.finance_params.as_mut().unwrap().buy_rate = Some(dec!(0.0499));
+
+        let program = make_subvention_program("PROMO09", end_date);
+        load_with_program(input, &program)
+    }
+
+    #[test]
+    fn test_subvented_rate_applied() {
+        let deal = make_subvented_deal(NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+        assert!(!deal.deal.profiles.warnings.iter().any(|w| w.code.starts_with("PROGRAM_")));
+
+        let result = build_structure(deal).unwrap();
+        let DealStructure::Finance(fin) = result.structure else {
+            panic!("Expected finance structure");
+        };
+        assert_eq!(fin.apr, dec!(0.009));
+        assert_eq!(fin.dealer_reserve, dec!(0));
+    }
+
+    #[test]
+    fn test_expired_program_falls_back_to_standard_rate() {
+        let deal = make_subvented_deal(NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+        assert!(deal.deal.profiles.warnings.iter().any(|w| w.code == "PROGRAM_INELIGIBLE"));
+
+        let result = build_structure(deal).unwrap();
+        let DealStructure::Finance(fin) = result.structure else {
+            panic!("Expected finance structure");
+        };
+        assert_eq!(fin.apr, dec!(0.0599));
+        assert!(fin.dealer_reserve > dec!(0));
+    }
+
+    fn make_marked_up_lease(money_factor: Decimal, msd_count: u8) -> StructuredDeal {
+        make_msd_lease(money_factor, msd_count, false)
+    }
+
+    fn make_msd_lease(money_factor: Decimal, msd_count: u8, apply_msd_reduction: bool) -> StructuredDeal {
+        use crate::types::{LeaseProgram, TierMoneyFactor, MsdConfig, CreditTier};
+
+        let lease_program = LeaseProgram {
+            money_factors: vec![TierMoneyFactor {
+                tier: CreditTier::Tier1,
+                money_factor: dec!(0.00125),
+                max_markup: Some(dec!(0.0004)),
+            }],
+            msd_config: Some(MsdConfig {
+                available: true,
+                max_count: 7,
+                mf_reduction_per_msd: dec!(0.00007),
+                amount_rounding: Default::default(),
+            }),
+            ..make_lease_program()
+        };
+        let program = make_program(vec![], Some(lease_program));
+
+        let mut input = make_lease_input();
+        input.customer.credit_tier = Some(crate::types::deal_input::CreditTier::Tier1);
+        let params = input.lease_params.as_mut().unwrap();
+        params.money_factor = MoneyFactor::new(money_factor);
+        params.msd_count = msd_count;
+        params.apply_msd_reduction = apply_msd_reduction;
+
+        build_structure(load_with_program(input, &program)).unwrap()
+    }
+
+    fn make_mileage_lease(selected_mileage: u32) -> LeaseStructure {
+        use crate::types::{LeaseProgram, MileageOption};
+
+        let option = |annual_miles, excess_rate, residual_adjustment| MileageOption {
+            annual_miles,
+            excess_rate,
+            residual_adjustment,
+        };
+        let lease_program = LeaseProgram {
+            mileage_options: vec![
+                option(10000, dec!(0.25), dec!(0.01)),
+                option(12000, dec!(0.25), dec!(0)),
+                option(15000, dec!(0.20), dec!(-0.02)),
+            ],
+            ..make_lease_program()
+        };
+        let program = make_program(vec![], Some(lease_program));
+
+        let mut input = make_lease_input();
+        input.lease_params.as_mut().unwrap().selected_mileage = Some(selected_mileage);
+
+        let DealStructure::Lease(lease) = build_structure(load_with_program(input, &program)).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        lease
+    }
+
+    #[test]
+    fn test_mileage_tier_adjusts_residual() {
+        let low = make_mileage_lease(10000);
+        let high = make_mileage_lease(15000);
+
+        // Base residual 55%: +1% at 10k, -2% at 15k
+        assert_eq!(low.residual_percentage, dec!(0.56));
+        assert_eq!(high.residual_percentage, dec!(0.53));
+        assert!(high.residual_value < low.residual_value);
+        assert!(high.base_monthly_payment > low.base_monthly_payment);
+
+        assert_eq!(high.annual_miles, 15000);
+        assert_eq!(high.excess_mileage_rate, Some(dec!(0.20)));
+        assert_eq!(low.excess_mileage_rate, Some(dec!(0.25)));
+    }
+
+    #[test]
+    fn test_money_factor_markup_within_cap() {
+        // Ceiling = 0.00125 + 0.0004 = 0.00165
+        let result = make_marked_up_lease(dec!(0.00165), 0);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_money_factor_markup_over_cap() {
+        let result = make_marked_up_lease(dec!(0.0020), 0);
+        assert_eq!(result.warnings.len(), 1);
+        assert_eq!(result.warnings[0].code, "MF_MARKUP_EXCEEDED");
+        assert!(result.warnings[0].message.contains("0.00165"));
+
+        // MSDs lower the buy rate, so the same markup can exceed the cap
+        let result = make_marked_up_lease(dec!(0.00165), 2);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].message.contains("0.00151"));
+    }
+
+    #[test]
+    fn test_msds_lower_effective_money_factor_and_apr() {
+        let structure = |msd_count| match make_msd_lease(dec!(0.00125), msd_count, true).structure {
+            DealStructure::Lease(lease) => lease,
+            _ => panic!("Expected lease structure"),
+        };
+        let without = structure(0);
+        let with_msds = structure(3);
+
+        assert_eq!(without.money_factor, dec!(0.00125));
+        assert_eq!(without.equivalent_apr, dec!(3.00));
+
+        // 3 MSDs x 0.00007 off the base money factor
+        assert_eq!(with_msds.base_money_factor, dec!(0.00125));
+        assert_eq!(with_msds.msd_mf_reduction, dec!(0.00021));
+        assert_eq!(with_msds.money_factor, dec!(0.00104));
+        assert_eq!(with_msds.equivalent_apr, dec!(2.496));
+        assert!(with_msds.rent_charge < without.rent_charge);
+
+        // Unless requested, the input money factor is already effective
+        let DealStructure::Lease(as_quoted) = make_marked_up_lease(dec!(0.00125), 3).structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(as_quoted.msd_mf_reduction, dec!(0));
+        assert_eq!(as_quoted.money_factor, dec!(0.00125));
+        assert_eq!(as_quoted.rent_charge, without.rent_charge);
+
+        // The output reports both rates
+        let output_lease = |msd_count| {
+            let cashflow = crate::phases::p6_cashflow::generate_cashflow(make_msd_lease(dec!(0.00125), msd_count, true))
+                .unwrap();
+            crate::phases::p7_finalize::finalize_output(cashflow).unwrap().output.lease_structure.unwrap()
+        };
+        let output = output_lease(3);
+        assert_eq!(output.base_money_factor.as_decimal(), dec!(0.00125));
+        assert_eq!(output.base_equivalent_apr.as_decimal(), dec!(0.03));
+        assert_eq!(output.money_factor.as_decimal(), dec!(0.00104));
+        assert_eq!(output.equivalent_apr.as_decimal(), dec!(0.02496));
+        assert!(output.equivalent_apr < output_lease(0).equivalent_apr);
+    }
+
+    fn make_drive_off_lease(
+        drive_off_rules: crate::types::DriveOffRules,
+        cash_down: Decimal,
+        signing_rebate: Decimal,
+    ) -> StructuredDeal {
+        let lease_program = crate::types::LeaseProgram { drive_off_rules, ..make_lease_program() };
+        let program = make_program(vec![], Some(lease_program));
+
+        let mut input = make_lease_input();
+        input.cash_down = cash_down;
+        input.trade_in_value = None;
+        // A rebate kept off the cap cost pays toward the amount due
+        input.rebates = vec![make_rebate("Drive-off Cash", signing_rebate, false, false)];
+        build_structure(load_with_program(input, &program)).unwrap()
+    }
+
+    fn warning_codes(result: &StructuredDeal) -> Vec<&str> {
+        result.warnings.iter().map(|w| w.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_zero_drive_off_on_program_that_disallows_it() {
+        use crate::types::DriveOffRules;
+
+        // Drive-off cash covers everything due at signing
+        let result = make_drive_off_lease(DriveOffRules::default(), dec!(0), dec!(5000));
+        let DealStructure::Lease(lease) = &result.structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(lease.due_at_signing, dec!(0));
+        assert_eq!(warning_codes(&result), vec!["SIGNING_REBATES_EXCEED_DUE", "ZERO_DRIVE_OFF_NOT_ALLOWED"]);
+
+        let allowed = DriveOffRules { zero_drive_off_allowed: true, ..Default::default() };
+        let result = make_drive_off_lease(allowed, dec!(0), dec!(5000));
+        assert_eq!(warning_codes(&result), vec!["SIGNING_REBATES_EXCEED_DUE"]);
+    }
+
+    #[test]
+    fn test_sign_and_drive_and_min_cap_reduction() {
+        use crate::types::DriveOffRules;
+
+        // No cash down, but the first payment and fees are still due
+        let result = make_drive_off_lease(DriveOffRules::default(), dec!(0), dec!(0));
+        assert_eq!(warning_codes(&result), vec!["SIGN_AND_DRIVE_NOT_AVAILABLE"]);
+
+        let sign_and_drive = DriveOffRules { sign_and_drive_available: true, ..Default::default() };
+        assert!(make_drive_off_lease(sign_and_drive, dec!(0), dec!(0)).warnings.is_empty());
+
+        let minimum = DriveOffRules { min_cap_reduction: Some(dec!(2000)), ..Default::default() };
+        let result = make_drive_off_lease(minimum.clone(), dec!(1500), dec!(0));
+        assert_eq!(warning_codes(&result), vec!["CAP_REDUCTION_BELOW_MINIMUM"]);
+        assert!(make_drive_off_lease(minimum, dec!(2000), dec!(0)).warnings.is_empty());
+    }
+
+    fn make_fee_program(acquisition_fee: Decimal, bank_fee: Decimal) -> crate::types::ProgramProfile {
+        let mut program = make_program(vec![], None);
+        program.fee_config.acquisition_fee = acquisition_fee;
+        program.fee_config.bank_fee = bank_fee;
+        program
+    }
+
+    #[test]
+    fn test_acquisition_fee_from_program() {
+        let mut input = make_lease_input();
+        input.fees.acquisition_fee = dec!(0);
+        let program = make_fee_program(dec!(895), dec!(0));
+
+        let deal = load_with_program(input, &program);
+        assert_eq!(deal.deal.deal.deal.input.inner.fees.acquisition_fee, dec!(895));
+        assert!(deal.deal.profiles.warnings.iter().all(|w| w.code != "PROGRAM_FEE_MISMATCH"));
+
+        let DealStructure::Lease(lease) = build_structure(deal).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        // Doc fee 299 + acquisition fee 895
+        assert_eq!(lease.capitalized_fees, dec!(1194));
+
+        // A program that forbids capitalizing its fees moves the fee upfront
+        let mut program = make_fee_program(dec!(895), dec!(0));
+        program.fee_config.fees_capitalizable = false;
+        let mut input = make_lease_input();
+        input.fees.acquisition_fee = dec!(0);
+        let DealStructure::Lease(upfront) = build_structure(load_with_program(input, &program)).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(upfront.capitalized_fees, dec!(299));
+        assert_eq!(upfront.acquisition_fee_upfront, dec!(895));
+        assert_eq!(upfront.upfront_fees - lease.upfront_fees, dec!(895));
+    }
+
+    #[test]
+    fn test_acquisition_fee_mismatch_warning() {
+        // make_lease_input carries a $595 acquisition fee
+        let program = make_fee_program(dec!(895), dec!(0));
+        let deal = load_with_program(make_lease_input(), &program);
+
+        assert_eq!(deal.deal.deal.deal.input.inner.fees.acquisition_fee, dec!(595));
+        let warning = deal.deal.profiles.warnings.iter()
+            .find(|w| w.code == "PROGRAM_FEE_MISMATCH")
+            .expect("mismatch warning");
+        assert_eq!(warning.field, "fees.acquisition_fee");
+    }
+
+    #[test]
+    fn test_bank_fee_financed() {
+        let standard = build_structure(make_finance_deal()).unwrap();
+        let program = make_fee_program(dec!(0), dec!(395));
+        let with_fee = build_structure(load_with_program(make_finance_input(), &program)).unwrap();
+
+        let (DealStructure::Finance(standard), DealStructure::Finance(with_fee)) =
+            (standard.structure, with_fee.structure)
+        else {
+            panic!("Expected finance structures");
+        };
+        assert_eq!(with_fee.amount_financed - standard.amount_financed, dec!(395));
+        assert!(with_fee.amount_financed_breakdown.iter()
+            .any(|item| item.label == "Bank fee" && item.amount == dec!(395)));
+        assert!(with_fee.amount_financed_breakdown.iter().all(|item| item.label != "Rounding"));
+        assert_eq!(with_fee.upfront_fees, dec!(0));
+    }
+
+    #[test]
+    fn test_bank_fee_paid_upfront_when_program_forbids_financing() {
+        let standard = build_structure(make_finance_deal()).unwrap();
+        let mut program = make_fee_program(dec!(0), dec!(395));
+        program.fee_config.fees_capitalizable = false;
+        let upfront = build_structure(load_with_program(make_finance_input(), &program)).unwrap();
+
+        let (DealStructure::Finance(standard), DealStructure::Finance(upfront)) =
+            (standard.structure, upfront.structure)
+        else {
+            panic!("Expected finance structures");
+        };
+        assert_eq!(upfront.amount_financed, standard.amount_financed);
+        assert_eq!(upfront.upfront_fees, dec!(395));
+        assert_eq!(upfront.due_at_signing, upfront.cash_down + dec!(395));
+        assert_eq!(upfront.total_sale_price - standard.total_sale_price, dec!(395));
+    }
+
+    fn make_finance_only_vsc() -> ProductProfile {
+        use crate::types::{
+            CommissionStructure, PricingModel, ProductEligibility, ProductMeta, ProductPricing,
+            ProviderInfo,
+        };
+
+        ProductProfile {
+            id: "vsc-finance-only".to_string(),
+            name: "Service Contract".to_string(),
+            product_type: ProductType::Vsc,
+            provider: ProviderInfo {
+                id: "provider-001".to_string(),
+                name: "AutoGuard".to_string(),
+                phone: None,
+                claims_phone: None,
+                website: None,
+                rating: None,
+            },
+            pricing: ProductPricing {
+                model: PricingModel::Flat,
+                base_cost: Some(dec!(1200)),
+                min_price: None,
+                max_price: None,
+                rate_table: None,
+                state_specific_pricing: false,
+                tax_inclusive: false,
+            },
+            coverage_options: vec![],
+            eligibility: ProductEligibility {
+                finance_eligible: true,
+                lease_eligible: false,
+                cash_eligible: true,
+                ..Default::default()
+            },
+            commission: CommissionStructure::default(),
+            state_rules: vec![],
+            meta: ProductMeta::default(),
+        }
+    }
+
+    fn load_with_products(input: DealInput, options: &PipelineOptions) -> TaxComputedDeal {
+        let resolved = resolved_deal(input);
+        let loaded =
+            load_profiles_with_options(resolved, None, &[make_finance_only_vsc()], options).unwrap();
+        calculate_tax(loaded).unwrap()
+    }
+
+    fn with_vsc(mut input: DealInput) -> DealInput {
+        input.products = vec![Product {
+            id: "vsc".to_string(),
+            name: "Service Contract".to_string(),
+            price: dec!(2500),
+            cost: dec!(1200),
+            product_type: ProductType::Vsc,
+            term_months: Some(60),
+            mileage_limit: Some(75000),
+            deductible: Some(dec!(100)),
+            taxable: false,
+        }];
+        input
+    }
+
+    #[test]
+    fn test_finance_only_vsc_flagged_on_lease() {
+        let deal = load_with_products(with_vsc(make_lease_input()), &PipelineOptions::default());
+        let warning = deal.deal.profiles.warnings.iter()
+            .find(|w| w.code == "PRODUCT_INELIGIBLE")
+            .expect("ineligible product warning");
+        assert_eq!(warning.field, "products.vsc");
+        assert!(warning.message.contains("lease"));
+
+        // Flag only: the product stays on the deal
+        let DealStructure::Lease(lease) = build_structure(deal).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(lease.capitalized_fi_products, dec!(2500));
+    }
+
+    #[test]
+    fn test_finance_only_vsc_excluded_from_lease() {
+        let options = PipelineOptions { exclude_ineligible_products: true, ..Default::default() };
+        let deal = load_with_products(with_vsc(make_lease_input()), &options);
+        assert!(deal.deal.deal.deal.input.inner.products.is_empty());
+        assert!(deal.deal.profiles.warnings.iter().any(|w| w.code == "PRODUCT_INELIGIBLE"));
+
+        let DealStructure::Lease(lease) = build_structure(deal).unwrap().structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(lease.capitalized_fi_products, dec!(0));
+    }
+
+    #[test]
+    fn test_finance_only_vsc_allowed_on_finance() {
+        let deal = load_with_products(with_vsc(make_finance_input()), &PipelineOptions::default());
+        assert!(deal.deal.profiles.warnings.iter().all(|w| w.code != "PRODUCT_INELIGIBLE"));
+        assert_eq!(deal.deal.deal.deal.input.inner.products.len(), 1);
+    }
+
+    fn make_gap_profile(tiers: Vec<crate::types::RateTier>) -> ProductProfile {
+        use crate::types::{PricingModel, ProductPricing, RateTable};
+
+        let mut profile = make_finance_only_vsc();
+        profile.id = "gap".to_string();
+        profile.name = "GAP".to_string();
+        profile.product_type = ProductType::Gap;
+        profile.pricing = ProductPricing {
+            model: PricingModel::AmountFinancedBased,
+            base_cost: None,
+            min_price: None,
+            max_price: None,
+            rate_table: Some(RateTable { description: "Percent of amount financed".to_string(), tiers }),
+            state_specific_pricing: false,
+            tax_inclusive: false,
+        };
+        profile
+    }
+
+    fn gap_tier(min_value: Decimal, max_value: Decimal, rate: Decimal) -> crate::types::RateTier {
+        crate::types::RateTier { min_value, max_value, rate, min_cost: None, max_cost: None }
+    }
+
+    fn structure_with_gap(profile: ProductProfile) -> UdcResult<StructuredDeal> {
+        let mut input = make_finance_input();
+        input.products = vec![Product {
+            id: "gap".to_string(),
+            name: "GAP".to_string(),
+            price: dec!(0),
+            cost: dec!(300),
+            product_type: ProductType::Gap,
+            term_months: Some(60),
+            mileage_limit: None,
+            deductible: None,
+            taxable: false,
+        }];
+
+        let options = PipelineOptions::default();
+        let loaded = load_profiles_with_options(resolved_deal(input), None, &[profile], &options).unwrap();
+        build_structure_with_options(calculate_tax(loaded).unwrap(), &options)
+    }
+
+    #[test]
+    fn test_amount_financed_based_gap_converges() {
+        let profile = make_gap_profile(vec![gap_tier(dec!(0), dec!(1000000), dec!(0.02))]);
+        let structured = structure_with_gap(profile).unwrap();
+        let DealStructure::Finance(finance) = &structured.structure else {
+            panic!("Expected finance structure");
+        };
+
+        let gap_price = structured.deal.deal.deal.deal.input.inner.products[0].price;
+        assert!(gap_price > dec!(0));
+        assert_eq!(finance.fi_products_financed, gap_price);
+
+        // GAP is 2% of the amount financed that includes it
+        let expected = (finance.amount_financed * dec!(0.02)).round_dp(2);
+        assert!((gap_price - expected).abs() <= dec!(0.01),
+            "GAP {} should be 2% of {} ({})", gap_price, finance.amount_financed, expected);
+    }
+
+    #[test]
+    fn test_amount_financed_based_pricing_non_convergence() {
+        // A tier boundary that flips the price on and off never settles
+        let profile = make_gap_profile(vec![
+            gap_tier(dec!(0), dec!(40000), dec!(0.50)),
+            gap_tier(dec!(40000.01), dec!(1000000), dec!(0)),
+        ]);
+        let result = structure_with_gap(profile);
+        assert!(matches!(result, Err(UdcError::Calculation { .. })));
+    }
+
+    fn make_small_loan(amount_financed: Decimal, program: &crate::types::ProgramProfile) -> TaxComputedDeal {
+        // Cash down doesn't change the TX tax base, so it sets the amount financed exactly
+        let DealStructure::Finance(base) = build_structure(load_with_program(make_finance_input(), program))
+            .unwrap()
+            .structure
+        else {
+            panic!("Expected finance structure");
+        };
+        let mut input = make_finance_input();
+        input.cash_down += base.amount_financed - amount_financed;
+        load_with_program(input, program)
+    }
+
+    #[test]
+    fn test_min_payment_shortens_term() {
+        let mut program = make_program(vec![], None);
+        program.structure_rules.min_payment = Some(dec!(50));
+
+        let structured = build_structure(make_small_loan(dec!(500), &program)).unwrap();
+        let DealStructure::Finance(finance) = &structured.structure else {
+            panic!("Expected finance structure");
+        };
+        assert_eq!(finance.amount_financed, dec!(500));
+        assert_eq!(finance.term_months, 10);
+        assert_eq!(finance.number_of_payments, 10);
+        assert!(finance.monthly_payment >= dec!(50));
+        assert!(structured.warnings.iter().any(|w| w.code == "MIN_PAYMENT_TERM_SHORTENED"));
+
+        // One more month would drop below the minimum
+        let (longer, _, _) = calculate_loan_payment(dec!(500), finance.apr, 11).unwrap();
+        assert!(longer < dec!(50));
+
+        // The schedule still reconciles over the shortened term
+        let cashflow = crate::phases::p6_cashflow::generate_cashflow(structured).unwrap();
+        let Some(crate::phases::p6_cashflow::Cashflow::Finance(cf)) = cashflow.cashflow else {
+            panic!("Expected finance cashflow");
+        };
+        assert_eq!(cf.schedule.len(), 10);
+        assert_eq!(cf.schedule[9].remaining_balance.as_decimal(), dec!(0));
+        let principal: Decimal = cf.schedule.iter().map(|e| e.principal.as_decimal()).sum();
+        assert_eq!(principal, dec!(500));
+    }
+
+    #[test]
+    fn test_min_payment_infeasible() {
+        let mut program = make_program(vec![], None);
+        program.structure_rules.min_payment = Some(dec!(600));
+
+        let structured = build_structure(make_small_loan(dec!(500), &program)).unwrap();
+        assert!(structured.warnings.iter().any(|w| w.code == "MIN_PAYMENT_INFEASIBLE"));
+
+        let strict = PipelineOptions { strict_validation: true, ..Default::default() };
+        let result = build_structure_with_options(make_small_loan(dec!(500), &program), &strict);
+        assert!(matches!(result, Err(UdcError::Validation { .. })));
+    }
+
+    #[test]
+    fn test_msds_are_refundable_due_at_signing() {
+        let DealStructure::Lease(lease) = make_marked_up_lease(dec!(0.00125), 4).structure else {
+            panic!("Expected lease structure");
+        };
+
+        // Four deposits of the payment rounded up to the next $50
+        let per_deposit = (lease.total_monthly_payment / dec!(50)).ceil() * dec!(50);
+        assert_eq!(lease.msd_deposit, per_deposit * dec!(4));
+        assert_eq!(lease.refundable_due_at_signing, lease.msd_deposit + lease.security_deposit);
+        assert_eq!(
+            lease.nonrefundable_due_at_signing,
+            lease.first_payment + lease.cash_down + lease.upfront_fees + lease.upfront_tax
+        );
+        assert_eq!(
+            lease.refundable_due_at_signing + lease.nonrefundable_due_at_signing,
+            lease.due_at_signing
+        );
+
+        // Without MSDs nothing is refundable
+        let DealStructure::Lease(lease) = make_marked_up_lease(dec!(0.00125), 0).structure else {
+            panic!("Expected lease structure");
+        };
+        assert_eq!(lease.refundable_due_at_signing, dec!(0));
+        assert_eq!(lease.nonrefundable_due_at_signing, lease.due_at_signing);
+    }
+
+    #[test]
+    fn test_interim_rent_for_mid_month_start() {
+        let build = |contract_start: Option<NaiveDate>| {
+            let mut input = make_lease_input();
+            input.contract_start_date = contract_start;
+            input.first_payment_date = NaiveDate::from_ymd_opt(2024, 7, 1);
+            input.lease_params.as_mut().unwrap().day_count = DayCount::Thirty360;
+
+            let taxed = taxed_deal(input);
+            match build_structure(taxed).unwrap().structure {
+                DealStructure::Lease(lease) => lease,
+                _ => panic!("Expected lease structure"),
+            }
+        };
+
+        // Starts on the 20th; billing begins on the 1st: 11 days under 30/360
+        let lease = build(NaiveDate::from_ymd_opt(2024, 6, 20));
+        let expected = (lease.total_monthly_payment / dec!(30) * dec!(11)).round_money();
+        assert!(expected > dec!(0));
+        assert_eq!(lease.interim_rent, expected);
+
+        let aligned = build(None);
+        assert_eq!(aligned.interim_rent, dec!(0));
+        assert_eq!(lease.due_at_signing, aligned.due_at_signing + expected);
+        assert_eq!(lease.nonrefundable_due_at_signing, aligned.nonrefundable_due_at_signing + expected);
+    }
+
+    fn load_with_caps(
+        input: DealInput,
+        program: &crate::types::ProgramProfile,
+        profile: ProductProfile,
+    ) -> Vec<ValidationWarning> {
+        let resolved = resolved_deal(input);
+        load_profiles_with_options(resolved, Some(program), &[profile], &PipelineOptions::default())
+            .unwrap()
+            .profiles
+            .warnings
+    }
+
+    #[test]
+    fn test_vsc_over_max_price() {
+        let mut profile = make_finance_only_vsc();
+        profile.pricing.max_price = Some(dec!(2000));
+        let program = make_program(vec![], None);
+
+        let warnings = load_with_caps(with_vsc(make_finance_input()), &program, profile);
+        let warning = warnings.iter()
+            .find(|w| w.code == "PRODUCT_OVER_MAX_PRICE")
+            .expect("max price warning");
+        assert_eq!(warning.field, "products.vsc");
+        assert!(warning.message.contains("2000"));
+        assert!(warning.message.contains("by 500"));
+    }
+
+    #[test]
+    fn test_backend_over_program_percent() {
+        let mut program = make_program(vec![], None);
+        program.structure_rules.max_backend_percent = Some(dec!(0.05));
+
+        // 5% of 30000 is 1500; the 2500 VSC is 1000 over
+        let warnings = load_with_caps(with_vsc(make_finance_input()), &program, make_finance_only_vsc());
+        let warning = warnings.iter()
+            .find(|w| w.code == "BACKEND_OVER_MAX_PERCENT")
+            .expect("backend percent warning");
+        assert!(warning.message.contains("(1500.00) by 1000.00"));
+        assert!(!warnings.iter().any(|w| w.code == "PRODUCT_OVER_MAX_PRICE"));
+
+        program.structure_rules.max_backend_percent = Some(dec!(0.10));
+        let warnings = load_with_caps(with_vsc(make_finance_input()), &program, make_finance_only_vsc());
+        assert!(!warnings.iter().any(|w| w.code.starts_with("BACKEND_OVER")));
+    }
+
+    #[test]
+    fn test_lease_monthly_tax_base() {
+        use crate::types::LeaseMonthlyTaxBase;
+
+        let lease_with_base = |tax_base: LeaseMonthlyTaxBase| {
+            let mut loaded = loaded_deal(make_lease_input());
+            loaded.profiles.primary_rules.lease_monthly_tax_base = tax_base;
+            let DealStructure::Lease(lease) = build_structure(calculate_tax(loaded).unwrap()).unwrap().structure
+            else {
+                panic!("Expected lease structure");
+            };
+            lease
+        };
+
+        let full = lease_with_base(LeaseMonthlyTaxBase::FullPayment);
+        let without_fees = lease_with_base(LeaseMonthlyTaxBase::DepreciationPlusRent);
+        let depreciation_only = lease_with_base(LeaseMonthlyTaxBase::DepreciationOnly);
+
+        // Same lease, same base payment; only the taxed slice changes
+        assert_eq!(full.lease_tax_mode, LeaseTaxMode::MonthlyPayment);
+        assert!(full.capitalized_fees > dec!(0));
+        assert_eq!(full.base_monthly_payment, without_fees.base_monthly_payment);
+        assert_eq!(full.base_monthly_payment, depreciation_only.base_monthly_payment);
+
+        let rate = full.monthly_tax / full.base_monthly_payment;
+        assert!(full.monthly_tax > without_fees.monthly_tax);
+        assert!(full.monthly_tax > depreciation_only.monthly_tax);
+
+        let monthly_fees = (full.capitalized_fees / dec!(36)).round_money();
+        let expected = ((full.base_monthly_payment - monthly_fees) * rate).round_money();
+        assert!((without_fees.monthly_tax - expected).abs() <= dec!(0.01));
+        let expected = (full.monthly_depreciation * rate).round_money();
+        assert!((depreciation_only.monthly_tax - expected).abs() <= dec!(0.01));
+    }
+
+    #[test]
+    fn test_zero_money_factor_lease() {
+        let mut input = make_lease_input();
+        input.lease_params.as_mut().unwrap().money_factor = MoneyFactor::new(dec!(0));
+        let structured = build_structure(taxed_deal(input)).unwrap();
+        assert!(structured.warnings.iter().all(|w| !w.field.contains("money_factor")));
+        let DealStructure::Lease(lease) = structured.structure else {
+            panic!("Expected lease structure");
+        };
+
+        // No rent: the base payment is pure depreciation
+        assert_eq!(lease.rent_charge, dec!(0));
+        assert_eq!(lease.monthly_rent_charge, dec!(0));
+        assert_eq!(lease.equivalent_apr, dec!(0));
+        assert!(lease.monthly_depreciation > dec!(0));
+        assert_eq!(lease.base_monthly_payment, lease.monthly_depreciation);
+    }
+
+    #[test]
+    fn test_lease_tax_rate_change_mid_term() {
+        use crate::phases::p6_cashflow::{generate_cashflow, Cashflow};
+        use crate::types::ScheduledTaxRate;
+
+        let structured = |schedule: Vec<ScheduledTaxRate>| {
+            let mut input = make_lease_input();
+            input.lease_params.as_mut().unwrap().tax_rate_schedule = schedule;
+            build_structure(taxed_deal(input)).unwrap()
+        };
+        let flat = structured(vec![]);
+        let stepped = structured(vec![ScheduledTaxRate {
+            effective_month: 12,
+            rate: Rate::from_decimal(dec!(0.09)),
+        }]);
+        let (DealStructure::Lease(flat_lease), DealStructure::Lease(lease)) = (&flat.structure, &stepped.structure)
+        else {
+            panic!("Expected lease structures");
+        };
+        assert_eq!(lease.lease_tax_mode, LeaseTaxMode::MonthlyPayment);
+        assert_eq!(lease.monthly_tax, flat_lease.monthly_tax);
+
+        let Some(Cashflow::Lease(cashflow)) = generate_cashflow(stepped.clone()).unwrap().cashflow else {
+            panic!("Expected lease cashflow");
+        };
+        let schedule = &cashflow.schedule;
+        assert_eq!(schedule.len(), 36);
+
+        // Payments 1-11 keep the opening rate; 12 on carry the higher one
+        let raised_tax = (lease.taxable_monthly_payment * dec!(0.09)).round_money();
+        assert!(raised_tax > lease.monthly_tax);
+        assert!(schedule[..11].iter().all(|e| e.tax.as_decimal() == lease.monthly_tax));
+        assert!(schedule[11..].iter().all(|e| e.tax.as_decimal() == raised_tax));
+        assert!(schedule[11].total_payment > schedule[10].total_payment);
+        assert_eq!(schedule[11].total_payment.as_decimal(), lease.base_monthly_payment + raised_tax);
+
+        // Total tax sums the schedule
+        let scheduled_tax: Decimal = schedule.iter().map(|e| e.tax.as_decimal()).sum();
+        assert_eq!(scheduled_tax, lease.monthly_tax * dec!(11) + raised_tax * dec!(25));
+        assert_eq!(lease.total_tax - lease.upfront_tax, scheduled_tax);
+        assert_eq!(lease.total_tax - flat_lease.total_tax, (raised_tax - lease.monthly_tax) * dec!(25));
+    }
+
+    #[test]
+    fn test_lease_tax_rate_schedule_validation() {
+        use crate::types::ScheduledTaxRate;
+
+        let with_schedule = |months: &[u32]| {
+            let mut input = make_lease_input();
+            input.lease_params.as_mut().unwrap().tax_rate_schedule = months
+                .iter()
+                .map(|&effective_month| ScheduledTaxRate { effective_month, rate: Rate::from_decimal(dec!(0.07)) })
+                .collect();
+            normalize_deal_input(input)
+        };
+
+        assert!(with_schedule(&[12, 24]).is_ok());
+        assert!(with_schedule(&[24, 12]).is_err());
+        assert!(with_schedule(&[0]).is_err());
+        // The first payment is taxed at the opening rate
+        assert!(with_schedule(&[1]).is_err());
+        assert!(with_schedule(&[2]).is_ok());
+        assert!(with_schedule(&[37]).is_err());
+    }
+
+    #[test]
+    fn test_lease_trade_credit() {
+        let lease_with_credit = |lease_trade_credit: bool| {
+            let mut loaded = loaded_deal(make_lease_input());
+            loaded.profiles.primary_rules.lease_trade_credit = lease_trade_credit;
+            let DealStructure::Lease(lease) = build_structure(calculate_tax(loaded).unwrap()).unwrap().structure
+            else {
+                panic!("Expected lease structure");
+            };
+            lease
+        };
+
+        let credit = lease_with_credit(true);
+        let no_credit = lease_with_credit(false);
+
+        // The trade lowers the payment either way; only the tax differs
+        assert_eq!(credit.lease_tax_mode, LeaseTaxMode::MonthlyPayment);
+        assert_eq!(credit.trade_credit, dec!(5000));
+        assert_eq!(credit.base_monthly_payment, no_credit.base_monthly_payment);
+
+        // A no-credit state taxes the trade's monthly share of $5,000 / 36
+        let rate = credit.monthly_tax / credit.base_monthly_payment;
+        let trade_share = (dec!(5000) / dec!(36)).round_money();
+        let expected = ((credit.base_monthly_payment + trade_share) * rate).round_money();
+        assert!(no_credit.monthly_tax > credit.monthly_tax);
+        assert!((no_credit.monthly_tax - expected).abs() <= dec!(0.01));
+    }
+
+    fn make_rebate_lease(reduces_cap_cost: bool, reduces_tax_basis: bool) -> LeaseStructure {
+        make_state_rebate_lease(StateCode::TX, reduces_cap_cost, reduces_tax_basis)
+    }
+
+    fn make_state_rebate_lease(state: StateCode, reduces_cap_cost: bool, reduces_tax_basis: bool) -> LeaseStructure {
+        let mut input = make_lease_input();
+        input.home_state = state;
+        input.transaction_state = state;
+        input.rebates = vec![make_rebate("Lease Cash", dec!(1800), reduces_cap_cost, reduces_tax_basis)];
+        let DealStructure::Lease(lease) = build_structure(taxed_deal(input)).unwrap().structure
+        else {
+            panic!("Expected lease structure");
+        };
+        lease
+    }
+
+    #[test]
+    fn test_lease_rebate_reduces_cap_cost_but_taxable() {
+        let taxable = make_rebate_lease(true, false);
+        let both = make_rebate_lease(true, true);
+
+        // Both lower the payment by the same cap cost reduction
+        assert_eq!(taxable.rebates, dec!(1800));
+        assert_eq!(taxable.total_cap_reduction, dec!(9800));
+        assert_eq!(taxable.base_monthly_payment, both.base_monthly_payment);
+
+        // The taxable rebate's monthly share ($1,800 / 36 = $50) is still taxed
+        let rate = both.monthly_tax / both.base_monthly_payment;
+        let expected = ((both.base_monthly_payment + dec!(50)) * rate).round_money();
+        assert!(taxable.monthly_tax > both.monthly_tax);
+        assert!((taxable.monthly_tax - expected).abs() <= dec!(0.01));
+    }
+
+    #[test]
+    fn test_lease_rebate_reduces_cap_cost_and_tax_basis() {
+        let both = make_rebate_lease(true, true);
+        let at_signing = make_rebate_lease(false, true);
+
+        // Off the cap cost the rebate raises the payment and pays down the
+        // amount due at signing instead
+        assert_eq!(at_signing.rebates, dec!(0));
+        assert_eq!(at_signing.total_cap_reduction, dec!(8000));
+        assert!(both.base_monthly_payment < at_signing.base_monthly_payment);
+        assert!(both.monthly_tax < at_signing.monthly_tax);
+        assert_eq!(
+            at_signing.nonrefundable_due_at_signing,
+            at_signing.first_payment + at_signing.interim_rent + at_signing.cash_down
+                + at_signing.upfront_fees + at_signing.upfront_tax - dec!(1800)
+        );
+    }
+
+    #[test]
+    fn test_tax_basis_rebate_not_taxed_as_cap_reduction() {
+        // CA taxes the cap cost reduction at signing, but not the part of it
+        // that is a rebate reducing the tax basis
+        let taxable = make_state_rebate_lease(StateCode::CA, true, false);
+        let exempt = make_state_rebate_lease(StateCode::CA, true, true);
+
+        assert_eq!(taxable.total_cap_reduction, exempt.total_cap_reduction);
+        assert_eq!(taxable.upfront_tax - exempt.upfront_tax, (dec!(1800) * dec!(0.0825)).round_money());
+    }
+
+    fn with_vehicle_values(mut input: DealInput) -> DealInput {
+        input.vehicle_values = Some(crate::types::VehicleValues {
+            msrp: Some(dec!(32000)),
+            invoice: None,
+            book_value: Some(dec!(20000)),
+        });
+        input
+    }
+
+    fn finance_of(structured: StructuredDeal) -> (FinanceStructure, Vec<ValidationWarning>) {
+        match structured.structure {
+            DealStructure::Finance(finance) => (finance, structured.warnings),
+            _ => panic!("Expected finance structure"),
+        }
+    }
+
+    #[test]
+    fn test_ltv_against_book_value_and_msrp() {
+        let load = || {
+            taxed_deal(with_vehicle_values(make_finance_input()))
+        };
+
+        let (msrp, _) = finance_of(build_structure(load()).unwrap());
+        assert_eq!(msrp.ltv_basis, LtvBasis::Msrp);
+        assert_eq!(msrp.ltv, Some((msrp.amount_financed / dec!(32000)).round_dp(4)));
+
+        let options = PipelineOptions { ltv_basis: LtvBasis::BookValue, ..Default::default() };
+        let (book, _) = finance_of(build_structure_with_options(load(), &options).unwrap());
+        assert_eq!(book.ltv_basis, LtvBasis::BookValue);
+        assert_eq!(book.ltv, Some((book.amount_financed / dec!(20000)).round_dp(4)));
+        assert!(book.ltv > msrp.ltv);
+
+        // No invoice on the deal, so no LTV against it
+        let options = PipelineOptions { ltv_basis: LtvBasis::Invoice, ..Default::default() };
+        let (invoice, _) = finance_of(build_structure_with_options(load(), &options).unwrap());
+        assert_eq!(invoice.ltv, None);
+    }
+
+    #[test]
+    fn test_program_ltv_cap_uses_program_basis() {
+        let mut program = make_program(vec![], None);
+        program.structure_rules.max_ltv = Some(dec!(1.10));
+
+        // Over 110% of the $20,000 book value, well under 110% of MSRP
+        program.structure_rules.ltv_basis = Some(LtvBasis::BookValue);
+        let deal = load_with_program(with_vehicle_values(make_finance_input()), &program);
+        let (finance, warnings) = finance_of(build_structure(deal).unwrap());
+        assert_eq!(finance.ltv_basis, LtvBasis::BookValue);
+        assert!(finance.ltv.unwrap() > dec!(1.10));
+        let warning = warnings.iter().find(|w| w.code == "LTV_OVER_PROGRAM_MAX").expect("LTV warning");
+        assert!(warning.message.contains("BookValue"));
+
+        // The program's basis wins over the configured one
+        program.structure_rules.ltv_basis = Some(LtvBasis::Msrp);
+        let options = PipelineOptions { ltv_basis: LtvBasis::BookValue, ..Default::default() };
+        let deal = load_with_program(with_vehicle_values(make_finance_input()), &program);
+        let (finance, warnings) = finance_of(build_structure_with_options(deal, &options).unwrap());
+        assert_eq!(finance.ltv_basis, LtvBasis::Msrp);
+        assert!(warnings.iter().all(|w| w.code != "LTV_OVER_PROGRAM_MAX"));
+    }
+
+    #[test]
+    fn test_capitalized_upfront_tax() {
+        let ny_lease = |capitalize_upfront_tax: bool| {
+            let mut input = make_lease_input();
+            input.home_state = StateCode::NY;
+            input.transaction_state = StateCode::NY;
+            input.lease_params.as_mut().unwrap().capitalize_upfront_tax = capitalize_upfront_tax;
+            let DealStructure::Lease(lease) = build_structure(taxed_deal(input)).unwrap().structure
+            else {
+                panic!("Expected lease structure");
+            };
+            lease
+        };
+
+        let paid = ny_lease(false);
+        let capitalized = ny_lease(true);
+        assert_eq!(paid.lease_tax_mode, LeaseTaxMode::CapCostUpfront);
+
+        // Paid at signing: nothing capitalized
+        let tax = paid.upfront_tax;
+        assert!(tax > dec!(0));
+        assert_eq!(paid.capitalized_tax, dec!(0));
+
+        // Capitalized: the same tax raises the cap cost and payment instead
+        assert_eq!(capitalized.capitalized_tax, tax);
+        assert_eq!(capitalized.upfront_tax, dec!(0));
+        assert_eq!(capitalized.gross_cap_cost, paid.gross_cap_cost + tax);
+        assert!(capitalized.base_monthly_payment > paid.base_monthly_payment);
+        assert!(capitalized.due_at_signing < paid.due_at_signing);
+
+        // Same tax either way; the total cost differs only by rent on the tax
+        assert_eq!(capitalized.total_tax, paid.total_tax);
+        let extra_rent = capitalized.rent_charge - paid.rent_charge;
+        let cost_difference = capitalized.total_lease_cost - paid.total_lease_cost - extra_rent;
+        assert!(cost_difference.abs() <= dec!(0.36), "difference {}", cost_difference);
+
+        // Both totals reconcile with the payments and amount due at signing
+        assert!(validate_lease_invariants(&paid).is_ok());
+        assert!(validate_lease_invariants(&capitalized).is_ok());
+        let mut double_counted = capitalized.clone();
+        double_counted.total_lease_cost += tax;
+        assert!(matches!(validate_lease_invariants(&double_counted), Err(UdcError::Calculation { .. })));
+    }
+
+    #[test]
+    fn test_gross_cap_cost_breakdown_reconciles() {
+        let lease_for = |state: StateCode, capitalize_upfront_tax: bool| {
+            let mut input = make_lease_input();
+            input.home_state = state;
+            input.transaction_state = state;
+            input.lease_params.as_mut().unwrap().capitalize_upfront_tax = capitalize_upfront_tax;
+            input.fees.title_fee = dec!(33);
+            input.fees.registration_fee = dec!(50.75);
+            build_structure(taxed_deal(input)).unwrap()
+        };
+        let lease = |structured: &StructuredDeal| match &structured.structure {
+            DealStructure::Lease(lease) => lease.clone(),
+            _ => panic!("Expected lease structure"),
+        };
+        let labels = |lease: &LeaseStructure| {
+            lease.gross_cap_cost_breakdown.iter().map(|i| i.label.clone()).collect::<Vec<_>>()
+        };
+
+        // TX taxes the monthly payment: nothing is capitalized as tax
+        let monthly = lease(&lease_for(StateCode::TX, false));
+        let total: Decimal = monthly.gross_cap_cost_breakdown.iter().map(|i| i.amount).sum();
+        assert_eq!(total, monthly.gross_cap_cost);
+        assert_eq!(
+            labels(&monthly),
+            vec!["Agreed value", "Documentary fee", "Title fee", "Registration fee", "Acquisition fee"]
+        );
+
+        // NY cap-cost tax paid at signing stays out of the cap cost
+        let paid = lease(&lease_for(StateCode::NY, false));
+        assert!(!labels(&paid).contains(&"Capitalized tax".to_string()));
+
+        // Rolled in, it appears as its own line and still reconciles
+        let structured = lease_for(StateCode::NY, true);
+        let capitalized = lease(&structured);
+        let tax_line = capitalized.gross_cap_cost_breakdown.last().unwrap();
+        assert_eq!(tax_line.label, "Capitalized tax");
+        assert_eq!(tax_line.amount, capitalized.capitalized_tax);
+        let total: Decimal = capitalized.gross_cap_cost_breakdown.iter().map(|i| i.amount).sum();
+        assert_eq!(total, capitalized.gross_cap_cost);
+
+        // The Reg M disclosure itemizes the same components
+        let cashflow = crate::phases::p6_cashflow::generate_cashflow(structured).unwrap();
+        let output = crate::phases::p7_finalize::finalize_output(cashflow).unwrap().output;
+        let reg_m = output.disclosures.iter().find(|d| d.code == "REG-M").unwrap();
+        assert!(reg_m.text.contains(&format!("  Capitalized tax: ${:.2}\n", capitalized.capitalized_tax)));
+        assert!(reg_m.text.contains("  Agreed value: $35000.00\n"));
+        let output_lease = output.lease_structure.unwrap();
+        assert_eq!(output_lease.gross_cap_cost_breakdown.len(), capitalized.gross_cap_cost_breakdown.len());
+    }
+
+    #[test]
+    fn test_lease_doc_fee_exempt_from_cap_cost_tax() {
+        // A NY-style state that taxes the doc fee on retail deals
+        let load_ny = |mut input: DealInput, lease_doc_fee_taxable: Option<bool>| {
+            input.home_state = StateCode::NY;
+            input.transaction_state = StateCode::NY;
+            let mut loaded = loaded_deal(input);
+            loaded.profiles.primary_rules.base_rules.doc_fee_taxable = true;
+            loaded.profiles.primary_rules.lease_doc_fee_taxable = lease_doc_fee_taxable;
+            loaded
+        };
+        let ny_lease = |lease_doc_fee_taxable: Option<bool>| {
+            let loaded = load_ny(make_lease_input(), lease_doc_fee_taxable);
+            let DealStructure::Lease(lease) = build_structure(calculate_tax(loaded).unwrap()).unwrap().structure
+            else {
+                panic!("Expected lease structure");
+            };
+            lease
+        };
+
+        // Finance keeps the retail rule even with the lease exemption set
+        let finance = calculate_tax(load_ny(make_finance_input(), Some(false))).unwrap();
+        assert_eq!(finance.tax.base_breakdown.taxable_fees, dec!(299));
+
+        let taxed = ny_lease(None);
+        let exempt = ny_lease(Some(false));
+        assert_eq!(taxed.lease_tax_mode, LeaseTaxMode::CapCostUpfront);
+
+        // The doc fee stays in the cap cost but leaves the tax base
+        assert_eq!(taxed.capitalized_fees, exempt.capitalized_fees);
+        let rate = load_ny(make_lease_input(), None).profiles.primary_rules.rates.default_combined_rate;
+        let doc_fee_tax = (dec!(299) * rate).round_money();
+        assert!((taxed.upfront_tax - exempt.upfront_tax - doc_fee_tax).abs() <= dec!(0.01));
+    }
 }
@@ -10,11 +10,11 @@
 //! Truth in Lending Act (TILA) Regulation Z.
 
 use rust_decimal::Decimal;
-use rust_decimal_macros::dec;
 use chrono::{NaiveDate, Duration, Datelike};
 
-use crate::types::{DealType, Money, AmortizationEntry, UdcResult, UdcError};
+use crate::types::{Money, AmortizationEntry, PaymentFrequency, InterestMethod, DayCount, UdcResult};
 use super::p5_structure::{StructuredDeal, DealStructure};
+use crate::algorithms::{apr_from_cashflows, rule_of_78s_interest, per_diem_interest};
 
 /// Deal with generated cashflow/amortization
 #[derive(Debug, Clone)]
@@ -45,8 +45,12 @@ pub struct FinanceCashflow {
     pub schedule: Vec<AmortizationEntry>,
     /// Total interest (finance charge)
     pub total_interest: Money,
-    /// Odd days interest (if deferred first payment)
+    /// Odd days interest charged with the first payment (zero unless the
+    /// contract opts in)
     pub odd_days_interest: Money,
+    /// APR for TILA disclosure (solved over the due dates when the payment
+    /// stream is irregular, else the contract rate)
+    pub annual_percentage_rate: Decimal,
 }
 
 /// Lease deal cashflow
@@ -84,28 +88,52 @@ pub struct LeasePaymentEntry {
 /// ## Finance Amortization
 /// For each payment:
 /// ```text
-/// interest = remaining_balance * (apr / 12)
+/// interest = remaining_balance * (apr / periods_per_year)
 /// principal = payment - interest
 /// new_balance = remaining_balance - principal
 /// ```
+/// Rule-of-78s contracts instead earn the precomputed finance charge by
+/// sum-of-digits weighting, and simple-daily contracts accrue interest for
+/// the days since the previous due date (see `algorithms::amortization`).
+///
+/// ## Odd Days
+/// When the first payment falls more than one period after the deal date,
+/// the extra days accrue per-diem interest on the amount financed:
+/// ```text
+/// odd_days = days(deal_date, first_payment_date - one period)
+/// odd_days_interest = amount_financed * (apr / days_per_year) * odd_days
+/// ```
+/// Days and the year basis follow the contract's `DayCount`, and odd days
+/// count from the `contract_start_date` when one is given. Odd-days
+/// interest is only charged when the contract opts in with
+/// `FinanceParams::charge_odd_days_interest`: the first payment's interest
+/// and amount include it, and it is added to the structure's finance
+/// charge, total of payments and total sale price. Otherwise the odd days
+/// are interest free and none is disclosed. The scheduled periods
+/// themselves always run due date to due date, whatever the interest
+/// method.
 ///
 /// ## Lease Schedule
 /// For each payment:
 /// ```text
 /// base_payment = constant (from P5)
-/// tax = base_payment * tax_rate (if monthly tax mode)
+/// tax = taxable_payment * tax_rate (if monthly tax mode)
 /// total = base_payment + tax
 /// ```
+/// The tax rate is the one in effect for the payment, so a scheduled rate
+/// change (`LeaseParams::tax_rate_schedule`) alters every later payment.
 ///
 /// # Date Handling
 /// - First payment typically 30-45 days from deal date
-/// - Payments on same day of month
+/// - Payments on same day of month (monthly/quarterly)
+/// - Semi-monthly payments fall on the 1st and 15th
+/// - Weekly/bi-weekly payments step by 7/14 days
 /// - Handles month-end adjustments (e.g., 31st -> 30th for Apr, Jun, etc.)
 ///
 /// # Complexity
 /// - Time: O(n) where n = number of payments
 /// - Space: O(n) for storing schedule
-pub fn generate_cashflow(deal: StructuredDeal) -> UdcResult<CashflowDeal> {
+pub fn generate_cashflow(mut deal: StructuredDeal) -> UdcResult<CashflowDeal> {
     let deal_type = deal.deal.deal.deal.deal.input.inner.deal_type;
 
     log::debug!("P6: Generating cashflow for {:?} deal", deal_type);
@@ -116,6 +144,14 @@ pub fn generate_cashflow(deal: StructuredDeal) -> UdcResult<CashflowDeal> {
         DealStructure::Lease(l) => Some(Cashflow::Lease(generate_lease_cashflow(&deal, l)?)),
     };
 
+    // Odd-days interest is a finance charge paid with the first payment
+    if let (Some(Cashflow::Finance(cf)), DealStructure::Finance(f)) = (&cashflow, &mut deal.structure) {
+        let odd_days_interest = cf.odd_days_interest.as_decimal();
+        f.finance_charge += odd_days_interest;
+        f.total_of_payments += odd_days_interest;
+        f.total_sale_price += odd_days_interest;
+    }
+
     Ok(CashflowDeal { deal, cashflow })
 }
 
@@ -127,28 +163,157 @@ fn generate_finance_cashflow(
     let input = &deal.deal.deal.deal.deal.input;
 
     // Determine first payment date (default: 30 days from deal date)
-    let deal_date = input.inner.deal_date.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let deal_date = input.inner.effective_deal_date();
+    let frequency = structure.payment_frequency;
     let first_payment_date = input.inner.first_payment_date
         .unwrap_or_else(|| add_days(deal_date, 30));
+    let first_payment_date = if frequency == PaymentFrequency::SemiMonthly {
+        next_semi_monthly_date(first_payment_date)
+    } else {
+        first_payment_date
+    };
 
     let payment_day = first_payment_date.day() as u8;
 
+    let interest_method = input.inner.finance_params
+        .as_ref()
+        .map(|p| p.interest_method)
+        .unwrap_or_default();
+    let day_count = input.inner.finance_params
+        .as_ref()
+        .map(|p| p.day_count)
+        .unwrap_or_default();
+    let charge_odd_days_interest = input.inner.finance_params
+        .as_ref()
+        .is_some_and(|p| p.charge_odd_days_interest);
+
+    // Interest accrues from the contract start when it differs from the deal date
+    let period_start = input.inner.contract_start_date.unwrap_or(deal_date);
+
     // Generate amortization schedule
+    let mut schedule = amortize_finance(structure, interest_method, day_count, first_payment_date);
+    let odd_days_interest = if charge_odd_days_interest {
+        odd_days_interest(structure, day_count, period_start, first_payment_date)
+    } else {
+        Decimal::ZERO
+    };
+
+    // Prorate the first payment for the odd days
+    if !odd_days_interest.is_zero() {
+        if let Some(first) = schedule.first_mut() {
+            let odd_days_interest = Money::new(odd_days_interest);
+            first.interest = first.interest + odd_days_interest;
+            first.payment_amount = first.payment_amount + odd_days_interest;
+        }
+    }
+    let total_interest = schedule.iter().map(|e| e.interest.as_decimal()).sum::<Decimal>();
+    let annual_percentage_rate = disclosed_apr(structure, &schedule, period_start, first_payment_date);
+
+    Ok(FinanceCashflow {
+        first_payment_date,
+        payment_day,
+        schedule,
+        total_interest: Money::new(total_interest),
+        odd_days_interest: Money::new(odd_days_interest),
+        annual_percentage_rate,
+    })
+}
+
+/// APR to disclose for a finance schedule.
+///
+/// A regular monthly stream - a full first period and level payments up to
+/// the final adjustment - discloses the contract rate. An irregular one (a
+/// long or short first period, or uneven payments) is solved over its actual
+/// due dates with `apr_from_cashflows`. Other frequencies keep the contract
+/// rate. Prepaid finance charges always take the solved path, since they
+/// shrink the amount financed the payments are measured against.
+fn disclosed_apr(
+    structure: &crate::phases::p5_structure::FinanceStructure,
+    schedule: &[AmortizationEntry],
+    period_start: NaiveDate,
+    first_payment_date: NaiveDate,
+) -> Decimal {
+    let prepaid = structure.prepaid_finance_charge > Decimal::ZERO;
+    if (structure.payment_frequency != PaymentFrequency::Monthly && !prepaid) || schedule.is_empty() {
+        return structure.apr;
+    }
+
+    let odd_first_period = previous_due_date(first_payment_date, PaymentFrequency::Monthly) != period_start;
+    let level_payments = schedule[..schedule.len() - 1]
+        .iter()
+        .all(|entry| entry.payment_amount.as_decimal() == structure.monthly_payment);
+    if !odd_first_period && level_payments && !prepaid {
+        return structure.apr;
+    }
+
+    let cashflows: Vec<(NaiveDate, Decimal)> = schedule
+        .iter()
+        .map(|entry| (entry.due_date, entry.payment_amount.as_decimal()))
+        .collect();
+    apr_from_cashflows(structure.amount_financed - structure.prepaid_finance_charge, period_start, &cashflows)
+}
+
+/// Per-diem interest for the days between the start of the contract and the
+/// start of the first regular payment period.
+fn odd_days_interest(
+    structure: &crate::phases::p5_structure::FinanceStructure,
+    day_count: DayCount,
+    period_start: NaiveDate,
+    first_payment_date: NaiveDate,
+) -> Decimal {
+    let regular_start = previous_due_date(first_payment_date, structure.payment_frequency);
+    let odd_days = day_count.days_between(period_start, regular_start);
+
+    per_diem_interest(structure.amount_financed, structure.apr, odd_days, day_count).round_dp(2)
+}
+
+/// Amortize a finance structure from its first payment date.
+///
+/// Every period, the first included, runs from the previous due date to its
+/// own; the first period starts one payment period before the first payment
+/// (any odd days before that are [`odd_days_interest`]). `day_count` only
+/// matters for simple-daily contracts.
+pub(crate) fn amortize_finance(
+    structure: &crate::phases::p5_structure::FinanceStructure,
+    interest_method: InterestMethod,
+    day_count: DayCount,
+    first_payment_date: NaiveDate,
+) -> Vec<AmortizationEntry> {
     // p5_structure uses raw Decimal values, not Money wrapper types
-    let mut schedule = Vec::with_capacity(structure.term_months as usize);
+    let frequency = structure.payment_frequency;
+    let number_of_payments = structure.number_of_payments;
+    let mut schedule = Vec::with_capacity(number_of_payments as usize);
     let mut remaining = structure.amount_financed;
-    let monthly_rate = structure.apr / dec!(12);
+    let periodic_rate = structure.apr / Decimal::from(frequency.periods_per_year());
     let payment = structure.monthly_payment;
+    // Prepaid finance charges are collected at signing, not earned over the term
+    let scheduled_interest = structure.finance_charge - structure.prepaid_finance_charge;
 
     let mut total_interest = Decimal::ZERO;
+    let mut period_start = previous_due_date(first_payment_date, frequency);
 
-    for i in 0..structure.term_months {
+    for i in 0..number_of_payments {
         let payment_num = (i + 1) as u16;
-        let due_date = add_months(first_payment_date, i as i32);
+        let due_date = payment_due_date(first_payment_date, frequency, i);
+        let days = day_count.days_between(period_start, due_date);
+        period_start = due_date;
 
         // Calculate interest and principal
-        let interest = (remaining * monthly_rate).round_dp(2);
-        let principal = if payment_num as u32 == structure.term_months {
+        let interest = match interest_method {
+            InterestMethod::RuleOf78s if payment_num as u32 == number_of_payments => {
+                scheduled_interest - total_interest
+            }
+            InterestMethod::RuleOf78s => {
+                rule_of_78s_interest(scheduled_interest, number_of_payments, i + 1).round_dp(2)
+            }
+            InterestMethod::SimpleDaily => {
+                per_diem_interest(remaining, structure.apr, days, day_count).round_dp(2)
+            }
+            InterestMethod::Actuarial => {
+                (remaining * periodic_rate).round_dp(2)
+            }
+        };
+        let principal = if payment_num as u32 == number_of_payments {
             // Final payment: clear remaining balance
             remaining
         } else {
@@ -161,7 +326,7 @@ fn generate_finance_cashflow(
         schedule.push(AmortizationEntry {
             payment_number: payment_num,
             due_date,
-            payment_amount: Money::new(if payment_num as u32 == structure.term_months {
+            payment_amount: Money::new(if payment_num as u32 == number_of_payments {
                 principal + interest
             } else {
                 payment
@@ -172,13 +337,7 @@ fn generate_finance_cashflow(
         });
     }
 
-    Ok(FinanceCashflow {
-        first_payment_date,
-        payment_day,
-        schedule,
-        total_interest: Money::new(total_interest),
-        odd_days_interest: Money::ZERO, // Simplified - would calculate based on actual days
-    })
+    schedule
 }
 
 /// Generate lease payment schedule
@@ -189,7 +348,7 @@ fn generate_lease_cashflow(
     let input = &deal.deal.deal.deal.deal.input;
 
     // Determine first payment date
-    let deal_date = input.inner.deal_date.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let deal_date = input.inner.effective_deal_date();
     let first_payment_date = input.inner.first_payment_date
         .unwrap_or_else(|| deal_date); // Lease first payment often at signing
 
@@ -207,13 +366,15 @@ fn generate_lease_cashflow(
             add_months(first_payment_date, i as i32)
         };
 
-        // p5_structure uses raw Decimal values, wrap them in Money
+        // p5_structure uses raw Decimal values, wrap them in Money; the tax
+        // follows any scheduled rate change
+        let tax = structure.monthly_tax_for(payment_num as u32);
         let entry = LeasePaymentEntry {
             payment_number: payment_num,
             due_date,
             base_payment: Money::new(structure.base_monthly_payment),
-            tax: Money::new(structure.monthly_tax),
-            total_payment: Money::new(structure.total_monthly_payment),
+            tax: Money::new(tax),
+            total_payment: Money::new(structure.base_monthly_payment + tax),
         };
 
         total_payments = total_payments + entry.total_payment;
@@ -228,6 +389,51 @@ fn generate_lease_cashflow(
     })
 }
 
+/// Due date of the payment at `index` (0-based) for the given frequency
+fn payment_due_date(first: NaiveDate, frequency: PaymentFrequency, index: u32) -> NaiveDate {
+    match frequency {
+        PaymentFrequency::Monthly => add_months(first, index as i32),
+        PaymentFrequency::Quarterly => add_months(first, (index * 3) as i32),
+        PaymentFrequency::Weekly => add_days(first, index as i64 * 7),
+        PaymentFrequency::BiWeekly => add_days(first, index as i64 * 14),
+        PaymentFrequency::SemiMonthly => {
+            // Count half-months from the 1st of the first payment's month
+            let half = index + if first.day() == 15 { 1 } else { 0 };
+            let month_start = first.with_day(1).unwrap_or(first);
+            let date = add_months(month_start, (half / 2) as i32);
+            if half.is_multiple_of(2) { date } else { date.with_day(15).unwrap_or(date) }
+        }
+    }
+}
+
+/// Due date one period before `date` (the start of the first regular period)
+fn previous_due_date(date: NaiveDate, frequency: PaymentFrequency) -> NaiveDate {
+    let months_back = |months: u32| {
+        date.checked_sub_months(chrono::Months::new(months)).unwrap_or(date)
+    };
+
+    match frequency {
+        PaymentFrequency::Monthly => months_back(1),
+        PaymentFrequency::Quarterly => months_back(3),
+        PaymentFrequency::Weekly => add_days(date, -7),
+        PaymentFrequency::BiWeekly => add_days(date, -14),
+        PaymentFrequency::SemiMonthly if date.day() >= 15 => date.with_day(1).unwrap_or(date),
+        PaymentFrequency::SemiMonthly => {
+            let prior = months_back(1);
+            prior.with_day(15).unwrap_or(prior)
+        }
+    }
+}
+
+/// Move a date forward to the next 1st or 15th of the month
+pub(crate) fn next_semi_monthly_date(date: NaiveDate) -> NaiveDate {
+    match date.day() {
+        1 | 15 => date,
+        2..=14 => date.with_day(15).unwrap_or(date),
+        _ => add_months(date.with_day(1).unwrap_or(date), 1),
+    }
+}
+
 /// Add days to a date
 fn add_days(date: NaiveDate, days: i64) -> NaiveDate {
     date + Duration::days(days)
@@ -285,4 +491,31 @@ mod tests {
         assert!(!is_leap_year(2100));
         assert!(is_leap_year(2000));
     }
+
+    #[test]
+    fn test_semi_monthly_due_dates() {
+        let first = next_semi_monthly_date(NaiveDate::from_ymd_opt(2024, 1, 20).unwrap());
+        assert_eq!(first, NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+
+        let dates: Vec<NaiveDate> = (0..4)
+            .map(|i| payment_due_date(first, PaymentFrequency::SemiMonthly, i))
+            .collect();
+        assert_eq!(dates, vec![
+            NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 15).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+        ]);
+
+        let mid = NaiveDate::from_ymd_opt(2024, 12, 15).unwrap();
+        assert_eq!(payment_due_date(mid, PaymentFrequency::SemiMonthly, 1),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn test_quarterly_due_dates() {
+        let first = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap();
+        assert_eq!(payment_due_date(first, PaymentFrequency::Quarterly, 1),
+            NaiveDate::from_ymd_opt(2024, 4, 30).unwrap());
+    }
 }
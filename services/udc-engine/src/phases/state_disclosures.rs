@@ -0,0 +1,406 @@
+//! State Disclosure Library
+//!
+//! Maps each `(StateCode, DealType)` to the disclosures that state mandates
+//! on the deal documents: retail installment and lease notices, doc-fee
+//! notices, used-vehicle rights, and similar. P7 renders them alongside the
+//! federal TILA / Reg M disclosures.
+//!
+//! Disclosure text is a template; `{state}` and `{doc_fee}` are replaced
+//! with the deal's values when rendered.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use rust_decimal::Decimal;
+
+use crate::types::{DealType, Disclosure, StateCode, VehicleCondition};
+
+/// A state-mandated disclosure before rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateDisclosure {
+    /// Disclosure code (e.g., "CA-CAR-BUYERS")
+    pub code: String,
+    /// Short title
+    pub title: String,
+    /// Disclosure text with `{state}` / `{doc_fee}` placeholders
+    pub template: String,
+    /// Whether the buyer or lessee must sign it
+    pub signature_required: bool,
+    /// Statutes or regulations requiring it
+    pub citations: Vec<String>,
+    /// Only required when the vehicle is used (including certified pre-owned)
+    pub used_vehicles_only: bool,
+}
+
+impl StateDisclosure {
+    /// Create a disclosure required for every vehicle.
+    pub fn new(
+        code: &str,
+        title: &str,
+        template: &str,
+        signature_required: bool,
+        citations: &[&str],
+    ) -> Self {
+        Self {
+            code: code.to_string(),
+            title: title.to_string(),
+            template: template.to_string(),
+            signature_required,
+            citations: citations.iter().map(|c| c.to_string()).collect(),
+            used_vehicles_only: false,
+        }
+    }
+
+    /// Limit this disclosure to used vehicles.
+    pub fn used_only(mut self) -> Self {
+        self.used_vehicles_only = true;
+        self
+    }
+}
+
+/// Deal values a disclosure template can reference.
+#[derive(Debug, Clone, Copy)]
+pub struct DisclosureContext {
+    /// Governing state
+    pub state: StateCode,
+    /// Deal type
+    pub deal_type: DealType,
+    /// Vehicle condition, if known
+    pub vehicle_condition: Option<VehicleCondition>,
+    /// Documentation fee charged
+    pub doc_fee: Decimal,
+}
+
+impl DisclosureContext {
+    fn is_used(&self) -> bool {
+        matches!(
+            self.vehicle_condition,
+            Some(VehicleCondition::Used | VehicleCondition::CertifiedPreOwned)
+        )
+    }
+}
+
+/// Registry of state-mandated disclosures by state and deal type.
+#[derive(Debug, Clone, Default)]
+pub struct StateDisclosureRegistry {
+    entries: HashMap<(StateCode, DealType), Vec<StateDisclosure>>,
+}
+
+impl StateDisclosureRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in disclosure library, built once.
+    pub fn standard() -> &'static StateDisclosureRegistry {
+        static STANDARD: OnceLock<StateDisclosureRegistry> = OnceLock::new();
+        STANDARD.get_or_init(build_standard_registry)
+    }
+
+    /// Require a disclosure for a state and deal type.
+    pub fn register(&mut self, state: StateCode, deal_type: DealType, disclosure: StateDisclosure) {
+        self.entries.entry((state, deal_type)).or_default().push(disclosure);
+    }
+
+    /// Require a disclosure for a state across several deal types.
+    pub fn register_all(&mut self, state: StateCode, deal_types: &[DealType], disclosure: StateDisclosure) {
+        for &deal_type in deal_types {
+            self.register(state, deal_type, disclosure.clone());
+        }
+    }
+
+    /// Disclosures registered for a state and deal type.
+    pub fn required(&self, state: StateCode, deal_type: DealType) -> &[StateDisclosure] {
+        self.entries
+            .get(&(state, deal_type))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// States with at least one registered disclosure.
+    pub fn states(&self) -> Vec<StateCode> {
+        let mut states: Vec<StateCode> = self.entries.keys().map(|(state, _)| *state).collect();
+        states.sort_by_key(|state| state.to_string());
+        states.dedup();
+        states
+    }
+
+    /// Render the disclosures that apply to a deal.
+    ///
+    /// Used-vehicle disclosures are skipped unless the vehicle is known to
+    /// be used.
+    pub fn render(&self, context: &DisclosureContext) -> Vec<Disclosure> {
+        self.required(context.state, context.deal_type)
+            .iter()
+            .filter(|d| !d.used_vehicles_only || context.is_used())
+            .map(|d| Disclosure {
+                code: d.code.clone(),
+                category: "state".to_string(),
+                title: d.title.clone(),
+                text: d.template
+                    .replace("{state}", &context.state.to_string())
+                    .replace("{doc_fee}", &format!("{:.2}", context.doc_fee)),
+                signature_required: d.signature_required,
+                regulations: d.citations.clone(),
+            })
+            .collect()
+    }
+}
+
+/// Build the built-in library.
+fn build_standard_registry() -> StateDisclosureRegistry {
+    use DealType::{Cash, Finance, Lease};
+
+    const ALL: &[DealType] = &[Finance, Lease, Cash];
+    const PURCHASE: &[DealType] = &[Finance, Cash];
+
+    let mut registry = StateDisclosureRegistry::new();
+
+    // California
+    registry.register_all(StateCode::CA, PURCHASE, StateDisclosure::new(
+        "CA-CAR-BUYERS",
+        "California Car Buyer's Bill of Rights",
+        "You have certain rights under California law when you buy a vehicle, \
+         including the right to a copy of the contract in the language in which \
+         it was negotiated.",
+        true,
+        &["CA Vehicle Code 11713.21", "CA Civil Code 1632"],
+    ));
+    registry.register_all(StateCode::CA, PURCHASE, StateDisclosure::new(
+        "CA-CONTRACT-CANCEL",
+        "Contract Cancellation Option",
+        "If this used vehicle's purchase price is under $40,000, you may buy a \
+         contract cancellation option allowing you to return it within two days.",
+        true,
+        &["CA Vehicle Code 11713.21"],
+    ).used_only());
+    registry.register(StateCode::CA, Lease, StateDisclosure::new(
+        "CA-LEASE-NOTICE",
+        "California Vehicle Leasing Act Notice",
+        "Do not sign this lease before you read it or if it contains any blank \
+         spaces to be filled in. You are entitled to a completely filled-in copy \
+         of this lease.",
+        true,
+        &["CA Civil Code 2985.8"],
+    ));
+    registry.register_all(StateCode::CA, ALL, StateDisclosure::new(
+        "CA-DOC-FEE",
+        "Document Processing Charge",
+        "The document processing charge of ${doc_fee} is not a governmental fee.",
+        false,
+        &["CA Vehicle Code 4456.5"],
+    ));
+
+    // Texas
+    registry.register_all(StateCode::TX, ALL, StateDisclosure::new(
+        "TX-DOC-FEE",
+        "Documentary Fee Notice",
+        "A documentary fee is not an official fee. A documentary fee is not \
+         required by law, but may be charged to buyers for handling documents \
+         relating to the sale. A documentary fee may not exceed a reasonable \
+         amount agreed to by the parties. This notice is required by law.",
+        false,
+        &["Tex. Fin. Code 348.006"],
+    ));
+    registry.register(StateCode::TX, Finance, StateDisclosure::new(
+        "TX-RISC-NOTICE",
+        "Retail Installment Contract Notice",
+        "Notice to the buyer: do not sign this contract before you read it or if \
+         it contains any blank spaces. You are entitled to an exact copy of the \
+         contract you sign.",
+        true,
+        &["Tex. Fin. Code 348.101"],
+    ));
+
+    // New York
+    registry.register(StateCode::NY, Finance, StateDisclosure::new(
+        "NY-RISC-NOTICE",
+        "Motor Vehicle Retail Instalment Contract Notice",
+        "Do not sign this contract before you read it or if it contains any \
+         blank space. You are entitled to a completely filled-in copy.",
+        true,
+        &["NY Personal Property Law 302"],
+    ));
+    registry.register(StateCode::NY, Lease, StateDisclosure::new(
+        "NY-LEASE-NOTICE",
+        "Motor Vehicle Retail Leasing Act Disclosures",
+        "You may have to pay a substantial charge if you end this lease early. \
+         The charge may be up to several thousand dollars. The actual charge \
+         will depend on when the lease is terminated.",
+        true,
+        &["NY Personal Property Law 334"],
+    ));
+    registry.register_all(StateCode::NY, PURCHASE, StateDisclosure::new(
+        "NY-USED-LEMON",
+        "Used Car Lemon Law Rights",
+        "New York's used car lemon law requires the dealer to give a written \
+         warranty on used vehicles sold for $1,500 or more.",
+        false,
+        &["NY Gen. Bus. Law 198-b"],
+    ).used_only());
+
+    // Florida
+    registry.register_all(StateCode::FL, ALL, StateDisclosure::new(
+        "FL-DEALER-FEE",
+        "Dealer Fee Notice",
+        "This charge of ${doc_fee} represents costs and profit to the dealer for \
+         items such as inspecting, cleaning, and adjusting vehicles, and \
+         preparing documents related to the sale.",
+        false,
+        &["Fla. Stat. 501.976"],
+    ));
+
+    // Georgia
+    registry.register_all(StateCode::GA, ALL, StateDisclosure::new(
+        "GA-TAVT",
+        "Title Ad Valorem Tax Notice",
+        "Georgia charges a one-time title ad valorem tax in place of sales tax \
+         and annual ad valorem tax on this vehicle.",
+        false,
+        &["O.C.G.A. 48-5C-1"],
+    ));
+
+    // North Carolina
+    registry.register_all(StateCode::NC, ALL, StateDisclosure::new(
+        "NC-ADMIN-FEE",
+        "Administrative Fee Notice",
+        "The administrative fee of ${doc_fee} is charged by the dealer and is \
+         not a governmental fee.",
+        false,
+        &["N.C. Gen. Stat. 20-101.1"],
+    ));
+
+    // Illinois
+    registry.register_all(StateCode::IL, ALL, StateDisclosure::new(
+        "IL-DOC-FEE",
+        "Documentary Fee Notice",
+        "This documentary fee of ${doc_fee} is not a governmental fee and is not \
+         required by law. This notice is required by law.",
+        false,
+        &["815 ILCS 375/11.1"],
+    ));
+
+    // Pennsylvania
+    registry.register(StateCode::PA, Finance, StateDisclosure::new(
+        "PA-RISC-NOTICE",
+        "Installment Sale Contract Notice",
+        "Notice to buyer: do not sign this contract in blank. You are entitled \
+         to an exact copy of the contract you sign.",
+        true,
+        &["12 Pa.C.S. 6221"],
+    ));
+
+    // Ohio
+    registry.register(StateCode::OH, Finance, StateDisclosure::new(
+        "OH-RISC-NOTICE",
+        "Retail Installment Contract Notice",
+        "Notice to the buyer: do not sign this contract before you read it or \
+         if it contains any blank spaces.",
+        true,
+        &["Ohio Rev. Code 1317.06"],
+    ));
+
+    // New Jersey
+    registry.register_all(StateCode::NJ, ALL, StateDisclosure::new(
+        "NJ-DOC-FEE",
+        "Documentary Service Fee Notice",
+        "The documentary service fee of ${doc_fee} is charged by the dealer and \
+         is not a governmental fee.",
+        false,
+        &["N.J.A.C. 13:45A-26B"],
+    ));
+
+    // Colorado
+    registry.register_all(StateCode::CO, ALL, StateDisclosure::new(
+        "CO-DEALER-HANDLING",
+        "Dealer Handling Fee Notice",
+        "The dealer handling fee of ${doc_fee} is not a governmental fee and is \
+         retained by the dealer.",
+        false,
+        &["C.R.S. 44-20-121"],
+    ));
+
+    // Washington
+    registry.register_all(StateCode::WA, ALL, StateDisclosure::new(
+        "WA-DOC-FEE",
+        "Documentary Service Fee Notice",
+        "A negotiable documentary service fee of ${doc_fee} has been added to the \
+         sale price or the capitalized cost.",
+        false,
+        &["RCW 46.70.180"],
+    ));
+
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn context(state: StateCode, deal_type: DealType) -> DisclosureContext {
+        DisclosureContext {
+            state,
+            deal_type,
+            vehicle_condition: Some(VehicleCondition::New),
+            doc_fee: dec!(85),
+        }
+    }
+
+    fn codes(disclosures: &[Disclosure]) -> Vec<&str> {
+        disclosures.iter().map(|d| d.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_ca_lease_disclosures() {
+        let disclosures = StateDisclosureRegistry::standard().render(&context(StateCode::CA, DealType::Lease));
+
+        assert_eq!(codes(&disclosures), vec!["CA-LEASE-NOTICE", "CA-DOC-FEE"]);
+        assert!(disclosures[0].signature_required);
+        assert_eq!(disclosures[0].regulations, vec!["CA Civil Code 2985.8"]);
+        assert_eq!(disclosures[1].text, "The document processing charge of $85.00 is not a governmental fee.");
+        assert!(disclosures.iter().all(|d| d.category == "state"));
+    }
+
+    #[test]
+    fn test_tx_finance_disclosures() {
+        let disclosures = StateDisclosureRegistry::standard().render(&context(StateCode::TX, DealType::Finance));
+
+        assert_eq!(codes(&disclosures), vec!["TX-DOC-FEE", "TX-RISC-NOTICE"]);
+        assert!(!disclosures[0].signature_required);
+        assert!(disclosures[1].signature_required);
+    }
+
+    #[test]
+    fn test_ny_lease_disclosures() {
+        let disclosures = StateDisclosureRegistry::standard().render(&context(StateCode::NY, DealType::Lease));
+
+        assert_eq!(codes(&disclosures), vec!["NY-LEASE-NOTICE"]);
+        assert_eq!(disclosures[0].regulations, vec!["NY Personal Property Law 334"]);
+    }
+
+    #[test]
+    fn test_used_vehicle_disclosures() {
+        let registry = StateDisclosureRegistry::standard();
+        let mut ctx = context(StateCode::CA, DealType::Finance);
+        assert!(!codes(&registry.render(&ctx)).contains(&"CA-CONTRACT-CANCEL"));
+
+        ctx.vehicle_condition = Some(VehicleCondition::Used);
+        assert!(codes(&registry.render(&ctx)).contains(&"CA-CONTRACT-CANCEL"));
+    }
+
+    #[test]
+    fn test_standard_registry_coverage() {
+        let registry = StateDisclosureRegistry::standard();
+        assert!(registry.states().len() >= 10);
+        assert!(registry.required(StateCode::AK, DealType::Finance).is_empty());
+
+        let mut custom = StateDisclosureRegistry::new();
+        custom.register(StateCode::AK, DealType::Cash, StateDisclosure::new(
+            "AK-TEST", "Test", "{state} notice", false, &[],
+        ));
+        let rendered = custom.render(&context(StateCode::AK, DealType::Cash));
+        assert_eq!(rendered[0].text, "AK notice");
+    }
+}
@@ -0,0 +1,308 @@
+//! Deal solvers.
+//!
+//! The pipeline computes forward: deal terms in, payment out. Solvers answer
+//! the reverse desking questions ("how much down to get to $450/month?") by
+//! re-running the pipeline through P5 and searching the input.
+//!
+//! Solvers only vary the field being solved for; everything else (rebates,
+//! trade, fees, products, rate) is taken from the base input.
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+use crate::phases::{self, p5_structure::DealStructure, PipelineOptions};
+use crate::types::{DealInput, DealType, ProgramProfile, UdcError, UdcResult};
+
+/// Search granularity for cash down (one cent)
+const CENT: Decimal = dec!(0.01);
+
+/// Solve for the cash down needed to reach a target monthly payment.
+///
+/// Binary-searches cash down between zero and "full down" and returns the
+/// smallest down, to the cent, whose payment does not exceed the target.
+/// Full down is the zero-down amount financed for a finance deal, and the
+/// down that brings the adjusted cap cost to the residual for a lease.
+///
+/// # Edge Cases
+/// - Target at or above the zero-down payment: returns zero
+/// - Target below the payment at full down: returns a validation error
+///   (for leases the rent charge on the residual is the floor)
+///
+/// # Errors
+/// - Cash deals have no payment to solve for
+/// - Any pipeline error from P0-P5
+pub fn solve_cash_down_for_payment(
+    base_input: &DealInput,
+    target_payment: Decimal,
+    program_profile: Option<&ProgramProfile>,
+) -> UdcResult<Decimal> {
+    if base_input.deal_type == DealType::Cash {
+        return Err(UdcError::validation_field(
+            "Cash deals have no payment to solve for",
+            "deal_type",
+        ));
+    }
+    if target_payment < dec!(0) {
+        return Err(UdcError::validation_field(
+            "Target payment cannot be negative",
+            "target_payment",
+        ));
+    }
+
+    let structure_at = |cash_down: Decimal| -> UdcResult<DealStructure> {
+        let mut input = base_input.clone();
+        input.cash_down = cash_down;
+        build_structure(input, program_profile)
+    };
+
+    let zero_down = structure_at(dec!(0))?;
+    if monthly_payment(&zero_down)? <= target_payment {
+        return Ok(dec!(0));
+    }
+
+    let full_down = full_down(&zero_down);
+    let payment_at_full_down = monthly_payment(&structure_at(full_down)?)?;
+    if payment_at_full_down > target_payment {
+        return Err(UdcError::validation_field(
+            format!(
+                "Target payment {} is unreachable; payment with {} down is {}",
+                target_payment, full_down, payment_at_full_down
+            ),
+            "target_payment",
+        ));
+    }
+
+    // Payment is non-increasing in cash down: search whole cents for the
+    // smallest down that meets the target
+    let mut low = 0_i64;
+    let mut high = (full_down / CENT).ceil().try_into().unwrap_or(i64::MAX);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if monthly_payment(&structure_at(Decimal::from(mid) * CENT)?)? <= target_payment {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    Ok(Decimal::from(high) * CENT)
+}
+
+/// Solve for the shortest term whose monthly payment meets a target.
+///
+/// Evaluates `allowed_terms` from shortest to longest and returns the first
+/// whose payment is at or below the target. When a program profile is given,
+/// terms it does not offer (`available_terms`) or that exceed the limit for
+/// the vehicle's age (`max_term_by_age`) are skipped.
+///
+/// # Errors
+/// - Cash deals have no payment to solve for
+/// - No allowed term is offered by the program
+/// - No offered term brings the payment down to the target
+/// - Any pipeline error from P0-P5
+pub fn solve_term_for_payment(
+    base_input: &DealInput,
+    target_payment: Decimal,
+    allowed_terms: &[u32],
+    program_profile: Option<&ProgramProfile>,
+) -> UdcResult<u32> {
+    if base_input.deal_type == DealType::Cash {
+        return Err(UdcError::validation_field(
+            "Cash deals have no payment to solve for",
+            "deal_type",
+        ));
+    }
+
+    let vehicle_age = base_input.vehicle_age();
+    let mut terms: Vec<u32> = allowed_terms
+        .iter()
+        .copied()
+        .filter(|&term| term > 0)
+        .filter(|&term| {
+            program_profile.is_none_or(|program| program.is_term_available(term, vehicle_age))
+        })
+        .collect();
+    terms.sort_unstable();
+    terms.dedup();
+
+    if terms.is_empty() {
+        return Err(UdcError::validation_field(
+            "None of the allowed terms are offered by the program",
+            "allowed_terms",
+        ));
+    }
+
+    let mut last_payment = dec!(0);
+    for &term in &terms {
+        let mut input = base_input.clone();
+        if let Some(params) = input.finance_params.as_mut() {
+            params.term_months = term;
+        }
+        if let Some(params) = input.lease_params.as_mut() {
+            params.term_months = term;
+        }
+
+        last_payment = monthly_payment(&build_structure(input, program_profile)?)?;
+        if last_payment <= target_payment {
+            return Ok(term);
+        }
+    }
+
+    Err(UdcError::validation_field(
+        format!(
+            "Target payment {} is unreachable; payment at {} months is {}",
+            target_payment,
+            terms[terms.len() - 1],
+            last_payment
+        ),
+        "target_payment",
+    ))
+}
+
+/// Run P0-P5 and return the deal structure.
+fn build_structure(
+    input: DealInput,
+    program_profile: Option<&ProgramProfile>,
+) -> UdcResult<DealStructure> {
+    let options = PipelineOptions::default();
+    let normalized = phases::normalize_deal_input(input)?;
+    let routed = phases::route_deal(normalized)?;
+    let jurisdictioned = phases::resolve_jurisdiction(routed)?;
+    let profiled = phases::load_profiles_with_program(jurisdictioned, program_profile)?;
+    let taxed = phases::calculate_tax_with_options(profiled, &options)?;
+    Ok(phases::build_structure_with_options(taxed, &options)?.structure)
+}
+
+/// Monthly payment of a structure (finance payment or lease total).
+fn monthly_payment(structure: &DealStructure) -> UdcResult<Decimal> {
+    match structure {
+        DealStructure::Finance(finance) => Ok(finance.monthly_payment),
+        DealStructure::Lease(lease) => Ok(lease.total_monthly_payment),
+        DealStructure::Cash(_) => Err(UdcError::calculation(
+            "Cash deals have no monthly payment",
+            "SOLVER",
+        )),
+    }
+}
+
+/// Upper bound for cash down, taken from the zero-down structure.
+fn full_down(zero_down: &DealStructure) -> Decimal {
+    match zero_down {
+        DealStructure::Finance(finance) => finance.amount_financed,
+        DealStructure::Lease(lease) => (lease.adjusted_cap_cost - lease.residual_value).max(dec!(0)),
+        DealStructure::Cash(_) => dec!(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DealFees, FinanceParams, MoneyFactor, StateCode};
+
+    fn make_finance_input() -> DealInput {
+        DealInput {
+            fees: DealFees {
+                doc_fee: Some(dec!(150)),
+                ..Default::default()
+            },
+            finance_params: Some(FinanceParams::new(60, dec!(0.0599))),
+            ..DealInput::new(DealType::Finance, dec!(30000), StateCode::TX)
+        }
+    }
+
+    #[test]
+    fn test_solve_cash_down_mid_range() {
+        let input = make_finance_input();
+        let target = dec!(450);
+
+        let down = solve_cash_down_for_payment(&input, target, None).unwrap();
+        assert!(down > dec!(0));
+
+        let mut solved = input.clone();
+        solved.cash_down = down;
+        let payment = monthly_payment(&build_structure(solved, None).unwrap()).unwrap();
+        assert!(payment <= target);
+        assert!(target - payment <= CENT, "payment {} not within a cent of {}", payment, target);
+
+        // One cent less down misses the target
+        let mut short = input;
+        short.cash_down = down - CENT;
+        assert!(monthly_payment(&build_structure(short, None).unwrap()).unwrap() > target);
+    }
+
+    #[test]
+    fn test_solve_cash_down_target_above_zero_down_payment() {
+        let down = solve_cash_down_for_payment(&make_finance_input(), dec!(1000), None).unwrap();
+        assert_eq!(down, dec!(0));
+    }
+
+    #[test]
+    fn test_solve_cash_down_unreachable() {
+        let mut input = make_finance_input();
+        input.deal_type = DealType::Lease;
+        input.finance_params = None;
+        input.lease_params = Some(crate::types::LeaseParams::new(36, MoneyFactor::new(dec!(0.00125)), dec!(0.55), 12000));
+
+        // Rent charge on the residual alone exceeds $10/month
+        let result = solve_cash_down_for_payment(&input, dec!(10), None);
+        assert!(matches!(result, Err(UdcError::Validation { .. })));
+    }
+
+    fn make_term_program() -> ProgramProfile {
+        use crate::types::{
+            EligibilityRules, ProgramFeeConfig, ProgramMeta, RateSheet, StructureRules,
+        };
+
+        ProgramProfile {
+            id: "bank-2024".to_string(),
+            name: "Bank Auto".to_string(),
+            lender_code: "BANK".to_string(),
+            program_type: DealType::Finance,
+            is_captive: false,
+            brand: None,
+            rate_sheet: RateSheet {
+                effective_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                expiration_date: None,
+                tiers: vec![],
+                reserve_caps: vec![],
+                default_max_reserve: dec!(0),
+                special_rates: vec![],
+            },
+            structure_rules: StructureRules {
+                available_terms: vec![36, 48, 60, 72],
+                ..Default::default()
+            },
+            fee_config: ProgramFeeConfig::default(),
+            eligibility: EligibilityRules::default(),
+            lease_program: None,
+            meta: ProgramMeta::default(),
+        }
+    }
+
+    #[test]
+    fn test_solve_term_selects_72_when_60_too_high() {
+        let input = make_finance_input();
+        let program = make_term_program();
+
+        let mut at_60 = input.clone();
+        at_60.finance_params.as_mut().unwrap().term_months = 60;
+        let payment_60 = monthly_payment(&build_structure(at_60, None).unwrap()).unwrap();
+        let target = dec!(600);
+        assert!(payment_60 > target);
+
+        // 84 is requested but not offered by the program
+        let term = solve_term_for_payment(&input, target, &[48, 60, 72, 84], Some(&program)).unwrap();
+        assert_eq!(term, 72);
+    }
+
+    #[test]
+    fn test_solve_term_unreachable() {
+        let program = make_term_program();
+        let result = solve_term_for_payment(&make_finance_input(), dec!(300), &[60, 72, 84], Some(&program));
+        assert!(matches!(result, Err(UdcError::Validation { .. })));
+
+        // No requested term is offered
+        let result = solve_term_for_payment(&make_finance_input(), dec!(1000), &[84], Some(&program));
+        assert!(matches!(result, Err(UdcError::Validation { .. })));
+    }
+}
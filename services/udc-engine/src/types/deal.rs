@@ -74,6 +74,13 @@ pub struct TradeIn {
     pub payoff_amount: Money,
     /// Actual Cash Value for tax purposes (may differ from gross)
     pub acv: Option<Money>,
+    /// Whether the trade is titled to the buyer
+    #[serde(default = "default_true")]
+    pub titled_to_buyer: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl TradeIn {
@@ -128,6 +135,10 @@ pub struct Vehicle {
     pub selling_price: Money,
     /// Odometer reading
     pub odometer: u32,
+    /// Destination/freight charged on this vehicle. Part of its price and
+    /// its cost, not a dealer fee.
+    #[serde(default)]
+    pub destination_fee: Option<Money>,
 }
 
 /// Jurisdiction information for tax calculations.
@@ -296,6 +307,7 @@ mod tests {
             gross_value: Money::new(rust_decimal_macros::dec!(15000)),
             payoff_amount: Money::new(rust_decimal_macros::dec!(12000)),
             acv: None,
+            titled_to_buyer: true,
         };
 
         assert_eq!(
@@ -312,6 +324,7 @@ mod tests {
             gross_value: Money::new(rust_decimal_macros::dec!(20000)),
             payoff_amount: Money::new(rust_decimal_macros::dec!(25000)),
             acv: None,
+            titled_to_buyer: true,
         };
 
         assert_eq!(
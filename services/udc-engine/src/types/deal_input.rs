@@ -4,20 +4,38 @@
 
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use chrono::NaiveDate;
+use chrono::{Datelike, NaiveDate};
+use uuid::Uuid;
 
-use super::{DealType, Money, StateCode};
+use super::{DealType, DealerProfile, LtvBasis, MoneyFactor, Rate, StateCode, TradeIn, Vehicle};
 
 /// Primary input structure for the UDC engine.
 /// Contains all information needed to calculate a deal.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DealInput {
+    /// Caller-supplied deal identifier (generated if not provided)
+    #[serde(default)]
+    pub deal_id: Option<Uuid>,
+
     /// Type of transaction (Cash, Finance, Lease)
     pub deal_type: DealType,
 
     /// Vehicle selling price (agreed upon price before rebates/incentives)
     pub vehicle_price: Decimal,
 
+    /// Vehicle details (MSRP, invoice) used for profit analysis
+    #[serde(default)]
+    pub vehicle: Option<Vehicle>,
+
+    /// Valuations used as the loan-to-value basis (MSRP and invoice
+    /// default to the vehicle's)
+    #[serde(default)]
+    pub vehicle_values: Option<VehicleValues>,
+
+    /// Dealer configuration (pack, holdback) loaded in P3
+    #[serde(default)]
+    pub dealer_profile: Option<DealerProfile>,
+
     /// Trade-in vehicle value (if any)
     #[serde(default)]
     pub trade_in_value: Option<Decimal>,
@@ -26,6 +44,28 @@ pub struct DealInput {
     #[serde(default)]
     pub trade_in_payoff: Option<Decimal>,
 
+    /// Actual cash value of the trade-in (defaults to the allowance)
+    #[serde(default)]
+    pub trade_in_acv: Option<Decimal>,
+
+    /// Whether the trade-in is titled to the buyer (some states credit
+    /// only the buyer's own trade)
+    #[serde(default = "default_true")]
+    pub trade_in_titled_to_buyer: bool,
+
+    /// Date through which the trade-in payoff quote is good
+    #[serde(default)]
+    pub trade_in_payoff_good_through: Option<NaiveDate>,
+
+    /// Daily interest accruing on the trade-in payoff after the good-through date
+    #[serde(default)]
+    pub trade_in_per_diem: Option<Decimal>,
+
+    /// Further trade-ins beyond the one in `trade_in_value`; allowances and
+    /// payoffs are summed with it
+    #[serde(default)]
+    pub additional_trades: Vec<TradeIn>,
+
     /// Cash down payment from customer
     #[serde(default)]
     pub cash_down: Decimal,
@@ -38,6 +78,14 @@ pub struct DealInput {
     #[serde(default)]
     pub products: Vec<Product>,
 
+    /// Amount of the price exempt from sales tax (e.g., an EV exemption)
+    #[serde(default)]
+    pub exempt_amount: Option<Decimal>,
+
+    /// Why the exemption applies, shown in the tax disclosure
+    #[serde(default)]
+    pub exempt_reason: Option<String>,
+
     /// Deal fees (doc fee, registration, etc.)
     pub fees: DealFees,
 
@@ -51,6 +99,13 @@ pub struct DealInput {
     #[serde(default)]
     pub garaging_state: Option<StateCode>,
 
+    /// Combined sales tax rate for the customer's address, when the host
+    /// already knows it. Replaces the engine's state/local rate lookup, and
+    /// the special tax rate in TAVT/HUT/excise states, for retail and lease
+    /// tax alike.
+    #[serde(default)]
+    pub override_combined_rate: Option<Rate>,
+
     /// Customer information
     pub customer: CustomerInfo,
 
@@ -69,14 +124,83 @@ pub struct DealInput {
     /// First payment date (calculated if not provided)
     #[serde(default)]
     pub first_payment_date: Option<NaiveDate>,
+
+    /// Date the contract starts when it differs from the billing cycle; the
+    /// partial first period is prorated (finance odd-days interest, lease
+    /// interim rent)
+    #[serde(default)]
+    pub contract_start_date: Option<NaiveDate>,
+
+    /// Date the deal funds (defaults to deal_date)
+    #[serde(default)]
+    pub funding_date: Option<NaiveDate>,
 }
 
 impl DealInput {
-    /// Calculate the net trade-in value (value minus payoff)
+    /// Deal of the given type bought and registered in `state`, with every
+    /// optional field at its default (no trade, down payment, rebates,
+    /// products, fees or finance/lease parameters)
+    pub fn new(deal_type: DealType, vehicle_price: Decimal, state: StateCode) -> Self {
+        Self {
+            deal_id: None,
+            deal_type,
+            vehicle_price,
+            vehicle: None,
+            vehicle_values: None,
+            dealer_profile: None,
+            trade_in_value: None,
+            trade_in_payoff: None,
+            trade_in_acv: None,
+            trade_in_titled_to_buyer: true,
+            trade_in_payoff_good_through: None,
+            trade_in_per_diem: None,
+            additional_trades: vec![],
+            cash_down: Decimal::ZERO,
+            rebates: vec![],
+            products: vec![],
+            exempt_amount: None,
+            exempt_reason: None,
+            fees: DealFees::default(),
+            home_state: state,
+            transaction_state: state,
+            garaging_state: None,
+            override_combined_rate: None,
+            customer: CustomerInfo::default(),
+            finance_params: None,
+            lease_params: None,
+            deal_date: None,
+            first_payment_date: None,
+            contract_start_date: None,
+            funding_date: None,
+        }
+    }
+
+    /// Calculate the net trade-in value (value minus payoff) across all trades
     pub fn net_trade(&self) -> Decimal {
-        let value = self.trade_in_value.unwrap_or_default();
-        let payoff = self.trade_in_payoff.unwrap_or_default();
-        value - payoff
+        self.total_trade_value() - self.total_trade_payoff()
+    }
+
+    /// Whether the deal has any trade-in
+    pub fn has_trade(&self) -> bool {
+        self.trade_in_value.is_some() || self.trade_in_payoff.is_some() || !self.additional_trades.is_empty()
+    }
+
+    /// Gross trade allowance across all trades
+    pub fn total_trade_value(&self) -> Decimal {
+        self.trade_in_value.unwrap_or_default()
+            + self.additional_trades.iter().map(|t| t.gross_value.as_decimal()).sum::<Decimal>()
+    }
+
+    /// Trade payoff across all trades
+    pub fn total_trade_payoff(&self) -> Decimal {
+        self.trade_in_payoff.unwrap_or_default()
+            + self.additional_trades.iter().map(|t| t.payoff_amount.as_decimal()).sum::<Decimal>()
+    }
+
+    /// Actual cash value across all trades (each defaults to its allowance)
+    pub fn total_trade_acv(&self) -> Decimal {
+        let primary = self.trade_in_acv.or(self.trade_in_value).unwrap_or_default();
+        primary + self.additional_trades.iter().map(|t| t.acv.unwrap_or(t.gross_value).as_decimal()).sum::<Decimal>()
     }
 
     /// Check if there is negative equity in the trade
@@ -89,10 +213,73 @@ impl DealInput {
         self.rebates.iter().map(|r| r.amount).sum()
     }
 
+    /// Rebates flagged to reduce the tax basis
+    pub fn tax_basis_rebates(&self) -> Decimal {
+        self.rebates.iter().filter(|r| r.reduces_tax_basis).map(|r| r.amount).sum()
+    }
+
+    /// Rebates flagged to reduce the lease cap cost
+    pub fn cap_cost_rebates(&self) -> Decimal {
+        self.rebates.iter().filter(|r| r.reduces_cap_cost).map(|r| r.amount).sum()
+    }
+
+    /// Rebates that reduce the lease cap cost but not the tax basis
+    pub fn taxable_cap_cost_rebates(&self) -> Decimal {
+        self.rebates
+            .iter()
+            .filter(|r| r.reduces_cap_cost && !r.reduces_tax_basis)
+            .map(|r| r.amount)
+            .sum()
+    }
+
     /// Calculate total F&I products cost
     pub fn total_products(&self) -> Decimal {
         self.products.iter().map(|p| p.price).sum()
     }
+
+    /// Vehicle value used as the loan-to-value denominator, if known
+    pub fn ltv_basis_value(&self, basis: LtvBasis) -> Option<Decimal> {
+        let values = self.vehicle_values.as_ref();
+        let value = match basis {
+            LtvBasis::Msrp => values
+                .and_then(|v| v.msrp)
+                .or_else(|| self.vehicle.as_ref().map(|v| v.msrp.as_decimal())),
+            LtvBasis::Invoice => values
+                .and_then(|v| v.invoice)
+                .or_else(|| self.vehicle.as_ref().and_then(|v| v.invoice).map(|i| i.as_decimal())),
+            LtvBasis::BookValue => values.and_then(|v| v.book_value),
+        };
+        value.filter(|v| *v > Decimal::ZERO)
+    }
+
+    /// Deal date, or today when none is given
+    pub fn effective_deal_date(&self) -> NaiveDate {
+        self.deal_date.unwrap_or_else(|| chrono::Local::now().date_naive())
+    }
+
+    /// Vehicle age in model years as of the deal date (0 = current model year)
+    pub fn vehicle_age(&self) -> Option<u32> {
+        let vehicle = self.vehicle.as_ref()?;
+        let deal_date = self.effective_deal_date();
+        let age = deal_date.year() - i32::from(vehicle.year);
+        Some(age.max(0) as u32)
+    }
+}
+
+/// Vehicle valuations a lender may measure loan-to-value against
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VehicleValues {
+    /// Manufacturer's suggested retail price
+    #[serde(default)]
+    pub msrp: Option<Decimal>,
+
+    /// Dealer invoice
+    #[serde(default)]
+    pub invoice: Option<Decimal>,
+
+    /// Guide book value (NADA, KBB, etc.)
+    #[serde(default)]
+    pub book_value: Option<Decimal>,
 }
 
 /// Rebate or incentive applied to the deal
@@ -110,10 +297,16 @@ pub struct Rebate {
     /// Type of rebate
     pub rebate_type: RebateType,
 
-    /// Whether this rebate is taxable (reduces taxable amount)
+    /// Whether this rebate reduces the taxable amount (where the state
+    /// allows rebates to reduce it)
     #[serde(default = "default_true")]
     pub reduces_tax_basis: bool,
 
+    /// Whether this rebate reduces the lease cap cost; otherwise it is paid
+    /// toward the amount due at signing
+    #[serde(default = "default_true")]
+    pub reduces_cap_cost: bool,
+
     /// Program code from manufacturer
     #[serde(default)]
     pub program_code: Option<String>,
@@ -174,7 +367,8 @@ pub struct Product {
     #[serde(default)]
     pub deductible: Option<Decimal>,
 
-    /// Whether this product is taxable in the transaction state
+    /// Whether this product is taxable in the transaction state.
+    /// Informational only - P4 applies the state's ancillary rules by product type.
     #[serde(default = "default_true")]
     pub taxable: bool,
 }
@@ -212,9 +406,10 @@ pub enum ProductType {
 /// Fees associated with the deal
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DealFees {
-    /// Documentary/administrative fee
+    /// Documentary/administrative fee (none charges the dealer profile's
+    /// default doc fee; an explicit zero charges none)
     #[serde(default)]
-    pub doc_fee: Decimal,
+    pub doc_fee: Option<Decimal>,
 
     /// Title fee
     #[serde(default)]
@@ -228,6 +423,10 @@ pub struct DealFees {
     #[serde(default)]
     pub plate_fee: Decimal,
 
+    /// Plate is transferred from the customer's existing vehicle
+    #[serde(default)]
+    pub plate_transfer: bool,
+
     /// Inspection fee
     #[serde(default)]
     pub inspection_fee: Decimal,
@@ -248,6 +447,12 @@ pub struct DealFees {
     #[serde(default)]
     pub destination_fee: Decimal,
 
+    /// Destination is already part of `vehicle_price`. The fee is then
+    /// informational only: it is not charged again, and states that do not
+    /// tax destination take it back out of the taxable price.
+    #[serde(default)]
+    pub destination_included_in_price: bool,
+
     /// Dealer handling/prep fee
     #[serde(default)]
     pub dealer_handling_fee: Decimal,
@@ -256,6 +461,10 @@ pub struct DealFees {
     #[serde(default)]
     pub acquisition_fee: Decimal,
 
+    /// Lender bank/origination fee (finance)
+    #[serde(default)]
+    pub bank_fee: Decimal,
+
     /// Disposition fee (lease - usually not upfront)
     #[serde(default)]
     pub disposition_fee: Decimal,
@@ -263,9 +472,93 @@ pub struct DealFees {
     /// Other miscellaneous fees
     #[serde(default)]
     pub other_fees: Vec<OtherFee>,
+
+    /// Which fees are capitalized into a lease (the rest are paid upfront)
+    #[serde(default)]
+    pub capitalize: LeaseFeeCapitalization,
+
+    /// Apportioned (IRP) registration: `registration_fee` is split across
+    /// jurisdictions by mileage. Fleet/commercial buyers only.
+    #[serde(default)]
+    pub apportioned_registration: Option<ApportionedRegistration>,
+}
+
+/// Apportioned (IRP) registration for a fleet vehicle operated across
+/// jurisdictions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApportionedRegistration {
+    /// Miles driven in each jurisdiction over the reporting period
+    pub jurisdictions: Vec<JurisdictionMiles>,
+}
+
+impl ApportionedRegistration {
+    /// Total miles across all jurisdictions
+    pub fn total_miles(&self) -> u64 {
+        self.jurisdictions.iter().map(|j| u64::from(j.miles)).sum()
+    }
+}
+
+/// Miles driven in one jurisdiction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JurisdictionMiles {
+    /// Jurisdiction
+    pub state: StateCode,
+    /// Miles driven there
+    pub miles: u32,
+}
+
+/// Per-fee lease capitalization choices.
+///
+/// Every fee is capitalized into the gross cap cost unless its flag is
+/// cleared, in which case it is paid upfront as part of due at signing. The
+/// acquisition fee is controlled by `LeaseParams::cap_acquisition_fee` and
+/// other fees by `OtherFee::capitalize`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaseFeeCapitalization {
+    /// Capitalize the documentary fee
+    #[serde(default = "default_true")]
+    pub doc_fee: bool,
+
+    /// Capitalize the destination/freight charge
+    #[serde(default = "default_true")]
+    pub destination_fee: bool,
+
+    /// Capitalize the dealer handling/prep fee
+    #[serde(default = "default_true")]
+    pub dealer_handling_fee: bool,
+
+    /// Capitalize the electronic filing fee
+    #[serde(default = "default_true")]
+    pub electronic_filing_fee: bool,
+
+    /// Capitalize government fees (title, registration, plate, etc.)
+    #[serde(default = "default_true")]
+    pub government_fees: bool,
+}
+
+impl Default for LeaseFeeCapitalization {
+    fn default() -> Self {
+        Self {
+            doc_fee: true,
+            destination_fee: true,
+            dealer_handling_fee: true,
+            electronic_filing_fee: true,
+            government_fees: true,
+        }
+    }
 }
 
 impl DealFees {
+    /// Destination charged on top of the vehicle price (zero when it is
+    /// bundled into the price)
+    pub fn charged_destination_fee(&self) -> Decimal {
+        if self.destination_included_in_price {
+            Decimal::ZERO
+        } else {
+            self.destination_fee
+        }
+    }
+
     /// Sum of all government/regulatory fees
     pub fn total_government_fees(&self) -> Decimal {
         self.title_fee
@@ -276,18 +569,23 @@ impl DealFees {
             + self.smog_fee
     }
 
+    /// Doc fee charged (zero when none is given)
+    pub fn doc_fee_amount(&self) -> Decimal {
+        self.doc_fee.unwrap_or_default()
+    }
+
     /// Sum of all dealer fees
     pub fn total_dealer_fees(&self) -> Decimal {
-        self.doc_fee
+        self.doc_fee_amount()
             + self.electronic_filing_fee
-            + self.destination_fee
+            + self.charged_destination_fee()
             + self.dealer_handling_fee
             + self.other_fees.iter().filter(|f| f.dealer_fee).map(|f| f.amount).sum::<Decimal>()
     }
 
     /// Total of all fees
     pub fn total(&self) -> Decimal {
-        self.doc_fee
+        self.doc_fee_amount()
             + self.title_fee
             + self.registration_fee
             + self.plate_fee
@@ -295,11 +593,93 @@ impl DealFees {
             + self.electronic_filing_fee
             + self.tire_fee
             + self.smog_fee
-            + self.destination_fee
+            + self.charged_destination_fee()
             + self.dealer_handling_fee
             + self.acquisition_fee
+            + self.bank_fee
             + self.other_fees.iter().map(|f| f.amount).sum::<Decimal>()
     }
+
+    /// Every fee charged on the deal as a separate item, in a fixed order.
+    ///
+    /// Zero-amount fees, a destination bundled into the price, and the
+    /// disposition fee (due at lease end) are left out, so the items sum to
+    /// [`DealFees::total`].
+    pub fn items(&self) -> Vec<FeeItem<'_>> {
+        let standard = [
+            (FeeKind::Doc, "DOC", "Documentary fee", self.doc_fee_amount()),
+            (FeeKind::Title, "TITLE", "Title fee", self.title_fee),
+            (FeeKind::Registration, "REGISTRATION", "Registration fee", self.registration_fee),
+            (FeeKind::Government, "PLATE", "Plate fee", self.plate_fee),
+            (FeeKind::Government, "INSPECTION", "Inspection fee", self.inspection_fee),
+            (FeeKind::ElectronicFiling, "ELECTRONIC_FILING", "Electronic filing fee", self.electronic_filing_fee),
+            (FeeKind::Government, "TIRE", "Tire fee", self.tire_fee),
+            (FeeKind::Government, "SMOG", "Smog fee", self.smog_fee),
+            (FeeKind::Destination, "DESTINATION", "Destination fee", self.charged_destination_fee()),
+            (FeeKind::DealerHandling, "DEALER_HANDLING", "Dealer handling fee", self.dealer_handling_fee),
+            (FeeKind::Acquisition, "ACQUISITION", "Acquisition fee", self.acquisition_fee),
+            (FeeKind::Bank, "BANK", "Bank fee", self.bank_fee),
+        ];
+
+        let mut items: Vec<FeeItem<'_>> = standard
+            .into_iter()
+            .map(|(kind, code, name, amount)| FeeItem {
+                kind,
+                code: code.to_string(),
+                name: name.to_string(),
+                amount,
+            })
+            .collect();
+
+        items.extend(self.other_fees.iter().map(|fee| FeeItem {
+            kind: FeeKind::Other(fee),
+            code: fee.code.clone().unwrap_or_else(|| fee.name.clone()),
+            name: fee.name.clone(),
+            amount: fee.amount,
+        }));
+
+        items.retain(|item| !item.amount.is_zero());
+        items
+    }
+}
+
+/// Category of a fee, which decides how state taxability and lease
+/// capitalization are resolved for it
+#[derive(Debug, Clone, Copy)]
+pub enum FeeKind<'a> {
+    /// Documentary/administrative fee
+    Doc,
+    /// Title fee
+    Title,
+    /// Registration/license fee
+    Registration,
+    /// Other government fees (plate, inspection, tire, smog)
+    Government,
+    /// Electronic filing fee
+    ElectronicFiling,
+    /// Destination charged on top of the price
+    Destination,
+    /// Dealer handling/prep fee
+    DealerHandling,
+    /// Lease acquisition fee
+    Acquisition,
+    /// Lender bank/origination fee
+    Bank,
+    /// Custom fee, carrying its own flags
+    Other(&'a OtherFee),
+}
+
+/// A single fee charged on the deal (see [`DealFees::items`])
+#[derive(Debug, Clone)]
+pub struct FeeItem<'a> {
+    /// Fee category
+    pub kind: FeeKind<'a>,
+    /// Stable code (the custom fee's code, else its name)
+    pub code: String,
+    /// Display name
+    pub name: String,
+    /// Amount charged
+    pub amount: Decimal,
 }
 
 /// Custom fee entry
@@ -307,14 +687,20 @@ impl DealFees {
 pub struct OtherFee {
     /// Fee name/description
     pub name: String,
+    /// Fee code, matched against state fee taxability overrides
+    #[serde(default)]
+    pub code: Option<String>,
     /// Fee amount
     pub amount: Decimal,
     /// Is this a dealer fee (vs government)
     #[serde(default)]
     pub dealer_fee: bool,
-    /// Is this fee taxable
+    /// Is this fee taxable (a state override for the fee code wins)
     #[serde(default)]
     pub taxable: bool,
+    /// Capitalize this fee into a lease (else paid upfront)
+    #[serde(default = "default_true")]
+    pub capitalize: bool,
 }
 
 /// Customer information
@@ -357,16 +743,32 @@ pub struct CustomerInfo {
     pub city: Option<String>,
 }
 
+impl CustomerInfo {
+    /// Credit tier for program lookups - explicit tier, else derived from score
+    pub fn effective_credit_tier(&self) -> Option<super::CreditTier> {
+        self.credit_tier
+            .map(super::CreditTier::from)
+            .or(self.credit_score.map(super::CreditTier::from_score))
+    }
+}
+
 /// Customer type classification
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CustomerType {
+    /// Retail buyer
     #[default]
     Individual,
+    /// Business buyer
     Business,
+    /// Fleet or commercial buyer (may register under apportioned plates)
     FleetCommercial,
+    /// Government agency
     Government,
+    /// Non-profit organization
     NonProfit,
+    /// Dealer buying for resale
+    Dealer,
 }
 
 /// Credit tier for rate determination
@@ -387,6 +789,19 @@ pub enum CreditTier {
     Tier6,
 }
 
+impl From<CreditTier> for super::CreditTier {
+    fn from(tier: CreditTier) -> Self {
+        match tier {
+            CreditTier::Tier1 => super::CreditTier::Tier1,
+            CreditTier::Tier2 => super::CreditTier::Tier2,
+            CreditTier::Tier3 => super::CreditTier::Tier3,
+            CreditTier::Tier4 => super::CreditTier::Tier4,
+            CreditTier::Tier5 => super::CreditTier::Tier5,
+            CreditTier::Tier6 => super::CreditTier::Tier6,
+        }
+    }
+}
+
 /// Finance-specific parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FinanceParams {
@@ -415,6 +830,203 @@ pub struct FinanceParams {
     /// Days to first payment (if deferred)
     #[serde(default)]
     pub days_to_first_payment: Option<u32>,
+
+    /// Lender fee for deferring the first payment. A prepaid finance
+    /// charge under TILA: collected at signing, it adds to the finance
+    /// charge and disclosed APR but not to the loan principal
+    #[serde(default)]
+    pub deferral_fee: Option<Decimal>,
+
+    /// Payment frequency (defaults to monthly)
+    #[serde(default)]
+    pub payment_frequency: PaymentFrequency,
+
+    /// Interest earning method (defaults to actuarial)
+    #[serde(default)]
+    pub interest_method: InterestMethod,
+
+    /// Day-count convention for per-diem interest (defaults to Actual/365)
+    #[serde(default)]
+    pub day_count: DayCount,
+
+    /// Charge per-diem interest for the odd days before the first regular
+    /// payment period. It is added to the first payment and to the finance
+    /// charge and total of payments; without it the odd days are interest
+    /// free
+    #[serde(default)]
+    pub charge_odd_days_interest: bool,
+
+    /// Portions of the amount financed funded by other lenders; the primary
+    /// lender funds what they leave of each portion
+    #[serde(default)]
+    pub funding_allocations: Vec<FundingAllocation>,
+
+    /// OEM subvention/promotional program code
+    #[serde(default)]
+    pub program_code: Option<String>,
+}
+
+impl FinanceParams {
+    /// Monthly actuarial loan at `apr` with every optional field at its default
+    pub fn new(term_months: u32, apr: Decimal) -> Self {
+        Self {
+            term_months,
+            apr,
+            lender_id: None,
+            buy_rate: None,
+            max_reserve_points: None,
+            deferred_first_payment: false,
+            days_to_first_payment: None,
+            deferral_fee: None,
+            payment_frequency: PaymentFrequency::default(),
+            interest_method: InterestMethod::default(),
+            day_count: DayCount::default(),
+            charge_odd_days_interest: false,
+            funding_allocations: vec![],
+            program_code: None,
+        }
+    }
+}
+
+/// Portion of the amount financed assigned to a secondary lender
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingAllocation {
+    /// Lender funding this portion
+    pub lender_id: String,
+
+    /// Which part of the amount financed this lender funds
+    pub portion: FundingPortion,
+
+    /// Amount funded; required for `Fixed`, and caps the portion otherwise
+    #[serde(default)]
+    pub amount: Option<Decimal>,
+
+    /// APR on this portion, if it differs from the primary loan
+    #[serde(default)]
+    pub apr: Option<Decimal>,
+
+    /// Term on this portion, if it differs from the primary loan
+    #[serde(default)]
+    pub term_months: Option<u32>,
+}
+
+/// Part of the amount financed covered by a funding split
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FundingPortion {
+    /// The vehicle: price, fees and tax less the down payment, trade and
+    /// rebates (the primary lender funds what the other splits leave)
+    Vehicle,
+    /// Financed F&I products
+    FiProducts,
+    /// Negative equity rolled in from the trade
+    NegativeEquity,
+    /// A fixed dollar amount
+    Fixed,
+}
+
+/// Method used to earn interest over the life of a loan
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterestMethod {
+    /// Actuarial method - interest on the outstanding balance each period
+    #[default]
+    Actuarial,
+    /// Rule of 78s - precomputed interest earned by sum-of-digits weighting
+    #[serde(rename = "rule_of_78s")]
+    RuleOf78s,
+    /// Simple interest accrued daily on the outstanding balance
+    SimpleDaily,
+}
+
+/// Day-count convention used to accrue per-diem interest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DayCount {
+    /// Actual days elapsed over a 365-day year
+    #[default]
+    #[serde(rename = "actual_365")]
+    Actual365,
+    /// Actual days elapsed over a 360-day year
+    #[serde(rename = "actual_360")]
+    Actual360,
+    /// 30-day months over a 360-day year
+    #[serde(rename = "thirty_360")]
+    Thirty360,
+}
+
+impl DayCount {
+    /// Days in the year used to derive the daily rate
+    pub fn days_per_year(&self) -> u32 {
+        match self {
+            DayCount::Actual365 => 365,
+            DayCount::Actual360 | DayCount::Thirty360 => 360,
+        }
+    }
+
+    /// Days from `start` to `end` under this convention (negative if `end`
+    /// is before `start`).
+    ///
+    /// 30/360 follows the US rule: a 31st start day counts as the 30th, and
+    /// a 31st end day counts as the 30th when the start day is the 30th or 31st.
+    pub fn days_between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        match self {
+            DayCount::Actual365 | DayCount::Actual360 => (end - start).num_days(),
+            DayCount::Thirty360 => {
+                let d1 = start.day().min(30) as i64;
+                let d2 = if end.day() == 31 && d1 == 30 { 30 } else { end.day() as i64 };
+                360 * (end.year() - start.year()) as i64
+                    + 30 * (end.month() as i64 - start.month() as i64)
+                    + (d2 - d1)
+            }
+        }
+    }
+}
+
+/// Payment frequency for finance deals
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentFrequency {
+    /// 12 payments per year
+    #[default]
+    Monthly,
+    /// 24 payments per year (1st and 15th)
+    SemiMonthly,
+    /// 26 payments per year
+    BiWeekly,
+    /// 52 payments per year
+    Weekly,
+    /// 4 payments per year
+    Quarterly,
+}
+
+impl PaymentFrequency {
+    /// Number of payment periods per year
+    pub fn periods_per_year(&self) -> u32 {
+        match self {
+            PaymentFrequency::Monthly => 12,
+            PaymentFrequency::SemiMonthly => 24,
+            PaymentFrequency::BiWeekly => 26,
+            PaymentFrequency::Weekly => 52,
+            PaymentFrequency::Quarterly => 4,
+        }
+    }
+
+    /// Number of payments over a term in months (partial periods round up)
+    pub fn number_of_payments(&self, term_months: u32) -> u32 {
+        (term_months * self.periods_per_year()).div_ceil(12)
+    }
+
+    /// Human-readable label (e.g., "semi-monthly")
+    pub fn label(&self) -> &'static str {
+        match self {
+            PaymentFrequency::Monthly => "monthly",
+            PaymentFrequency::SemiMonthly => "semi-monthly",
+            PaymentFrequency::BiWeekly => "bi-weekly",
+            PaymentFrequency::Weekly => "weekly",
+            PaymentFrequency::Quarterly => "quarterly",
+        }
+    }
 }
 
 /// Lease-specific parameters
@@ -423,19 +1035,39 @@ pub struct LeaseParams {
     /// Lease term in months
     pub term_months: u32,
 
-    /// Money factor (rent charge factor)
-    pub money_factor: Decimal,
+    /// Money factor (rent charge factor, e.g., 0.00125 = 3.00% APR)
+    pub money_factor: MoneyFactor,
 
     /// Residual percentage (as decimal, e.g., 0.52 for 52%)
     pub residual_percent: Decimal,
 
+    /// MSRP the residual is set against (defaults to the vehicle's MSRP,
+    /// then the selling price)
+    #[serde(default)]
+    pub msrp: Option<Decimal>,
+
     /// Annual mileage allowance
     pub annual_miles: u32,
 
+    /// Mileage tier to price from the lessor's program (defaults to
+    /// `annual_miles`)
+    #[serde(default)]
+    pub selected_mileage: Option<u32>,
+
     /// Excess mileage charge per mile
     #[serde(default)]
     pub excess_mileage_rate: Option<Decimal>,
 
+    /// Miles per year the customer expects to drive, for the lease-end
+    /// excess mileage estimate (none assumes the allowance is not exceeded)
+    #[serde(default)]
+    pub estimated_annual_miles: Option<u32>,
+
+    /// Purchase option fee on a lease-end buyout (defaults to the lessor
+    /// program's)
+    #[serde(default)]
+    pub purchase_option_fee: Option<Decimal>,
+
     /// Lessor/captive ID
     #[serde(default)]
     pub lessor_id: Option<String>,
@@ -444,6 +1076,11 @@ pub struct LeaseParams {
     #[serde(default)]
     pub msd_count: u8,
 
+    /// Lower `money_factor` by the lessor's MSD reduction. Off by default:
+    /// the input money factor is taken as already effective.
+    #[serde(default)]
+    pub apply_msd_reduction: bool,
+
     /// Security deposit amount (if not MSD)
     #[serde(default)]
     pub security_deposit: Option<Decimal>,
@@ -452,35 +1089,81 @@ pub struct LeaseParams {
     #[serde(default = "default_true")]
     pub cap_acquisition_fee: bool,
 
+    /// Roll tax due at signing in cap-cost-taxed states into the cap cost
+    /// instead of collecting it at signing
+    #[serde(default)]
+    pub capitalize_upfront_tax: bool,
+
     /// Cap cost reduction (additional to down payment)
     #[serde(default)]
     pub cap_cost_reduction: Decimal,
+
+    /// Day-count convention for interim rent (defaults to Actual/365)
+    #[serde(default)]
+    pub day_count: DayCount,
+
+    /// OEM subvention/promotional program code
+    #[serde(default)]
+    pub program_code: Option<String>,
+
+    /// Scheduled changes to the monthly tax rate during the term, for
+    /// states that tax each payment at the prevailing rate (ascending by
+    /// month)
+    #[serde(default)]
+    pub tax_rate_schedule: Vec<ScheduledTaxRate>,
+}
+
+impl LeaseParams {
+    /// Lease with every optional field at its default (acquisition fee
+    /// capitalized, no MSDs or cap cost reduction)
+    pub fn new(term_months: u32, money_factor: MoneyFactor, residual_percent: Decimal, annual_miles: u32) -> Self {
+        Self {
+            term_months,
+            money_factor,
+            residual_percent,
+            msrp: None,
+            annual_miles,
+            selected_mileage: None,
+            excess_mileage_rate: None,
+            estimated_annual_miles: None,
+            purchase_option_fee: None,
+            lessor_id: None,
+            msd_count: 0,
+            apply_msd_reduction: false,
+            security_deposit: None,
+            cap_acquisition_fee: true,
+            capitalize_upfront_tax: false,
+            cap_cost_reduction: Decimal::ZERO,
+            day_count: DayCount::default(),
+            program_code: None,
+            tax_rate_schedule: vec![],
+        }
+    }
+}
+
+/// A monthly lease tax rate taking effect partway through the term
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTaxRate {
+    /// First payment (1-based) taxed at the new rate; at least 2, since
+    /// the first payment is collected at signing at the opening rate
+    pub effective_month: u32,
+    /// Combined tax rate from that payment on
+    pub rate: Rate,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::Money;
     use rust_decimal_macros::dec;
 
     #[test]
     fn test_net_trade_positive() {
         let input = DealInput {
-            deal_type: DealType::Finance,
-            vehicle_price: dec!(30000),
             trade_in_value: Some(dec!(8000)),
             trade_in_payoff: Some(dec!(5000)),
             cash_down: dec!(2000),
-            rebates: vec![],
-            products: vec![],
-            fees: DealFees::default(),
-            home_state: StateCode::TX,
-            transaction_state: StateCode::TX,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: None,
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
+            ..DealInput::new(DealType::Finance, dec!(30000), StateCode::TX)
         };
 
         assert_eq!(input.net_trade(), dec!(3000));
@@ -490,32 +1173,42 @@ mod tests {
     #[test]
     fn test_net_trade_negative_equity() {
         let input = DealInput {
-            deal_type: DealType::Finance,
-            vehicle_price: dec!(30000),
             trade_in_value: Some(dec!(5000)),
             trade_in_payoff: Some(dec!(8000)),
             cash_down: dec!(2000),
-            rebates: vec![],
-            products: vec![],
-            fees: DealFees::default(),
-            home_state: StateCode::TX,
-            transaction_state: StateCode::TX,
-            garaging_state: None,
-            customer: CustomerInfo::default(),
-            finance_params: None,
-            lease_params: None,
-            deal_date: None,
-            first_payment_date: None,
+            ..DealInput::new(DealType::Finance, dec!(30000), StateCode::TX)
         };
 
         assert_eq!(input.net_trade(), dec!(-3000));
         assert!(input.has_negative_equity());
     }
 
+    #[test]
+    fn test_total_trade_acv_across_trades() {
+        let trade = |gross_value, acv: Option<Decimal>| TradeIn {
+            vin: None,
+            description: "Trade".to_string(),
+            gross_value: Money::new(gross_value),
+            payoff_amount: Money::ZERO,
+            acv: acv.map(Money::new),
+            titled_to_buyer: false,
+        };
+        let input = DealInput {
+            trade_in_value: Some(dec!(8000)),
+            trade_in_acv: Some(dec!(7500)),
+            additional_trades: vec![trade(dec!(6000), Some(dec!(5500))), trade(dec!(2000), None)],
+            ..DealInput::new(DealType::Finance, dec!(30000), StateCode::TX)
+        };
+
+        // Each trade's ACV, defaulting to its allowance
+        assert_eq!(input.total_trade_value(), dec!(16000));
+        assert_eq!(input.total_trade_acv(), dec!(15000));
+    }
+
     #[test]
     fn test_total_fees() {
         let fees = DealFees {
-            doc_fee: dec!(299),
+            doc_fee: Some(dec!(299)),
             title_fee: dec!(33),
             registration_fee: dec!(75),
             plate_fee: dec!(25),
@@ -525,4 +1218,18 @@ mod tests {
         assert_eq!(fees.total_government_fees(), dec!(133));
         assert_eq!(fees.total_dealer_fees(), dec!(299));
     }
+
+    #[test]
+    fn test_day_count_days_between() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+
+        assert_eq!(DayCount::Actual365.days_between(date(1, 1), date(2, 15)), 45);
+        assert_eq!(DayCount::Actual360.days_between(date(1, 1), date(2, 15)), 45);
+        assert_eq!(DayCount::Thirty360.days_between(date(1, 1), date(2, 15)), 44);
+
+        // Month ends count as the 30th
+        assert_eq!(DayCount::Thirty360.days_between(date(1, 31), date(3, 31)), 60);
+        assert_eq!(DayCount::Thirty360.days_between(date(2, 29), date(3, 31)), 32);
+        assert_eq!(DayCount::Thirty360.days_per_year(), 360);
+    }
 }
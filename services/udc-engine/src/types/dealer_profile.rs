@@ -0,0 +1,36 @@
+//! Dealer-level profiles.
+//!
+//! Contains store configuration that affects gross profit reporting,
+//! such as pack and manufacturer holdback.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Money, Rate};
+
+/// Profile for a dealership (rooftop).
+/// Defines the cost adjustments used in profit analysis.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DealerProfile {
+    /// Unique dealer identifier
+    #[serde(default)]
+    pub dealer_id: String,
+
+    /// Pack - fixed cost added to vehicle cost for gross calculation
+    #[serde(default)]
+    pub pack: Money,
+
+    /// Holdback as a percentage of MSRP, rebated to the dealer by the OEM
+    #[serde(default)]
+    pub holdback_percent: Rate,
+
+    /// Documentation fee charged when the deal gives none (applied in P0)
+    #[serde(default)]
+    pub default_doc_fee: Money,
+}
+
+impl DealerProfile {
+    /// Calculate holdback for a vehicle with the given MSRP
+    pub fn holdback(&self, msrp: Money) -> Money {
+        self.holdback_percent.apply(msrp).round_cents()
+    }
+}
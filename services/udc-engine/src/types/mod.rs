@@ -12,6 +12,7 @@
 //! - `rule_profile` - Detailed state tax rules
 //! - `program_profile` - Lender/lessor program parameters
 //! - `product_profile` - F&I product definitions
+//! - `dealer_profile` - Dealer pack/holdback configuration
 //! - `output` - Calculation results (the "ciphertext")
 
 pub mod deal;
@@ -22,12 +23,14 @@ pub mod profiles;
 pub mod rule_profile;
 pub mod program_profile;
 pub mod product_profile;
+pub mod dealer_profile;
 
 // Re-export primary types - use explicit imports to avoid ambiguity
 // deal_input.rs is the canonical DealInput used by the pipeline
 pub use deal_input::{
-    DealInput, DealFees, CustomerInfo, CustomerType, FinanceParams, LeaseParams,
-    Rebate, RebateType, Product, ProductType, OtherFee,
+    DealInput, DealFees, CustomerInfo, CustomerType, FinanceParams, PaymentFrequency, InterestMethod, DayCount, FundingAllocation, FundingPortion, LeaseParams,
+    Rebate, RebateType, Product, ProductType, OtherFee, LeaseFeeCapitalization, VehicleValues,
+    FeeItem, FeeKind, ApportionedRegistration, JurisdictionMiles, ScheduledTaxRate,
 };
 
 // deal.rs types that don't conflict
@@ -43,13 +46,14 @@ pub use output::*;
 
 // rule_profile.rs is the canonical RuleProfile used by the pipeline
 pub use rule_profile::{
-    RuleProfile, TaxRates, BaseRules, AncillaryRules, ReciprocityRules,
-    PartialCreditState, ProfileMeta, LeaseTaxConfig,
+    RuleProfile, TaxRates, BaseRules, AncillaryRules, FeeTaxOverride, ReciprocityRules,
+    PartialCreditState, ProfileMeta, LeaseTaxConfig, GovernmentFeeSchedule,
+    RULE_PROFILE_SCHEMA_VERSION,
 };
 
 // profiles.rs types that don't conflict with rule_profile
 pub use profiles::{
-    TradeInTaxTreatment, RebateTaxTreatment, LeaseTaxMode, TaxStackingMode,
+    TradeInTaxTreatment, RebateTaxTreatment, LeaseTaxMode, LeaseMonthlyTaxBase, TaxRounding, TaxStackingMode,
     ReciprocityType, SpecialTaxType, TaxRateComponent, RoundingMode, FeeCapitalization,
     ResidualEntry,
 };
@@ -57,7 +61,7 @@ pub use profiles::{
 // program_profile.rs is the canonical ProgramProfile
 pub use program_profile::{
     ProgramProfile, RateSheet, TierRates, TermRate, ReserveCap, SpecialRate,
-    StructureRules, VehicleAgeTerm, ProgramFeeConfig, EarlyTerminationFee,
+    StructureRules, LtvBasis, VehicleAgeTerm, ProgramFeeConfig, EarlyTerminationFee,
     EligibilityRules, ProgramMeta, LeaseProgram, TierMoneyFactor, ResidualConfig,
     MileageOption, MileageAdjustment, SecurityDepositConfig, DepositCalculation,
     MsdConfig, MsdRounding, DriveOffRules,
@@ -71,14 +75,16 @@ pub use product_profile::{
     ProductCatalog, ProductBundle, CatalogMeta,
 };
 
+pub use dealer_profile::DealerProfile;
+
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumString};
+use strum::{Display, EnumIter, EnumString};
 use thiserror::Error;
 
 /// US State codes (50 states + DC + territories)
 #[derive(
-    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, Display,
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, Display, EnumIter,
 )]
 #[strum(serialize_all = "UPPERCASE")]
 #[serde(rename_all = "UPPERCASE")]
@@ -226,6 +232,41 @@ impl UdcError {
             message: message.into(),
         }
     }
+
+    /// Machine-stable error code, for clients to branch on instead of the
+    /// message.
+    ///
+    /// | Variant | Code |
+    /// |---------|------|
+    /// | `Validation` | `VALIDATION` |
+    /// | `Calculation` in phase P0-P7 | `CALC_P0` ... `CALC_P7` |
+    /// | `Calculation` elsewhere (e.g., solvers) | `CALCULATION` |
+    /// | `ProfileNotFound` | `PROFILE_NOT_FOUND` |
+    /// | `StateRule` | `STATE_RULE` |
+    /// | `Serialization` | `SERIALIZATION` |
+    /// | `Wasm` | `WASM` |
+    /// | `Internal` | `INTERNAL` |
+    pub fn code(&self) -> &'static str {
+        match self {
+            UdcError::Validation { .. } => "VALIDATION",
+            UdcError::Calculation { phase, .. } => match phase.split('_').next() {
+                Some("P0") => "CALC_P0",
+                Some("P1") => "CALC_P1",
+                Some("P2") => "CALC_P2",
+                Some("P3") => "CALC_P3",
+                Some("P4") => "CALC_P4",
+                Some("P5") => "CALC_P5",
+                Some("P6") => "CALC_P6",
+                Some("P7") => "CALC_P7",
+                _ => "CALCULATION",
+            },
+            UdcError::ProfileNotFound { .. } => "PROFILE_NOT_FOUND",
+            UdcError::StateRule { .. } => "STATE_RULE",
+            UdcError::Serialization { .. } => "SERIALIZATION",
+            UdcError::Wasm { .. } => "WASM",
+            UdcError::Internal { .. } => "INTERNAL",
+        }
+    }
 }
 
 /// Result type alias for UDC operations
@@ -263,6 +304,31 @@ impl DecimalExt for Decimal {
     }
 }
 
+/// When intermediate money values are rounded to cents.
+///
+/// Final disclosed figures (tax totals, payments, amount financed) are
+/// always rounded; the strategy only controls intermediate steps:
+///
+/// - **P5 Lease**: `RoundEachStep` adds the rounded monthly depreciation and
+///   rent charge; `RoundAtEnd` divides the exact totals and rounds the payment.
+/// - **P5 Finance**: the payment is always computed from the rounded amount
+///   financed, as disclosed under TILA.
+///
+/// Tax totals are rounded by the jurisdiction's
+/// [`TaxRounding`], not by this strategy.
+///
+/// tax-engine-rs rounds lease base payments once, as `RoundAtEnd` does; its
+/// finance payment matches under either strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingStrategy {
+    /// Round to cents after every step
+    #[default]
+    RoundEachStep,
+    /// Carry full precision and round only final figures
+    RoundAtEnd,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -278,6 +344,32 @@ mod tests {
         assert_eq!(CreditTier::from_score(450), CreditTier::Tier6);
     }
 
+    #[test]
+    fn test_rounding_strategy_serde() {
+        assert_eq!(RoundingStrategy::default(), RoundingStrategy::RoundEachStep);
+        let json = serde_json::to_string(&RoundingStrategy::RoundEachStep).unwrap();
+        assert_eq!(json, "\"round_each_step\"");
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let cases = [
+            (UdcError::validation_field("Vehicle price must be positive", "vehicle_price"), "VALIDATION"),
+            (UdcError::calculation("Tax invariant violated", "P4_TAX"), "CALC_P4"),
+            (UdcError::calculation("Missing lease params", "P5_STRUCTURE_LEASE"), "CALC_P5"),
+            (UdcError::calculation("Cash deals have no monthly payment", "SOLVER"), "CALCULATION"),
+            (UdcError::profile_not_found("RuleProfile", "TX"), "PROFILE_NOT_FOUND"),
+            (UdcError::state_rule("Unsupported lease mode", "GA"), "STATE_RULE"),
+            (UdcError::serialization("unexpected end of input"), "SERIALIZATION"),
+            (UdcError::wasm("bad handle"), "WASM"),
+            (UdcError::internal("unreachable"), "INTERNAL"),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(error.code(), code, "{}", error);
+        }
+    }
+
     #[test]
     fn test_decimal_ext() {
         assert_eq!(dec!(10.125).round_money(), dec!(10.12)); // Banker's rounding
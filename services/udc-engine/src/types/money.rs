@@ -6,8 +6,12 @@
 
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
 use std::ops::{Add, Div, Mul, Sub};
+use std::str::FromStr;
+
+use super::{UdcError, UdcResult};
 
 /// Represents a monetary amount with 2-decimal precision for display,
 /// but internally maintains full precision for calculations.
@@ -143,13 +147,53 @@ impl std::fmt::Display for Money {
 
 /// Represents a rate (APR, tax rate, etc.) as a decimal percentage.
 /// Stored as the actual decimal value (e.g., 0.0725 for 7.25%)
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+///
+/// Serializes as the decimal value. Deserializes from any of:
+/// - `{"percent": 6.25}` or `{"decimal": 0.0625}`
+/// - a percent string, `"6.25%"`
+/// - a bare number or string in decimal form, up to [`Rate::MAX_BARE`]
+///
+/// Bare values above the limit are rejected rather than guessed at, since
+/// `6.25` could be a percent typed into a decimal field. Non-human-readable
+/// formats carry the plain decimal, as they always have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 #[serde(transparent)]
 pub struct Rate(Decimal);
 
 impl Rate {
     pub const ZERO: Rate = Rate(dec!(0));
 
+    /// Largest bare (unlabelled) value accepted as a decimal rate: 100%
+    pub const MAX_BARE: Decimal = dec!(1);
+
+    /// Parse a rate from text: `"6.25%"` is a percent, `"0.0625"` a decimal.
+    ///
+    /// A bare value above [`Rate::MAX_BARE`] is ambiguous and rejected;
+    /// write it with a `%` instead.
+    pub fn try_from_str(s: &str) -> UdcResult<Self> {
+        let s = s.trim();
+        let parse = |text: &str| {
+            Decimal::from_str(text.trim())
+                .map_err(|_| UdcError::validation(format!("Invalid rate '{}'", s)))
+        };
+        match s.strip_suffix('%') {
+            Some(percent) => Ok(Rate::from_percentage(parse(percent)?)),
+            None => Rate::try_from_bare(parse(s)?),
+        }
+    }
+
+    /// Accept an unlabelled value as a decimal rate if it is within
+    /// [`Rate::MAX_BARE`]
+    fn try_from_bare(value: Decimal) -> UdcResult<Self> {
+        if value.abs() > Rate::MAX_BARE {
+            return Err(UdcError::validation(format!(
+                "Ambiguous rate {}: bare rates are decimals up to {}; write {}% or {{\"percent\": {}}} for a percent",
+                value, Rate::MAX_BARE, value, value
+            )));
+        }
+        Ok(Rate(value))
+    }
+
     /// Create a rate from decimal form (e.g., 0.0725 for 7.25%)
     #[inline]
     pub fn from_decimal(value: Decimal) -> Self {
@@ -187,6 +231,68 @@ impl Default for Rate {
     }
 }
 
+/// Displays as a percent to three places, trailing zeros dropped (`8.25%`)
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.as_percentage().round_dp(3).normalize())
+    }
+}
+
+/// Labelled rate form: exactly one of `percent` or `decimal`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LabelledRate {
+    #[serde(default)]
+    percent: Option<Decimal>,
+    #[serde(default)]
+    decimal: Option<Decimal>,
+}
+
+struct RateVisitor;
+
+impl<'de> serde::de::Visitor<'de> for RateVisitor {
+    type Value = Rate;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a decimal rate, a percent string like \"6.25%\", or {\"percent\": ..} / {\"decimal\": ..}")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Rate, E> {
+        Rate::try_from_str(v).map_err(E::custom)
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Rate, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Rate, E> {
+        Rate::try_from_bare(Decimal::from(v)).map_err(E::custom)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Rate, E> {
+        Rate::try_from_bare(Decimal::from(v)).map_err(E::custom)
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Rate, A::Error> {
+        let labelled = LabelledRate::deserialize(serde::de::value::MapAccessDeserializer::new(map))?;
+        match (labelled.percent, labelled.decimal) {
+            (Some(percent), None) => Ok(Rate::from_percentage(percent)),
+            (None, Some(decimal)) => Ok(Rate::from_decimal(decimal)),
+            _ => Err(serde::de::Error::custom("rate needs exactly one of \"percent\" or \"decimal\"")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Rate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(RateVisitor)
+        } else {
+            <Decimal as Deserialize>::deserialize(deserializer).map(Rate)
+        }
+    }
+}
+
 impl Add for Rate {
     type Output = Self;
     #[inline]
@@ -205,7 +311,7 @@ impl Sub for Rate {
 
 /// Represents a money factor for lease calculations.
 /// Typically a small decimal like 0.00125
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct MoneyFactor(Decimal);
 
@@ -264,10 +370,109 @@ mod tests {
         assert_eq!(tax.as_decimal(), dec!(725));
     }
 
+    #[test]
+    fn test_rate_try_from_str() {
+        assert_eq!(Rate::try_from_str("6.25%").unwrap(), Rate::from_percentage(dec!(6.25)));
+        assert_eq!(Rate::try_from_str(" 6.25 % ").unwrap().as_decimal(), dec!(0.0625));
+        assert_eq!(Rate::try_from_str("0.0625").unwrap().as_decimal(), dec!(0.0625));
+        assert!(Rate::try_from_str("6.25").is_err());
+        assert!(Rate::try_from_str("abc%").is_err());
+    }
+
+    #[test]
+    fn test_rate_deserializes_percent_and_decimal() {
+        let parse = |json: &str| serde_json::from_str::<Rate>(json);
+        let expected = Rate::from_decimal(dec!(0.0625));
+
+        assert_eq!(parse(r#"{"percent": 6.25}"#).unwrap(), expected);
+        assert_eq!(parse(r#"{"decimal": 0.0625}"#).unwrap(), expected);
+        assert_eq!(parse(r#""6.25%""#).unwrap(), expected);
+        assert_eq!(parse(r#""0.0625""#).unwrap(), expected);
+        assert_eq!(parse("0.0625").unwrap(), expected);
+        assert_eq!(parse("0").unwrap(), Rate::ZERO);
+
+        // Serialized rates read back unchanged
+        let json = serde_json::to_string(&expected).unwrap();
+        assert_eq!(json, "\"0.0625\"");
+        assert_eq!(parse(&json).unwrap(), expected);
+
+        assert!(parse(r#"{"percent": 6.25, "decimal": 0.0625}"#).is_err());
+        assert!(parse(r#"{"rate": 6.25}"#).is_err());
+    }
+
+    #[test]
+    fn test_rate_rejects_ambiguous_bare_value() {
+        let err = serde_json::from_str::<Rate>("625").unwrap_err();
+        assert!(err.to_string().contains("Ambiguous rate 625"), "{}", err);
+        assert!(serde_json::from_str::<Rate>("6.25").is_err());
+        assert!(serde_json::from_str::<Rate>(r#""625""#).is_err());
+
+        // Labelled, the same number is a (large) percent
+        assert_eq!(
+            serde_json::from_str::<Rate>(r#"{"percent": 625}"#).unwrap().as_decimal(),
+            dec!(6.25)
+        );
+    }
+
+    /// Non-human-readable view of a JSON value, standing in for a binary format
+    struct Compact(serde_json::Value);
+
+    impl<'de> Deserializer<'de> for Compact {
+        type Error = serde_json::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            self.0.deserialize_any(visitor)
+        }
+
+        fn is_human_readable(&self) -> bool {
+            false
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    #[test]
+    fn test_rate_compact_formats_read_plain_decimal() {
+        let parse = |value: serde_json::Value| Rate::deserialize(Compact(value));
+
+        assert_eq!(parse(serde_json::json!("0.0625")).unwrap().as_decimal(), dec!(0.0625));
+        // No percent disambiguation: the stored decimal is taken as is
+        assert_eq!(parse(serde_json::json!("6.25")).unwrap().as_decimal(), dec!(6.25));
+        assert!(parse(serde_json::json!("6.25%")).is_err());
+        assert!(parse(serde_json::json!({"percent": 6.25})).is_err());
+    }
+
+    #[test]
+    fn test_rate_display_percent() {
+        assert_eq!(Rate::from_decimal(dec!(0.0825)).to_string(), "8.25%");
+        assert_eq!(Rate::from_decimal(dec!(0.07)).to_string(), "7%");
+        assert_eq!(Rate::from_decimal(dec!(0.0825333)).to_string(), "8.253%");
+        assert_eq!(Rate::ZERO.to_string(), "0%");
+    }
+
     #[test]
     fn test_money_factor_conversion() {
         let mf = MoneyFactor::new(dec!(0.00125));
         let apr = mf.to_apr();
         assert_eq!(apr.as_percentage(), dec!(3.00)); // 0.00125 * 2400 = 3.0%
     }
+
+    #[test]
+    fn test_money_factor_apr_round_trip() {
+        let mf = MoneyFactor::new(dec!(0.00125));
+        assert_eq!(mf.to_apr(), Rate::from_percentage(dec!(3)));
+        assert_eq!(MoneyFactor::from_apr(mf.to_apr()), mf);
+    }
+
+    #[test]
+    fn test_money_factor_serializes_full_precision() {
+        let mf = MoneyFactor::new(dec!(0.000123456789));
+        let json = serde_json::to_string(&mf).unwrap();
+        assert_eq!(json, "\"0.000123456789\"");
+        assert_eq!(serde_json::from_str::<MoneyFactor>(&json).unwrap(), mf);
+    }
 }
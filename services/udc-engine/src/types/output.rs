@@ -8,7 +8,10 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::deal::DealType;
-use super::money::{Money, Rate};
+use super::deal_input::{FundingPortion, InterestMethod, PaymentFrequency};
+use super::money::{Money, MoneyFactor, Rate};
+use super::program_profile::LtvBasis;
+use super::{CreditTier, StateCode};
 
 // ============================================================================
 // TAX BREAKDOWN
@@ -23,12 +26,18 @@ pub struct TaxLineItem {
     pub name: String,
     /// Tax rate applied
     pub rate: Rate,
+    /// Tax rate for display, as a percent (e.g., "8.25%")
+    #[serde(default)]
+    pub rate_percent: String,
     /// Base amount taxed
     pub taxable_base: Money,
     /// Calculated tax amount
     pub tax_amount: Money,
     /// Whether this is a credit (negative)
     pub is_credit: bool,
+    /// Whether the locality collects this tax directly (home-rule)
+    #[serde(default)]
+    pub self_collected: bool,
 }
 
 /// Complete tax breakdown for a deal.
@@ -46,6 +55,9 @@ pub struct TaxBreakdown {
     pub tax_base: Money,
     /// Combined effective rate
     pub effective_rate: Rate,
+    /// Combined effective rate for display, as a percent (e.g., "8.25%")
+    #[serde(default)]
+    pub effective_rate_percent: String,
     /// Special tax type used (if any)
     pub special_tax_type: Option<String>,
     /// Whether trade-in reduced tax base
@@ -56,6 +68,54 @@ pub struct TaxBreakdown {
     pub rebates_applied: bool,
     /// Rebate amount used for tax reduction
     pub rebate_amount_used: Money,
+    /// Vehicle selling price included in the tax base
+    #[serde(default)]
+    pub selling_price: Money,
+    /// Fees included in the tax base
+    #[serde(default)]
+    pub taxable_fees: Money,
+    /// F&I products included in the tax base
+    #[serde(default)]
+    pub taxable_products: Money,
+    /// Price-basis exemption taken off the tax base (e.g., EV exemption)
+    #[serde(default)]
+    pub exemption_applied: Money,
+    /// Other base adjustments (e.g., a state cap); the base is
+    /// `selling_price + taxable_fees + taxable_products - trade_in_credit_used
+    /// - rebate_amount_used - exemption_applied + base_adjustments`
+    #[serde(default)]
+    pub base_adjustments: Money,
+    /// Reason a tax base cap was applied (if any)
+    #[serde(default)]
+    pub base_cap_applied: Option<String>,
+}
+
+/// A single fee as resolved by the engine, after state overrides.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeLine {
+    /// Stable fee code (e.g., "DOC", "TITLE", or the custom fee's code)
+    pub code: String,
+    /// Display name
+    pub name: String,
+    /// Amount charged
+    pub amount: Money,
+    /// Included in the tax base
+    pub taxable: bool,
+    /// Rolled into the lease cap cost (always false for cash and finance)
+    pub capitalized: bool,
+}
+
+/// One jurisdiction's share of an apportioned (IRP) registration fee.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApportionedRegistrationLine {
+    /// Jurisdiction
+    pub state: StateCode,
+    /// Miles driven there
+    pub miles: u32,
+    /// Share of total miles
+    pub share: Rate,
+    /// Registration fee owed to the jurisdiction
+    pub fee: Money,
 }
 
 impl Default for TaxBreakdown {
@@ -67,11 +127,18 @@ impl Default for TaxBreakdown {
             net_tax: Money::ZERO,
             tax_base: Money::ZERO,
             effective_rate: Rate::ZERO,
+            effective_rate_percent: Rate::ZERO.to_string(),
             special_tax_type: None,
             trade_in_applied: false,
             trade_in_credit_used: Money::ZERO,
             rebates_applied: false,
             rebate_amount_used: Money::ZERO,
+            selling_price: Money::ZERO,
+            taxable_fees: Money::ZERO,
+            taxable_products: Money::ZERO,
+            exemption_applied: Money::ZERO,
+            base_adjustments: Money::ZERO,
+            base_cap_applied: None,
         }
     }
 }
@@ -101,18 +168,204 @@ pub struct FinanceStructure {
     pub sales_tax: Money,
     /// Amount financed (principal)
     pub amount_financed: Money,
+    /// Signed line items that sum to the amount financed
+    #[serde(default)]
+    pub amount_financed_breakdown: Vec<AmountFinancedLineItem>,
     /// APR
     pub apr: Rate,
     /// Term in months
     pub term_months: u16,
-    /// Monthly payment
+    /// Term the caller asked for, when it was snapped to the nearest term
+    /// the program offers
+    #[serde(default)]
+    pub requested_term_months: Option<u16>,
+    /// Payment frequency
+    #[serde(default)]
+    pub payment_frequency: PaymentFrequency,
+    /// Number of payments over the term
+    #[serde(default)]
+    pub number_of_payments: u16,
+    /// Periodic payment (monthly unless another frequency is selected)
     pub monthly_payment: Money,
+    /// Tax collected with each payment, in states that tax finance
+    /// payments monthly (zero when the tax is financed)
+    #[serde(default)]
+    pub monthly_tax: Money,
+    /// Periodic payment plus the tax collected with it
+    #[serde(default)]
+    pub payment_with_tax: Money,
+    /// Tax collected with the last payment, trued up so the installments
+    /// add up to the sales tax
+    #[serde(default)]
+    pub final_monthly_tax: Money,
     /// Total of payments
     pub total_of_payments: Money,
-    /// Total finance charge (interest)
+    /// Total finance charge (interest plus prepaid finance charges)
     pub finance_charge: Money,
+    /// Prepaid finance charges collected at signing (e.g., a deferral fee)
+    #[serde(default)]
+    pub prepaid_finance_charge: Money,
+    /// Fees paid at signing instead of financed
+    #[serde(default)]
+    pub upfront_fees: Money,
+    /// Cash due at signing: the down payment, fees paid upfront, and any
+    /// prepaid finance charge
+    #[serde(default)]
+    pub due_at_signing: Money,
     /// Total sale price (TTP on buyer's order)
     pub total_sale_price: Money,
+    /// Per-diem interest for the days before the first regular payment
+    /// period, charged with the first payment and included in the finance
+    /// charge (zero unless `charge_odd_days_interest` is set)
+    #[serde(default)]
+    pub odd_days_interest: Money,
+    /// APR disclosed under TILA; differs from `apr` when the payment stream
+    /// is irregular (e.g., a long first period)
+    #[serde(default)]
+    pub disclosed_apr: Option<Rate>,
+    /// Loan-to-value: amount financed over `ltv_basis` (None when that
+    /// vehicle value is unknown)
+    #[serde(default)]
+    pub ltv: Option<Rate>,
+    /// Vehicle value the LTV is measured against
+    #[serde(default)]
+    pub ltv_basis: LtvBasis,
+    /// How the amount financed is funded across lenders (empty when a
+    /// single lender funds the whole deal)
+    #[serde(default)]
+    pub funding_splits: Vec<FundingSplit>,
+}
+
+/// Loan math for a known amount financed, without tax or deal structure.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinanceQuote {
+    /// Amount financed (principal)
+    pub amount_financed: Money,
+    /// APR
+    pub apr: Rate,
+    /// Term in months
+    pub term_months: u16,
+    /// Payment frequency
+    pub payment_frequency: PaymentFrequency,
+    /// Number of payments over the term
+    pub number_of_payments: u16,
+    /// Periodic payment (monthly unless another frequency is selected)
+    pub payment: Money,
+    /// Total of payments
+    pub total_of_payments: Money,
+    /// Total finance charge (interest)
+    pub finance_charge: Money,
+    /// Amortization schedule (when a first payment date is given)
+    #[serde(default)]
+    pub amortization_schedule: Option<Vec<AmortizationEntry>>,
+}
+
+/// Payment quotes across a grid of terms and APRs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentMatrix {
+    /// Amount financed every cell is quoted on
+    pub amount_financed: Money,
+    /// One cell per term and APR, ordered by term then APR
+    pub cells: Vec<PaymentMatrixCell>,
+}
+
+/// A single term/APR quote in a [`PaymentMatrix`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentMatrixCell {
+    /// Term in months
+    pub term_months: u16,
+    /// APR
+    pub apr: Rate,
+    /// Monthly payment
+    pub monthly_payment: Money,
+    /// Total of payments (payment x term)
+    pub total_of_payments: Money,
+    /// Finance charge (total of payments less the amount financed)
+    pub finance_charge: Money,
+}
+
+/// Early payoff of a finance contract after a given payment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayoffQuote {
+    /// Payments made before the payoff (0 = payoff before the first payment)
+    pub as_of_payment: u16,
+    /// Payments remaining under the contract
+    pub remaining_payments: u16,
+    /// Method the unearned interest is rebated by
+    pub interest_method: InterestMethod,
+    /// Sum of the remaining scheduled payments
+    pub gross_remaining: Money,
+    /// Unearned interest rebated to the customer
+    pub interest_rebate: Money,
+    /// Amount to pay the contract off (gross remaining less the rebate)
+    pub net_payoff: Money,
+}
+
+/// Early termination payoff of a lease after a given payment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeasePayoffQuote {
+    /// Payments made before the payoff (0 = payoff before the first payment)
+    pub as_of_payment: u16,
+    /// Payments remaining under the lease
+    pub remaining_payments: u16,
+    /// Residual value
+    pub residual_value: Money,
+    /// Depreciation not yet paid down (straight-line over the term)
+    pub unpaid_depreciation: Money,
+    /// Lease balance (residual plus unpaid depreciation)
+    pub gross_payoff: Money,
+    /// Sales tax prepaid on the lease, at signing or in the cap cost
+    pub prepaid_tax: Money,
+    /// Unearned prepaid tax refunded (zero where the state does not refund it)
+    pub prepaid_tax_refund: Money,
+    /// Amount to terminate the lease (gross payoff less the tax refund)
+    pub net_payoff: Money,
+}
+
+/// Loan balance against projected vehicle value at one month of a contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    /// Month of the contract (0 = at signing, before the first payment)
+    pub month: u32,
+    /// Loan balance after that month's payment
+    pub loan_balance: Money,
+    /// Projected vehicle value
+    pub vehicle_value: Money,
+    /// Vehicle value less loan balance (negative = upside down)
+    pub equity: Money,
+}
+
+/// Labeled component of the amount financed (reductions are negative).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmountFinancedLineItem {
+    /// Line item label (e.g., "Doc fee", "Cash down")
+    pub label: String,
+    /// Signed amount
+    pub amount: Money,
+}
+
+/// Labeled component of the gross capitalized cost.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapCostLineItem {
+    /// Line item label (e.g., "Agreed value", "Capitalized tax")
+    pub label: String,
+    /// Amount capitalized
+    pub amount: Money,
+}
+
+/// Portion of the amount financed funded by one lender.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingSplit {
+    /// Lender funding this portion (None for an unnamed primary lender)
+    pub lender_id: Option<String>,
+    /// Part of the amount financed covered
+    pub portion: FundingPortion,
+    /// Amount funded
+    pub amount: Money,
+    /// APR on this portion
+    pub apr: Rate,
+    /// Term on this portion
+    pub term_months: u16,
 }
 
 /// Single amortization schedule entry.
@@ -150,6 +403,9 @@ pub struct LeaseStructure {
     pub capitalized_fi_products: Money,
     /// Gross capitalized cost
     pub gross_cap_cost: Money,
+    /// Line items that sum to the gross capitalized cost
+    #[serde(default)]
+    pub gross_cap_cost_breakdown: Vec<CapCostLineItem>,
     /// Cap cost reductions (down, trade, rebates)
     pub cap_cost_reductions: Money,
     /// Adjusted capitalized cost
@@ -161,10 +417,24 @@ pub struct LeaseStructure {
     /// Residual value (dollar amount)
     pub residual_value: Money,
 
+    // === Mileage ===
+    /// Annual mileage tier the lease is priced on
+    #[serde(default)]
+    pub annual_miles: u32,
+    /// Excess mileage charge per mile (for overage estimates)
+    #[serde(default)]
+    pub excess_mileage_rate: Option<Money>,
+
     // === Lease Charge ===
-    /// Money factor
-    pub money_factor: Decimal,
-    /// Equivalent APR
+    /// Money factor before any MSD reduction
+    #[serde(default)]
+    pub base_money_factor: MoneyFactor,
+    /// Equivalent APR of the base money factor
+    #[serde(default)]
+    pub base_equivalent_apr: Rate,
+    /// Effective money factor after any MSD reduction (raw scale, e.g., 0.00125)
+    pub money_factor: MoneyFactor,
+    /// Equivalent APR of the effective money factor (money factor x 2400, as a rate)
     pub equivalent_apr: Rate,
     /// Term in months
     pub term_months: u16,
@@ -182,18 +452,37 @@ pub struct LeaseStructure {
     pub total_monthly_payment: Money,
     /// Due at signing
     pub due_at_signing: Money,
+    /// Portion of due at signing returned at lease end (deposits, MSDs)
+    #[serde(default)]
+    pub refundable_due_at_signing: Money,
+    /// Portion of due at signing that is spent (first payment, interim
+    /// rent, fees, cap reduction, upfront tax)
+    #[serde(default)]
+    pub nonrefundable_due_at_signing: Money,
     /// Security deposit
     pub security_deposit: Money,
+    /// Multiple security deposits
+    #[serde(default)]
+    pub msd_deposit: Money,
+    /// Prorated rent for a contract that starts before the billing cycle
+    #[serde(default)]
+    pub interim_rent: Money,
     /// First month's payment
     pub first_payment: Money,
     /// Acquisition fee
     pub acquisition_fee: Money,
+    /// All fees paid upfront (including an upfront acquisition fee)
+    #[serde(default)]
+    pub upfront_fees: Money,
 
     // === Tax Mode ===
     /// Lease tax mode used
     pub lease_tax_mode: String,
     /// Upfront tax (if cap cost tax mode)
     pub upfront_tax: Option<Money>,
+    /// Tax rolled into the gross capitalized cost instead of paid upfront
+    #[serde(default)]
+    pub capitalized_tax: Money,
     /// Total of all taxes
     pub total_tax: Money,
 
@@ -202,6 +491,37 @@ pub struct LeaseStructure {
     pub total_base_payments: Money,
     /// Total lease cost
     pub total_lease_cost: Money,
+
+    // === Lease End ===
+    /// Cost to return the vehicle vs buy it out at lease end
+    #[serde(default)]
+    pub end_of_lease: Option<EndOfLeaseOptions>,
+}
+
+/// Lease-end choices: return the vehicle or exercise the purchase option.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndOfLeaseOptions {
+    // === Return ===
+    /// Disposition fee due on return
+    pub disposition_fee: Money,
+    /// Estimated miles over the allowance for the term
+    pub estimated_excess_miles: u32,
+    /// Estimated excess mileage charge
+    pub excess_mileage_charge: Money,
+    /// Excess wear and use charge (not estimated; always zero)
+    pub excess_wear_charge: Money,
+    /// Total cost to return the vehicle
+    pub return_cost: Money,
+
+    // === Buyout ===
+    /// Residual value (purchase option price)
+    pub residual_value: Money,
+    /// Purchase option fee
+    pub purchase_option_fee: Money,
+    /// Tax due on the buyout
+    pub buyout_tax: Money,
+    /// Total cost to buy the vehicle
+    pub buyout_cost: Money,
 }
 
 // ============================================================================
@@ -227,6 +547,64 @@ pub struct CashStructure {
     pub total_cash_price: Money,
 }
 
+// ============================================================================
+// TRADE-IN
+// ============================================================================
+
+/// Trade-in amounts for disclosure and funding.
+///
+/// Exactly one of `net_equity` / `negative_equity` is non-zero when the
+/// allowance and payoff differ.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeBreakdown {
+    /// Trade allowance credited to the customer
+    pub gross_allowance: Money,
+    /// Actual cash value of the trade (the allowance when not appraised separately)
+    pub acv: Money,
+    /// Payoff sent to the lienholder (including accrued per-diem)
+    pub payoff: Money,
+    /// Equity applied to the deal (allowance above payoff)
+    pub net_equity: Money,
+    /// Negative equity carried into the deal (payoff above allowance)
+    pub negative_equity: Money,
+}
+
+// ============================================================================
+// PROFIT ANALYSIS
+// ============================================================================
+
+/// Dealer profit analysis (front-end and back-end gross).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfitAnalysis {
+    // === Front-End ===
+    /// Vehicle cost basis (invoice, or estimated from MSRP)
+    pub vehicle_cost: Money,
+    /// Vehicle gross (selling price - vehicle cost)
+    pub vehicle_gross: Money,
+    /// Holdback credited to the dealer
+    pub holdback: Money,
+    /// Pack added to vehicle cost
+    pub pack: Money,
+    /// Trade allowance above the trade's actual cash value, taken out of
+    /// front-end gross (negative when the trade was allowed under ACV)
+    #[serde(default)]
+    pub trade_over_allowance: Money,
+    /// Front-end gross
+    pub front_end_gross: Money,
+
+    // === Back-End ===
+    /// F&I product gross (price - cost)
+    pub fi_product_gross: Money,
+    /// Dealer reserve from rate participation
+    pub dealer_reserve: Money,
+    /// Back-end gross
+    pub back_end_gross: Money,
+
+    // === Totals ===
+    /// Total gross (front-end + back-end)
+    pub total_gross: Money,
+}
+
 // ============================================================================
 // DISCLOSURES
 // ============================================================================
@@ -286,6 +664,10 @@ pub struct AuditTrace {
     pub input_checksum: String,
     /// Checksum of outputs
     pub output_checksum: String,
+    /// Wall-clock duration of each phase
+    #[cfg(feature = "metrics")]
+    #[serde(default)]
+    pub phase_timings: PhaseTimings,
 }
 
 impl Default for AuditTrace {
@@ -298,10 +680,51 @@ impl Default for AuditTrace {
             calculated_at: Utc::now(),
             input_checksum: String::new(),
             output_checksum: String::new(),
+            #[cfg(feature = "metrics")]
+            phase_timings: PhaseTimings::default(),
         }
     }
 }
 
+/// Per-phase durations of one pipeline run, in execution order.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTimings {
+    /// One entry per phase that ran
+    pub phases: Vec<PhaseTiming>,
+}
+
+#[cfg(feature = "metrics")]
+impl PhaseTimings {
+    /// Record a phase's duration.
+    pub fn record(&mut self, phase: &str, elapsed: std::time::Duration) {
+        self.phases.push(PhaseTiming {
+            phase: phase.to_string(),
+            micros: u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX),
+        });
+    }
+
+    /// Duration of a phase in microseconds, if it ran.
+    pub fn get(&self, phase: &str) -> Option<u64> {
+        self.phases.iter().find(|t| t.phase == phase).map(|t| t.micros)
+    }
+
+    /// Sum of all phase durations in microseconds.
+    pub fn total_micros(&self) -> u64 {
+        self.phases.iter().map(|t| t.micros).sum()
+    }
+}
+
+/// Duration of a single phase.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    /// Phase id ("P0" through "P7")
+    pub phase: String,
+    /// Wall-clock duration in microseconds
+    pub micros: u64,
+}
+
 // ============================================================================
 // VALIDATION RESULT
 // ============================================================================
@@ -317,18 +740,30 @@ pub struct ValidationWarning {
     pub message: String,
 }
 
+/// Validation error (fatal) tied to an input field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Error code
+    pub code: String,
+    /// Field path (e.g., "finance_params.apr"); empty if not field-specific
+    pub field: String,
+    /// Error message
+    pub message: String,
+}
+
 /// Validation result.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ValidationResult {
-    /// Whether validation passed
+    /// Whether validation passed (no blocking errors)
     pub valid: bool,
     /// Errors (fatal)
-    pub errors: Vec<String>,
+    pub errors: Vec<FieldError>,
     /// Warnings (non-fatal)
     pub warnings: Vec<ValidationWarning>,
 }
 
 impl ValidationResult {
+    /// Create a passing result with no warnings.
     pub fn ok() -> Self {
         ValidationResult {
             valid: true,
@@ -337,13 +772,36 @@ impl ValidationResult {
         }
     }
 
+    /// Create a failing result with a single error not tied to a field.
     pub fn with_error(error: impl Into<String>) -> Self {
+        let mut result = Self::ok();
+        result.add_error("VALIDATION_ERROR", "", error);
+        result
+    }
+
+    /// Create a passing result carrying the given warnings.
+    pub fn with_warnings(warnings: Vec<ValidationWarning>) -> Self {
         ValidationResult {
-            valid: false,
-            errors: vec![error.into()],
-            warnings: Vec::new(),
+            valid: true,
+            errors: Vec::new(),
+            warnings,
         }
     }
+
+    /// Record a blocking error against a field.
+    pub fn add_error(
+        &mut self,
+        code: impl Into<String>,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.valid = false;
+        self.errors.push(FieldError {
+            code: code.into(),
+            field: field.into(),
+            message: message.into(),
+        });
+    }
 }
 
 // ============================================================================
@@ -370,6 +828,21 @@ pub struct UdcOutput {
     /// Complete tax breakdown
     pub tax_breakdown: TaxBreakdown,
 
+    // === Fees ===
+    /// Every fee charged, with its resolved taxability and capitalization
+    #[serde(default)]
+    pub fee_itemization: Vec<FeeLine>,
+    /// Registration fee split by jurisdiction mileage (fleet apportioned
+    /// registration; empty otherwise). Sums to the registration fee.
+    #[serde(default)]
+    pub apportioned_registration: Vec<ApportionedRegistrationLine>,
+
+    // === Credit ===
+    /// Credit tier used for rate sheet and money factor lookups (explicit,
+    /// else derived from the credit score)
+    #[serde(default)]
+    pub credit_tier: Option<CreditTier>,
+
     // === Structure (one will be populated based on deal_type) ===
     /// Cash deal structure
     pub cash_structure: Option<CashStructure>,
@@ -382,6 +855,16 @@ pub struct UdcOutput {
     /// Full amortization schedule
     pub amortization_schedule: Option<Vec<AmortizationEntry>>,
 
+    // === Trade-In ===
+    /// Trade allowance, payoff, and equity (None when there is no trade)
+    #[serde(default)]
+    pub trade_breakdown: Option<TradeBreakdown>,
+
+    // === Profit ===
+    /// Dealer profit analysis (requires vehicle cost information)
+    #[serde(default)]
+    pub profit_analysis: Option<ProfitAnalysis>,
+
     // === Disclosures ===
     /// Required disclosures
     pub disclosures: Vec<Disclosure>,
@@ -393,6 +876,28 @@ pub struct UdcOutput {
     // === Summary ===
     /// One-line summary (e.g., "$450/mo for 60 months")
     pub summary: String,
+    /// The summary's figures as typed fields (None when validation failed)
+    #[serde(default)]
+    pub deal_summary: Option<DealSummary>,
+}
+
+/// Headline terms of a deal, typed; `UdcOutput::summary` is the same in
+/// words.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DealSummary {
+    /// Deal type
+    pub deal_type: DealType,
+    /// Payment per period (None for cash); a lease payment includes its tax
+    pub monthly_payment: Option<Money>,
+    /// Term in months (None for cash)
+    pub term_months: Option<u16>,
+    /// Contract APR, or a lease's money factor as an equivalent APR
+    pub apr: Option<Rate>,
+    /// Due from the customer at signing
+    pub due_at_signing: Money,
+    /// Total the customer pays: the cash price, the total sale price
+    /// (finance), or the total lease cost
+    pub total_due: Money,
 }
 
 impl UdcOutput {
@@ -405,13 +910,19 @@ impl UdcOutput {
             calculated_at: Utc::now(),
             validation: ValidationResult::with_error(error),
             tax_breakdown: TaxBreakdown::default(),
+            fee_itemization: Vec::new(),
+            apportioned_registration: Vec::new(),
+            credit_tier: None,
             cash_structure: None,
             finance_structure: None,
             lease_structure: None,
             amortization_schedule: None,
+            trade_breakdown: None,
+            profit_analysis: None,
             disclosures: Vec::new(),
             audit_trace: AuditTrace::default(),
             summary: "Validation failed".to_string(),
+            deal_summary: None,
         }
     }
 
@@ -424,6 +935,56 @@ impl UdcOutput {
         }
     }
 
+    /// Get the contract term in months (finance or lease).
+    pub fn term_months(&self) -> Option<u16> {
+        match self.deal_type {
+            DealType::Finance => self.finance_structure.as_ref().map(|f| f.term_months),
+            DealType::Lease => self.lease_structure.as_ref().map(|l| l.term_months),
+            DealType::Cash => None,
+        }
+    }
+
+    /// Get the amount financed (finance) or adjusted cap cost (lease).
+    pub fn amount_financed(&self) -> Money {
+        match self.deal_type {
+            DealType::Cash => Money::ZERO,
+            DealType::Finance => self.finance_structure.as_ref()
+                .map(|f| f.amount_financed)
+                .unwrap_or(Money::ZERO),
+            DealType::Lease => self.lease_structure.as_ref()
+                .map(|l| l.adjusted_cap_cost)
+                .unwrap_or(Money::ZERO),
+        }
+    }
+
+    /// Get the sum of all scheduled payments over the term.
+    pub fn total_of_payments(&self) -> Money {
+        match self.deal_type {
+            DealType::Cash => Money::ZERO,
+            DealType::Finance => self.finance_structure.as_ref()
+                .map(|f| f.total_of_payments)
+                .unwrap_or(Money::ZERO),
+            DealType::Lease => self.lease_structure.as_ref()
+                .map(|l| l.total_monthly_payment * Decimal::from(l.term_months))
+                .unwrap_or(Money::ZERO),
+        }
+    }
+
+    /// Get the amount the customer pays at signing.
+    pub fn due_at_signing(&self) -> Money {
+        match self.deal_type {
+            DealType::Cash => self.cash_structure.as_ref()
+                .map(|c| c.total_cash_price)
+                .unwrap_or(Money::ZERO),
+            DealType::Finance => self.finance_structure.as_ref()
+                .map(|f| f.due_at_signing)
+                .unwrap_or(Money::ZERO),
+            DealType::Lease => self.lease_structure.as_ref()
+                .map(|l| l.due_at_signing)
+                .unwrap_or(Money::ZERO),
+        }
+    }
+
     /// Get the total amount due.
     pub fn total_due(&self) -> Money {
         match self.deal_type {
@@ -440,6 +1001,26 @@ impl UdcOutput {
     }
 }
 
+/// Side-by-side comparison of two deal scenarios.
+///
+/// Deltas are `b - a`, so a negative `monthly_payment` means scenario `b`
+/// has the lower payment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputDiff {
+    /// Change in monthly payment
+    pub monthly_payment: Money,
+    /// Change in amount financed (adjusted cap cost for leases)
+    pub amount_financed: Money,
+    /// Change in total of payments
+    pub total_of_payments: Money,
+    /// Change in net tax
+    pub net_tax: Money,
+    /// Change in amount due at signing
+    pub due_at_signing: Money,
+    /// Names of the fields that differ between the scenarios
+    pub changed_fields: Vec<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -7,7 +7,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDate;
 
-use super::ProductType;
+use super::{DealType, ProductType, Vehicle, VehicleCondition};
 
 /// Complete profile for an F&I product.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,6 +92,58 @@ impl ProductProfile {
 
         true
     }
+
+    /// Why this product cannot be sold on a deal, or None if it can.
+    ///
+    /// Checks the deal type flags, then the vehicle's age, mileage,
+    /// condition, make, and model when vehicle details are known.
+    pub fn ineligibility_reason(
+        &self,
+        deal_type: DealType,
+        vehicle: Option<&Vehicle>,
+        vehicle_age: Option<u32>,
+    ) -> Option<String> {
+        let rules = &self.eligibility;
+
+        let deal_type_eligible = match deal_type {
+            DealType::Finance => rules.finance_eligible,
+            DealType::Lease => rules.lease_eligible,
+            DealType::Cash => rules.cash_eligible,
+        };
+        if !deal_type_eligible {
+            return Some(format!("not available on {:?} deals", deal_type).to_lowercase());
+        }
+
+        if let (Some(max_age), Some(age)) = (rules.max_vehicle_age, vehicle_age) {
+            if age > max_age {
+                return Some(format!("vehicle age {} exceeds maximum {}", age, max_age));
+            }
+        }
+
+        let vehicle = vehicle?;
+        if let Some(max_mileage) = rules.max_mileage_at_sale {
+            if vehicle.odometer > max_mileage {
+                return Some(format!(
+                    "odometer {} exceeds maximum {}",
+                    vehicle.odometer, max_mileage
+                ));
+            }
+        }
+        if rules.new_only && vehicle.condition != VehicleCondition::New {
+            return Some("new vehicles only".to_string());
+        }
+        if rules.used_only && vehicle.condition == VehicleCondition::New {
+            return Some("used vehicles only".to_string());
+        }
+        if rules.excluded_makes.iter().any(|m| m.eq_ignore_ascii_case(&vehicle.make)) {
+            return Some(format!("{} is an excluded make", vehicle.make));
+        }
+        if rules.excluded_models.iter().any(|m| m.eq_ignore_ascii_case(&vehicle.model)) {
+            return Some(format!("{} is an excluded model", vehicle.model));
+        }
+
+        None
+    }
 }
 
 /// Provider/administrator information
@@ -151,6 +203,28 @@ pub struct ProductPricing {
     pub tax_inclusive: bool,
 }
 
+impl ProductPricing {
+    /// Price from the rate table for a value (vehicle value or amount
+    /// financed), clamped to the min/max selling price and rounded to the
+    /// cent. None when there is no rate table or no tier covers the value.
+    pub fn rate_price(&self, value: Decimal) -> Option<Decimal> {
+        let tier = self.rate_table
+            .as_ref()?
+            .tiers
+            .iter()
+            .find(|t| value >= t.min_value && value <= t.max_value)?;
+
+        let mut price = value * tier.rate;
+        if let Some(min) = self.min_price {
+            price = price.max(min);
+        }
+        if let Some(max) = self.max_price {
+            price = price.min(max);
+        }
+        Some(price.round_dp(2))
+    }
+}
+
 /// Pricing model types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -681,4 +755,16 @@ mod tests {
         // Vehicle with 150k miles should not be eligible
         assert!(!product.is_vehicle_eligible(2022, 150000, "sedan"));
     }
+
+    #[test]
+    fn test_ineligibility_reason_by_deal_type() {
+        let product = create_test_product();
+
+        assert_eq!(product.ineligibility_reason(DealType::Finance, None, Some(2)), None);
+        assert_eq!(
+            product.ineligibility_reason(DealType::Lease, None, Some(2)).as_deref(),
+            Some("not available on lease deals")
+        );
+        assert!(product.ineligibility_reason(DealType::Finance, None, Some(9)).is_some());
+    }
 }
@@ -61,6 +61,34 @@ pub enum LeaseTaxMode {
     Exempt,
 }
 
+/// Which slice of the lease payment a monthly-taxed lease is taxed on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum LeaseMonthlyTaxBase {
+    /// The full base payment
+    #[default]
+    FullPayment,
+    /// Depreciation plus rent, excluding the amortized capitalized fees
+    DepreciationPlusRent,
+    /// Depreciation only, excluding the rent charge
+    DepreciationOnly,
+}
+
+/// Where standard sales tax is rounded to the cent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TaxRounding {
+    /// Each component (state, county, city, district) is rounded and the
+    /// total is their sum
+    #[default]
+    PerComponent,
+    /// Only the total is rounded; the rounding remainder goes to the
+    /// largest component so the components still sum to the total
+    OnTotal,
+}
+
 /// How state/county/city taxes stack.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
 #[serde(rename_all = "snake_case")]
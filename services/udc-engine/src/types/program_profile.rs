@@ -7,7 +7,7 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDate;
 
-use super::{CreditTier, DealType, StateCode};
+use super::{CreditTier, DealType, StateCode, Vehicle};
 
 /// Program profile for a lender or lessor.
 /// Defines financing terms, rate sheets, and constraints.
@@ -45,6 +45,10 @@ pub struct ProgramProfile {
     /// Eligibility requirements
     pub eligibility: EligibilityRules,
 
+    /// Lease program (money factors, residuals) for lessors
+    #[serde(default)]
+    pub lease_program: Option<LeaseProgram>,
+
     /// Program metadata
     pub meta: ProgramMeta,
 }
@@ -72,6 +76,14 @@ impl ProgramProfile {
             .unwrap_or(self.rate_sheet.default_max_reserve)
     }
 
+    /// Find a special/promotional rate by program code (case-insensitive)
+    pub fn find_special_rate(&self, code: &str) -> Option<&SpecialRate> {
+        self.rate_sheet
+            .special_rates
+            .iter()
+            .find(|s| s.code.eq_ignore_ascii_case(code))
+    }
+
     /// Check if a deal amount is within program limits
     pub fn is_amount_eligible(&self, amount: Decimal) -> bool {
         if let Some(min) = self.structure_rules.min_amount_financed {
@@ -86,6 +98,29 @@ impl ProgramProfile {
         }
         true
     }
+
+    /// Maximum term for a vehicle of the given age (years), from the
+    /// tightest `max_term_by_age` bracket that covers it
+    pub fn max_term_for_age(&self, vehicle_age: u32) -> Option<u32> {
+        self.structure_rules
+            .max_term_by_age
+            .iter()
+            .filter(|t| vehicle_age <= t.max_age)
+            .min_by_key(|t| t.max_age)
+            .map(|t| t.max_term)
+    }
+
+    /// Check if a term is offered by the program for a vehicle of the given age
+    pub fn is_term_available(&self, term_months: u32, vehicle_age: Option<u32>) -> bool {
+        let terms = &self.structure_rules.available_terms;
+        if !terms.is_empty() && !terms.contains(&term_months) {
+            return false;
+        }
+        match vehicle_age.and_then(|age| self.max_term_for_age(age)) {
+            Some(max_term) => term_months <= max_term,
+            None => true,
+        }
+    }
 }
 
 /// Rate sheet configuration
@@ -170,6 +205,10 @@ pub struct SpecialRate {
     /// The special rate
     pub rate: Decimal,
 
+    /// Subvented money factor (lease programs)
+    #[serde(default)]
+    pub money_factor: Option<Decimal>,
+
     /// Required credit tiers
     #[serde(default)]
     pub required_tiers: Vec<CreditTier>,
@@ -193,6 +232,33 @@ pub struct SpecialRate {
     pub max_reserve_with_special: Option<Decimal>,
 }
 
+impl SpecialRate {
+    /// Check if the program is active on a date
+    pub fn is_active_on(&self, date: NaiveDate) -> bool {
+        date >= self.start_date && date <= self.end_date
+    }
+
+    /// Check if a credit tier qualifies (empty requirements = all tiers)
+    pub fn is_tier_eligible(&self, tier: Option<CreditTier>) -> bool {
+        self.required_tiers.is_empty()
+            || tier.is_some_and(|t| self.required_tiers.contains(&t))
+    }
+
+    /// Check if a vehicle qualifies by model or "make model" (empty requirements = all vehicles)
+    pub fn is_vehicle_eligible(&self, vehicle: Option<&Vehicle>) -> bool {
+        if self.vehicle_requirements.is_empty() {
+            return true;
+        }
+        let Some(vehicle) = vehicle else {
+            return false;
+        };
+        let make_model = format!("{} {}", vehicle.make, vehicle.model);
+        self.vehicle_requirements.iter().any(|r| {
+            r.eq_ignore_ascii_case(&vehicle.model) || r.eq_ignore_ascii_case(&make_model)
+        })
+    }
+}
+
 /// Structure rules and constraints
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct StructureRules {
@@ -212,6 +278,11 @@ pub struct StructureRules {
     #[serde(default)]
     pub max_ltv: Option<Decimal>,
 
+    /// Vehicle value the program measures loan-to-value against
+    /// (overrides the engine's configured basis)
+    #[serde(default)]
+    pub ltv_basis: Option<LtvBasis>,
+
     /// Minimum down payment percentage
     #[serde(default)]
     pub min_down_percent: Option<Decimal>,
@@ -248,7 +319,7 @@ pub struct StructureRules {
     #[serde(default)]
     pub max_backend_amount: Option<Decimal>,
 
-    /// Maximum back-end as percent of vehicle price
+    /// Maximum back-end as percent of vehicle price (as decimal, e.g. 0.15)
     #[serde(default)]
     pub max_backend_percent: Option<Decimal>,
 
@@ -259,6 +330,23 @@ pub struct StructureRules {
     /// Debt-to-income ratio limit
     #[serde(default)]
     pub max_dti: Option<Decimal>,
+
+    /// Minimum periodic payment; smaller loans get a shorter term
+    #[serde(default)]
+    pub min_payment: Option<Decimal>,
+}
+
+/// Vehicle value used as the loan-to-value denominator
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LtvBasis {
+    /// Manufacturer's suggested retail price
+    #[default]
+    Msrp,
+    /// Dealer invoice
+    Invoice,
+    /// Guide book value (NADA, KBB, etc.)
+    BookValue,
 }
 
 /// Maximum term based on vehicle age
@@ -271,7 +359,7 @@ pub struct VehicleAgeTerm {
 }
 
 /// Program fee configuration
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgramFeeConfig {
     /// Acquisition fee (lease)
     #[serde(default)]
@@ -293,11 +381,29 @@ pub struct ProgramFeeConfig {
     #[serde(default)]
     pub disposition_fee: Decimal,
 
+    /// Purchase option fee charged on a lease-end buyout (lease)
+    #[serde(default)]
+    pub purchase_option_fee: Decimal,
+
     /// Early termination fee structure
     #[serde(default)]
     pub early_termination: Option<EarlyTerminationFee>,
 }
 
+impl Default for ProgramFeeConfig {
+    fn default() -> Self {
+        Self {
+            acquisition_fee: Decimal::ZERO,
+            bank_fee: Decimal::ZERO,
+            processing_fee: Decimal::ZERO,
+            fees_capitalizable: true,
+            disposition_fee: Decimal::ZERO,
+            purchase_option_fee: Decimal::ZERO,
+            early_termination: None,
+        }
+    }
+}
+
 fn default_true() -> bool {
     true
 }
@@ -421,6 +527,28 @@ pub struct LeaseProgram {
     pub drive_off_rules: DriveOffRules,
 }
 
+impl LeaseProgram {
+    /// Look up the buy-rate money factor for a credit tier
+    pub fn money_factor_for(&self, tier: CreditTier) -> Option<&TierMoneyFactor> {
+        self.money_factors.iter().find(|m| m.tier == tier)
+    }
+
+    /// Look up the mileage option for an annual mileage tier
+    pub fn mileage_option(&self, annual_miles: u32) -> Option<&MileageOption> {
+        self.mileage_options.iter().find(|m| m.annual_miles == annual_miles)
+    }
+
+    /// Money factor reduction earned by multiple security deposits
+    pub fn msd_reduction(&self, msd_count: u8) -> Decimal {
+        match &self.msd_config {
+            Some(msd) if msd.available => {
+                Decimal::from(msd_count.min(msd.max_count)) * msd.mf_reduction_per_msd
+            }
+            _ => Decimal::ZERO,
+        }
+    }
+}
+
 /// Money factor by credit tier
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TierMoneyFactor {
@@ -614,6 +742,7 @@ mod tests {
             },
             fee_config: ProgramFeeConfig::default(),
             eligibility: EligibilityRules::default(),
+            lease_program: None,
             meta: ProgramMeta::default(),
         }
     }
@@ -646,6 +775,26 @@ mod tests {
         assert_eq!(program.max_reserve(84), dec!(2.0)); // Falls back to default
     }
 
+    #[test]
+    fn test_term_availability() {
+        let mut program = create_test_program();
+        program.structure_rules.available_terms = vec![36, 48, 60, 72, 84];
+        program.structure_rules.max_term_by_age = vec![
+            VehicleAgeTerm { max_age: 2, max_term: 84 },
+            VehicleAgeTerm { max_age: 5, max_term: 72 },
+            VehicleAgeTerm { max_age: 8, max_term: 60 },
+        ];
+
+        assert_eq!(program.max_term_for_age(0), Some(84));
+        assert_eq!(program.max_term_for_age(4), Some(72));
+        assert_eq!(program.max_term_for_age(10), None);
+
+        assert!(program.is_term_available(84, Some(1)));
+        assert!(!program.is_term_available(84, Some(4)));
+        assert!(!program.is_term_available(66, None));
+        assert!(program.is_term_available(84, None));
+    }
+
     #[test]
     fn test_amount_eligibility() {
         let program = create_test_program();
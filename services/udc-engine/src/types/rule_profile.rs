@@ -7,7 +7,11 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDate;
 
-use super::{DealType, LeaseTaxMode, StateCode, TaxType};
+use super::{DealType, LeaseMonthlyTaxBase, LeaseTaxMode, ProductType, StateCode, TaxRounding, TaxType};
+
+/// Version of the `RuleProfile` JSON schema; bumped when a change would
+/// break profiles written for an earlier version.
+pub const RULE_PROFILE_SCHEMA_VERSION: &str = "1";
 
 /// Complete tax rule profile for a state/deal-type combination.
 /// This is the authoritative source for how taxes are calculated.
@@ -38,6 +42,55 @@ pub struct RuleProfile {
     #[serde(default)]
     pub lease_tax_mode: Option<LeaseTaxMode>,
 
+    /// Leases of vehicles garaged in this state are taxed at this state's
+    /// rate and lease tax mode, wherever the lease is signed
+    #[serde(default)]
+    pub lease_sourced_to_garaging: bool,
+
+    /// Leases: the cap cost reduction (cash, trade, and rebates applied
+    /// down) is taxed at signing in addition to the monthly payment tax.
+    /// P5 reads it directly; [`LeaseTaxConfig::tax_cap_reduction`] carries
+    /// it to `calculate_lease_tax`
+    #[serde(default)]
+    pub tax_cap_reduction: bool,
+
+    /// Leases taxed monthly: which slice of the payment is taxed
+    #[serde(default)]
+    pub lease_monthly_tax_base: LeaseMonthlyTaxBase,
+
+    /// Leases: trade equity applied to the lease escapes tax, independent
+    /// of `base_rules.trade_in_reduces_basis`. Without the credit the trade
+    /// is taxed: cap-cost modes tax the cap cost before the trade, and
+    /// payment modes add the trade's monthly share to the taxed payment
+    /// (unless it is already taxed with the cap cost reduction)
+    #[serde(default = "default_true")]
+    pub lease_trade_credit: bool,
+
+    /// Leases: whether a capitalized doc fee is in the lease tax base, where
+    /// the state treats it differently from retail. `None` follows
+    /// `base_rules.doc_fee_taxable`. Carried into
+    /// [`LeaseTaxConfig::doc_fee_taxable`] by [`RuleProfile::lease_tax_config`]
+    #[serde(default)]
+    pub lease_doc_fee_taxable: Option<bool>,
+
+    /// Leases: the unearned share of tax prepaid on the lease (at signing
+    /// or in the cap cost) is refunded pro rata on early termination
+    #[serde(default)]
+    pub lease_prepaid_tax_refundable: bool,
+
+    /// Finance deals: the tax is collected in installments with each
+    /// payment instead of being added to the amount financed
+    #[serde(default)]
+    pub finance_tax_monthly: bool,
+
+    /// Whether standard tax is rounded per component or on the total
+    #[serde(default)]
+    pub tax_rounding: TaxRounding,
+
+    /// Statutory title/registration/plate fees
+    #[serde(default)]
+    pub government_fees: GovernmentFeeSchedule,
+
     /// Profile metadata
     pub meta: ProfileMeta,
 }
@@ -48,6 +101,40 @@ impl RuleProfile {
         self.rates.state_rate
     }
 
+    /// Replace the rate the state's tax type charges with a caller-supplied
+    /// combined rate: the combined sales rate in sales/use states, the
+    /// special tax rate in TAVT, HUT and excise states.
+    pub fn override_combined_rate(&mut self, rate: Decimal) {
+        match self.tax_type {
+            TaxType::Sales | TaxType::Use => self.rates.default_combined_rate = rate,
+            TaxType::Tavt => self.rates.tavt_rate = Some(rate),
+            TaxType::Hut => self.rates.hut_rate = Some(rate),
+            TaxType::Excise => self.rates.excise_rate = Some(rate),
+            TaxType::None => {}
+        }
+    }
+
+    /// The profile's lease tax settings as a [`LeaseTaxConfig`], for
+    /// [`calculate_lease_tax`](crate::phases::p4_tax_cipher::calculate_lease_tax)
+    pub fn lease_tax_config(&self) -> LeaseTaxConfig {
+        LeaseTaxConfig {
+            mode: self.lease_tax_mode.unwrap_or(LeaseTaxMode::MonthlyPayment),
+            monthly_rate: None,
+            upfront_rate: None,
+            msd_taxable: false,
+            acquisition_fee_taxable: false,
+            cap_acq_fee_in_base: false,
+            tax_cap_reduction: self.tax_cap_reduction,
+            monthly_tax_base: self.lease_monthly_tax_base,
+            doc_fee_taxable: self.lease_doc_fee_taxable,
+        }
+    }
+
+    /// Whether the doc fee is taxed on a lease (see `lease_doc_fee_taxable`)
+    pub fn doc_fee_taxable_on_lease(&self) -> bool {
+        self.lease_doc_fee_taxable.unwrap_or(self.base_rules.doc_fee_taxable)
+    }
+
     /// Check if trade-in reduces tax basis
     pub fn trade_reduces_basis(&self) -> bool {
         self.base_rules.trade_in_reduces_basis
@@ -113,6 +200,14 @@ pub struct BaseRules {
     #[serde(default)]
     pub max_trade_in_credit: Option<Decimal>,
 
+    /// Minimum allowance a trade needs to earn any credit
+    #[serde(default)]
+    pub trade_credit_min_value: Option<Decimal>,
+
+    /// Credit only trades titled to the buyer
+    #[serde(default)]
+    pub same_owner_required: bool,
+
     /// Do manufacturer rebates reduce the taxable amount?
     #[serde(default)]
     pub rebates_reduce_basis: bool,
@@ -159,6 +254,8 @@ impl Default for BaseRules {
         Self {
             trade_in_reduces_basis: true,
             max_trade_in_credit: None,
+            trade_credit_min_value: None,
+            same_owner_required: false,
             rebates_reduce_basis: false,
             rebate_types_reduce_basis: vec![],
             dealer_discount_reduces_basis: true,
@@ -234,9 +331,31 @@ pub struct AncillaryRules {
     /// Is title fee taxable?
     #[serde(default)]
     pub title_fee_taxable: bool,
+
+    /// Fee codes the state forces taxable or exempt, regardless of the
+    /// dealer's `OtherFee::taxable` flag
+    #[serde(default)]
+    pub fee_overrides: Vec<FeeTaxOverride>,
+}
+
+/// State-mandated taxability for a fee code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTaxOverride {
+    /// Fee code (matched case-insensitively against `OtherFee::code`)
+    pub code: String,
+    /// Forced taxable (true) or exempt (false)
+    pub taxable: bool,
 }
 
 impl AncillaryRules {
+    /// State-forced taxability for a fee code, if the state overrides it
+    pub fn fee_override(&self, code: &str) -> Option<bool> {
+        self.fee_overrides
+            .iter()
+            .find(|o| o.code.eq_ignore_ascii_case(code))
+            .map(|o| o.taxable)
+    }
+
     /// Check if a product type is taxable according to these rules
     pub fn is_product_taxable(&self, product_type: &str) -> bool {
         match product_type.to_lowercase().as_str() {
@@ -254,6 +373,24 @@ impl AncillaryRules {
             _ => self.default_product_taxable,
         }
     }
+
+    /// Check if a product category is taxable according to these rules
+    pub fn is_product_type_taxable(&self, product_type: ProductType) -> bool {
+        match product_type {
+            ProductType::Vsc => self.vsc_taxable,
+            ProductType::Gap => self.gap_taxable,
+            ProductType::TireWheel => self.tire_wheel_taxable,
+            ProductType::Appearance => self.appearance_taxable,
+            ProductType::Maintenance => self.maintenance_taxable,
+            ProductType::KeyReplacement => self.key_replacement_taxable,
+            ProductType::TheftProtection => self.theft_taxable,
+            ProductType::Windshield => self.windshield_taxable,
+            ProductType::DentProtection => self.dent_taxable,
+            ProductType::CreditLife => self.credit_life_taxable,
+            ProductType::CreditDisability => self.credit_disability_taxable,
+            ProductType::Other => self.default_product_taxable,
+        }
+    }
 }
 
 /// Interstate tax reciprocity rules
@@ -296,6 +433,38 @@ pub struct PartialCreditState {
     pub conditions: Option<String>,
 }
 
+/// Statutory government fee schedule for a state.
+/// Zero means the state has no schedule for that fee (input is used as-is).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GovernmentFeeSchedule {
+    /// Title application fee
+    #[serde(default)]
+    pub title_fee: Decimal,
+
+    /// Base registration fee
+    #[serde(default)]
+    pub registration_fee: Decimal,
+
+    /// Fee for issuing a new plate
+    #[serde(default)]
+    pub new_plate_fee: Decimal,
+
+    /// Fee for transferring an existing plate
+    #[serde(default)]
+    pub plate_transfer_fee: Decimal,
+}
+
+impl GovernmentFeeSchedule {
+    /// Statutory plate fee for a new plate or a plate transfer
+    pub fn plate_fee(&self, plate_transfer: bool) -> Decimal {
+        if plate_transfer {
+            self.plate_transfer_fee
+        } else {
+            self.new_plate_fee
+        }
+    }
+}
+
 /// Metadata about the profile
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProfileMeta {
@@ -365,6 +534,18 @@ pub struct LeaseTaxConfig {
     /// Is capitalized acquisition fee included in tax base?
     #[serde(default)]
     pub cap_acq_fee_in_base: bool,
+
+    /// Is the cap cost reduction taxed upfront?
+    #[serde(default)]
+    pub tax_cap_reduction: bool,
+
+    /// Slice of the monthly payment that is taxed (if monthly mode)
+    #[serde(default)]
+    pub monthly_tax_base: LeaseMonthlyTaxBase,
+
+    /// Is the doc fee taxable on a lease? (`None` follows the retail rule)
+    #[serde(default)]
+    pub doc_fee_taxable: Option<bool>,
 }
 
 #[cfg(test)]
@@ -392,6 +573,15 @@ mod tests {
             ancillaries: AncillaryRules::default(),
             reciprocity: ReciprocityRules::default(),
             lease_tax_mode: None,
+            lease_sourced_to_garaging: false,
+            tax_cap_reduction: false,
+            lease_monthly_tax_base: LeaseMonthlyTaxBase::FullPayment,
+            lease_trade_credit: true,
+            lease_doc_fee_taxable: None,
+            lease_prepaid_tax_refundable: false,
+            finance_tax_monthly: false,
+            tax_rounding: TaxRounding::PerComponent,
+            government_fees: GovernmentFeeSchedule::default(),
             meta: ProfileMeta::default(),
         };
 
@@ -414,5 +604,9 @@ mod tests {
         assert!(!rules.is_product_taxable("gap"));
         assert!(rules.is_product_taxable("tire_wheel"));
         assert!(rules.is_product_taxable("unknown_product"));
+
+        assert!(!rules.is_product_type_taxable(ProductType::Vsc));
+        assert!(rules.is_product_type_taxable(ProductType::TireWheel));
+        assert!(rules.is_product_type_taxable(ProductType::Other));
     }
 }
@@ -24,8 +24,18 @@
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-#[cfg(feature = "wasm")]
-use crate::engine::run_udc_json;
+use crate::types::UdcError;
+
+/// JSON body for an error returned from a WASM function.
+///
+/// `code` is the stable [`UdcError::code`]; clients should branch on it
+/// rather than on the message.
+fn error_json(error: &UdcError) -> serde_json::Value {
+    serde_json::json!({
+        "error": error.to_string(),
+        "code": error.code(),
+    })
+}
 
 /// Initialize panic hook for better error messages in WASM.
 #[cfg(feature = "wasm")]
@@ -36,20 +46,29 @@ pub fn wasm_init() {
     console_error_panic_hook::set_once();
 }
 
-/// Run UDC calculation from JSON inputs.
+/// Run the full P0-P7 pipeline from JSON inputs.
 ///
 /// # Arguments
 ///
 /// * `deal_json` - JSON string containing the DealInput
-/// * `rules_json` - JSON string containing the RuleProfile
-/// * `program_json` - Optional JSON string containing ProgramProfile (pass null/empty for none)
-/// * `products_json` - Optional JSON string containing ProductProfile array (pass null/empty for none)
+/// * `rules_json` - JSON string containing the RuleProfile. It replaces the
+///   engine's built-in rules when the deal is governed by its `state_code`;
+///   deals governed elsewhere use the built-in profile for that state.
+/// * `program_json` - Optional JSON string containing the lender's
+///   ProgramProfile: rate sheet, special rates, lease program, fees and
+///   structure limits are all applied (pass null/empty for none)
+/// * `products_json` - Optional JSON string containing a ProductProfile
+///   array, used for product eligibility, caps and pricing (pass
+///   null/empty for none)
 ///
 /// # Returns
 ///
 /// JSON string containing either:
 /// - Success: `UdcOutput` object
-/// - Error: `{"error": "error message"}`
+/// - Error: `{"error": "error message", "code": "VALIDATION", "success": false}`
+///   (see `UdcError::code` for the codes). A payload that fails to parse
+///   has code `SERIALIZATION` and names the argument, e.g.
+///   `"Failed to parse rule profile (rules_json): ..."`.
 ///
 /// # Example
 ///
@@ -65,8 +84,8 @@ pub fn wasm_init() {
 ///
 /// const rulesJson = JSON.stringify({
 ///   state_code: "TX",
-///   deal_type: "finance",
-///   state_rate: "0.0625",
+///   mode: "finance",
+///   rates: { state_rate: "0.0625" },
 ///   // ... other fields
 /// });
 ///
@@ -74,7 +93,7 @@ pub fn wasm_init() {
 /// const output = JSON.parse(result);
 ///
 /// if (output.error) {
-///   console.error("Calculation failed:", output.error);
+///   console.error("Calculation failed:", output.code, output.error);
 /// } else {
 ///   console.log("Monthly payment:", output.finance_structure.monthly_payment);
 /// }
@@ -87,16 +106,26 @@ pub fn run_udc_wasm(
     program_json: Option<String>,
     products_json: Option<String>,
 ) -> String {
-    let program = program_json.as_deref().filter(|s| !s.is_empty());
-    let products = products_json.as_deref().filter(|s| !s.is_empty());
+    run_pipeline_json(deal_json, rules_json, program_json.as_deref(), products_json.as_deref())
+}
 
-    match run_udc_json(deal_json, rules_json, program, products) {
+/// Shared body of `run_udc_wasm` and its non-WASM stub. Empty or
+/// whitespace-only optional payloads count as absent.
+fn run_pipeline_json(
+    deal_json: &str,
+    rules_json: &str,
+    program_json: Option<&str>,
+    products_json: Option<&str>,
+) -> String {
+    let program_json = program_json.filter(|s| !s.trim().is_empty());
+    let products_json = products_json.filter(|s| !s.trim().is_empty());
+
+    match crate::engine::run_udc_json_typed(deal_json, rules_json, program_json, products_json) {
         Ok(output_json) => output_json,
-        Err(error_msg) => {
-            serde_json::json!({
-                "error": error_msg,
-                "success": false
-            }).to_string()
+        Err(error) => {
+            let mut body = error_json(&error);
+            body["success"] = serde_json::json!(false);
+            body.to_string()
         }
     }
 }
@@ -109,46 +138,74 @@ pub fn run_udc_wasm(
 ///
 /// # Returns
 ///
-/// JSON string containing:
-/// - `{"valid": true, "warnings": [...]}` on success
-/// - `{"valid": false, "error": "...", "field": "..."}` on validation failure
+/// JSON-serialized `ValidationResult`, always with both lists:
+///
+/// ```json
+/// {
+///   "valid": false,
+///   "errors":   [{"code": "VALIDATION_ERROR", "field": "vehicle_price", "message": "..."}],
+///   "warnings": [{"code": "HIGH_APR", "field": "finance_params.apr", "message": "..."}]
+/// }
+/// ```
+///
+/// `valid` is false whenever `errors` is non-empty. JSON parse failures are
+/// reported as a single error with code `PARSE_ERROR` and an empty field.
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn validate_deal_wasm(deal_json: &str) -> String {
-    use crate::types::DealInput;
+    use crate::types::{DealInput, ValidationResult};
 
     // Parse deal input
     let input: Result<DealInput, _> = serde_json::from_str(deal_json);
 
-    match input {
-        Ok(deal) => {
-            match crate::engine::validate_deal(&deal) {
-                Ok(warnings) => {
-                    serde_json::json!({
-                        "valid": true,
-                        "warnings": warnings
-                    }).to_string()
-                }
-                Err(e) => {
-                    let (message, field) = match e {
-                        crate::types::UdcError::Validation { message, field } => (message, field),
-                        _ => (e.to_string(), None),
-                    };
-                    serde_json::json!({
-                        "valid": false,
-                        "error": message,
-                        "field": field
-                    }).to_string()
-                }
+    let result = match input {
+        Ok(deal) => match crate::engine::validate_deal(&deal) {
+            Ok(result) => result,
+            Err(e) => {
+                let mut result = ValidationResult::ok();
+                result.add_error("ENGINE_ERROR", "", e.to_string());
+                result
             }
+        },
+        Err(e) => {
+            let mut result = ValidationResult::ok();
+            result.add_error("PARSE_ERROR", "", format!("JSON parse error: {}", e));
+            result
         }
+    };
+
+    serde_json::to_string(&result).unwrap_or_else(|e| {
+        error_json(&UdcError::serialization(e.to_string())).to_string()
+    })
+}
+
+/// Normalize a deal (P0 only) without running tax or structure.
+///
+/// # Arguments
+///
+/// * `deal_json` - JSON string containing the DealInput
+///
+/// # Returns
+///
+/// JSON string containing the normalized deal (`inner` plus computed
+/// `net_trade`, `total_rebates`, product and fee totals) or error.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn normalize_deal_wasm(deal_json: &str) -> String {
+    use crate::types::DealInput;
+
+    let input: DealInput = match serde_json::from_str(deal_json) {
+        Ok(input) => input,
         Err(e) => {
-            serde_json::json!({
-                "valid": false,
-                "error": format!("JSON parse error: {}", e),
-                "field": null
-            }).to_string()
+            return error_json(&UdcError::serialization(format!("Failed to parse deal: {}", e))).to_string();
         }
+    };
+
+    match crate::engine::normalize_only(input) {
+        Ok(normalized) => serde_json::to_string(&normalized).unwrap_or_else(|e| {
+            error_json(&UdcError::serialization(e.to_string())).to_string()
+        }),
+        Err(e) => error_json(&e).to_string(),
     }
 }
 
@@ -157,7 +214,8 @@ pub fn validate_deal_wasm(deal_json: &str) -> String {
 /// # Arguments
 ///
 /// * `deal_json` - JSON string containing the DealInput
-/// * `rules_json` - JSON string containing the RuleProfile
+/// * `rules_json` - JSON string containing the RuleProfile, applied as in
+///   `run_udc_wasm`
 ///
 /// # Returns
 ///
@@ -175,19 +233,376 @@ pub fn calculate_tax_wasm(deal_json: &str, rules_json: &str) -> String {
             match crate::engine::calculate_tax_only(deal, profile) {
                 Ok(breakdown) => {
                     serde_json::to_string(&breakdown).unwrap_or_else(|e| {
-                        serde_json::json!({"error": format!("Serialization error: {}", e)}).to_string()
+                        error_json(&UdcError::serialization(e.to_string())).to_string()
                     })
                 }
-                Err(e) => {
-                    serde_json::json!({"error": e.to_string()}).to_string()
-                }
+                Err(e) => error_json(&e).to_string(),
+            }
+        }
+        (Err(e), _) => {
+            error_json(&UdcError::serialization(format!("Failed to parse deal: {}", e))).to_string()
+        }
+        (_, Err(e)) => {
+            error_json(&UdcError::serialization(format!("Failed to parse rules: {}", e))).to_string()
+        }
+    }
+}
+
+/// Calculate tax for one deal in several candidate jurisdictions.
+///
+/// # Arguments
+///
+/// * `deal_json` - JSON string containing the DealInput
+/// * `jurisdictions_json` - JSON array of Jurisdiction objects
+///   (`state`, `county`, `city`, `zip`)
+/// * `rules_json` - Optional JSON object of RuleProfiles keyed by state
+///   code, replacing the built-in rules for those states (pass null/empty
+///   for none)
+///
+/// # Returns
+///
+/// JSON array with one entry per jurisdiction, in order: a TaxBreakdown,
+/// or an error object (`{"error": ..., "code": ...}`) for a jurisdiction
+/// that failed. A payload that fails to parse returns a single error object.
+///
+/// # Example
+///
+/// ```javascript
+/// const quotes = JSON.parse(calculate_tax_batch_wasm(
+///   dealJson,
+///   JSON.stringify([{state: "TX", zip: "75001"}, {state: "GA", zip: "30301"}]),
+///   null
+/// ));
+/// ```
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn calculate_tax_batch_wasm(deal_json: &str, jurisdictions_json: &str, rules_json: Option<String>) -> String {
+    tax_batch_json(deal_json, jurisdictions_json, rules_json.as_deref())
+}
+
+/// Shared body of `calculate_tax_batch_wasm` and its non-WASM stub
+fn tax_batch_json(deal_json: &str, jurisdictions_json: &str, rules_json: Option<&str>) -> String {
+    use std::collections::HashMap;
+    use crate::types::{DealInput, Jurisdiction, RuleProfile, StateCode};
+
+    let parse_error = |what: &str, e: serde_json::Error| {
+        error_json(&UdcError::serialization(format!("Failed to parse {}: {}", what, e))).to_string()
+    };
+    let deal: DealInput = match serde_json::from_str(deal_json) {
+        Ok(deal) => deal,
+        Err(e) => return parse_error("deal (deal_json)", e),
+    };
+    let jurisdictions: Vec<Jurisdiction> = match serde_json::from_str(jurisdictions_json) {
+        Ok(jurisdictions) => jurisdictions,
+        Err(e) => return parse_error("jurisdictions (jurisdictions_json)", e),
+    };
+    let rules_by_state: HashMap<StateCode, RuleProfile> = match rules_json.filter(|s| !s.trim().is_empty()) {
+        Some(json) => match serde_json::from_str(json) {
+            Ok(rules) => rules,
+            Err(e) => return parse_error("rule profiles (rules_json)", e),
+        },
+        None => HashMap::new(),
+    };
+
+    let results: Vec<serde_json::Value> = crate::engine::calculate_tax_batch(deal, jurisdictions, &rules_by_state)
+        .into_iter()
+        .map(|result| match result {
+            Ok(breakdown) => serde_json::to_value(&breakdown)
+                .unwrap_or_else(|e| error_json(&UdcError::serialization(e.to_string()))),
+            Err(e) => error_json(&e),
+        })
+        .collect();
+    serde_json::Value::Array(results).to_string()
+}
+
+/// Finance-only quote request (see [`calculate_finance_wasm`])
+#[cfg(feature = "wasm")]
+#[derive(serde::Deserialize)]
+struct FinanceQuoteRequest {
+    amount_financed: rust_decimal::Decimal,
+    apr: rust_decimal::Decimal,
+    term_months: u32,
+    #[serde(default)]
+    payment_frequency: crate::types::PaymentFrequency,
+    #[serde(default)]
+    first_payment_date: Option<chrono::NaiveDate>,
+}
+
+/// Calculate loan math only, for a known amount financed.
+///
+/// # Arguments
+///
+/// * `request_json` - JSON object with `amount_financed`, `apr`,
+///   `term_months`, and optionally `payment_frequency` (default monthly)
+///   and `first_payment_date` (include to get an amortization schedule)
+///
+/// # Returns
+///
+/// JSON string containing the FinanceQuote or error.
+///
+/// # Example
+///
+/// ```javascript
+/// const quote = JSON.parse(calculate_finance_wasm(JSON.stringify({
+///   amount_financed: "25000",
+///   apr: "0.0599",
+///   term_months: 60,
+///   first_payment_date: "2024-09-01"
+/// })));
+/// ```
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn calculate_finance_wasm(request_json: &str) -> String {
+    let request: FinanceQuoteRequest = match serde_json::from_str(request_json) {
+        Ok(request) => request,
+        Err(e) => {
+            return error_json(&UdcError::serialization(format!("Failed to parse request: {}", e))).to_string();
+        }
+    };
+
+    match crate::engine::calculate_finance_only(
+        request.amount_financed,
+        request.apr,
+        request.term_months,
+        request.payment_frequency,
+        request.first_payment_date,
+    ) {
+        Ok(quote) => serde_json::to_string(&quote).unwrap_or_else(|e| {
+            error_json(&UdcError::serialization(e.to_string())).to_string()
+        }),
+        Err(e) => error_json(&e).to_string(),
+    }
+}
+
+/// Payment matrix request (see [`generate_payment_matrix_wasm`])
+#[cfg(feature = "wasm")]
+#[derive(serde::Deserialize)]
+struct PaymentMatrixRequest {
+    amount_financed: rust_decimal::Decimal,
+    terms: Vec<u32>,
+    aprs: Vec<rust_decimal::Decimal>,
+}
+
+/// Quote monthly payments across a grid of terms and APRs.
+///
+/// # Arguments
+///
+/// * `request_json` - JSON object with `amount_financed`, `terms` (months)
+///   and `aprs`
+///
+/// # Returns
+///
+/// JSON string containing the PaymentMatrix or error. Each cell carries
+/// the monthly payment, total of payments and finance charge.
+///
+/// # Example
+///
+/// ```javascript
+/// const matrix = JSON.parse(generate_payment_matrix_wasm(JSON.stringify({
+///   amount_financed: "25000",
+///   terms: [36, 48, 60, 72],
+///   aprs: ["0.0499", "0.0599"]
+/// })));
+/// ```
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn generate_payment_matrix_wasm(request_json: &str) -> String {
+    let request: PaymentMatrixRequest = match serde_json::from_str(request_json) {
+        Ok(request) => request,
+        Err(e) => {
+            return error_json(&UdcError::serialization(format!("Failed to parse request: {}", e))).to_string();
+        }
+    };
+
+    match crate::engine::generate_payment_matrix(request.amount_financed, &request.terms, &request.aprs) {
+        Ok(matrix) => serde_json::to_string(&matrix).unwrap_or_else(|e| {
+            error_json(&UdcError::serialization(e.to_string())).to_string()
+        }),
+        Err(e) => error_json(&e).to_string(),
+    }
+}
+
+/// Calculate the payment for a loan from its APR.
+///
+/// Uses the precise Decimal PMT in `algorithms::amortization` (unlike the
+/// legacy f64 `calculate_finance_payment`).
+///
+/// # Arguments
+///
+/// * `principal` - Amount financed, as a decimal string
+/// * `apr` - APR as a decimal string (e.g. `"0.0599"`)
+/// * `term` - Term in months
+/// * `frequency` - `monthly`, `semi_monthly`, `bi_weekly`, `weekly` or
+///   `quarterly` (empty for monthly)
+///
+/// # Returns
+///
+/// JSON string with `payment`, `number_of_payments`, `total_of_payments`
+/// and `finance_charge`, or error.
+///
+/// # Example
+///
+/// ```javascript
+/// const quote = JSON.parse(calc_payment_wasm("25000", "0.0599", 60, "monthly"));
+/// console.log(quote.payment); // "483.20"
+/// ```
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn calc_payment_wasm(principal: &str, apr: &str, term: u32, frequency: &str) -> String {
+    payment_json(principal, apr, term, frequency)
+}
+
+/// Back the APR out of a monthly loan payment.
+///
+/// The inverse of [`calc_payment_wasm`] for monthly payments, solved by
+/// Newton-Raphson in `algorithms::amortization`.
+///
+/// # Arguments
+///
+/// * `principal` - Amount financed, as a decimal string
+/// * `payment` - Monthly payment, as a decimal string
+/// * `term` - Number of monthly payments
+///
+/// # Returns
+///
+/// JSON string with `apr`, `total_of_payments` and `finance_charge`, or
+/// error.
+///
+/// # Example
+///
+/// ```javascript
+/// const quote = JSON.parse(calc_apr_wasm("25000", "483.20", 60));
+/// console.log(quote.apr); // ~0.0599
+/// ```
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn calc_apr_wasm(principal: &str, payment: &str, term: u32) -> String {
+    apr_json(principal, payment, term)
+}
+
+/// Parse a decimal string argument, naming it in the error
+fn parse_decimal_arg(value: &str, field: &str) -> Result<rust_decimal::Decimal, String> {
+    use std::str::FromStr;
+
+    rust_decimal::Decimal::from_str(value.trim()).map_err(|e| {
+        error_json(&UdcError::validation_field(format!("{} is not a decimal: {}", field, e), field)).to_string()
+    })
+}
+
+/// Shared body of `calc_payment_wasm` and its non-WASM stub
+fn payment_json(principal: &str, apr: &str, term: u32, frequency: &str) -> String {
+    use crate::algorithms::amortization::{calculate_payment, RoundingMode};
+    use crate::types::PaymentFrequency;
+
+    let (principal, apr) = match (parse_decimal_arg(principal, "principal"), parse_decimal_arg(apr, "apr")) {
+        (Ok(principal), Ok(apr)) => (principal, apr),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    let frequency: PaymentFrequency = if frequency.trim().is_empty() {
+        PaymentFrequency::Monthly
+    } else {
+        match serde_json::from_value(serde_json::Value::String(frequency.trim().to_string())) {
+            Ok(frequency) => frequency,
+            Err(e) => {
+                return error_json(&UdcError::validation_field(
+                    format!("Unknown payment frequency: {}", e),
+                    "frequency",
+                ))
+                .to_string();
             }
         }
+    };
+    if let Err(e) = validate_loan_args(principal, term) {
+        return e;
+    }
+    if apr < rust_decimal::Decimal::ZERO {
+        return error_json(&UdcError::validation_field("APR cannot be negative", "apr")).to_string();
+    }
+
+    // The monthly PMT runs at apr / 12 per period; scaling the rate by
+    // 12 / periods_per_year gives the periodic rate for other frequencies
+    let number_of_payments = frequency.number_of_payments(term);
+    let scaled_apr = apr * rust_decimal::Decimal::from(12) / rust_decimal::Decimal::from(frequency.periods_per_year());
+    let result = calculate_payment(principal.round_dp(2), scaled_apr, number_of_payments, RoundingMode::BankersRounding);
+
+    serde_json::json!({
+        "payment": result.monthly_payment,
+        "apr": apr,
+        "payment_frequency": frequency,
+        "number_of_payments": number_of_payments,
+        "total_of_payments": result.total_of_payments,
+        "finance_charge": result.finance_charge,
+    })
+    .to_string()
+}
+
+/// Shared body of `calc_apr_wasm` and its non-WASM stub
+fn apr_json(principal: &str, payment: &str, term: u32) -> String {
+    use crate::algorithms::amortization::calculate_apr_from_payment;
+
+    let (principal, payment) = match (parse_decimal_arg(principal, "principal"), parse_decimal_arg(payment, "payment")) {
+        (Ok(principal), Ok(payment)) => (principal.round_dp(2), payment),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    if let Err(e) = validate_loan_args(principal, term) {
+        return e;
+    }
+    if payment <= rust_decimal::Decimal::ZERO {
+        return error_json(&UdcError::validation_field("Payment must be positive", "payment")).to_string();
+    }
+
+    let total_of_payments = payment * rust_decimal::Decimal::from(term);
+    serde_json::json!({
+        "apr": calculate_apr_from_payment(principal, payment, term),
+        "payment": payment,
+        "number_of_payments": term,
+        "total_of_payments": total_of_payments,
+        "finance_charge": total_of_payments - principal,
+    })
+    .to_string()
+}
+
+/// Check the principal and term shared by the payment and APR bindings
+fn validate_loan_args(principal: rust_decimal::Decimal, term: u32) -> Result<(), String> {
+    if principal < rust_decimal::Decimal::ZERO {
+        return Err(error_json(&UdcError::validation_field("Principal cannot be negative", "principal")).to_string());
+    }
+    if term == 0 {
+        return Err(error_json(&UdcError::validation_field("Term must be positive", "term")).to_string());
+    }
+    Ok(())
+}
+
+/// Compare two calculated scenarios.
+///
+/// # Arguments
+///
+/// * `a_json` - JSON string containing the first UdcOutput
+/// * `b_json` - JSON string containing the second UdcOutput
+///
+/// # Returns
+///
+/// JSON string containing the OutputDiff (`b - a` deltas) or error.
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn diff_outputs_wasm(a_json: &str, b_json: &str) -> String {
+    use crate::types::UdcOutput;
+
+    let a: Result<UdcOutput, _> = serde_json::from_str(a_json);
+    let b: Result<UdcOutput, _> = serde_json::from_str(b_json);
+
+    match (a, b) {
+        (Ok(a), Ok(b)) => {
+            let diff = crate::engine::diff_outputs(&a, &b);
+            serde_json::to_string(&diff).unwrap_or_else(|e| {
+                error_json(&UdcError::serialization(e.to_string())).to_string()
+            })
+        }
         (Err(e), _) => {
-            serde_json::json!({"error": format!("Failed to parse deal: {}", e)}).to_string()
+            error_json(&UdcError::serialization(format!("Failed to parse first output: {}", e))).to_string()
         }
         (_, Err(e)) => {
-            serde_json::json!({"error": format!("Failed to parse rules: {}", e)}).to_string()
+            error_json(&UdcError::serialization(format!("Failed to parse second output: {}", e))).to_string()
         }
     }
 }
@@ -203,13 +618,10 @@ pub fn get_version() -> String {
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn get_engine_info() -> String {
-    let info = crate::engine::engine_info();
-    serde_json::json!({
-        "version": info.version,
-        "name": info.name,
-        "features": info.features,
-        "target": "wasm32"
-    }).to_string()
+    let mut info = serde_json::to_value(crate::engine::engine_info())
+        .unwrap_or_else(|_| serde_json::json!({}));
+    info["target"] = serde_json::json!("wasm32");
+    info.to_string()
 }
 
 // Non-WASM stubs for when the feature is not enabled
@@ -220,8 +632,25 @@ pub fn run_udc_wasm(
     program_json: Option<&str>,
     products_json: Option<&str>,
 ) -> String {
-    crate::engine::run_udc_json(deal_json, rules_json, program_json, products_json)
-        .unwrap_or_else(|e| format!(r#"{{"error": "{}"}}"#, e))
+    run_pipeline_json(deal_json, rules_json, program_json, products_json)
+}
+
+/// Non-WASM stub of `calculate_tax_batch_wasm`
+#[cfg(not(feature = "wasm"))]
+pub fn calculate_tax_batch_wasm(deal_json: &str, jurisdictions_json: &str, rules_json: Option<&str>) -> String {
+    tax_batch_json(deal_json, jurisdictions_json, rules_json)
+}
+
+/// Non-WASM stub of `calc_payment_wasm`
+#[cfg(not(feature = "wasm"))]
+pub fn calc_payment_wasm(principal: &str, apr: &str, term: u32, frequency: &str) -> String {
+    payment_json(principal, apr, term, frequency)
+}
+
+/// Non-WASM stub of `calc_apr_wasm`
+#[cfg(not(feature = "wasm"))]
+pub fn calc_apr_wasm(principal: &str, payment: &str, term: u32) -> String {
+    apr_json(principal, payment, term)
 }
 
 #[cfg(test)]
@@ -262,4 +691,147 @@ mod tests {
         // Should return JSON (either success or error)
         assert!(result.starts_with('{'));
     }
+
+    const FINANCE_DEAL: &str = include_str!("../tests/wasm/finance_deal.json");
+    const TX_RULES: &str = include_str!("../tests/wasm/tx_rules.json");
+    const CAPTIVE_PROGRAM: &str = include_str!("../tests/wasm/captive_program.json");
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_run_udc_wasm_stub_program_override() {
+        let output: crate::types::UdcOutput =
+            serde_json::from_str(&run_udc_wasm(FINANCE_DEAL, TX_RULES, Some(CAPTIVE_PROGRAM), Some(""))).unwrap();
+        let finance = output.finance_structure.unwrap();
+        assert_eq!(finance.apr.as_decimal(), rust_decimal_macros::dec!(0.009));
+
+        // Without the program the promo code falls back to the contract rate
+        let output: crate::types::UdcOutput =
+            serde_json::from_str(&run_udc_wasm(FINANCE_DEAL, TX_RULES, None, None)).unwrap();
+        assert_eq!(output.finance_structure.unwrap().apr.as_decimal(), rust_decimal_macros::dec!(0.0599));
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_run_udc_wasm_stub_applies_rules() {
+        use rust_decimal_macros::dec;
+
+        let run = |rules_json: &str| -> crate::types::UdcOutput {
+            serde_json::from_str(&run_udc_wasm(FINANCE_DEAL, rules_json, None, None)).unwrap()
+        };
+        let tax = run(TX_RULES).tax_breakdown;
+        assert_eq!(tax.effective_rate.as_decimal(), dec!(0.0625));
+
+        let higher = run(&TX_RULES.replace(r#""state_rate": "0.0625""#, r#""state_rate": "0.08""#)).tax_breakdown;
+        assert_eq!(higher.effective_rate.as_decimal(), dec!(0.08));
+        assert_eq!(higher.tax_base, tax.tax_base);
+        assert!(higher.net_tax > tax.net_tax);
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_run_udc_wasm_stub_names_bad_payload() {
+        let body: serde_json::Value =
+            serde_json::from_str(&run_udc_wasm(FINANCE_DEAL, r#"{"state_code": "TX"}"#, None, None)).unwrap();
+        assert_eq!(body["code"], "SERIALIZATION");
+        assert_eq!(body["success"], false);
+        assert!(body["error"].as_str().unwrap().contains("Failed to parse rule profile (rules_json):"));
+
+        let body: serde_json::Value =
+            serde_json::from_str(&run_udc_wasm(FINANCE_DEAL, TX_RULES, Some("[]"), None)).unwrap();
+        assert!(body["error"].as_str().unwrap().contains("Failed to parse program profile (program_json):"));
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_calculate_tax_batch_wasm_stub() {
+        let jurisdictions = r#"[{"state": "TX", "zip": "75001"}, {"state": "GA", "zip": "30301"}, {"state": "XX", "zip": ""}]"#;
+        let body: serde_json::Value =
+            serde_json::from_str(&calculate_tax_batch_wasm(FINANCE_DEAL, jurisdictions, None)).unwrap();
+        let entries = body.as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0]["net_tax"].is_string());
+        assert!(entries[1]["special_tax_type"].as_str().unwrap().contains("TAVT"));
+        assert_eq!(entries[2]["code"], "VALIDATION");
+
+        let body: serde_json::Value =
+            serde_json::from_str(&calculate_tax_batch_wasm(FINANCE_DEAL, "{}", Some(""))).unwrap();
+        assert!(body["error"].as_str().unwrap().contains("(jurisdictions_json)"));
+    }
+
+    #[test]
+    fn test_error_json_includes_code() {
+        let body = error_json(&UdcError::calculation("Tax invariant violated", "P4_TAX"));
+        assert_eq!(body["code"], "CALC_P4");
+        assert_eq!(body["error"], "Calculation error in phase P4_TAX: Tax invariant violated");
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_run_udc_wasm_stub_error_code() {
+        let rules_json = r#"{
+            "state_code": "TX",
+            "mode": "cash",
+            "tax_type": "SALES",
+            "rates": {"state_rate": "0.0625"},
+            "base_rules": {},
+            "ancillaries": {},
+            "reciprocity": {},
+            "meta": {"version": "1", "effective_date": "2024-01-01"}
+        }"#;
+
+        let body: serde_json::Value =
+            serde_json::from_str(&run_udc_wasm("{not json", rules_json, None, None)).unwrap();
+        assert_eq!(body["code"], "SERIALIZATION");
+        assert!(body["error"].as_str().unwrap().contains("Failed to parse deal input"));
+
+        let deal_json = r#"{
+            "deal_type": "cash",
+            "vehicle_price": "-1",
+            "cash_down": "0",
+            "fees": {},
+            "home_state": "TX",
+            "transaction_state": "TX",
+            "customer": {}
+        }"#;
+        let body: serde_json::Value =
+            serde_json::from_str(&run_udc_wasm(deal_json, rules_json, None, None)).unwrap();
+        assert_eq!(body["code"], "VALIDATION");
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_calc_payment_and_apr_round_trip() {
+        use rust_decimal::Decimal;
+        use std::str::FromStr;
+
+        let decimal = |body: &serde_json::Value, key: &str| Decimal::from_str(body[key].as_str().unwrap()).unwrap();
+
+        let quote: serde_json::Value = serde_json::from_str(&calc_payment_wasm("25000", "0.0599", 60, "monthly")).unwrap();
+        assert_eq!(decimal(&quote, "payment"), rust_decimal_macros::dec!(483.20));
+        assert_eq!(decimal(&quote, "finance_charge"), decimal(&quote, "total_of_payments") - rust_decimal_macros::dec!(25000));
+
+        let payment = quote["payment"].as_str().unwrap();
+        let back: serde_json::Value = serde_json::from_str(&calc_apr_wasm("25000", payment, 60)).unwrap();
+        assert!((decimal(&back, "apr") - rust_decimal_macros::dec!(0.0599)).abs() < rust_decimal_macros::dec!(0.0001));
+        assert_eq!(decimal(&back, "finance_charge"), decimal(&quote, "finance_charge"));
+
+        // Bi-weekly: 130 payments at apr / 26 per period
+        let quote: serde_json::Value = serde_json::from_str(&calc_payment_wasm("25000", "0.0599", 60, "bi_weekly")).unwrap();
+        assert_eq!(quote["number_of_payments"], 130);
+        assert!(decimal(&quote, "payment") < rust_decimal_macros::dec!(241.60));
+    }
+
+    #[test]
+    #[cfg(not(feature = "wasm"))]
+    fn test_calc_payment_rejects_bad_args() {
+        let body: serde_json::Value = serde_json::from_str(&calc_payment_wasm("abc", "0.05", 60, "")).unwrap();
+        assert_eq!(body["code"], "VALIDATION");
+        assert!(body["error"].as_str().unwrap().contains("principal"));
+
+        let body: serde_json::Value = serde_json::from_str(&calc_payment_wasm("25000", "0.05", 60, "daily")).unwrap();
+        assert_eq!(body["code"], "VALIDATION");
+
+        let body: serde_json::Value = serde_json::from_str(&calc_apr_wasm("25000", "450", 0)).unwrap();
+        assert_eq!(body["code"], "VALIDATION");
+    }
 }
@@ -0,0 +1,175 @@
+//! Golden (reference) tests for the UDC Engine.
+//!
+//! Each JSON file in `tests/golden/` is a hand-verified deal: a `DealInput`
+//! plus its key figures, worked out by hand from the deal terms (never
+//! copied from engine output) with the working recorded in `derivation`.
+//! Every fixture is run through the full pipeline and compared to the penny.
+//!
+//! Adding a regression case only requires adding a fixture file:
+//!
+//! ```json
+//! {
+//!   "description": "TX retail finance, 60 months @ 5.99%",
+//!   "input": { ...DealInput... },
+//!   "expected": {
+//!     "net_tax": "1525.50",
+//!     "payment": "512.34",
+//!     "amount_financed": "26510.25",
+//!     "residual": null
+//!   },
+//!   "derivation": ["Tax base: 32000.00 price - 10000.00 trade = ...", "..."]
+//! }
+//! ```
+//!
+//! Expected figures are optional; only those present are checked.
+//! `payment` is the finance payment or the lease total monthly payment.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Deserialize;
+
+use udc_engine::phases::execute_pipeline_with_defaults;
+use udc_engine::{DealInput, UdcOutput};
+
+/// Maximum allowed difference from the reference figures
+const TOLERANCE: Decimal = dec!(0.01);
+
+#[derive(Debug, Deserialize)]
+struct GoldenCase {
+    description: String,
+    input: DealInput,
+    expected: ExpectedFigures,
+    /// How the expected figures were calculated
+    derivation: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExpectedFigures {
+    #[serde(default)]
+    net_tax: Option<Decimal>,
+    #[serde(default)]
+    payment: Option<Decimal>,
+    #[serde(default)]
+    amount_financed: Option<Decimal>,
+    #[serde(default)]
+    residual: Option<Decimal>,
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn load_fixtures() -> Vec<(PathBuf, GoldenCase)> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(golden_dir())
+        .expect("tests/golden directory should exist")
+        .map(|entry| entry.expect("readable directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let json = fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            let case: GoldenCase = serde_json::from_str(&json)
+                .unwrap_or_else(|e| panic!("{}: invalid fixture: {}", path.display(), e));
+            assert!(
+                !case.derivation.is_empty(),
+                "{}: fixture must show how its expected figures were derived",
+                path.display()
+            );
+            (path, case)
+        })
+        .collect()
+}
+
+fn actual_payment(output: &UdcOutput) -> Option<Decimal> {
+    output
+        .finance_structure
+        .as_ref()
+        .map(|f| f.monthly_payment.as_decimal())
+        .or_else(|| {
+            output
+                .lease_structure
+                .as_ref()
+                .map(|l| l.total_monthly_payment.as_decimal())
+        })
+}
+
+fn check(
+    failures: &mut Vec<String>,
+    case: &str,
+    figure: &str,
+    expected: Option<Decimal>,
+    actual: Option<Decimal>,
+) {
+    let Some(expected) = expected else {
+        return;
+    };
+    match actual {
+        Some(actual) if (actual - expected).abs() <= TOLERANCE => {}
+        Some(actual) => failures.push(format!(
+            "{}: {} expected {} got {}",
+            case, figure, expected, actual
+        )),
+        None => failures.push(format!("{}: {} expected {} got none", case, figure, expected)),
+    }
+}
+
+#[test]
+fn test_golden_fixtures() {
+    let fixtures = load_fixtures();
+    assert!(!fixtures.is_empty(), "no fixtures in {}", golden_dir().display());
+
+    let mut failures = Vec::new();
+
+    for (path, case) in fixtures {
+        let name = format!(
+            "{} ({})",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            case.description
+        );
+
+        let failures_before = failures.len();
+        let output = match execute_pipeline_with_defaults(case.input) {
+            Ok(output) => output,
+            Err(e) => {
+                failures.push(format!("{}: engine error: {}", name, e));
+                continue;
+            }
+        };
+
+        let expected = &case.expected;
+        check(
+            &mut failures,
+            &name,
+            "net_tax",
+            expected.net_tax,
+            Some(output.tax_breakdown.net_tax.as_decimal()),
+        );
+        check(&mut failures, &name, "payment", expected.payment, actual_payment(&output));
+        check(
+            &mut failures,
+            &name,
+            "amount_financed",
+            expected.amount_financed,
+            output.finance_structure.as_ref().map(|f| f.amount_financed.as_decimal()),
+        );
+        check(
+            &mut failures,
+            &name,
+            "residual",
+            expected.residual,
+            output.lease_structure.as_ref().map(|l| l.residual_value.as_decimal()),
+        );
+        if failures.len() > failures_before {
+            failures.push(format!("  derivation:\n    {}", case.derivation.join("\n    ")));
+        }
+    }
+
+    assert!(failures.is_empty(), "golden mismatches:\n{}", failures.join("\n"));
+}
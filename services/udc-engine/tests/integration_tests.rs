@@ -8,8 +8,8 @@ use rust_decimal::Decimal;
 
 // Import from the crate
 use udc_engine::{
-    DealInput, DealType, DealFees, CustomerInfo, FinanceParams, LeaseParams,
-    RuleProfile, Money, Rate, StateCode,
+    DealInput, DealType, DealFees, FinanceParams, LeaseParams,
+    RuleProfile, MoneyFactor, StateCode,
     run_udc, validate_deal, engine_version,
 };
 
@@ -22,59 +22,15 @@ fn create_finance_deal(
     state: StateCode,
 ) -> DealInput {
     DealInput {
-        deal_type: DealType::Finance,
-        vehicle_price: price,
-        trade_in_value: None,
-        trade_in_payoff: None,
         cash_down: down,
-        rebates: vec![],
-        products: vec![],
-        fees: DealFees::default(),
-        home_state: state,
-        transaction_state: state,
-        garaging_state: None,
-        customer: CustomerInfo::default(),
-        finance_params: Some(FinanceParams {
-            term_months: term,
-            apr,
-            lender_id: None,
-            buy_rate: None,
-            max_reserve_points: None,
-            deferred_first_payment: false,
-            days_to_first_payment: None,
-        }),
-        lease_params: None,
-        deal_date: None,
-        first_payment_date: None,
+        finance_params: Some(FinanceParams::new(term, apr)),
+        ..DealInput::new(DealType::Finance, price, state)
     }
 }
 
 /// Helper to create default Texas rule profile
 fn create_texas_rules() -> RuleProfile {
-    RuleProfile {
-        state_code: "TX".to_string(),
-        deal_type: DealType::Finance,
-        effective_date: chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
-        state_rate: Rate::from_percentage(dec!(6.25)),
-        county_rate: None,
-        city_rate: None,
-        district_rates: vec![],
-        trade_in_treatment: udc_engine::TradeInTaxTreatment::ReducesTaxableAmount,
-        trade_in_credit_cap: None,
-        rebate_treatment: udc_engine::RebateTaxTreatment::NoReduction,
-        doc_fee_taxable: true,
-        title_fee_taxable: false,
-        registration_fee_taxable: false,
-        lease_tax_mode: udc_engine::LeaseTaxMode::MonthlyPayment,
-        tax_stacking_mode: udc_engine::TaxStackingMode::Additive,
-        reciprocity: udc_engine::ReciprocityType::FullCredit,
-        special_tax_type: udc_engine::types::SpecialTaxType::Standard,
-        flat_tax_amount: None,
-        max_tax_cap: None,
-        min_tax_floor: None,
-        rounding_precision: 2,
-        rounding_mode: udc_engine::RoundingMode::HalfUp,
-    }
+    serde_json::from_str(include_str!("wasm/tx_rules.json")).unwrap()
 }
 
 #[test]
@@ -94,66 +50,34 @@ fn test_validate_finance_deal() {
         StateCode::TX,
     );
 
-    let result = validate_deal(&deal);
-    assert!(result.is_ok());
+    let result = validate_deal(&deal).unwrap();
+    assert!(result.valid);
 }
 
 #[test]
 fn test_validate_rejects_negative_price() {
-    let deal = DealInput {
-        deal_type: DealType::Finance,
-        vehicle_price: dec!(-1000),
-        trade_in_value: None,
-        trade_in_payoff: None,
-        cash_down: dec!(0),
-        rebates: vec![],
-        products: vec![],
-        fees: DealFees::default(),
-        home_state: StateCode::TX,
-        transaction_state: StateCode::TX,
-        garaging_state: None,
-        customer: CustomerInfo::default(),
-        finance_params: Some(FinanceParams {
-            term_months: 60,
-            apr: dec!(0.0599),
-            lender_id: None,
-            buy_rate: None,
-            max_reserve_points: None,
-            deferred_first_payment: false,
-            days_to_first_payment: None,
-        }),
-        lease_params: None,
-        deal_date: None,
-        first_payment_date: None,
-    };
+    let deal = create_finance_deal(
+        dec!(-1000),
+        dec!(0),
+        dec!(0.0599),
+        60,
+        StateCode::TX,
+    );
 
-    let result = validate_deal(&deal);
-    assert!(result.is_err());
+    let result = validate_deal(&deal).unwrap();
+    assert!(!result.valid);
 }
 
 #[test]
 fn test_validate_rejects_missing_finance_params() {
     let deal = DealInput {
-        deal_type: DealType::Finance,
-        vehicle_price: dec!(30000),
-        trade_in_value: None,
-        trade_in_payoff: None,
         cash_down: dec!(3000),
-        rebates: vec![],
-        products: vec![],
-        fees: DealFees::default(),
-        home_state: StateCode::TX,
-        transaction_state: StateCode::TX,
-        garaging_state: None,
-        customer: CustomerInfo::default(),
         finance_params: None, // Missing!
-        lease_params: None,
-        deal_date: None,
-        first_payment_date: None,
+        ..DealInput::new(DealType::Finance, dec!(30000), StateCode::TX)
     };
 
-    let result = validate_deal(&deal);
-    assert!(result.is_err());
+    let result = validate_deal(&deal).unwrap();
+    assert!(!result.valid);
 }
 
 #[test]
@@ -243,26 +167,12 @@ fn test_finance_with_negative_equity() {
 #[test]
 fn test_cash_deal() {
     let deal = DealInput {
-        deal_type: DealType::Cash,
-        vehicle_price: dec!(25000),
         trade_in_value: Some(dec!(5000)),
-        trade_in_payoff: None,
-        cash_down: dec!(0),
-        rebates: vec![],
-        products: vec![],
-        fees: DealFees::default(),
-        home_state: StateCode::TX,
-        transaction_state: StateCode::TX,
-        garaging_state: None,
-        customer: CustomerInfo::default(),
-        finance_params: None,
-        lease_params: None,
-        deal_date: None,
-        first_payment_date: None,
+        ..DealInput::new(DealType::Cash, dec!(25000), StateCode::TX)
     };
 
     let mut rules = create_texas_rules();
-    rules.deal_type = DealType::Cash;
+    rules.mode = DealType::Cash;
 
     let result = run_udc(deal, rules, None, None);
     assert!(result.is_ok());
@@ -328,47 +238,26 @@ fn test_tax_with_trade_in_credit() {
     assert!(tax.net_tax.as_decimal() < dec!(1500));
 }
 
-// Lease tests (when lease params are properly implemented)
+// Lease tests
 #[test]
-#[ignore = "Requires full lease implementation"]
 fn test_lease_calculation() {
     let deal = DealInput {
-        deal_type: DealType::Lease,
-        vehicle_price: dec!(35000),
-        trade_in_value: None,
-        trade_in_payoff: None,
         cash_down: dec!(2000),
-        rebates: vec![],
-        products: vec![],
         fees: DealFees {
             acquisition_fee: dec!(695),
             ..Default::default()
         },
-        home_state: StateCode::CA,
-        transaction_state: StateCode::CA,
-        garaging_state: None,
-        customer: CustomerInfo::default(),
-        finance_params: None,
         lease_params: Some(LeaseParams {
-            term_months: 36,
-            money_factor: dec!(0.00125),
-            residual_percent: dec!(0.55),
-            annual_miles: 12000,
             excess_mileage_rate: Some(dec!(0.25)),
-            lessor_id: None,
-            msd_count: 0,
-            security_deposit: None,
-            cap_acquisition_fee: true,
-            cap_cost_reduction: Decimal::ZERO,
+            ..LeaseParams::new(36, MoneyFactor::new(dec!(0.00125)), dec!(0.55), 12000)
         }),
-        deal_date: None,
-        first_payment_date: None,
+        ..DealInput::new(DealType::Lease, dec!(35000), StateCode::CA)
     };
 
     let rules = RuleProfile {
-        state_code: "CA".to_string(),
-        deal_type: DealType::Lease,
-        lease_tax_mode: udc_engine::LeaseTaxMode::MonthlyPayment,
+        state_code: StateCode::CA,
+        mode: DealType::Lease,
+        lease_tax_mode: Some(udc_engine::LeaseTaxMode::MonthlyPayment),
         ..create_texas_rules()
     };
 
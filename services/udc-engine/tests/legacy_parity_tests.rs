@@ -0,0 +1,200 @@
+//! Rounding parity tests against the legacy tax-engine-rs.
+//!
+//! Each deal is run through both engines and the figures udc-engine is
+//! documented to reproduce are compared to the cent: tax-engine-rs rounds
+//! tax totals once, as [`TaxRounding::OnTotal`] does, rounds lease base
+//! payments once, as `RoundingStrategy::RoundAtEnd` does, and computes the
+//! finance payment from the rounded amount financed, as both strategies do.
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde_json::{json, Value};
+
+use udc_engine::phases::{
+    calculate_tax, execute_pipeline_with_options, load_profiles, normalize_deal_input, resolve_jurisdiction,
+    route_deal, PipelineOptions, TaxComputedDeal,
+};
+use udc_engine::types::TaxRounding;
+use udc_engine::{DealInput, RoundingStrategy, UdcOutput};
+
+fn run_udc(input: Value, rounding: RoundingStrategy) -> UdcOutput {
+    let input: DealInput = serde_json::from_value(input).expect("valid deal input");
+    let options = PipelineOptions { rounding, ..Default::default() };
+    execute_pipeline_with_options(input, None, None, &options).expect("udc-engine result")
+}
+
+fn run_udc_tax(input: Value, tax_rounding: TaxRounding) -> TaxComputedDeal {
+    let input: DealInput = serde_json::from_value(input).expect("valid deal input");
+    let normalized = normalize_deal_input(input).expect("normalized");
+    let resolved = resolve_jurisdiction(route_deal(normalized).expect("routed")).expect("resolved");
+    let mut loaded = load_profiles(resolved).expect("profiles");
+    loaded.profiles.primary_rules.tax_rounding = tax_rounding;
+    calculate_tax(loaded).expect("tax")
+}
+
+fn run_legacy(input: Value) -> Value {
+    let result = tax_engine_rs::calculate_deal(&input.to_string())
+        .unwrap_or_else(|_| panic!("tax-engine-rs rejected {}", input));
+    serde_json::from_str(&result).expect("tax-engine-rs JSON")
+}
+
+fn cents(value: &Value) -> Decimal {
+    let value = value.as_f64().unwrap_or_else(|| panic!("expected a number, got {}", value));
+    Decimal::from_f64(value).expect("finite amount").round_dp(2)
+}
+
+fn legacy_deal(deal_type: &str, state: &str, price: f64, fees: Value) -> Value {
+    json!({
+        "deal_type": deal_type,
+        "state_code": state,
+        "local_jurisdiction": null,
+        "vehicle_msrp": price,
+        "vehicle_invoice": null,
+        "selling_price": price,
+        "trade_in": null,
+        "rebates": [],
+        "cash_down": 0.0,
+        "fi_products": [],
+        "fees": fees,
+        "finance_input": null,
+        "lease_input": null
+    })
+}
+
+fn legacy_fee(code: &str, amount: f64, taxable: bool) -> Value {
+    json!({
+        "name": code,
+        "code": code,
+        "amount": amount,
+        "taxable": taxable,
+        "capitalize_in_lease": true
+    })
+}
+
+#[test]
+fn test_tax_total_matches_legacy_when_rounded_on_total() {
+    // 20299.10 taxable at 6.25% + 2%: the components round to
+    // 1268.69 + 405.98 = 1674.67, the exact total to 1674.68
+    let udc_input = json!({
+        "deal_type": "cash",
+        "vehicle_price": "30000.10",
+        "trade_in_value": "10000.00",
+        "fees": { "doc_fee": "299.00" },
+        "home_state": "TX",
+        "transaction_state": "TX",
+        "customer": {},
+        "deal_date": "2024-06-15"
+    });
+    let mut legacy_input = legacy_deal("CASH", "TX", 30000.10, json!([legacy_fee("DOC_FEE", 299.0, true)]));
+    legacy_input["trade_in"] = json!({
+        "gross_allowance": 10000.0,
+        "payoff_amount": 0.0,
+        "payoff_good_through": null,
+        "per_diem": null,
+        "acv": null,
+        "year": null,
+        "make": null,
+        "model": null,
+        "vin": null,
+        "mileage": null
+    });
+    let legacy = cents(&run_legacy(legacy_input)["tax_breakdown"]["total_tax"]);
+    assert_eq!(legacy, dec!(1674.68));
+
+    let on_total = run_udc_tax(udc_input.clone(), TaxRounding::OnTotal);
+    assert_eq!(on_total.tax.net_tax, legacy);
+    let component_sum: Decimal = on_total.tax.components.iter().map(|c| c.amount).sum();
+    assert_eq!(component_sum, legacy);
+
+    let per_component = run_udc_tax(udc_input, TaxRounding::PerComponent);
+    assert_eq!(per_component.tax.net_tax, dec!(1674.67));
+}
+
+#[test]
+fn test_finance_payment_matches_legacy() {
+    let udc_input = json!({
+        "deal_type": "finance",
+        "vehicle_price": "32000.00",
+        "trade_in_value": "10000.00",
+        "trade_in_payoff": "4000.00",
+        "cash_down": "2000.00",
+        "fees": { "doc_fee": "150.00" },
+        "home_state": "TX",
+        "transaction_state": "TX",
+        "customer": {},
+        "finance_params": { "term_months": 60, "apr": "0.0599" },
+        "deal_date": "2024-06-15"
+    });
+
+    for rounding in [RoundingStrategy::RoundEachStep, RoundingStrategy::RoundAtEnd] {
+        let output = run_udc(udc_input.clone(), rounding);
+        let finance = output.finance_structure.expect("finance structure");
+        let amount_financed = finance.amount_financed.as_decimal().to_f64().unwrap();
+
+        let legacy = tax_engine_rs::calculate_finance_payment(amount_financed, 5.99, 60);
+        assert_eq!(
+            finance.monthly_payment.as_decimal(),
+            Decimal::from_f64(legacy).unwrap().round_dp(2),
+            "{:?}",
+            rounding
+        );
+    }
+}
+
+#[test]
+fn test_lease_base_payment_matches_legacy_when_rounded_at_end() {
+    // Depreciation 456.1111 + rent 136.7940 = 592.9051: rounded once it is
+    // 592.91, rounded part by part 456.11 + 136.79 = 592.90
+    let udc_input = json!({
+        "deal_type": "lease",
+        "vehicle_price": "42000.00",
+        "cash_down": "2000.00",
+        "fees": { "doc_fee": "85.00", "acquisition_fee": "695.00" },
+        "home_state": "CA",
+        "transaction_state": "CA",
+        "customer": {},
+        "lease_params": {
+            "term_months": 36,
+            "money_factor": "0.0021",
+            "residual_percent": "0.58",
+            "annual_miles": 10000
+        },
+        "deal_date": "2024-06-15"
+    });
+    let mut legacy_input = legacy_deal("LEASE", "CA", 42000.0, json!([legacy_fee("DOC_FEE", 85.0, false)]));
+    legacy_input["cash_down"] = json!(2000.0);
+    legacy_input["lease_input"] = json!({
+        "term_months": 36,
+        "annual_mileage": 10000,
+        "excess_mileage_rate": 0.25,
+        "residual_percent": 58.0,
+        "residual_value": null,
+        "money_factor": 0.0021,
+        "buy_rate_mf": null,
+        "security_deposit": 0.0,
+        "security_deposit_waived": true,
+        "msd_count": null,
+        "msd_rate_reduction": null,
+        "first_payment_due_at_signing": true,
+        "acquisition_fee": 695.0,
+        "acquisition_fee_cap": true,
+        "disposition_fee": 0.0,
+        "disposition_fee_waived": true,
+        "sign_and_drive": false,
+        "one_pay_lease": false
+    });
+    let legacy = run_legacy(legacy_input);
+    assert_eq!(cents(&legacy["price_breakdown"]["adjusted_cap_cost"]), dec!(40780));
+    let legacy_base = cents(&legacy["payment_info"]["payment"]);
+    assert_eq!(legacy_base, dec!(592.91));
+
+    let at_end = run_udc(udc_input.clone(), RoundingStrategy::RoundAtEnd);
+    let lease = at_end.lease_structure.expect("lease structure");
+    assert_eq!(lease.adjusted_cap_cost.as_decimal(), dec!(40780));
+    assert_eq!(lease.base_monthly_payment.as_decimal(), legacy_base);
+
+    let each_step = run_udc(udc_input, RoundingStrategy::RoundEachStep);
+    let lease = each_step.lease_structure.expect("lease structure");
+    assert_eq!(lease.base_monthly_payment.as_decimal(), dec!(592.90));
+}
@@ -0,0 +1,104 @@
+//! Serialization stability tests for `UdcOutput`.
+//!
+//! WASM/JS front-ends consume `UdcOutput` as JSON, so its field names and
+//! value encodings (money and rates as decimal strings, enums as snake_case
+//! strings) are a public contract.
+//!
+//! `tests/schema/udc_output_v1.json` is a checked-in v1 sample with every
+//! optional section populated. It must keep deserializing, and every field
+//! in it must re-serialize with the same name and value. Adding output
+//! fields is fine; renaming or removing one, or changing its encoding, fails
+//! here and needs a new schema version.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use udc_engine::phases::execute_pipeline_with_defaults;
+use udc_engine::{DealInput, UdcOutput};
+
+fn test_dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(name)
+}
+
+fn load_json(path: &Path) -> Value {
+    let json = fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    serde_json::from_str(&json).unwrap_or_else(|e| panic!("{}: invalid JSON: {}", path.display(), e))
+}
+
+/// Collect paths in `expected` whose value is missing or different in `actual`.
+fn missing_fields(expected: &Value, actual: &Value, path: &str, out: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                let child = format!("{}.{}", path, key);
+                match actual.get(key) {
+                    Some(actual) => missing_fields(value, actual, &child, out),
+                    None => out.push(format!("{}: missing", child)),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                missing_fields(expected, actual, &format!("{}[{}]", path, i), out);
+            }
+        }
+        _ if expected != actual => out.push(format!("{}: expected {} got {}", path, expected, actual)),
+        _ => {}
+    }
+}
+
+#[test]
+fn test_v1_sample_is_stable() {
+    let sample = load_json(&test_dir("schema").join("udc_output_v1.json"));
+
+    let output: UdcOutput = serde_json::from_value(sample.clone())
+        .unwrap_or_else(|e| panic!("v1 sample no longer deserializes: {}", e));
+    let reserialized = serde_json::to_value(&output).unwrap();
+
+    let mut changed = Vec::new();
+    missing_fields(&sample, &reserialized, "$", &mut changed);
+    assert!(changed.is_empty(), "v1 fields changed:\n{}", changed.join("\n"));
+
+    // Deserializing the re-serialized form loses nothing
+    let again: UdcOutput = serde_json::from_value(reserialized).unwrap();
+    assert_eq!(again, output);
+}
+
+#[test]
+fn test_v1_sample_encodings() {
+    let sample = load_json(&test_dir("schema").join("udc_output_v1.json"));
+
+    // Money, rates and money factors are decimal strings; enums are snake_case
+    assert_eq!(sample["deal_type"], "finance");
+    assert_eq!(sample["finance_structure"]["apr"], "0.0599");
+    assert_eq!(sample["finance_structure"]["payment_frequency"], "monthly");
+    assert_eq!(sample["lease_structure"]["money_factor"], "0.0021");
+    assert!(sample["tax_breakdown"]["net_tax"].is_string());
+}
+
+#[test]
+fn test_engine_outputs_round_trip() {
+    let mut paths: Vec<PathBuf> = fs::read_dir(test_dir("golden"))
+        .expect("tests/golden directory should exist")
+        .map(|entry| entry.expect("readable directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    for path in paths {
+        let input: DealInput = serde_json::from_value(load_json(&path)["input"].clone())
+            .unwrap_or_else(|e| panic!("{}: invalid input: {}", path.display(), e));
+        let output = execute_pipeline_with_defaults(input)
+            .unwrap_or_else(|e| panic!("{}: engine error: {}", path.display(), e));
+
+        let json = serde_json::to_string(&output).unwrap();
+        let parsed: UdcOutput = serde_json::from_str(&json)
+            .unwrap_or_else(|e| panic!("{}: output does not deserialize: {}", path.display(), e));
+
+        assert_eq!(parsed, output, "{}", path.display());
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json, "{}", path.display());
+    }
+}
@@ -0,0 +1,77 @@
+//! WebAssembly tests for the JavaScript-facing bindings.
+//!
+//! Run with `wasm-pack test --node -- --features wasm`. On other targets
+//! this file compiles to nothing; the non-WASM stubs are covered by the
+//! unit tests in `src/wasm.rs`.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use rust_decimal_macros::dec;
+use serde_json::Value;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use udc_engine::{calc_apr_wasm, calc_payment_wasm, calculate_tax_batch_wasm, run_udc_wasm, TaxBreakdown, UdcOutput};
+
+const FINANCE_DEAL: &str = include_str!("wasm/finance_deal.json");
+const TX_RULES: &str = include_str!("wasm/tx_rules.json");
+const CAPTIVE_PROGRAM: &str = include_str!("wasm/captive_program.json");
+
+#[wasm_bindgen_test]
+fn finance_deal_with_program_override() {
+    let result = run_udc_wasm(
+        FINANCE_DEAL,
+        TX_RULES,
+        Some(CAPTIVE_PROGRAM.to_string()),
+        None,
+    );
+    let output: UdcOutput = serde_json::from_str(&result).expect("UdcOutput JSON");
+
+    // The program's promotional rate replaces the contract APR
+    let finance = output.finance_structure.expect("finance structure");
+    assert_eq!(finance.apr.as_decimal(), dec!(0.009));
+    assert!(output.validation.warnings.iter().all(|w| !w.code.starts_with("PROGRAM_")));
+}
+
+#[wasm_bindgen_test]
+fn empty_optional_payloads_are_absent() {
+    let result = run_udc_wasm(FINANCE_DEAL, TX_RULES, Some(String::new()), Some(" ".to_string()));
+    let output: UdcOutput = serde_json::from_str(&result).expect("UdcOutput JSON");
+    assert_eq!(output.finance_structure.expect("finance structure").apr.as_decimal(), dec!(0.0599));
+}
+
+#[wasm_bindgen_test]
+fn malformed_rules_payload_is_named() {
+    let result = run_udc_wasm(FINANCE_DEAL, "{\"state_code\": ", None, None);
+    let body: Value = serde_json::from_str(&result).expect("error JSON");
+
+    assert_eq!(body["success"], false);
+    assert_eq!(body["code"], "SERIALIZATION");
+    let message = body["error"].as_str().unwrap();
+    assert!(message.contains("Failed to parse rule profile (rules_json)"), "{}", message);
+}
+
+#[wasm_bindgen_test]
+fn tax_batch_quotes_each_state() {
+    let jurisdictions = r#"[{"state": "TX", "zip": ""}, {"state": "CA", "zip": ""}, {"state": "GA", "zip": ""}]"#;
+    let result = calculate_tax_batch_wasm(FINANCE_DEAL, jurisdictions, None);
+    let quotes: Vec<TaxBreakdown> = serde_json::from_str(&result).expect("TaxBreakdown array");
+
+    assert_eq!(quotes.len(), 3);
+    assert!(quotes[2].special_tax_type.as_deref().unwrap_or_default().contains("TAVT"));
+}
+
+#[wasm_bindgen_test]
+fn payment_then_apr_recovers_rate() {
+    for (apr, term) in [("0.0599", 60), ("0.0249", 36), ("0.1299", 84)] {
+        let quote: Value = serde_json::from_str(&calc_payment_wasm("28750.00", apr, term, "monthly")).expect("payment JSON");
+        let payment = quote["payment"].as_str().expect("payment");
+
+        let back: Value = serde_json::from_str(&calc_apr_wasm("28750.00", payment, term)).expect("APR JSON");
+        let recovered: rust_decimal::Decimal = back["apr"].as_str().unwrap().parse().unwrap();
+        let expected: rust_decimal::Decimal = apr.parse().unwrap();
+
+        // The payment is rounded to cents, so the rate comes back within a basis point
+        assert!((recovered - expected).abs() < dec!(0.0001), "{} -> {} -> {}", apr, payment, recovered);
+        assert_eq!(back["finance_charge"], quote["finance_charge"]);
+    }
+}